@@ -7,7 +7,7 @@ use builder::permissioned::init_node;
 use clap::Parser;
 use espresso_types::{
     eth_signature_key::EthKeyPair, parse_duration, FeeVersion, MarketplaceVersion,
-    SequencerVersions, V0_0, V0_1,
+    SequencerVersions, DEFAULT_L1_CACHE_CAPACITY, V0_0, V0_1,
 };
 use ethers::types::Address;
 use hotshot_types::{
@@ -139,9 +139,16 @@ pub struct PermissionedBuilderOptions {
     #[clap(long, env = "ESPRESSO_BUILDER_ETH_ACCOUNT_INDEX", default_value = "8")]
     pub eth_account_index: u32,
 
-    /// Url we will use for RPC communication with L1.
-    #[clap(long, env = "ESPRESSO_BUILDER_L1_PROVIDER")]
-    pub l1_provider_url: Url,
+    /// Urls we will use for RPC communication with L1.
+    ///
+    /// Multiple providers can be given as a comma-separated list, in order of preference.
+    #[clap(
+        long,
+        env = "ESPRESSO_BUILDER_L1_PROVIDER",
+        value_delimiter = ',',
+        required = true
+    )]
+    pub l1_provider_url: Vec<Url>,
 
     /// Peer nodes use to fetch missing state
     #[clap(long, env = "ESPRESSO_SEQUENCER_STATE_PEERS", value_delimiter = ',')]
@@ -229,8 +236,12 @@ async fn run<V: Versions>(
     let (private_staking_key, private_state_key) = opt.private_keys()?;
 
     let l1_params = L1Params {
-        url: opt.l1_provider_url,
+        urls: opt.l1_provider_url,
         events_max_block_range: 10000,
+        finalized_quorum: 1,
+        ws_url: None,
+        cache_size: DEFAULT_L1_CACHE_CAPACITY,
+        persist_cache: false,
     };
 
     let builder_key_pair = EthKeyPair::from_mnemonic(&opt.eth_mnemonic, opt.eth_account_index)?;
@@ -264,6 +275,7 @@ async fn run<V: Versions>(
         public_api_url: None,
         config_peers: None,
         catchup_backoff: Default::default(),
+        catchup_scoring: Default::default(),
     };
 
     let builder_server_url: Url = format!("http://0.0.0.0:{}", opt.port).parse().unwrap();
@@ -4,7 +4,7 @@ use builder::non_permissioned::{build_instance_state, BuilderConfig};
 use clap::Parser;
 use espresso_types::{
     eth_signature_key::EthKeyPair, parse_duration, FeeVersion, MarketplaceVersion,
-    SequencerVersions, V0_0, V0_1,
+    SequencerVersions, DEFAULT_L1_CACHE_CAPACITY, V0_0, V0_1,
 };
 use hotshot::traits::ValidatedState;
 use hotshot_types::{
@@ -38,9 +38,16 @@ struct NonPermissionedBuilderOptions {
     #[clap(long, env = "ESPRESSO_BUILDER_ETH_ACCOUNT_INDEX", default_value = "8")]
     eth_account_index: u32,
 
-    /// Url we will use for RPC communication with L1.
-    #[clap(long, env = "ESPRESSO_BUILDER_L1_PROVIDER")]
-    l1_provider_url: Url,
+    /// Urls we will use for RPC communication with L1.
+    ///
+    /// Multiple providers can be given as a comma-separated list, in order of preference.
+    #[clap(
+        long,
+        env = "ESPRESSO_BUILDER_L1_PROVIDER",
+        value_delimiter = ',',
+        required = true
+    )]
+    l1_provider_url: Vec<Url>,
 
     /// Peer nodes use to fetch missing state
     #[clap(long, env = "ESPRESSO_SEQUENCER_STATE_PEERS", value_delimiter = ',')]
@@ -126,8 +133,12 @@ async fn run<V: Versions>(
     opt: NonPermissionedBuilderOptions,
 ) -> anyhow::Result<()> {
     let l1_params = L1Params {
-        url: opt.l1_provider_url,
+        urls: opt.l1_provider_url,
         events_max_block_range: 10000,
+        finalized_quorum: 1,
+        ws_url: None,
+        cache_size: DEFAULT_L1_CACHE_CAPACITY,
+        persist_cache: false,
     };
 
     let builder_key_pair = EthKeyPair::from_mnemonic(&opt.eth_mnemonic, opt.eth_account_index)?;
@@ -136,7 +147,9 @@ async fn run<V: Versions>(
     let builder_server_url: Url = format!("http://0.0.0.0:{}", opt.port).parse().unwrap();
 
     let instance_state =
-        build_instance_state::<V>(genesis.chain_config, l1_params, opt.state_peers).unwrap();
+        build_instance_state::<V>(genesis.chain_config, l1_params, opt.state_peers)
+            .unwrap()
+            .with_chain_config_upgrades(genesis.chain_config_upgrades.clone());
 
     let base_fee = genesis.max_base_fee();
     tracing::info!(?base_fee, "base_fee");
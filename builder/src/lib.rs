@@ -45,7 +45,7 @@ use hotshot_stake_table::config::STAKE_TABLE_CAPACITY;
 use hotshot_types::{
     consensus::ConsensusMetricsValue,
     event::LeafInfo,
-    light_client::StateKeyPair,
+    light_client::{StakeTableState, StateKeyPair},
     signature_key::{BLSPrivKey, BLSPubKey},
     traits::{
         block_contents::{
@@ -64,7 +64,7 @@ use sequencer::{
     catchup::StatePeers,
     context::{Consensus, SequencerContext},
     network,
-    state_signature::{static_stake_table_commitment, StakeTableCommitmentType, StateSigner},
+    state_signature::{static_stake_table_state, StateSigner},
     L1Params, NetworkParams, Node, SequencerApiVersion,
 };
 use tide_disco::{app, method::ReadState, App, Url};
@@ -353,10 +353,8 @@ pub mod testing {
         )> {
             let num_staked_nodes = self.num_staked_nodes();
             let mut is_staked = false;
-            let stake_table_commit = static_stake_table_commitment(
-                &self.config.known_nodes_with_stake,
-                Self::total_nodes(),
-            );
+            let stake_table_state =
+                static_stake_table_state(&self.config.known_nodes_with_stake, Self::total_nodes());
 
             join_all((0..self.num_staking_non_staking_nodes()).map(|i| {
                 is_staked = i < num_staked_nodes;
@@ -367,7 +365,7 @@ pub mod testing {
                         .init_node(
                             i,
                             is_staked,
-                            stake_table_commit,
+                            stake_table_state,
                             &NoMetrics,
                             bind_version,
                             persistence,
@@ -384,7 +382,7 @@ pub mod testing {
             &self,
             i: usize,
             is_staked: bool,
-            stake_table_commit: StakeTableCommitmentType,
+            stake_table_state: StakeTableState,
             metrics: &dyn Metrics,
             bind_version: V,
             persistence: P,
@@ -427,7 +425,7 @@ pub mod testing {
                 metrics,
                 i as u64,
                 None,
-                stake_table_commit,
+                stake_table_state,
                 bind_version,
                 persistence,
             )
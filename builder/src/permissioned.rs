@@ -79,7 +79,7 @@ use hotshot_types::{
     consensus::ConsensusMetricsValue,
     data::{fake_commitment, Leaf, ViewNumber},
     event::Event,
-    light_client::StateKeyPair,
+    light_client::{StakeTableState, StateKeyPair},
     signature_key::{BLSPrivKey, BLSPubKey},
     traits::{
         auction_results_provider::AuctionResultsProvider,
@@ -100,7 +100,7 @@ use sequencer::{
     context::{Consensus, SequencerContext},
     genesis::L1Finalized,
     network::{self, libp2p::split_off_peer_id},
-    state_signature::{static_stake_table_commitment, StakeTableCommitmentType, StateSigner},
+    state_signature::{static_stake_table_state, StateSigner},
     Genesis, L1Params, NetworkParams, Node, SequencerApiVersion,
 };
 use surf_disco::Client;
@@ -255,7 +255,12 @@ pub async fn init_node<P: SequencerPersistence, V: Versions>(
         genesis_state.prefund_account(address, amount);
     }
 
-    let l1_client = L1Client::new(l1_params.url, l1_params.events_max_block_range);
+    let mut l1_client = L1Client::from_urls(l1_params.urls, l1_params.events_max_block_range)?
+        .with_finalized_quorum(l1_params.finalized_quorum)
+        .with_cache(l1_params.cache_size, None);
+    if let Some(url) = l1_params.ws_url {
+        l1_client = l1_client.with_ws_subscription(url);
+    }
     let l1_genesis = match genesis.l1_finalized {
         L1Finalized::Block(b) => b,
         L1Finalized::Number { number } => l1_client.wait_for_finalized_block(number).await,
@@ -273,11 +278,12 @@ pub async fn init_node<P: SequencerPersistence, V: Versions>(
         )),
         node_id: node_index,
         upgrades: Default::default(),
+        chain_config_upgrades: genesis.chain_config_upgrades,
         current_version: V::Base::VERSION,
     };
 
-    let stake_table_commit =
-        static_stake_table_commitment(&config.config.known_nodes_with_stake, STAKE_TABLE_CAPACITY);
+    let stake_table_state =
+        static_stake_table_state(&config.config.known_nodes_with_stake, STAKE_TABLE_CAPACITY);
 
     let (hotshot_handle, state_signer) = init_hotshot(
         config.config,
@@ -287,7 +293,7 @@ pub async fn init_node<P: SequencerPersistence, V: Versions>(
         metrics,
         node_index,
         Some(network_params.state_relay_server_url),
-        stake_table_commit,
+        stake_table_state,
         bind_version,
         persistence,
     )
@@ -325,7 +331,7 @@ pub async fn init_hotshot<N: ConnectedNetwork<PubKey>, P: SequencerPersistence,
     metrics: &dyn Metrics,
     node_id: u64,
     state_relay_server: Option<Url>,
-    stake_table_commit: StakeTableCommitmentType,
+    stake_table_state: StakeTableState,
     _: V,
     persistence: P,
 ) -> (Consensus<N, P, V>, StateSigner<SequencerApiVersion>) {
@@ -383,7 +389,7 @@ pub async fn init_hotshot<N: ConnectedNetwork<PubKey>, P: SequencerPersistence,
 
     tracing::debug!("Hotshot handle initialized");
 
-    let mut state_signer = StateSigner::new(state_key_pair, stake_table_commit);
+    let mut state_signer = StateSigner::new(state_key_pair, stake_table_state);
 
     if let Some(url) = state_relay_server {
         state_signer = state_signer.with_relay_server(url);
@@ -1,6 +1,9 @@
 use anyhow::Context;
 use async_std::task::sleep;
-use espresso_types::{FeeAccount, FeeAmount, FeeMerkleTree};
+use espresso_types::{
+    v0_3::TransferNonceMerkleTree, BalanceHistoryEntry, ChainId, FeeAccount, FeeAmount,
+    FeeMerkleTree, Header,
+};
 use ethers::types::Address;
 use jf_merkle_tree::{
     prelude::{MerkleProof, Sha3Node},
@@ -22,6 +25,8 @@ pub type SequencerApiVersion = StaticVersion<0, 1>;
 pub struct SequencerClient(surf_disco::Client<ClientError, SequencerApiVersion>);
 
 pub type FeeMerkleProof = MerkleProof<FeeAmount, FeeAccount, Sha3Node, { FeeMerkleTree::ARITY }>;
+pub type TransferNonceMerkleProof =
+    MerkleProof<u64, FeeAccount, Sha3Node, { TransferNonceMerkleTree::ARITY }>;
 
 impl SequencerClient {
     pub fn new(provider: Url) -> Self {
@@ -37,6 +42,22 @@ impl SequencerClient {
             .context("getting Espresso block height")
     }
 
+    /// Get the chain ID of the network, from the chain config of the latest block.
+    pub async fn get_chain_id(&self) -> anyhow::Result<ChainId> {
+        let height = self.get_height().await?;
+        let header = self
+            .0
+            .get::<Header>(&format!("availability/header/{}", height.saturating_sub(1)))
+            .send()
+            .await
+            .context("getting latest header")?;
+        let chain_config = header
+            .chain_config()
+            .resolve()
+            .context("latest header does not include the chain config")?;
+        Ok(chain_config.chain_id)
+    }
+
     /// Get the Number of Transactions
     pub async fn get_transaction_count(&self) -> anyhow::Result<u64> {
         self.0
@@ -64,6 +85,43 @@ impl SequencerClient {
         address: Address,
         block: Option<u64>,
     ) -> anyhow::Result<FeeAmount> {
+        let proof = self
+            .get_state_proof::<FeeMerkleProof>("fee-state", address, block)
+            .await
+            .context("getting account balance")?;
+
+        // If the element in the Merkle path is missing -- there is no account with this address -- the
+        // balance is defined to be 0.
+        let balance = proof.elem().copied().unwrap_or(0.into());
+        Ok(balance)
+    }
+
+    /// Get the nonce to use in the next transfer from an account.
+    ///
+    /// This does not account for transfers from the account which are still waiting to be
+    /// sequenced.
+    pub async fn get_transfer_nonce(
+        &self,
+        address: Address,
+        block: Option<u64>,
+    ) -> anyhow::Result<u64> {
+        let proof = self
+            .get_state_proof::<TransferNonceMerkleProof>("transfer-nonce-state", address, block)
+            .await
+            .context("getting transfer nonce")?;
+
+        // An account which has never sent a transfer has no entry, and its first nonce is 0.
+        Ok(proof.elem().copied().unwrap_or(0))
+    }
+
+    /// Get the Merkle path for an account in one of the merklized state modules, at a given block
+    /// height, defaulting to the latest block.
+    async fn get_state_proof<P: DeserializeOwned>(
+        &self,
+        module: &str,
+        address: Address,
+        block: Option<u64>,
+    ) -> anyhow::Result<P> {
         // Get the block height to query at, defaulting to the latest block.
         let block = if let Some(block) = block {
             block - 1
@@ -76,36 +134,48 @@ impl SequencerClient {
                 - 1
         };
 
-        // Download the Merkle path for this fee account at the specified block height. Transient errors
+        // Download the Merkle path for this account at the specified block height. Transient errors
         // are possible (for example, if we are fetching from the latest block, the block height might
         // get incremented slightly before the state becomes available) so retry a few times.
         let mut retry = 0;
         let max_retries = 5;
-        let proof = loop {
-            tracing::debug!(%address, block, retry, "fetching Espresso balance");
+        loop {
+            tracing::debug!(%address, block, retry, module, "fetching Merkle path");
             match self
                 .0
-                .get::<FeeMerkleProof>(&format!("fee-state/{block}/{address:#x}"))
+                .get::<P>(&format!("{module}/{block}/{address:#x}"))
                 .send()
                 .await
             {
-                Ok(proof) => break proof,
+                Ok(proof) => break Ok(proof),
                 Err(err) => {
-                    tracing::warn!("error getting account balance: {err:#}");
+                    tracing::warn!("error getting Merkle path from {module}: {err:#}");
                     retry += 1;
 
                     if retry == max_retries {
-                        return Err(err).context("getting account balance");
+                        return Err(err.into());
                     } else {
                         sleep(Duration::from_millis(200)).await;
                     }
                 }
             }
-        };
+        }
+    }
 
-        // If the element in the Merkle path is missing -- there is no account with this address -- the
-        // balance is defined to be 0.
-        let balance = proof.elem().copied().unwrap_or(0.into());
-        Ok(balance)
+    /// Get every change to the balance of an account in blocks with heights in `from..until`.
+    ///
+    /// Each entry includes the balance after the change, with a proof, and the events which caused
+    /// it. This requires a node running the fee history API.
+    pub async fn get_balance_history(
+        &self,
+        address: Address,
+        from: u64,
+        until: u64,
+    ) -> anyhow::Result<Vec<BalanceHistoryEntry>> {
+        self.0
+            .get(&format!("fee-history/balance/{address:#x}/{from}/{until}"))
+            .send()
+            .await
+            .context("getting balance history")
     }
 }
@@ -345,6 +345,20 @@ pub mod light_client {
                                     ),
                                 ),
                             },
+                            ::ethers::core::abi::ethabi::Param {
+                                name: ::std::borrow::ToOwned::to_owned("nextStakeTable"),
+                                kind: ::ethers::core::abi::ethabi::ParamType::Tuple(::std::vec![
+                                    ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
+                                    ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
+                                    ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
+                                    ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
+                                ],),
+                                internal_type: ::core::option::Option::Some(
+                                    ::std::borrow::ToOwned::to_owned(
+                                        "struct LightClient.StakeTableState",
+                                    ),
+                                ),
+                            },
                             ::ethers::core::abi::ethabi::Param {
                                 name: ::std::borrow::ToOwned::to_owned("proof"),
                                 kind: ::ethers::core::abi::ethabi::ParamType::Tuple(::std::vec![
@@ -631,6 +645,45 @@ pub mod light_client {
                         state_mutability: ::ethers::core::abi::ethabi::StateMutability::Payable,
                     },],
                 ),
+                (
+                    ::std::borrow::ToOwned::to_owned("votingStakeTableState"),
+                    ::std::vec![::ethers::core::abi::ethabi::Function {
+                        name: ::std::borrow::ToOwned::to_owned("votingStakeTableState",),
+                        inputs: ::std::vec![],
+                        outputs: ::std::vec![
+                            ::ethers::core::abi::ethabi::Param {
+                                name: ::std::borrow::ToOwned::to_owned("threshold"),
+                                kind: ::ethers::core::abi::ethabi::ParamType::Uint(256usize,),
+                                internal_type: ::core::option::Option::Some(
+                                    ::std::borrow::ToOwned::to_owned("uint256"),
+                                ),
+                            },
+                            ::ethers::core::abi::ethabi::Param {
+                                name: ::std::borrow::ToOwned::to_owned("blsKeyComm"),
+                                kind: ::ethers::core::abi::ethabi::ParamType::Uint(256usize,),
+                                internal_type: ::core::option::Option::Some(
+                                    ::std::borrow::ToOwned::to_owned("BN254.ScalarField"),
+                                ),
+                            },
+                            ::ethers::core::abi::ethabi::Param {
+                                name: ::std::borrow::ToOwned::to_owned("schnorrKeyComm"),
+                                kind: ::ethers::core::abi::ethabi::ParamType::Uint(256usize,),
+                                internal_type: ::core::option::Option::Some(
+                                    ::std::borrow::ToOwned::to_owned("BN254.ScalarField"),
+                                ),
+                            },
+                            ::ethers::core::abi::ethabi::Param {
+                                name: ::std::borrow::ToOwned::to_owned("amountComm"),
+                                kind: ::ethers::core::abi::ethabi::ParamType::Uint(256usize,),
+                                internal_type: ::core::option::Option::Some(
+                                    ::std::borrow::ToOwned::to_owned("BN254.ScalarField"),
+                                ),
+                            },
+                        ],
+                        constant: ::core::option::Option::None,
+                        state_mutability: ::ethers::core::abi::ethabi::StateMutability::View,
+                    },],
+                ),
             ]),
             events: ::core::convert::From::from([
                 (
@@ -1063,14 +1116,15 @@ pub mod light_client {
                 .method_hash([224, 48, 51, 1], (block_number, threshold))
                 .expect("method not found (this should never happen)")
         }
-        ///Calls the contract's `newFinalizedState` (0x2063d4f7) function
+        ///Calls the contract's `newFinalizedState` (0x757c37ad) function
         pub fn new_finalized_state(
             &self,
             new_state: LightClientState,
+            next_stake_table: StakeTableState,
             proof: PlonkProof,
         ) -> ::ethers::contract::builders::ContractCall<M, ()> {
             self.0
-                .method_hash([32, 99, 212, 247], (new_state, proof))
+                .method_hash([117, 124, 55, 173], (new_state, next_stake_table, proof))
                 .expect("method not found (this should never happen)")
         }
         ///Calls the contract's `owner` (0x8da5cb5b) function
@@ -1166,6 +1220,22 @@ pub mod light_client {
                 .method_hash([79, 30, 242, 134], (new_implementation, data))
                 .expect("method not found (this should never happen)")
         }
+        ///Calls the contract's `votingStakeTableState` (0x0625e19b) function
+        pub fn voting_stake_table_state(
+            &self,
+        ) -> ::ethers::contract::builders::ContractCall<
+            M,
+            (
+                ::ethers::core::types::U256,
+                ::ethers::core::types::U256,
+                ::ethers::core::types::U256,
+                ::ethers::core::types::U256,
+            ),
+        > {
+            self.0
+                .method_hash([6, 37, 225, 155], ())
+                .expect("method not found (this should never happen)")
+        }
         ///Gets the contract's `Initialized` event
         pub fn initialized_filter(
             &self,
@@ -2386,7 +2456,7 @@ pub mod light_client {
         pub block_number: ::ethers::core::types::U256,
         pub threshold: ::ethers::core::types::U256,
     }
-    ///Container type for all input parameters for the `newFinalizedState` function with signature `newFinalizedState((uint64,uint64,uint256),((uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256))` and selector `0x2063d4f7`
+    ///Container type for all input parameters for the `newFinalizedState` function with signature `newFinalizedState((uint64,uint64,uint256),(uint256,uint256,uint256,uint256),((uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256))` and selector `0x757c37ad`
     #[derive(
         Clone,
         ::ethers::contract::EthCall,
@@ -2396,10 +2466,11 @@ pub mod light_client {
     )]
     #[ethcall(
         name = "newFinalizedState",
-        abi = "newFinalizedState((uint64,uint64,uint256),((uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256))"
+        abi = "newFinalizedState((uint64,uint64,uint256),(uint256,uint256,uint256,uint256),((uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),(uint256,uint256),uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256))"
     )]
     pub struct NewFinalizedStateCall {
         pub new_state: LightClientState,
+        pub next_stake_table: StakeTableState,
         pub proof: PlonkProof,
     }
     ///Container type for all input parameters for the `owner` function with signature `owner()` and selector `0x8da5cb5b`
@@ -2585,6 +2656,21 @@ pub mod light_client {
         pub new_implementation: ::ethers::core::types::Address,
         pub data: ::ethers::core::types::Bytes,
     }
+    ///Container type for all input parameters for the `votingStakeTableState` function with signature `votingStakeTableState()` and selector `0x0625e19b`
+    #[derive(
+        Clone,
+        ::ethers::contract::EthCall,
+        ::ethers::contract::EthDisplay,
+        serde::Serialize,
+        serde::Deserialize,
+        Default,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+    )]
+    #[ethcall(name = "votingStakeTableState", abi = "votingStakeTableState()")]
+    pub struct VotingStakeTableStateCall;
     ///Container type for all of the contract's call
     #[derive(Clone, ::ethers::contract::EthAbiType, serde::Serialize, serde::Deserialize)]
     pub enum LightClientCalls {
@@ -2611,6 +2697,7 @@ pub mod light_client {
         StateHistoryRetentionPeriod(StateHistoryRetentionPeriodCall),
         TransferOwnership(TransferOwnershipCall),
         UpgradeToAndCall(UpgradeToAndCallCall),
+        VotingStakeTableState(VotingStakeTableStateCall),
     }
     impl ::ethers::core::abi::AbiDecode for LightClientCalls {
         fn decode(
@@ -2724,6 +2811,11 @@ pub mod light_client {
             {
                 return Ok(Self::UpgradeToAndCall(decoded));
             }
+            if let Ok(decoded) =
+                <VotingStakeTableStateCall as ::ethers::core::abi::AbiDecode>::decode(data)
+            {
+                return Ok(Self::VotingStakeTableState(decoded));
+            }
             Err(::ethers::core::abi::Error::InvalidData.into())
         }
     }
@@ -2779,6 +2871,9 @@ pub mod light_client {
                 }
                 Self::TransferOwnership(element) => ::ethers::core::abi::AbiEncode::encode(element),
                 Self::UpgradeToAndCall(element) => ::ethers::core::abi::AbiEncode::encode(element),
+                Self::VotingStakeTableState(element) => {
+                    ::ethers::core::abi::AbiEncode::encode(element)
+                }
             }
         }
     }
@@ -2812,6 +2907,7 @@ pub mod light_client {
                 Self::StateHistoryRetentionPeriod(element) => ::core::fmt::Display::fmt(element, f),
                 Self::TransferOwnership(element) => ::core::fmt::Display::fmt(element, f),
                 Self::UpgradeToAndCall(element) => ::core::fmt::Display::fmt(element, f),
+                Self::VotingStakeTableState(element) => ::core::fmt::Display::fmt(element, f),
             }
         }
     }
@@ -2930,6 +3026,11 @@ pub mod light_client {
             Self::UpgradeToAndCall(value)
         }
     }
+    impl ::core::convert::From<VotingStakeTableStateCall> for LightClientCalls {
+        fn from(value: VotingStakeTableStateCall) -> Self {
+            Self::VotingStakeTableState(value)
+        }
+    }
     ///Container type for all return fields from the `UPGRADE_INTERFACE_VERSION` function with signature `UPGRADE_INTERFACE_VERSION()` and selector `0xad3cb1cc`
     #[derive(
         Clone,
@@ -3165,4 +3266,23 @@ pub mod light_client {
         Hash,
     )]
     pub struct StateHistoryRetentionPeriodReturn(pub u32);
+    ///Container type for all return fields from the `votingStakeTableState` function with signature `votingStakeTableState()` and selector `0x0625e19b`
+    #[derive(
+        Clone,
+        ::ethers::contract::EthAbiType,
+        ::ethers::contract::EthAbiCodec,
+        serde::Serialize,
+        serde::Deserialize,
+        Default,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+    )]
+    pub struct VotingStakeTableStateReturn {
+        pub threshold: ::ethers::core::types::U256,
+        pub bls_key_comm: ::ethers::core::types::U256,
+        pub schnorr_key_comm: ::ethers::core::types::U256,
+        pub amount_comm: ::ethers::core::types::U256,
+    }
 }
@@ -26,8 +26,8 @@ fn main() {
 
     let mut domain_elements_str = "".to_owned();
 
-    // Generates the domain elements for 8 inputs: 1, g, g^2,...,g^7
-    for i in 0..8 {
+    // Generates the domain elements for 11 inputs: 1, g, g^2,...,g^10
+    for i in 0..11 {
         let mut element_fr = domain.group_gen;
         element_fr = element_fr.pow([i]);
 
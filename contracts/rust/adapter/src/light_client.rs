@@ -10,7 +10,7 @@ use ethers::{
     prelude::{AbiError, EthAbiCodec, EthAbiType},
     types::U256,
 };
-use hotshot_types::light_client::{GenericLightClientState, GenericStakeTableState, PublicInput};
+use hotshot_types::light_client::{GenericLightClientState, GenericStakeTableState};
 
 /// Intermediate representations for `LightClientState` in Solidity
 #[derive(Clone, Debug, EthAbiType, EthAbiCodec, PartialEq)]
@@ -73,16 +73,6 @@ impl<F: PrimeField> From<GenericLightClientState<F>> for ParsedLightClientState
     }
 }
 
-impl From<PublicInput> for ParsedLightClientState {
-    fn from(pi: PublicInput) -> Self {
-        Self {
-            view_num: field_to_u256(pi.view_number()).as_u64(),
-            block_height: field_to_u256(pi.block_height()).as_u64(),
-            block_comm_root: field_to_u256(pi.block_comm_root()),
-        }
-    }
-}
-
 impl From<(u64, u64, U256)> for ParsedLightClientState {
     fn from(s: (u64, u64, U256)) -> Self {
         Self {
@@ -125,7 +125,7 @@ fn main() {
 
             let log_size = cli.args[0].parse::<u32>().unwrap();
             let zeta = u256_to_field::<Fr>(cli.args[1].parse::<U256>().unwrap());
-            let pi_u256: [U256; 7] = AbiDecode::decode_hex(&cli.args[2]).unwrap();
+            let pi_u256: [U256; 11] = AbiDecode::decode_hex(&cli.args[2]).unwrap();
             let pi: Vec<Fr> = pi_u256.into_iter().map(u256_to_field).collect();
 
             let verifier = Verifier::<Bn254>::new(2u32.pow(log_size) as usize).unwrap();
@@ -257,7 +257,7 @@ fn main() {
             }
 
             let vk = cli.args[0].parse::<ParsedVerifyingKey>().unwrap().into();
-            let pi_u256: [U256; 7] = AbiDecode::decode_hex(&cli.args[1]).unwrap();
+            let pi_u256: [U256; 11] = AbiDecode::decode_hex(&cli.args[1]).unwrap();
             let pi: Vec<Fr> = pi_u256.into_iter().map(u256_to_field).collect();
             let proof: Proof<Bn254> = cli.args[2].parse::<ParsedPlonkProof>().unwrap().into();
             let msg = {
@@ -295,8 +295,8 @@ fn main() {
             .is_ok());
 
             let vk_parsed: ParsedVerifyingKey = vk.into();
-            let mut pi_parsed = [U256::default(); 7];
-            assert_eq!(public_input.len(), 7);
+            let mut pi_parsed = [U256::default(); 11];
+            assert_eq!(public_input.len(), 11);
             for (i, pi) in public_input.into_iter().enumerate() {
                 pi_parsed[i] = field_to_u256(pi);
             }
@@ -407,7 +407,7 @@ fn main() {
                 ledger.elapse_with_block();
 
                 let (pi, proof) = ledger.gen_state_proof();
-                new_states.push(pi.into());
+                new_states.push(pi.lc_state().clone().into());
                 proofs.push(proof.into());
             }
 
@@ -435,7 +435,7 @@ fn main() {
 
             let res = if require_valid_proof {
                 let (state, proof) = ledger.gen_state_proof();
-                let state_parsed: ParsedLightClientState = state.into();
+                let state_parsed: ParsedLightClientState = state.lc_state().clone().into();
                 let proof_parsed: ParsedPlonkProof = proof.into();
                 (state_parsed, proof_parsed)
             } else {
@@ -3,6 +3,7 @@
 pragma solidity ^0.8.0;
 
 import "forge-std/Script.sol";
+import { BN254 } from "bn254/BN254.sol";
 import { IPlonkVerifier as V } from "../src/interfaces/IPlonkVerifier.sol";
 import { LightClient as LC } from "../src/LightClient.sol";
 import { LightClientMock as LCMock } from "../test/mocks/LightClientMock.sol";
@@ -28,7 +29,17 @@ contract CallNewFinalizedState is Script {
 
         LCMock lc = LCMock(lcContractAddress);
 
-        lc.newFinalizedState(states[0], proofs[0]);
+        // The mock ledger keeps its stake table, so every update hands over to the genesis one.
+        (
+            uint256 threshold,
+            BN254.ScalarField blsKeyComm,
+            BN254.ScalarField schnorrKeyComm,
+            BN254.ScalarField amountComm
+        ) = lc.genesisStakeTableState();
+        LC.StakeTableState memory nextStakeTable =
+            LC.StakeTableState(threshold, blsKeyComm, schnorrKeyComm, amountComm);
+
+        lc.newFinalizedState(states[0], nextStakeTable, proofs[0]);
 
         vm.stopBroadcast();
     }
@@ -72,6 +72,12 @@ contract LightClient is Initializable, OwnableUpgradeable, UUPSUpgradeable {
     /// commitments
     StateHistoryCommitment[] public stateHistoryCommitments;
 
+    /// @notice the stake table the next state update is verified against, as committed to by the
+    /// previous state update
+    /// @dev contracts upgraded from a version without this field have a zero `threshold` here
+    /// until their first update, and verify against `genesisStakeTableState` instead
+    StakeTableState public votingStakeTableState;
+
     // === Data Structure ===
     //
     /// @notice The finalized HotShot state (as the digest of the entire HotShot state)
@@ -205,6 +211,7 @@ contract LightClient is Initializable, OwnableUpgradeable, UUPSUpgradeable {
         }
         genesisState = _genesis;
         genesisStakeTableState = _genesisStakeTableState;
+        votingStakeTableState = _genesisStakeTableState;
         finalizedState = _genesis;
 
         stateHistoryRetentionPeriod = _stateHistoryRetentionPeriod;
@@ -223,12 +230,15 @@ contract LightClient is Initializable, OwnableUpgradeable, UUPSUpgradeable {
     /// can call this function
     /// @dev the state history for `stateHistoryRetentionPeriod` L1 blocks are also recorded in the
     /// `stateHistoryCommitments` array
-    /// @notice While `newState.stakeTable*` refers to the (possibly) new stake table states,
-    /// the entire `newState` needs to be signed by stakers in `finalizedState`
+    /// @notice `newState` and `nextStakeTable` are signed together by the stakers of the current
+    /// voting stake table, and `nextStakeTable` becomes the voting stake table for the following
+    /// update. This is how the contract follows changes of the validator set.
     /// @param newState new light client state
+    /// @param nextStakeTable the stake table which verifies the next state update
     /// @param proof PlonkProof
     function newFinalizedState(
         LightClientState memory newState,
+        StakeTableState memory nextStakeTable,
         IPlonkVerifier.PlonkProof memory proof
     ) external virtual {
         //revert if we're in permissionedProver mode and the permissioned prover has not been set
@@ -244,12 +254,21 @@ contract LightClient is Initializable, OwnableUpgradeable, UUPSUpgradeable {
         }
         // format validity check
         BN254.validateScalarField(newState.blockCommRoot);
+        BN254.validateScalarField(nextStakeTable.blsKeyComm);
+        BN254.validateScalarField(nextStakeTable.schnorrKeyComm);
+        BN254.validateScalarField(nextStakeTable.amountComm);
+        // a zero threshold would let anyone prove the next update
+        if (nextStakeTable.threshold == 0) {
+            revert InvalidArgs();
+        }
 
         // check plonk proof
-        verifyProof(newState, proof);
+        verifyProof(newState, nextStakeTable, proof);
 
-        // upon successful verification, update the latest finalized state
+        // upon successful verification, update the latest finalized state and hand over to the
+        // next stake table
         finalizedState = newState;
+        votingStakeTableState = nextStakeTable;
 
         updateStateHistory(uint64(block.number), uint64(block.timestamp), newState);
 
@@ -258,27 +277,47 @@ contract LightClient is Initializable, OwnableUpgradeable, UUPSUpgradeable {
 
     /// @notice Verify the Plonk proof, marked as `virtual` for easier testing as we can swap VK
     /// used in inherited contracts.
-    function verifyProof(LightClientState memory state, IPlonkVerifier.PlonkProof memory proof)
-        internal
-        virtual
-    {
+    function verifyProof(
+        LightClientState memory state,
+        StakeTableState memory nextStakeTable,
+        IPlonkVerifier.PlonkProof memory proof
+    ) internal virtual {
         IPlonkVerifier.VerifyingKey memory vk = VkLib.getVk();
-
-        // Prepare the public input
-        uint256[7] memory publicInput;
-        publicInput[0] = uint256(state.viewNum);
-        publicInput[1] = uint256(state.blockHeight);
-        publicInput[2] = BN254.ScalarField.unwrap(state.blockCommRoot);
-        publicInput[3] = BN254.ScalarField.unwrap(genesisStakeTableState.blsKeyComm);
-        publicInput[4] = BN254.ScalarField.unwrap(genesisStakeTableState.schnorrKeyComm);
-        publicInput[5] = BN254.ScalarField.unwrap(genesisStakeTableState.amountComm);
-        publicInput[6] = genesisStakeTableState.threshold;
+        uint256[11] memory publicInput = preparePublicInput(state, nextStakeTable);
 
         if (!PlonkVerifier.verify(vk, publicInput, proof)) {
             revert InvalidProof();
         }
     }
 
+    /// @notice the stake table the next state update is verified against
+    function currentVotingStakeTable() internal view returns (StakeTableState memory) {
+        if (votingStakeTableState.threshold == 0) {
+            return genesisStakeTableState;
+        }
+        return votingStakeTableState;
+    }
+
+    /// @notice Prepare the public input of the state update circuit: the new state, followed by the
+    /// voting stake table and the next stake table, each as its commitments and threshold.
+    function preparePublicInput(
+        LightClientState memory state,
+        StakeTableState memory nextStakeTable
+    ) internal view returns (uint256[11] memory publicInput) {
+        StakeTableState memory votingStakeTable = currentVotingStakeTable();
+        publicInput[0] = uint256(state.viewNum);
+        publicInput[1] = uint256(state.blockHeight);
+        publicInput[2] = BN254.ScalarField.unwrap(state.blockCommRoot);
+        publicInput[3] = BN254.ScalarField.unwrap(votingStakeTable.blsKeyComm);
+        publicInput[4] = BN254.ScalarField.unwrap(votingStakeTable.schnorrKeyComm);
+        publicInput[5] = BN254.ScalarField.unwrap(votingStakeTable.amountComm);
+        publicInput[6] = votingStakeTable.threshold;
+        publicInput[7] = BN254.ScalarField.unwrap(nextStakeTable.blsKeyComm);
+        publicInput[8] = BN254.ScalarField.unwrap(nextStakeTable.schnorrKeyComm);
+        publicInput[9] = BN254.ScalarField.unwrap(nextStakeTable.amountComm);
+        publicInput[10] = nextStakeTable.threshold;
+    }
+
     /// @notice set the permissionedProverMode to true and set the permissionedProver to the
     /// non-zero address provided
     /// @dev this function can also be used to update the permissioned prover once it's a different
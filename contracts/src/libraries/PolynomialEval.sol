@@ -14,7 +14,7 @@ library PolynomialEval {
     struct EvalDomain {
         uint256 logSize; // log_2(self.size)
         uint256 sizeInv; // Inverse of the size in the field
-        uint256[7] elements; // 1, g, g^2, ..., g^6
+        uint256[11] elements; // 1, g, g^2, ..., g^10
     }
 
     /// @dev stores vanishing poly, lagrange at 1, and Public input poly
@@ -40,7 +40,11 @@ library PolynomialEval {
                     0x86812a00ac43ea801669c640171203c41a496671bfbc065ac8db24d52cf31e5,
                     0x2d965651cdd9e4811f4e51b80ddca8a8b4a93ee17420aae6adaa01c2617c6e85,
                     0x12597a56c2e438620b9041b98992ae0d4e705b780057bf7766a2767cece16e1d,
-                    0x2d94117cd17bcf1290fd67c01155dd40807857dff4a5a0b4dc67befa8aa34fd
+                    0x2d94117cd17bcf1290fd67c01155dd40807857dff4a5a0b4dc67befa8aa34fd,
+                    0x15ee2475bee517c4ee05e51fa1ee7312a8373a0b13db8c51baf04cb2e99bd2bd,
+                    0x6fab49b869ae62001deac878b2667bd31bf3e28e3a2d764aa49b8d9bbdd310,
+                    0x2e856bf6d037708ffa4c06d4d8820f45ccadce9c5a6d178cbd573f82e0f97011,
+                    0x1407eee35993f2b1ad5ec6d9b8950ca3af33135d06037f871c5e33bf566dd7b4
                 ]
             );
         } else if (domainSize == 1048576) {
@@ -54,7 +58,11 @@ library PolynomialEval {
                     0x2087ea2cd664278608fb0ebdb820907f598502c81b6690c185e2bf15cb935f42,
                     0x19ddbcaf3a8d46c15c0176fbb5b95e4dc57088ff13f4d1bd84c6bfa57dcdc0e0,
                     0x5a2c85cfc591789605cae818e37dd4161eef9aa666bec6fe4288d09e6d23418,
-                    0x11f70e5363258ff4f0d716a653e1dc41f1c64484d7f4b6e219d6377614a3905c
+                    0x11f70e5363258ff4f0d716a653e1dc41f1c64484d7f4b6e219d6377614a3905c,
+                    0x29e84143f5870d4776a92df8da8c6c9303d59088f37ba85f40cf6fd14265b4bc,
+                    0x1bf82deba7d74902c3708cc6e70e61f30512eca95655210e276e5858ce8f58e5,
+                    0x22b94b2e2b0043d04e662d5ec018ea1c8a99a23a62c9eb46f0318f6a194985f0,
+                    0x29969d8d5363bef1101a68e446a14e1da7ba9294e142a146a980fddb4d4d41a5
                 ]
             );
         }
@@ -70,7 +78,11 @@ library PolynomialEval {
                     0x1277ae6415f0ef18f2ba5fb162c39eb7311f386e2d26d64401f4a25da77c253b,
                     0x2b337de1c8c14f22ec9b9e2f96afef3652627366f8170a0a948dad4ac1bd5e80,
                     0x2fbd4dd2976be55d1a163aa9820fb88dfac5ddce77e1872e90632027327a5ebe,
-                    0x107aab49e65a67f9da9cd2abf78be38bd9dc1d5db39f81de36bcfa5b4b039043
+                    0x107aab49e65a67f9da9cd2abf78be38bd9dc1d5db39f81de36bcfa5b4b039043,
+                    0xe14b6364a47e9c4284a9f80a5fc41cd212b0d4dbf8a5703770a40a9a343990,
+                    0x30644e72e131a029048b6e193fd841045cea24f6fd736bec231204708f703636,
+                    0x22399c34139bffada8de046aac50c9628e3517a3a452795364e777cd65bb9f48,
+                    0x2290ee31c482cf92b79b1944db1c0147635e9004db8c3b9d13644bef31ec3bd3
                 ]
             );
         } else {
@@ -141,7 +153,7 @@ library PolynomialEval {
     /// @dev Evaluate public input polynomial at point `zeta`.
     function evaluatePiPoly(
         EvalDomain memory self,
-        uint256[7] memory pi,
+        uint256[11] memory pi,
         uint256 zeta,
         uint256 vanishingPolyEval
     ) internal view returns (uint256 res) {
@@ -149,7 +161,7 @@ library PolynomialEval {
 
         if (vanishingPolyEval == 0) {
             uint256 group = 1;
-            for (uint256 i = 0; i < 7; i++) {
+            for (uint256 i = 0; i < 11; i++) {
                 if (zeta == group) {
                     return pi[i];
                 }
@@ -190,7 +202,7 @@ library PolynomialEval {
         // n(n - 1) to 3n
         //
         // credit: @shresthagrawal and @jakovmitrovski from CommonPrefix
-        uint256[7] memory suffix;
+        uint256[11] memory suffix;
 
         // Assume we have [a, b, c, d] where a = zeta - g^0, b = zeta - g^1, ...
         //
@@ -198,15 +210,15 @@ library PolynomialEval {
         // suffix[length - 1] = 1
         // suffix = [dcb, dc, d, 1]
         assembly {
-            let suffixPtr := add(suffix, mul(6, 0x20))
-            let localDomainElementsPtr := add(mload(add(self, 0x40)), mul(6, 0x20))
+            let suffixPtr := add(suffix, mul(10, 0x20))
+            let localDomainElementsPtr := add(mload(add(self, 0x40)), mul(10, 0x20))
             let currentElementSuffix := 1
 
             // Last element of suffix is set to 1
             mstore(suffixPtr, currentElementSuffix)
 
             // Calculate prefix and suffix products
-            for { let i := 1 } lt(i, 7) { i := add(i, 1) } {
+            for { let i := 1 } lt(i, 11) { i := add(i, 1) } {
                 // move suffix pointer
                 suffixPtr := sub(suffixPtr, 0x20)
 
@@ -232,7 +244,7 @@ library PolynomialEval {
 
             // Compute the sum term \sum_{i=0}^{length} currentElementPrefix * suffix[i] * pi[i] *
             // g^i
-            for { let i := 0 } lt(i, 7) { i := add(i, 1) } {
+            for { let i := 0 } lt(i, 11) { i := add(i, 1) } {
                 // sum += currentElementPrefix * suffix[i] * pi[i] * g^i
                 let currentTerm :=
                     mulmod(
@@ -272,7 +284,7 @@ library PolynomialEval {
     }
 
     /// @dev compute the EvalData for a given domain and a challenge zeta
-    function evalDataGen(EvalDomain memory self, uint256 zeta, uint256[7] memory publicInput)
+    function evalDataGen(EvalDomain memory self, uint256 zeta, uint256[11] memory publicInput)
         internal
         view
         returns (EvalData memory evalData)
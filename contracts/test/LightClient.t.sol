@@ -189,7 +189,7 @@ contract LightClient_permissionedProver_Test is LightClientCommonTest {
         vm.expectEmit(true, true, true, true);
         emit LC.NewState(newState.viewNum, newState.blockHeight, newState.blockCommRoot);
         vm.prank(makeAddr("randomUser"));
-        lc.newFinalizedState(newState, newProof);
+        lc.newFinalizedState(newState, genesisStakeTableState, newProof);
     }
 
     function test_UpdatePermissionedProverWhenPermissionedProverModeDisabled() external {
@@ -250,13 +250,13 @@ contract LightClient_permissionedProver_Test is LightClientCommonTest {
         //confirm that the old prover doesn't work
         vm.prank(oldPermissionedProver);
         vm.expectRevert(LC.ProverNotPermissioned.selector);
-        lc.newFinalizedState(newState, newProof);
+        lc.newFinalizedState(newState, genesisStakeTableState, newProof);
 
         //confirm that the new prover works
         vm.prank(prover2);
         vm.expectEmit(true, true, true, true);
         emit LC.NewState(newState.viewNum, newState.blockHeight, newState.blockCommRoot);
-        lc.newFinalizedState(newState, newProof);
+        lc.newFinalizedState(newState, genesisStakeTableState, newProof);
     }
 
     function test_RevertWhen_sameProverSentInUpdate() public {
@@ -282,13 +282,13 @@ contract LightClient_permissionedProver_Test is LightClientCommonTest {
     function test_RevertWhen_ProverDoesNotHavePermissions() external {
         vm.expectRevert(LC.ProverNotPermissioned.selector);
         vm.prank(makeAddr("ProverWithNoPermissions"));
-        lc.newFinalizedState(newState, newProof);
+        lc.newFinalizedState(newState, genesisStakeTableState, newProof);
     }
 
     function test_RevertWhen_ProverAddressNotPermissionedEvenIfAdminAddress() external {
         vm.expectRevert(LC.ProverNotPermissioned.selector);
         vm.prank(admin);
-        lc.newFinalizedState(newState, newProof);
+        lc.newFinalizedState(newState, genesisStakeTableState, newProof);
     }
 }
 
@@ -312,7 +312,49 @@ contract LightClient_newFinalizedState_Test is LightClientCommonTest {
         vm.expectEmit(true, true, true, true);
         emit LC.NewState(states[0].viewNum, states[0].blockHeight, states[0].blockCommRoot);
         vm.prank(permissionedProver);
-        lc.newFinalizedState(states[0], proofs[0]);
+        lc.newFinalizedState(states[0], genesisStakeTableState, proofs[0]);
+    }
+
+    /// @dev Test that the stake table committed to by an update verifies the next update
+    function test_NextStakeTableBecomesVoting() external {
+        string[] memory cmds = new string[](3);
+        cmds[0] = "diff-test";
+        cmds[1] = "mock-consecutive-finalized-states";
+        cmds[2] = vm.toString(STAKE_TABLE_CAPACITY / 2);
+
+        bytes memory result = vm.ffi(cmds);
+        (LC.LightClientState[] memory states, V.PlonkProof[] memory proofs) =
+            abi.decode(result, (LC.LightClientState[], V.PlonkProof[]));
+
+        vm.startPrank(permissionedProver);
+        lc.newFinalizedState(states[0], genesisStakeTableState, proofs[0]);
+        (
+            uint256 threshold,
+            BN254.ScalarField blsKeyComm,
+            BN254.ScalarField schnorrKeyComm,
+            BN254.ScalarField amountComm
+        ) = lc.votingStakeTableState();
+        assertEq(threshold, genesisStakeTableState.threshold);
+        assertEq(
+            BN254.ScalarField.unwrap(blsKeyComm),
+            BN254.ScalarField.unwrap(genesisStakeTableState.blsKeyComm)
+        );
+        assertEq(
+            BN254.ScalarField.unwrap(schnorrKeyComm),
+            BN254.ScalarField.unwrap(genesisStakeTableState.schnorrKeyComm)
+        );
+        assertEq(
+            BN254.ScalarField.unwrap(amountComm),
+            BN254.ScalarField.unwrap(genesisStakeTableState.amountComm)
+        );
+
+        // The next update is only accepted with the stake table its signers committed to.
+        LC.StakeTableState memory otherStakeTable = genesisStakeTableState;
+        otherStakeTable.threshold = genesisStakeTableState.threshold + 1;
+        vm.expectRevert(LC.InvalidProof.selector);
+        lc.newFinalizedState(states[1], otherStakeTable, proofs[1]);
+        lc.newFinalizedState(states[1], genesisStakeTableState, proofs[1]);
+        vm.stopPrank();
     }
 
     /// @dev Test happy path for (the number of states + 1) consecutive new finalized blocks
@@ -355,7 +397,7 @@ contract LightClient_newFinalizedState_Test is LightClientCommonTest {
             vm.expectEmit(true, true, true, true);
             emit LC.NewState(states[i].viewNum, states[i].blockHeight, states[i].blockCommRoot);
             vm.prank(permissionedProver);
-            lc.newFinalizedState(states[i], proofs[i]);
+            lc.newFinalizedState(states[i], genesisStakeTableState, proofs[i]);
 
             (viewNum, blockHeight, blockCommRoot) = lc.finalizedState();
             assertEq(viewNum, states[i].viewNum);
@@ -386,7 +428,7 @@ contract LightClient_newFinalizedState_Test is LightClientCommonTest {
         vm.expectEmit(true, true, true, true);
         emit LC.NewState(state.viewNum, state.blockHeight, state.blockCommRoot);
         vm.prank(permissionedProver);
-        lc.newFinalizedState(state, proof);
+        lc.newFinalizedState(state, genesisStakeTableState, proof);
     }
 
     /// @dev Test unhappy path when a valid but oudated finalized state is submitted
@@ -409,13 +451,13 @@ contract LightClient_newFinalizedState_Test is LightClientCommonTest {
 
         // outdated view num
         vm.expectRevert(LC.OutdatedState.selector);
-        lc.newFinalizedState(newState, proof);
+        lc.newFinalizedState(newState, genesisStakeTableState, proof);
 
         // outdated block height
         state.viewNum = genesis.viewNum;
         state.blockHeight = numBlockSkipped + 1;
         vm.expectRevert(LC.OutdatedState.selector);
-        lc.newFinalizedState(newState, proof);
+        lc.newFinalizedState(newState, genesisStakeTableState, proof);
         vm.stopPrank();
     }
 
@@ -438,8 +480,20 @@ contract LightClient_newFinalizedState_Test is LightClientCommonTest {
         vm.startPrank(permissionedProver);
         badState.blockCommRoot = BN254.ScalarField.wrap(BN254.R_MOD);
         vm.expectRevert("Bn254: invalid scalar field");
-        lc.newFinalizedState(badState, proof);
+        lc.newFinalizedState(badState, genesisStakeTableState, proof);
         badState.blockCommRoot = newState.blockCommRoot;
+
+        // invalid scalar for the next stake table
+        LC.StakeTableState memory badNextStakeTable = genesisStakeTableState;
+        badNextStakeTable.schnorrKeyComm = BN254.ScalarField.wrap(BN254.R_MOD);
+        vm.expectRevert("Bn254: invalid scalar field");
+        lc.newFinalizedState(newState, badNextStakeTable, proof);
+        badNextStakeTable.schnorrKeyComm = genesisStakeTableState.schnorrKeyComm;
+
+        // a next stake table without a threshold
+        badNextStakeTable.threshold = 0;
+        vm.expectRevert(LC.InvalidArgs.selector);
+        lc.newFinalizedState(newState, badNextStakeTable, proof);
     }
 
     /// @dev Test unhappy path when the plonk proof or the public inputs are wrong
@@ -466,21 +520,31 @@ contract LightClient_newFinalizedState_Test is LightClientCommonTest {
         vm.startPrank(permissionedProver);
         badState.viewNum = newState.viewNum + 2;
         vm.expectRevert(LC.InvalidProof.selector);
-        lc.newFinalizedState(badState, proof);
+        lc.newFinalizedState(badState, genesisStakeTableState, proof);
         badState.viewNum = newState.viewNum;
 
         // wrong block height
         badState.blockHeight = newState.blockHeight + 1;
         vm.expectRevert(LC.InvalidProof.selector);
-        lc.newFinalizedState(badState, proof);
+        lc.newFinalizedState(badState, genesisStakeTableState, proof);
         badState.blockHeight = newState.blockHeight;
 
         // wrong blockCommRoot
         badState.blockCommRoot = randScalar;
         vm.expectRevert(LC.InvalidProof.selector);
-        lc.newFinalizedState(badState, proof);
+        lc.newFinalizedState(badState, genesisStakeTableState, proof);
         badState.blockCommRoot = newState.blockCommRoot;
 
+        // next stake table which was not signed
+        LC.StakeTableState memory badNextStakeTable = genesisStakeTableState;
+        badNextStakeTable.amountComm = randScalar;
+        vm.expectRevert(LC.InvalidProof.selector);
+        lc.newFinalizedState(newState, badNextStakeTable, proof);
+        badNextStakeTable.amountComm = genesisStakeTableState.amountComm;
+        badNextStakeTable.threshold = genesisStakeTableState.threshold + 1;
+        vm.expectRevert(LC.InvalidProof.selector);
+        lc.newFinalizedState(newState, badNextStakeTable, proof);
+
         cmds = new string[](3);
         cmds[0] = "diff-test";
         cmds[1] = "dummy-proof";
@@ -489,7 +553,7 @@ contract LightClient_newFinalizedState_Test is LightClientCommonTest {
         result = vm.ffi(cmds);
         (V.PlonkProof memory dummyProof) = abi.decode(result, (V.PlonkProof));
         vm.expectRevert(LC.InvalidProof.selector);
-        lc.newFinalizedState(newState, dummyProof);
+        lc.newFinalizedState(newState, genesisStakeTableState, dummyProof);
 
         vm.stopPrank();
     }
@@ -549,7 +613,7 @@ contract LightClient_StateUpdatesTest is LightClientCommonTest {
         vm.prank(permissionedProver);
         vm.expectEmit(true, true, true, true);
         emit LC.NewState(newState.viewNum, newState.blockHeight, newState.blockCommRoot);
-        lc.newFinalizedState(newState, newProof);
+        lc.newFinalizedState(newState, genesisStakeTableState, newProof);
 
         assertEq(lc.getStateHistoryCount(), blockUpdatesCount + 1);
     }
@@ -600,7 +664,7 @@ contract LightClient_StateUpdatesTest is LightClientCommonTest {
             vm.prank(permissionedProver);
             vm.expectEmit(true, true, true, true);
             emit LC.NewState(states[i].viewNum, states[i].blockHeight, states[i].blockCommRoot);
-            lc.newFinalizedState(states[i], proofs[i]);
+            lc.newFinalizedState(states[i], genesisStakeTableState, proofs[i]);
         }
 
         // assert that the first index is still zero as
@@ -621,7 +685,7 @@ contract LightClient_StateUpdatesTest is LightClientCommonTest {
         vm.expectEmit(true, true, true, true);
         emit LC.NewState(states[i].viewNum, states[i].blockHeight, states[i].blockCommRoot);
         vm.warp(initialBlockTimestamp + ((i + 1) * 1 days)); // increase the timestamp for each
-        lc.newFinalizedState(states[i], proofs[i]);
+        lc.newFinalizedState(states[i], genesisStakeTableState, proofs[i]);
         i++;
 
         // the duration between the updates are more than stateHistoryRetentionPeriod,  so the first
@@ -634,7 +698,7 @@ contract LightClient_StateUpdatesTest is LightClientCommonTest {
             vm.prank(permissionedProver);
             vm.expectEmit(true, true, true, true);
             emit LC.NewState(states[j].viewNum, states[j].blockHeight, states[j].blockCommRoot);
-            lc.newFinalizedState(states[j], proofs[j]);
+            lc.newFinalizedState(states[j], genesisStakeTableState, proofs[j]);
         }
 
         // get stale commitments and assert that it has been reset to zero
@@ -674,7 +738,7 @@ contract LightClient_StateUpdatesTest is LightClientCommonTest {
             vm.prank(permissionedProver);
             vm.expectEmit(true, true, true, true);
             emit LC.NewState(states[i].viewNum, states[i].blockHeight, states[i].blockCommRoot);
-            lc.newFinalizedState(states[i], proofs[i]);
+            lc.newFinalizedState(states[i], genesisStakeTableState, proofs[i]);
         }
 
         // the number of elements are equal to the max state history so the first index should still
@@ -695,7 +759,7 @@ contract LightClient_StateUpdatesTest is LightClientCommonTest {
         vm.expectEmit(true, true, true, true);
         emit LC.NewState(states[i].viewNum, states[i].blockHeight, states[i].blockCommRoot);
         vm.warp(initialBlockTimestamp + ((i + 1) * 1 days)); // increase the timestamp for each
-        lc.newFinalizedState(states[i], proofs[i]);
+        lc.newFinalizedState(states[i], genesisStakeTableState, proofs[i]);
         i++;
 
         // the duration between the updates are more than stateHistoryRetentionPeriod,  so the first
@@ -708,7 +772,7 @@ contract LightClient_StateUpdatesTest is LightClientCommonTest {
             vm.prank(permissionedProver);
             vm.expectEmit(true, true, true, true);
             emit LC.NewState(states[j].viewNum, states[j].blockHeight, states[j].blockCommRoot);
-            lc.newFinalizedState(states[j], proofs[j]);
+            lc.newFinalizedState(states[j], genesisStakeTableState, proofs[j]);
         }
 
         // get stale commitments and assert that it has been reset to zero
@@ -1111,7 +1175,7 @@ contract LightClient_HotShotCommUpdatesTest is LightClientCommonTest {
         vm.prank(permissionedProver);
         vm.expectEmit(true, true, true, true);
         emit LC.NewState(newState.viewNum, newState.blockHeight, newState.blockCommRoot);
-        lc.newFinalizedState(newState, newProof);
+        lc.newFinalizedState(newState, genesisStakeTableState, newProof);
 
         assertEq(lc.getStateHistoryCount(), blockCommCount + 1);
     }
@@ -1136,7 +1200,7 @@ contract LightClient_HotShotCommUpdatesTest is LightClientCommonTest {
             vm.prank(permissionedProver);
             vm.expectEmit(true, true, true, true);
             emit LC.NewState(state.viewNum, state.blockHeight, state.blockCommRoot);
-            lc.newFinalizedState(state, proof);
+            lc.newFinalizedState(state, genesisStakeTableState, proof);
         }
 
         assertEq(lc.getStateHistoryCount(), blockCommCount + statesCount);
@@ -1146,7 +1210,7 @@ contract LightClient_HotShotCommUpdatesTest is LightClientCommonTest {
         vm.prank(permissionedProver);
         vm.expectEmit(true, true, true, true);
         emit LC.NewState(newState.viewNum, newState.blockHeight, newState.blockCommRoot);
-        lc.newFinalizedState(newState, newProof);
+        lc.newFinalizedState(newState, genesisStakeTableState, newProof);
 
         // Test for a smaller hotShotBlockHeight
         (BN254.ScalarField blockComm, uint64 blockHeight) =
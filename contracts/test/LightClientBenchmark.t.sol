@@ -36,9 +36,10 @@ contract LightClientBench is LightClientCommonTest {
     function testCorrectUpdateBench() external {
         vm.pauseGasMetering();
         LC.LightClientState memory st = state;
+        LC.StakeTableState memory next = genesisStakeTableState;
         V.PlonkProof memory pf = proof;
         vm.prank(permissionedProver);
         vm.resumeGasMetering();
-        lc.newFinalizedState(st, pf);
+        lc.newFinalizedState(st, next, pf);
     }
 }
@@ -97,15 +97,11 @@ contract LightClientV2 is LightClient {
     ) internal virtual {
         IPlonkVerifier.VerifyingKey memory vk = VkLib.getVk();
 
-        // Prepare the public input
-        uint256[7] memory publicInput;
-        publicInput[0] = uint256(state.viewNum);
-        publicInput[1] = uint256(state.blockHeight);
-        publicInput[2] = BN254.ScalarField.unwrap(state.blockCommRoot);
-        publicInput[3] = BN254.ScalarField.unwrap(genesisStakeTableState.blsKeyComm);
-        publicInput[4] = BN254.ScalarField.unwrap(genesisStakeTableState.schnorrKeyComm);
-        publicInput[5] = BN254.ScalarField.unwrap(genesisStakeTableState.amountComm);
-        publicInput[6] = genesisStakeTableState.threshold;
+        // Prepare the public input, keeping the voting stake table for the next update
+        uint256[11] memory publicInput = preparePublicInput(
+            LightClientState(state.viewNum, state.blockHeight, state.blockCommRoot),
+            currentVotingStakeTable()
+        );
 
         if (!PlonkVerifier.verify(vk, publicInput, proof)) {
             revert InvalidProof();
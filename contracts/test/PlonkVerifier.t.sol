@@ -34,7 +34,7 @@ contract PlonkVerifierCommonTest is Test {
 
     /// @dev Sanitize all values in `a` to be valid scalar fields Bn254::Fr.
     /// This is helpful to sanitize fuzzer-generated random `uint[]` values.
-    function sanitizeScalarFields(uint256[7] memory a) public pure returns (uint256[7] memory) {
+    function sanitizeScalarFields(uint256[11] memory a) public pure returns (uint256[11] memory) {
         for (uint256 i = 0; i < a.length; i++) {
             a[i] = sanitizeScalarField(a[i]);
         }
@@ -74,7 +74,7 @@ contract PlonkVerifierCommonTest is Test {
     /// @dev helper function to generate some dummy but format-valid arguments for
     /// `prepareOpeningProof` step. The verifyingKey should be fixed/loaded from library,
     /// proof should be generated via `dummyProof()`, other inputs are from fuzzers.
-    function dummyArgsForOpeningProof(uint64 seed, uint256[7] memory publicInput)
+    function dummyArgsForOpeningProof(uint64 seed, uint256[11] memory publicInput)
         public
         returns (
             IPlonkVerifier.VerifyingKey memory,
@@ -139,9 +139,11 @@ contract PlonkVerifier_verify_Test is PlonkVerifierCommonTest {
         bytes memory result = vm.ffi(cmds);
         (
             IPlonkVerifier.VerifyingKey memory verifyingKey,
-            uint256[7] memory publicInput,
+            uint256[11] memory publicInput,
             IPlonkVerifier.PlonkProof memory proof
-        ) = abi.decode(result, (IPlonkVerifier.VerifyingKey, uint256[7], IPlonkVerifier.PlonkProof));
+        ) = abi.decode(
+            result, (IPlonkVerifier.VerifyingKey, uint256[11], IPlonkVerifier.PlonkProof)
+        );
 
         vm.resumeGasMetering();
         assert(V.verify(verifyingKey, publicInput, proof));
@@ -156,9 +158,11 @@ contract PlonkVerifier_verify_Test is PlonkVerifierCommonTest {
         bytes memory result = vm.ffi(cmds);
         (
             IPlonkVerifier.VerifyingKey memory verifyingKey,
-            uint256[7] memory publicInput,
+            uint256[11] memory publicInput,
             IPlonkVerifier.PlonkProof memory proof
-        ) = abi.decode(result, (IPlonkVerifier.VerifyingKey, uint256[7], IPlonkVerifier.PlonkProof));
+        ) = abi.decode(
+            result, (IPlonkVerifier.VerifyingKey, uint256[11], IPlonkVerifier.PlonkProof)
+        );
 
         // there are 18 points in verifying key
         // randomly choose one to mutate
@@ -183,10 +187,10 @@ contract PlonkVerifier_verify_Test is PlonkVerifierCommonTest {
     }
 
     // @dev Test when bad public input is supplied, the verification should fail
-    // We know our `gen_circuit_for_test` in `diff_test.rs` has only 8 public inputs
-    function testFuzz_badPublicInput_fails(uint256[7] calldata randPublicInput) external {
-        uint256[7] memory badPublicInput;
-        for (uint256 i = 0; i < 7; i++) {
+    // We know our `gen_circuit_for_test` in `diff_test.rs` has only 11 public inputs
+    function testFuzz_badPublicInput_fails(uint256[11] calldata randPublicInput) external {
+        uint256[11] memory badPublicInput;
+        for (uint256 i = 0; i < 11; i++) {
             badPublicInput[i] = randPublicInput[i];
         }
         badPublicInput = sanitizeScalarFields(badPublicInput);
@@ -197,7 +201,9 @@ contract PlonkVerifier_verify_Test is PlonkVerifierCommonTest {
 
         bytes memory result = vm.ffi(cmds);
         (IPlonkVerifier.VerifyingKey memory verifyingKey,, IPlonkVerifier.PlonkProof memory proof) =
-            abi.decode(result, (IPlonkVerifier.VerifyingKey, uint256[7], IPlonkVerifier.PlonkProof));
+            abi.decode(
+                result, (IPlonkVerifier.VerifyingKey, uint256[11], IPlonkVerifier.PlonkProof)
+            );
 
         assert(!V.verify(verifyingKey, badPublicInput, proof));
     }
@@ -211,8 +217,10 @@ contract PlonkVerifier_verify_Test is PlonkVerifierCommonTest {
         cmds[1] = "plonk-verify";
 
         bytes memory result = vm.ffi(cmds);
-        (IPlonkVerifier.VerifyingKey memory verifyingKey, uint256[7] memory publicInput,) =
-            abi.decode(result, (IPlonkVerifier.VerifyingKey, uint256[7], IPlonkVerifier.PlonkProof));
+        (IPlonkVerifier.VerifyingKey memory verifyingKey, uint256[11] memory publicInput,) =
+            abi.decode(
+                result, (IPlonkVerifier.VerifyingKey, uint256[11], IPlonkVerifier.PlonkProof)
+            );
 
         assert(!V.verify(verifyingKey, publicInput, badProof));
     }
@@ -278,11 +286,11 @@ contract PlonkVerifier_validateProof_Test is PlonkVerifierCommonTest {
 
 contract PlonkVerifier_computeChallenges_Test is PlonkVerifierCommonTest {
     /// @dev Test `computeChallenges` matches that of Jellyfish
-    function testFuzz_computeChallenges_matches(uint64 seed, uint256[7] memory _publicInput)
+    function testFuzz_computeChallenges_matches(uint64 seed, uint256[11] memory _publicInput)
         external
     {
-        uint256[7] memory publicInput;
-        for (uint256 i = 0; i < 7; i++) {
+        uint256[11] memory publicInput;
+        for (uint256 i = 0; i < 11; i++) {
             publicInput[i] = _publicInput[i];
         }
 
@@ -2,7 +2,6 @@
 
 pragma solidity ^0.8.0;
 
-import { BN254 } from "bn254/BN254.sol";
 import { LightClient as LC } from "../../src/LightClient.sol";
 import { IPlonkVerifier } from "../../src/interfaces/IPlonkVerifier.sol";
 import { PlonkVerifier } from "../../src/libraries/PlonkVerifier.sol";
@@ -27,22 +26,13 @@ contract LightClientMock is LC {
     }
 
     /// @dev override the production-implementation with test VK.
-    function verifyProof(LC.LightClientState memory state, IPlonkVerifier.PlonkProof memory proof)
-        internal
-        view
-        override
-    {
+    function verifyProof(
+        LC.LightClientState memory state,
+        LC.StakeTableState memory nextStakeTable,
+        IPlonkVerifier.PlonkProof memory proof
+    ) internal view override {
         IPlonkVerifier.VerifyingKey memory vk = VkLib.getVk();
-
-        // Prepare the public input
-        uint256[7] memory publicInput;
-        publicInput[0] = uint256(state.viewNum);
-        publicInput[1] = uint256(state.blockHeight);
-        publicInput[2] = BN254.ScalarField.unwrap(state.blockCommRoot);
-        publicInput[3] = BN254.ScalarField.unwrap(genesisStakeTableState.blsKeyComm);
-        publicInput[4] = BN254.ScalarField.unwrap(genesisStakeTableState.schnorrKeyComm);
-        publicInput[5] = BN254.ScalarField.unwrap(genesisStakeTableState.amountComm);
-        publicInput[6] = genesisStakeTableState.threshold;
+        uint256[11] memory publicInput = preparePublicInput(state, nextStakeTable);
 
         if (!PlonkVerifier.verify(vk, publicInput, proof)) {
             revert InvalidProof();
@@ -1,19 +1,26 @@
-use std::time::Duration;
+use std::{iter, path::PathBuf, time::Duration};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use espresso_types::parse_duration;
 use ethers::{
-    providers::{Http, Middleware, Provider},
-    signers::{coins_bip39::English, MnemonicBuilder, Signer},
+    core::k256::ecdsa::SigningKey,
+    signers::{coins_bip39::English, MnemonicBuilder},
     types::Address,
 };
 use hotshot_stake_table::config::STAKE_TABLE_CAPACITY;
-use hotshot_state_prover::service::{run_prover_once, run_prover_service, StateProverConfig};
+use hotshot_state_prover::{
+    key_cache::KeyCache,
+    service::{
+        precompute_keys, run_prover_once, run_prover_service, ProverTarget, StateProverConfig,
+    },
+    submit::SubmissionConfig,
+};
 use sequencer_utils::logging;
 use url::Url;
 use vbs::version::StaticVersion;
 
 #[derive(Parser)]
+#[clap(subcommand_negates_reqs = true)]
 struct Args {
     /// Start the prover service daemon
     #[clap(short, long, action)]
@@ -44,12 +51,12 @@ struct Args {
     l1_provider: Url,
 
     /// Address of LightClient contract on layer 1.
-    #[clap(long, env = "ESPRESSO_SEQUENCER_LIGHTCLIENT_ADDRESS")]
-    light_client_address: Address,
+    #[clap(long, env = "ESPRESSO_SEQUENCER_LIGHTCLIENT_ADDRESS", required = true)]
+    light_client_address: Option<Address>,
 
     /// Mnemonic phrase for a funded Ethereum wallet.
-    #[clap(long, env = "ESPRESSO_SEQUENCER_ETH_MNEMONIC", default_value = None)]
-    eth_mnemonic: String,
+    #[clap(long, env = "ESPRESSO_SEQUENCER_ETH_MNEMONIC", required = true)]
+    eth_mnemonic: Option<String>,
 
     /// Index of a funded account derived from eth-mnemonic.
     #[clap(
@@ -59,6 +66,60 @@ struct Args {
     )]
     eth_account_index: u32,
 
+    /// URLs of JSON-RPC providers for alternate chains to submit light client updates to.
+    ///
+    /// Each proof is generated once and submitted to the light client contract on layer 1 and on
+    /// each alternate chain. Transactions on each alternate chain are signed with the wallet given
+    /// by `--alt-eth-mnemonics` and `--alt-eth-account-indices`.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_PROVER_ALT_CHAIN_PROVIDERS",
+        num_args = 1..,
+        value_delimiter = ','
+    )]
+    alt_chain_providers: Vec<Url>,
+
+    /// Addresses of the LightClient contracts on the alternate chains, one per chain.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_PROVER_ALT_LIGHTCLIENT_ADDRESSES",
+        num_args = 1..,
+        value_delimiter = ','
+    )]
+    alt_light_client_addresses: Vec<Address>,
+
+    /// Mnemonic phrases for funded wallets on the alternate chains, one per chain.
+    ///
+    /// If there are fewer mnemonics provided than chains, the layer 1 mnemonic is used.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_PROVER_ALT_ETH_MNEMONICS",
+        num_args = 1..,
+        value_delimiter = ','
+    )]
+    alt_eth_mnemonics: Vec<String>,
+
+    /// Indices of funded accounts derived from the alternate mnemonics, one per chain.
+    ///
+    /// If there are fewer indices provided than chains, the layer 1 account index is used.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_PROVER_ALT_ACCOUNT_INDICES",
+        num_args = 1..,
+        value_delimiter = ','
+    )]
+    alt_eth_account_indices: Vec<u32>,
+
+    /// The frequency of updating the light client state on the alternate chains.
+    /// If there are fewer intervals provided than chains, the base update interval will be used.
+    #[clap(long, value_parser = parse_duration, env = "ESPRESSO_STATE_PROVER_ALT_UPDATE_INTERVALS", num_args = 1.., value_delimiter = ',')]
+    alt_update_intervals: Vec<Duration>,
+
+    /// Interval between retries if a state update fails on the alternate chains.
+    /// If there are fewer intervals provided than chains, the base retry interval will be used.
+    #[clap(long, value_parser = parse_duration, env = "ESPRESSO_STATE_PROVER_ALT_RETRY_INTERVALS", num_args = 1.., value_delimiter = ',')]
+    alt_retry_intervals: Vec<Duration>,
+
     /// URL of a sequencer node that is currently providing the HotShot config.
     /// This is used to initialize the stake table.
     #[clap(
@@ -68,6 +129,15 @@ struct Args {
     )]
     pub sequencer_url: Url,
 
+    /// Public network config file of the Espresso chain, to load the stake table from instead of
+    /// the sequencer node.
+    ///
+    /// The file is in the format served by the `config/hotshot` endpoint. It is reloaded before
+    /// each update, so that the light client contract follows validator set changes made by
+    /// updating it.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_NETWORK_CONFIG_FILE")]
+    pub network_config_file: Option<PathBuf>,
+
     /// If daemon and provided, the service will run a basic HTTP server on the given port.
     ///
     /// The server provides healthcheck and version endpoints.
@@ -78,8 +148,29 @@ struct Args {
     #[clap(short, long, env = "ESPRESSO_SEQUENCER_STAKE_TABLE_CAPACITY", default_value_t = STAKE_TABLE_CAPACITY)]
     pub stake_table_capacity: usize,
 
+    /// Directory in which to cache the proving key.
+    ///
+    /// Generating the proving key takes minutes for large stake tables. If this is set, the key is
+    /// loaded from the cache on startup, or saved to it once generated.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_KEY_CACHE_DIR")]
+    pub key_cache_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    submission: SubmissionConfig,
+
     #[clap(flatten)]
     logging: logging::Config,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate the proving key and save it in the key cache, then exit.
+    ///
+    /// Requires `--key-cache-dir`. The key is generated for `--stake-table-capacity`.
+    PrecomputeKeys,
 }
 
 #[async_std::main]
@@ -87,31 +178,89 @@ async fn main() {
     let args = Args::parse();
     args.logging.init();
 
+    if let Some(Command::PrecomputeKeys) = args.command {
+        let Some(dir) = args.key_cache_dir else {
+            tracing::error!("--key-cache-dir is required to precompute keys");
+            std::process::exit(1);
+        };
+        if let Err(err) = precompute_keys(args.stake_table_capacity, &KeyCache::new(dir)) {
+            tracing::error!("Error precomputing keys: {err:#}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if args.alt_chain_providers.len() != args.alt_light_client_addresses.len() {
+        tracing::error!(
+            "Expected a light client address for each of the {} alternate chains, got {}",
+            args.alt_chain_providers.len(),
+            args.alt_light_client_addresses.len()
+        );
+        std::process::exit(1);
+    }
+
     // prepare config for state prover from user options
-    let provider = Provider::<Http>::try_from(args.l1_provider.to_string()).unwrap();
-    let chain_id = provider.get_chainid().await.unwrap().as_u64();
+    // The mnemonic is required when not running a subcommand.
+    let eth_mnemonic = args.eth_mnemonic.unwrap();
+    let signing_key = derive_signing_key(&eth_mnemonic, args.eth_account_index);
+    let alt_signing_keys = args
+        .alt_eth_mnemonics
+        .into_iter()
+        .chain(iter::repeat(eth_mnemonic))
+        .zip(
+            args.alt_eth_account_indices
+                .into_iter()
+                .chain(iter::repeat(args.eth_account_index)),
+        )
+        .map(|(mnemonic, index)| derive_signing_key(&mnemonic, index));
+    let alt_targets = args
+        .alt_chain_providers
+        .into_iter()
+        .zip(args.alt_light_client_addresses)
+        .zip(alt_signing_keys)
+        .zip(
+            args.alt_update_intervals
+                .into_iter()
+                .chain(iter::repeat(args.update_interval)),
+        )
+        .zip(
+            args.alt_retry_intervals
+                .into_iter()
+                .chain(iter::repeat(args.retry_interval)),
+        )
+        .map(
+            |(
+                (((provider, light_client_address), signing_key), update_interval),
+                retry_interval,
+            )| {
+                ProverTarget {
+                    provider,
+                    light_client_address,
+                    signing_key,
+                    update_interval,
+                    retry_interval,
+                }
+            },
+        )
+        .collect();
     let config = StateProverConfig {
         relay_server: args.relay_server,
         update_interval: args.update_interval,
         retry_interval: args.retry_interval,
         provider: args.l1_provider,
-        light_client_address: args.light_client_address,
-        signing_key: MnemonicBuilder::<English>::default()
-            .phrase(args.eth_mnemonic.as_str())
-            .index(args.eth_account_index)
-            .expect("error building wallet")
-            .build()
-            .expect("error opening wallet")
-            .with_chain_id(chain_id)
-            .signer()
-            .clone(),
-
+        // This argument is required when not running a subcommand.
+        light_client_address: args.light_client_address.unwrap(),
+        signing_key,
         sequencer_url: args.sequencer_url,
+        network_config_file: args.network_config_file,
         port: args.port,
         stake_table_capacity: args.stake_table_capacity,
+        key_cache_dir: args.key_cache_dir,
+        submission: args.submission,
+        alt_targets,
     };
 
-    // validate that the light client contract is a proxy, panics otherwise
+    // validate that the light client contracts are proxies, panics otherwise
     config.validate_light_client_contract().await.unwrap();
 
     if args.daemon {
@@ -126,3 +275,15 @@ async fn main() {
         };
     }
 }
+
+/// Derive the transaction signing key of account `index` from `mnemonic`.
+fn derive_signing_key(mnemonic: &str, index: u32) -> SigningKey {
+    MnemonicBuilder::<English>::default()
+        .phrase(mnemonic)
+        .index(index)
+        .expect("error building wallet")
+        .build()
+        .expect("error opening wallet")
+        .signer()
+        .clone()
+}
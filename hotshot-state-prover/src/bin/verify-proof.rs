@@ -0,0 +1,174 @@
+//! Verify a light client state update proof offline.
+//!
+//! The proof and its public input are either given directly, ABI-encoded as for the light client
+//! contract, or taken from a `newFinalizedState` transaction on layer 1. The proof is checked
+//! against the verifying key for the given stake table capacity, and if it is invalid, the check
+//! which failed is reported.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{bail, ensure, Context};
+use clap::Parser;
+use contract_bindings::light_client::{LightClient, NewFinalizedStateCall};
+use ethers::{
+    abi::AbiDecode,
+    contract::EthCall,
+    providers::{Http, Middleware, Provider},
+    types::H256,
+};
+use hotshot_contract_adapter::{
+    jellyfish::ParsedPlonkProof,
+    light_client::{ParsedLightClientState, ParsedStakeTableState},
+};
+use hotshot_stake_table::config::STAKE_TABLE_CAPACITY;
+use hotshot_state_prover::{
+    key_cache::KeyCache, service::load_keys, verifier::verify_state_update,
+};
+use sequencer_utils::logging;
+use url::Url;
+
+#[derive(Parser)]
+struct Args {
+    /// ABI-encoded proof to verify.
+    #[clap(
+        long,
+        conflicts_with = "tx",
+        required_unless_present = "tx",
+        requires_all = ["state", "stake_table_state", "next_stake_table_state"]
+    )]
+    proof: Option<ParsedPlonkProof>,
+
+    /// ABI-encoded light client state proven by `--proof`.
+    #[clap(long, requires = "proof")]
+    state: Option<ParsedLightClientState>,
+
+    /// ABI-encoded stake table state `--proof` is verified against.
+    #[clap(long, requires = "proof")]
+    stake_table_state: Option<ParsedStakeTableState>,
+
+    /// ABI-encoded state of the stake table `--proof` hands over to.
+    #[clap(long, requires = "proof")]
+    next_stake_table_state: Option<ParsedStakeTableState>,
+
+    /// Hash of a `newFinalizedState` transaction to take the proof and public input from.
+    ///
+    /// The stake table state the proof is verified against is read from the light client contract
+    /// as of the block before the one which included the transaction.
+    #[clap(long)]
+    tx: Option<H256>,
+
+    /// URL of layer 1 Ethereum JSON-RPC provider, used with `--tx`.
+    #[clap(
+        long,
+        env = "ESPRESSO_SEQUENCER_L1_PROVIDER",
+        default_value = "http://localhost:8545"
+    )]
+    l1_provider: Url,
+
+    /// Stake table capacity for the prover circuit.
+    #[clap(short, long, env = "ESPRESSO_SEQUENCER_STAKE_TABLE_CAPACITY", default_value_t = STAKE_TABLE_CAPACITY)]
+    stake_table_capacity: usize,
+
+    /// Directory in which to cache the verifying key.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_KEY_CACHE_DIR")]
+    key_cache_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    logging: logging::Config,
+}
+
+/// Fetch a proof and its public input from a `newFinalizedState` transaction.
+async fn fetch_update(
+    l1_provider: Url,
+    hash: H256,
+) -> anyhow::Result<(
+    ParsedPlonkProof,
+    ParsedLightClientState,
+    ParsedStakeTableState,
+    ParsedStakeTableState,
+)> {
+    let provider = Arc::new(Provider::<Http>::try_from(l1_provider.to_string())?);
+    let tx = provider
+        .get_transaction(hash)
+        .await?
+        .context(format!("transaction {hash:#x} not found"))?;
+    let (Some(address), Some(block)) = (tx.to, tx.block_number) else {
+        bail!("transaction {hash:#x} is not an included contract call");
+    };
+    ensure!(
+        tx.input.starts_with(&NewFinalizedStateCall::selector()),
+        "transaction {hash:#x} is not a newFinalizedState call"
+    );
+    // The ABI encoding of the call arguments is the same as that of our parsed types, so we can
+    // decode them directly without going through the contract bindings.
+    let (state, next_stake_table_state, proof): (
+        ParsedLightClientState,
+        ParsedStakeTableState,
+        ParsedPlonkProof,
+    ) = AbiDecode::decode(&tx.input[4..]).context("malformed newFinalizedState call")?;
+
+    // The transaction replaces the voting stake table, so read the one it was verified against from
+    // the block before. Contracts which have not recorded one yet verify against the genesis stake
+    // table.
+    let contract = LightClient::new(address, provider);
+    let before = block.saturating_sub(1u64.into());
+    let mut stake_table_state: ParsedStakeTableState = contract
+        .voting_stake_table_state()
+        .block(before)
+        .call()
+        .await
+        .context("reading stake table state from contract")?
+        .into();
+    if stake_table_state.threshold.is_zero() {
+        stake_table_state = contract
+            .genesis_stake_table_state()
+            .block(before)
+            .call()
+            .await
+            .context("reading stake table state from contract")?
+            .into();
+    }
+    Ok((proof, state, stake_table_state, next_stake_table_state))
+}
+
+#[async_std::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    args.logging.init();
+
+    let (proof, state, stake_table_state, next_stake_table_state) = match args.tx {
+        Some(hash) => fetch_update(args.l1_provider, hash).await?,
+        // Clap ensures all of these are given if `--tx` is not.
+        None => (
+            args.proof.unwrap(),
+            args.state.unwrap(),
+            args.stake_table_state.unwrap(),
+            args.next_stake_table_state.unwrap(),
+        ),
+    };
+    tracing::info!(
+        ?state,
+        ?stake_table_state,
+        ?next_stake_table_state,
+        "verifying proof"
+    );
+
+    let key_cache = args.key_cache_dir.map(KeyCache::new);
+    let (_, vk) = load_keys(args.stake_table_capacity, key_cache.as_ref());
+    match verify_state_update(
+        &vk,
+        &proof,
+        &state,
+        &stake_table_state,
+        &next_stake_table_state,
+    ) {
+        Ok(()) => {
+            println!("proof is valid");
+            Ok(())
+        }
+        Err(err) => {
+            println!("proof is invalid: {err}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -5,9 +5,7 @@ use ark_ff::PrimeField;
 use ark_std::borrow::Borrow;
 use ethers::types::U256;
 use hotshot_contract_adapter::light_client::{ParsedLightClientState, ParsedStakeTableState};
-use hotshot_types::light_client::{
-    GenericLightClientState, GenericPublicInput, GenericStakeTableState,
-};
+use hotshot_types::light_client::{GenericLightClientState, GenericStakeTableState};
 use jf_plonk::PlonkError;
 use jf_relation::{BoolVar, Circuit, CircuitError, PlonkCircuit, Variable};
 use jf_rescue::{gadgets::RescueNativeGadget, RescueParameter};
@@ -23,6 +21,84 @@ pub(crate) fn u256_to_field<F: PrimeField>(v: &U256) -> F {
     F::from_le_bytes_mod_order(&bytes)
 }
 
+/// The message signed by state signers: the light client state followed by the stake table
+/// that will be in charge of the next state update.
+pub fn state_signature_message<F: PrimeField>(
+    state: &GenericLightClientState<F>,
+    next_stake_table_state: &GenericStakeTableState<F>,
+) -> [F; 7] {
+    [
+        F::from(state.view_number as u64),
+        F::from(state.block_height as u64),
+        state.block_comm_root,
+        next_stake_table_state.bls_key_comm,
+        next_stake_table_state.schnorr_key_comm,
+        next_stake_table_state.amount_comm,
+        next_stake_table_state.threshold,
+    ]
+}
+
+/// Public input of the state update circuit
+///
+/// It consists of the new light client state, the stake table that signed it and the stake table
+/// that the signers hand over to for the next update.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenericPublicInput<F: PrimeField> {
+    lc_state: GenericLightClientState<F>,
+    voting_stake_table_state: GenericStakeTableState<F>,
+    next_stake_table_state: GenericStakeTableState<F>,
+}
+
+impl<F: PrimeField> GenericPublicInput<F> {
+    /// Construct a public input from its parts
+    pub fn new(
+        lc_state: GenericLightClientState<F>,
+        voting_stake_table_state: GenericStakeTableState<F>,
+        next_stake_table_state: GenericStakeTableState<F>,
+    ) -> Self {
+        Self {
+            lc_state,
+            voting_stake_table_state,
+            next_stake_table_state,
+        }
+    }
+
+    /// The light client state being proven
+    pub fn lc_state(&self) -> &GenericLightClientState<F> {
+        &self.lc_state
+    }
+
+    /// The stake table whose signatures the proof checks
+    pub fn voting_stake_table_state(&self) -> &GenericStakeTableState<F> {
+        &self.voting_stake_table_state
+    }
+
+    /// The stake table committed to for the next update
+    pub fn next_stake_table_state(&self) -> &GenericStakeTableState<F> {
+        &self.next_stake_table_state
+    }
+
+    /// Flatten into the field elements in the order the circuit allocates them
+    pub fn to_vec(&self) -> Vec<F> {
+        let stake_table_fields = |st: &GenericStakeTableState<F>| {
+            [
+                st.bls_key_comm,
+                st.schnorr_key_comm,
+                st.amount_comm,
+                st.threshold,
+            ]
+        };
+        let mut v = vec![
+            F::from(self.lc_state.view_number as u64),
+            F::from(self.lc_state.block_height as u64),
+            self.lc_state.block_comm_root,
+        ];
+        v.extend(stake_table_fields(&self.voting_stake_table_state));
+        v.extend(stake_table_fields(&self.next_stake_table_state));
+        v
+    }
+}
+
 /// Variable for stake table entry
 #[derive(Clone, Debug)]
 pub struct StakeTableEntryVar {
@@ -91,7 +167,8 @@ impl LightClientStateVar {
 /// - a bit vector indicates the signers
 /// - a list of schnorr signatures of the updated states (`Vec<SchnorrSignature>`), default if the node doesn't sign the state
 /// - updated light client state (`(view_number, block_height, block_comm_root)`)
-/// - the static stake table state (containing 3 commitments to the 3 columns of the stake table and a threshold)
+/// - the voting stake table state (containing 3 commitments to the 3 columns of the stake table and a threshold)
+/// - the next stake table state, which the signers commit to alongside the light client state
 ///
 /// Lengths of input vectors should not exceed the `stake_table_capacity`.
 /// The list of stake table entries, bit indicators and signatures will be padded to the `stake_table_capacity`.
@@ -99,7 +176,7 @@ impl LightClientStateVar {
 /// - the vector that indicates who signed is a bit vector
 /// - the signers' accumulated weight exceeds the quorum threshold
 /// - the stake table corresponds to the one committed in the light client state
-/// - all Schnorr signatures over the light client state are valid
+/// - all Schnorr signatures over the light client state and the next stake table state are valid
 ///
 /// and returns
 /// - A circuit for proof generation
@@ -112,6 +189,7 @@ pub(crate) fn build<F, P, STIter, BitIter, SigIter>(
     signatures: SigIter,
     lightclient_state: &GenericLightClientState<F>,
     stake_table_state: &GenericStakeTableState<F>,
+    next_stake_table_state: &GenericStakeTableState<F>,
     stake_table_capacity: usize,
 ) -> Result<(PlonkCircuit<F>, GenericPublicInput<F>), PlonkError>
 where
@@ -217,6 +295,7 @@ where
     // public inputs
     let lightclient_state_pub_var = LightClientStateVar::new(&mut circuit, lightclient_state)?;
     let stake_table_state_pub_var = StakeTableVar::new(&mut circuit, stake_table_state)?;
+    let next_stake_table_state_pub_var = StakeTableVar::new(&mut circuit, next_stake_table_state)?;
 
     // Checking whether the accumulated weight exceeds the quorum threshold
     let mut signed_amount_var = (0..stake_table_capacity / 2)
@@ -284,6 +363,10 @@ where
                     lightclient_state_pub_var.view_num,
                     lightclient_state_pub_var.block_height,
                     lightclient_state_pub_var.block_comm_root,
+                    next_stake_table_state_pub_var.qc_keys_comm,
+                    next_stake_table_state_pub_var.state_keys_comm,
+                    next_stake_table_state_pub_var.stake_amount_comm,
+                    next_stake_table_state_pub_var.threshold,
                 ],
                 &sig,
             )
@@ -303,7 +386,11 @@ where
     circuit.finalize_for_arithmetization()?;
     Ok((
         circuit,
-        GenericPublicInput::new(lightclient_state.clone(), *stake_table_state),
+        GenericPublicInput::new(
+            lightclient_state.clone(),
+            *stake_table_state,
+            *next_stake_table_state,
+        ),
     ))
 }
 
@@ -324,6 +411,7 @@ where
         &[],
         &lightclient_state,
         &stake_table_state,
+        &stake_table_state,
         stake_table_capacity,
     )
 }
@@ -342,7 +430,7 @@ mod tests {
     };
     use jf_utils::test_rng;
 
-    use super::build;
+    use super::{build, state_signature_message};
     use crate::test_utils::{
         genesis_stake_table_state, key_pairs_for_testing, stake_table_for_testing,
     };
@@ -375,7 +463,7 @@ mod tests {
             block_height: 73,
             block_comm_root,
         };
-        let state_msg: [F; 3] = lightclient_state.clone().into();
+        let state_msg = state_signature_message(&lightclient_state, &st_state);
 
         let sigs = state_keys
             .iter()
@@ -409,11 +497,12 @@ mod tests {
             &bit_masked_sigs,
             &lightclient_state,
             &st_state,
+            &st_state,
             ST_CAPACITY,
         )
         .unwrap();
         assert!(circuit
-            .check_circuit_satisfiability(public_inputs.as_ref())
+            .check_circuit_satisfiability(&public_inputs.to_vec())
             .is_ok());
 
         // lower threshold should also pass
@@ -425,26 +514,28 @@ mod tests {
             &bit_masked_sigs,
             &lightclient_state,
             &good_st_state,
+            &st_state,
             ST_CAPACITY,
         )
         .unwrap();
         assert!(circuit
-            .check_circuit_satisfiability(public_inputs.as_ref())
+            .check_circuit_satisfiability(&public_inputs.to_vec())
             .is_ok());
 
         // bad path: feeding non-bit vector
-        let bit_vec = [F::from(2u64); 10];
+        let non_bit_vec = [F::from(2u64); 10];
         let (circuit, public_inputs) = build(
             &entries,
-            &bit_vec,
+            &non_bit_vec,
             &bit_masked_sigs,
             &lightclient_state,
             &st_state,
+            &st_state,
             ST_CAPACITY,
         )
         .unwrap();
         assert!(circuit
-            .check_circuit_satisfiability(public_inputs.as_ref())
+            .check_circuit_satisfiability(&public_inputs.to_vec())
             .is_err());
 
         // bad path: total weight doesn't meet the threshold
@@ -473,42 +564,66 @@ mod tests {
             &bad_bit_masked_sigs,
             &lightclient_state,
             &st_state,
+            &st_state,
             ST_CAPACITY,
         )
         .unwrap();
         assert!(bad_circuit
-            .check_circuit_satisfiability(public_inputs.as_ref())
+            .check_circuit_satisfiability(&public_inputs.to_vec())
             .is_err());
 
-        // bad path: bad stake table commitment
-        let bad_lightclient_state = lightclient_state.clone();
-        // bad_lightclient_state.stake_table_comm.1 = F::default();
-        let bad_state_msg: [F; 3] = bad_lightclient_state.clone().into();
-        let sig_for_bad_state = state_keys
+        // good path: handing over to a different stake table that the signers committed to
+        let (next_qc_keys, next_state_keys) = key_pairs_for_testing(num_validators, &mut prng);
+        let next_st = stake_table_for_testing(ST_CAPACITY, &next_qc_keys, &next_state_keys);
+        let next_st_state = genesis_stake_table_state(&next_st);
+        let next_state_msg = state_signature_message(&lightclient_state, &next_st_state);
+        let next_sigs = bit_vec
             .iter()
-            .map(|(key, _)| {
-                SchnorrSignatureScheme::<Config>::sign(&(), key, bad_state_msg, &mut prng)
+            .zip(state_keys.iter())
+            .map(|(bit, (key, _))| {
+                if *bit == F::from(1u64) {
+                    SchnorrSignatureScheme::<Config>::sign(&(), key, next_state_msg, &mut prng)
+                } else {
+                    Ok(Signature::<Config>::default())
+                }
             })
             .collect::<Result<Vec<_>, _>>()
             .unwrap();
+        let (circuit, public_inputs) = build(
+            &entries,
+            &bit_vec,
+            &next_sigs,
+            &lightclient_state,
+            &st_state,
+            &next_st_state,
+            ST_CAPACITY,
+        )
+        .unwrap();
+        assert!(circuit
+            .check_circuit_satisfiability(&public_inputs.to_vec())
+            .is_ok());
+
+        // bad path: signatures over a different next stake table than the one in the public input
         let (bad_circuit, public_inputs) = build(
             &entries,
             &bit_vec,
-            &sig_for_bad_state,
-            &bad_lightclient_state,
+            &bit_masked_sigs,
+            &lightclient_state,
             &st_state,
+            &next_st_state,
             ST_CAPACITY,
         )
         .unwrap();
         assert!(bad_circuit
-            .check_circuit_satisfiability(public_inputs.as_ref())
+            .check_circuit_satisfiability(&public_inputs.to_vec())
             .is_err());
 
         // bad path: incorrect signatures
-        let wrong_light_client_state = lightclient_state.clone();
-        // state with a different qc key commitment
-        // wrong_light_client_state.stake_table_comm.0 = F::default();
-        let wrong_state_msg: [F; 3] = wrong_light_client_state.into();
+        let wrong_light_client_state = LightClientState {
+            view_number: 101,
+            ..lightclient_state.clone()
+        };
+        let wrong_state_msg = state_signature_message(&wrong_light_client_state, &st_state);
         let wrong_sigs = state_keys
             .iter()
             .map(|(key, _)| {
@@ -522,11 +637,12 @@ mod tests {
             &wrong_sigs,
             &lightclient_state,
             &st_state,
+            &st_state,
             ST_CAPACITY,
         )
         .unwrap();
         assert!(bad_circuit
-            .check_circuit_satisfiability(public_inputs.as_ref())
+            .check_circuit_satisfiability(&public_inputs.to_vec())
             .is_err());
 
         // bad path: overflowing stake table size
@@ -536,6 +652,7 @@ mod tests {
             &bit_masked_sigs,
             &lightclient_state,
             &st_state,
+            &st_state,
             9
         )
         .is_err());
@@ -0,0 +1,195 @@
+//! On-disk cache for the keys of the state update circuit.
+//!
+//! Generating the proving key from the SRS takes minutes at production stake table capacities. The
+//! keys only depend on the circuit, which is determined by the stake table capacity, and on the
+//! SRS, so they are cached in a file keyed by both and reused on later starts.
+
+use std::{
+    fs::{self, File},
+    io::{ErrorKind, Write},
+    path::PathBuf,
+};
+
+use anyhow::{ensure, Context};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::snark::{ProvingKey, UniversalSrs, VerifyingKey};
+
+/// Version of the cache file format, to be incremented whenever it changes.
+const FORMAT_VERSION: u32 = 1;
+
+/// A digest identifying an SRS.
+pub type SrsDigest = [u8; 32];
+
+/// Compute the digest of an SRS, identifying the SRS that cached keys were generated from.
+pub fn srs_digest(srs: &UniversalSrs) -> anyhow::Result<SrsDigest> {
+    let mut hasher = blake3::Hasher::new();
+    srs.serialize_uncompressed(&mut hasher)?;
+    Ok(*hasher.finalize().as_bytes())
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Header {
+    format_version: u32,
+    stake_table_capacity: u64,
+    srs_digest: SrsDigest,
+    /// Digest of the serialized keys following the header.
+    keys_digest: [u8; 32],
+}
+
+/// A directory of cached proving and verifying keys.
+#[derive(Clone, Debug)]
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, stake_table_capacity: usize, srs_digest: &SrsDigest) -> PathBuf {
+        let srs = blake3::Hash::from(*srs_digest).to_hex();
+        self.dir.join(format!(
+            "state-prover-keys-{stake_table_capacity}-{}.bin",
+            &srs[..16]
+        ))
+    }
+
+    /// Load the cached keys for `stake_table_capacity`, generated from the SRS with `srs_digest`.
+    ///
+    /// Returns [`None`] if there are no such keys in the cache, and an error if the cache file
+    /// exists but is corrupted.
+    pub fn load(
+        &self,
+        stake_table_capacity: usize,
+        srs_digest: &SrsDigest,
+    ) -> anyhow::Result<Option<(ProvingKey, VerifyingKey)>> {
+        let path = self.path(stake_table_capacity, srs_digest);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).context(format!("reading {}", path.display())),
+        };
+
+        let header: Header = bincode::deserialize(&bytes).context("malformed header")?;
+        ensure!(
+            header.format_version == FORMAT_VERSION,
+            "unsupported format version {}",
+            header.format_version
+        );
+        ensure!(
+            header.stake_table_capacity == stake_table_capacity as u64,
+            "cached keys are for stake table capacity {}",
+            header.stake_table_capacity
+        );
+        ensure!(
+            header.srs_digest == *srs_digest,
+            "cached keys were generated from a different SRS"
+        );
+
+        let mut keys = &bytes[bincode::serialized_size(&header)? as usize..];
+        ensure!(
+            *blake3::hash(keys).as_bytes() == header.keys_digest,
+            "checksum mismatch"
+        );
+        // The checksum guarantees that these are exactly the keys we serialized, so we can skip the
+        // expensive curve point validity checks.
+        let pk = ProvingKey::deserialize_uncompressed_unchecked(&mut keys)
+            .context("malformed proving key")?;
+        let vk = VerifyingKey::deserialize_uncompressed_unchecked(&mut keys)
+            .context("malformed verifying key")?;
+        Ok(Some((pk, vk)))
+    }
+
+    /// Save keys for `stake_table_capacity`, generated from the SRS with `srs_digest`.
+    ///
+    /// Returns the path of the cache file.
+    pub fn store(
+        &self,
+        stake_table_capacity: usize,
+        srs_digest: &SrsDigest,
+        pk: &ProvingKey,
+        vk: &VerifyingKey,
+    ) -> anyhow::Result<PathBuf> {
+        let mut keys = vec![];
+        pk.serialize_uncompressed(&mut keys)?;
+        vk.serialize_uncompressed(&mut keys)?;
+        let header = Header {
+            format_version: FORMAT_VERSION,
+            stake_table_capacity: stake_table_capacity as u64,
+            srs_digest: *srs_digest,
+            keys_digest: *blake3::hash(&keys).as_bytes(),
+        };
+
+        fs::create_dir_all(&self.dir)
+            .context(format!("creating cache directory {}", self.dir.display()))?;
+        let path = self.path(stake_table_capacity, srs_digest);
+
+        // Write to a temporary file and rename it, so that a crash can never leave a partially
+        // written cache file behind.
+        let tmp = path.with_extension("swp");
+        let mut file = File::create(&tmp).context(format!("creating {}", tmp.display()))?;
+        bincode::serialize_into(&mut file, &header)?;
+        file.write_all(&keys)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path).context(format!("renaming {}", tmp.display()))?;
+
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_ed_on_bn254::EdwardsConfig;
+    use hotshot_types::light_client::CircuitField;
+    use jf_relation::Circuit as _;
+    use jf_utils::test_rng;
+
+    use super::*;
+    use crate::{
+        circuit::build_for_preprocessing, preprocess, test_utils::universal_setup_for_testing,
+    };
+
+    fn serialized(pk: &ProvingKey, vk: &VerifyingKey) -> Vec<u8> {
+        let mut bytes = vec![];
+        pk.serialize_uncompressed(&mut bytes).unwrap();
+        vk.serialize_uncompressed(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_key_cache() {
+        let capacity = 2;
+        let num_gates = build_for_preprocessing::<CircuitField, EdwardsConfig>(capacity)
+            .unwrap()
+            .0
+            .num_gates();
+        let srs = universal_setup_for_testing(num_gates + 2, &mut test_rng()).unwrap();
+        let digest = srs_digest(&srs).unwrap();
+        let (pk, vk) = preprocess(&srs, capacity).unwrap();
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cache = KeyCache::new(tmp.path().join("keys"));
+
+        // Initially, nothing is cached.
+        assert!(cache.load(capacity, &digest).unwrap().is_none());
+
+        // Store the keys and load them back.
+        let path = cache.store(capacity, &digest, &pk, &vk).unwrap();
+        let (cached_pk, cached_vk) = cache.load(capacity, &digest).unwrap().unwrap();
+        assert_eq!(serialized(&cached_pk, &cached_vk), serialized(&pk, &vk));
+
+        // Keys are not used for a different capacity or SRS.
+        assert!(cache.load(capacity + 1, &digest).unwrap().is_none());
+        assert!(cache.load(capacity, &[0; 32]).unwrap().is_none());
+
+        // Corruption is detected.
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&path, bytes).unwrap();
+        cache.load(capacity, &digest).unwrap_err();
+    }
+}
@@ -2,12 +2,20 @@
 
 /// State verifier circuit builder
 pub mod circuit;
+/// On-disk cache for proving and verifying keys
+pub mod key_cache;
 /// Utilities for test
 pub mod mock_ledger;
 /// Prover service related functionalities
 pub mod service;
 /// SNARK proof generation
 pub mod snark;
+/// Prover status and metrics
+pub mod status;
+/// Submission of light client updates to layer 1
+pub mod submit;
+/// Offline verification of state update proofs
+pub mod verifier;
 
 #[cfg(test)]
 mod test_utils;
@@ -16,8 +16,7 @@ use hotshot_contract_adapter::{
 use hotshot_stake_table::vec_based::StakeTable;
 use hotshot_types::{
     light_client::{
-        GenericLightClientState, GenericPublicInput, GenericStakeTableState, LightClientState,
-        StakeTableState,
+        GenericLightClientState, GenericStakeTableState, LightClientState, StakeTableState,
     },
     traits::stake_table::{SnapshotVersion, StakeTableScheme},
 };
@@ -36,7 +35,10 @@ use jf_signature::{
 use jf_utils::test_rng;
 
 use crate::{
-    generate_state_update_proof, preprocess, service::one_honest_threshold, Proof, VerifyingKey,
+    circuit::{state_signature_message, GenericPublicInput},
+    generate_state_update_proof, preprocess,
+    service::one_honest_threshold,
+    Proof, VerifyingKey,
 };
 
 type F = ark_ed_on_bn254::Fq;
@@ -207,8 +209,11 @@ impl MockLedger {
     // }
 
     /// Return the light client state and proof of consensus on this finalized state
+    ///
+    /// The mock stake table never rotates, so the proof hands over to the same stake table that
+    /// signed it.
     pub fn gen_state_proof(&mut self) -> (GenericPublicInput<F>, Proof) {
-        let state_msg: [F; 3] = self.state.clone().into();
+        let state_msg = state_signature_message(&self.state, &self.stake_table_state);
 
         let st: Vec<(BLSVerKey, U256, SchnorrVerKey)> = self
             .st
@@ -278,6 +283,7 @@ impl MockLedger {
             &sigs,
             &self.state,
             &self.stake_table_state,
+            &self.stake_table_state,
             STAKE_TABLE_CAPACITY,
         )
         .expect("Fail to generate state proof");
@@ -297,9 +303,7 @@ impl MockLedger {
             key_pairs_for_testing(STAKE_TABLE_CAPACITY, &mut self.rng);
         let adv_st = stake_table_for_testing(&adv_qc_keys, &adv_state_keys);
 
-        // replace new state with adversarial stake table commitment
-        // new_state.stake_table_comm = adv_st.commitment(SnapshotVersion::EpochStart).unwrap();
-        let state_msg: [F; 3] = new_state.clone().into();
+        let state_msg = state_signature_message(&new_state, &self.stake_table_state);
 
         // every fake stakers sign on the adverarial new state
         let bit_vec = vec![true; STAKE_TABLE_CAPACITY];
@@ -339,6 +343,7 @@ impl MockLedger {
             &sigs,
             &new_state,
             &self.stake_table_state,
+            &self.stake_table_state,
             STAKE_TABLE_CAPACITY,
         )
         .expect("Fail to generate state proof");
@@ -499,8 +504,8 @@ pub fn gen_circuit_for_test<F: PrimeField>(m: usize, a0: usize) -> Result<PlonkC
         (cs.witness(b[1])? + cs.witness(a[0])?) * (cs.witness(b[1])? - cs.witness(a[0])?),
     )?;
 
-    // Create other public variables so that the number of public inputs is 7
-    for _i in 0..4 {
+    // Create other public variables so that the number of public inputs is 11
+    for _i in 0..8 {
         cs.create_public_variable(F::from(0u64))?;
     }
 
@@ -1,35 +1,37 @@
 //! A light client prover service
 
 use std::{
+    borrow::Cow,
+    collections::VecDeque,
     iter,
+    path::{Path, PathBuf},
     time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Context, Result};
 use async_std::{
     io,
-    sync::Arc,
+    sync::{Arc, RwLock},
     task::{sleep, spawn, spawn_blocking},
 };
-use contract_bindings::light_client::{LightClient, LightClientErrors};
+use contract_bindings::light_client::LightClient;
 use displaydoc::Display;
 use ethers::{
     core::k256::ecdsa::SigningKey,
     middleware::SignerMiddleware,
     providers::{Http, Middleware, Provider, ProviderError},
     signers::{LocalWallet, Signer, Wallet},
-    types::{Address, U256},
+    types::{Address, TransactionReceipt, H256, U256},
 };
-use futures::FutureExt;
+use futures::{future::join_all, FutureExt};
 use hotshot_contract_adapter::{
     jellyfish::{field_to_u256, ParsedPlonkProof},
     light_client::{ParsedLightClientState, ParsedStakeTableState},
 };
-use hotshot_stake_table::vec_based::{config::FieldType, StakeTable};
+use hotshot_stake_table::vec_based::StakeTable;
 use hotshot_types::{
     light_client::{
-        CircuitField, LightClientState, PublicInput, StakeTableState, StateSignaturesBundle,
-        StateVerKey,
+        CircuitField, LightClientState, StakeTableState, StateSignaturesBundle, StateVerKey,
     },
     signature_key::BLSPubKey,
     traits::{
@@ -45,16 +47,39 @@ use jf_signature::constants::CS_ID_SCHNORR;
 use sequencer_utils::deployer::is_proxy_contract;
 use serde::Deserialize;
 use surf_disco::Client;
-use tide_disco::{error::ServerError, Api};
+use tide_disco::{error::ServerError, Api, Error as _, RequestParams, StatusCode};
 use time::ext::InstantExt;
 use url::Url;
 use vbs::version::StaticVersionType;
 
-use crate::snark::{generate_state_update_proof, Proof, ProvingKey};
+use crate::{
+    circuit::state_signature_message,
+    key_cache::{srs_digest, KeyCache},
+    snark::{
+        generate_state_update_proof, Proof, ProvingKey, PublicInput, UniversalSrs, VerifyingKey,
+    },
+    status::{ProofRecord, ProverMonitor, ProverPhase},
+    submit::{submit_update, SubmissionConfig},
+};
 
 /// A wallet with local signer and connected to network via http
 pub type SignerWallet = SignerMiddleware<Provider<Http>, LocalWallet>;
 
+/// A light client contract to which the prover submits state updates.
+#[derive(Debug, Clone)]
+pub struct ProverTarget {
+    /// URL of the chain (layer 1  or any layer 2) JSON-RPC provider.
+    pub provider: Url,
+    /// Address of LightClient contract
+    pub light_client_address: Address,
+    /// Transaction signing key for the chain
+    pub signing_key: SigningKey,
+    /// Interval between light client state updates of this contract
+    pub update_interval: Duration,
+    /// Interval between retries if a state update of this contract fails
+    pub retry_interval: Duration,
+}
+
 /// Configuration/Parameters used for hotshot state prover
 #[derive(Debug, Clone)]
 pub struct StateProverConfig {
@@ -71,22 +96,56 @@ pub struct StateProverConfig {
     /// Transaction signing key for Ethereum or any other layer 2
     pub signing_key: SigningKey,
     /// URL of a node that is currently providing the HotShot config.
-    /// This is used to initialize the stake table.
+    /// This is used to initialize the stake table, unless `network_config_file` is given.
     pub sequencer_url: Url,
+    /// Path to the public network config of the Espresso chain, in the format served by the
+    /// `config/hotshot` endpoint.
+    ///
+    /// If given, the stake table is loaded from this file instead of from `sequencer_url`, and the
+    /// file is read again before each update, so that a change of the validator set is picked up
+    /// once the file is updated. The stake table served by a sequencer node is fixed when the node
+    /// starts, so without this file the prover keeps using the stake table it started with.
+    pub network_config_file: Option<PathBuf>,
     /// If daemon and provided, the service will run a basic HTTP server on the given port.
     ///
     /// The server provides healthcheck and version endpoints.
     pub port: Option<u16>,
     /// Stake table capacity for the prover circuit.
     pub stake_table_capacity: usize,
+    /// Directory in which to cache the proving key, if any.
+    pub key_cache_dir: Option<PathBuf>,
+    /// Fee and retry policy for submitting updates to the light client contract.
+    pub submission: SubmissionConfig,
+    /// Light client contracts on other chains to submit the same updates to.
+    ///
+    /// Each proof is generated once and submitted to every contract which needs it.
+    pub alt_targets: Vec<ProverTarget>,
 }
 
 impl StateProverConfig {
-    pub async fn validate_light_client_contract(&self) -> anyhow::Result<()> {
-        let provider = Provider::<Http>::try_from(self.provider.to_string())?;
+    /// All the light client contracts to submit updates to, starting with the primary one.
+    pub fn targets(&self) -> Vec<ProverTarget> {
+        iter::once(ProverTarget {
+            provider: self.provider.clone(),
+            light_client_address: self.light_client_address,
+            signing_key: self.signing_key.clone(),
+            update_interval: self.update_interval,
+            retry_interval: self.retry_interval,
+        })
+        .chain(self.alt_targets.iter().cloned())
+        .collect()
+    }
 
-        if !is_proxy_contract(provider, self.light_client_address).await? {
-            anyhow::bail!("Light Client contract's address is not a proxy");
+    pub async fn validate_light_client_contract(&self) -> anyhow::Result<()> {
+        for target in self.targets() {
+            let provider = Provider::<Http>::try_from(target.provider.to_string())?;
+
+            if !is_proxy_contract(provider, target.light_client_address).await? {
+                anyhow::bail!(
+                    "Light Client contract's address is not a proxy: {:?}",
+                    target.light_client_address
+                );
+            }
         }
 
         Ok(())
@@ -127,38 +186,42 @@ struct PublicNetworkConfig {
     config: PublicHotShotConfig,
 }
 
-/// Initialize the stake table from a sequencer node that
-/// is currently providing the HotShot config.
-///
-/// Does not error, runs until the stake table is provided.
-async fn init_stake_table_from_sequencer(
+/// Fetch the current stake table from a sequencer node that is providing the HotShot config.
+async fn fetch_stake_table_from_sequencer(
     sequencer_url: &Url,
     stake_table_capacity: usize,
 ) -> Result<StakeTable<BLSPubKey, StateVerKey, CircuitField>> {
-    tracing::info!("Initializing stake table from node at {sequencer_url}");
-
     // Construct the URL to fetch the network config
     let config_url = sequencer_url
         .join("/v0/config/hotshot")
         .with_context(|| "Invalid URL")?;
 
-    // Request the configuration until it is successful
-    let network_config: PublicHotShotConfig = loop {
-        match reqwest::get(config_url.clone()).await {
-            Ok(resp) => match resp.json::<PublicNetworkConfig>().await {
-                Ok(config) => break config.config,
-                Err(e) => {
-                    tracing::error!("Failed to parse the network config: {e}");
-                    sleep(Duration::from_secs(5)).await;
-                }
-            },
-            Err(e) => {
-                tracing::error!("Failed to fetch the network config: {e}");
-                sleep(Duration::from_secs(5)).await;
-            }
-        }
-    };
+    let network_config = reqwest::get(config_url)
+        .await
+        .context("Failed to fetch the network config")?
+        .json::<PublicNetworkConfig>()
+        .await
+        .context("Failed to parse the network config")?
+        .config;
+    stake_table_from_config(network_config, stake_table_capacity)
+}
+
+/// Load the stake table from a public network config file.
+fn load_stake_table_from_file(
+    path: &Path,
+    stake_table_capacity: usize,
+) -> Result<StakeTable<BLSPubKey, StateVerKey, CircuitField>> {
+    let bytes = std::fs::read(path).context(format!("reading {}", path.display()))?;
+    let network_config = serde_json::from_slice::<PublicNetworkConfig>(&bytes)
+        .context(format!("malformed network config in {}", path.display()))?
+        .config;
+    stake_table_from_config(network_config, stake_table_capacity)
+}
 
+fn stake_table_from_config(
+    network_config: PublicHotShotConfig,
+    stake_table_capacity: usize,
+) -> Result<StakeTable<BLSPubKey, StateVerKey, CircuitField>> {
     // Create empty stake table
     let mut st = StakeTable::<BLSPubKey, StateVerKey, CircuitField>::new(stake_table_capacity);
 
@@ -169,7 +232,7 @@ async fn init_stake_table_from_sequencer(
             node.stake_table_entry.stake(),
             node.state_ver_key,
         )
-        .expect("Key registration shouldn't fail.");
+        .context("Failed to register a node in the stake table")?;
     }
 
     // Advance the stake table
@@ -179,6 +242,213 @@ async fn init_stake_table_from_sequencer(
     Ok(st)
 }
 
+/// Initialize the stake table from the network config file, if there is one, or else from the
+/// sequencer node.
+///
+/// Does not error, runs until the stake table is provided.
+async fn load_initial_stake_table(
+    config: &StateProverConfig,
+) -> Result<StakeTable<BLSPubKey, StateVerKey, CircuitField>> {
+    let Some(path) = &config.network_config_file else {
+        return init_stake_table_from_sequencer(&config.sequencer_url, config.stake_table_capacity)
+            .await;
+    };
+    tracing::info!("Initializing stake table from {}", path.display());
+    loop {
+        match load_stake_table_from_file(path, config.stake_table_capacity) {
+            Ok(st) => break Ok(st),
+            Err(err) => {
+                tracing::error!("{err:#}");
+                sleep(Duration::from_secs(5)).await;
+            }
+        }
+    }
+}
+
+/// Initialize the stake table from a sequencer node that
+/// is currently providing the HotShot config.
+///
+/// Does not error, runs until the stake table is provided.
+async fn init_stake_table_from_sequencer(
+    sequencer_url: &Url,
+    stake_table_capacity: usize,
+) -> Result<StakeTable<BLSPubKey, StateVerKey, CircuitField>> {
+    tracing::info!("Initializing stake table from node at {sequencer_url}");
+
+    // Request the stake table until it is successful
+    loop {
+        match fetch_stake_table_from_sequencer(sequencer_url, stake_table_capacity).await {
+            Ok(st) => break Ok(st),
+            Err(err) => {
+                tracing::error!("{err:#}");
+                sleep(Duration::from_secs(5)).await;
+            }
+        }
+    }
+}
+
+/// Maximum number of stake table epochs remembered by a [`StakeTableHistory`].
+const STAKE_TABLE_HISTORY_CAPACITY: usize = 16;
+
+/// The stake table of one epoch, together with the stake table state committing to it.
+#[derive(Debug)]
+pub struct StakeTableSnapshot {
+    /// The epoch in which this validator set became active, counting from 0 for the stake table
+    /// the prover started with.
+    pub epoch: u64,
+    pub state: StakeTableState,
+    pub stake_table: Arc<StakeTable<BLSPubKey, StateVerKey, CircuitField>>,
+}
+
+/// Snapshots of the stake table of each epoch seen by the prover.
+///
+/// A new epoch starts whenever the validator set changes. The light client contract verifies
+/// proofs against a particular stake table state, which can lag behind the current validator set.
+/// Keeping a snapshot per epoch lets the prover keep proving against whichever one the contract
+/// expects, and detect when it no longer knows it.
+#[derive(Debug)]
+pub struct StakeTableHistory {
+    /// Snapshots, oldest epoch first.
+    snapshots: VecDeque<StakeTableSnapshot>,
+}
+
+impl StakeTableHistory {
+    pub fn new(st: Arc<StakeTable<BLSPubKey, StateVerKey, CircuitField>>) -> Result<Self> {
+        Ok(Self {
+            snapshots: [StakeTableSnapshot {
+                epoch: 0,
+                state: stake_table_state(&st)?.into(),
+                stake_table: st,
+            }]
+            .into(),
+        })
+    }
+
+    /// Record the current stake table.
+    ///
+    /// If it differs from the stake table of the latest epoch, a new epoch is started and `true` is
+    /// returned. Returning to the validator set of an earlier epoch also starts a new epoch.
+    pub fn update(
+        &mut self,
+        st: Arc<StakeTable<BLSPubKey, StateVerKey, CircuitField>>,
+    ) -> Result<bool> {
+        let state: StakeTableState = stake_table_state(&st)?.into();
+        let latest = self.latest();
+        if latest.state == state {
+            return Ok(false);
+        }
+        let epoch = latest.epoch + 1;
+        self.snapshots.push_back(StakeTableSnapshot {
+            epoch,
+            state,
+            stake_table: st,
+        });
+        while self.snapshots.len() > STAKE_TABLE_HISTORY_CAPACITY {
+            self.snapshots.pop_front();
+        }
+        Ok(true)
+    }
+
+    /// The snapshot of the latest epoch.
+    pub fn latest(&self) -> &StakeTableSnapshot {
+        self.snapshots.back().unwrap()
+    }
+
+    /// The snapshot of `epoch`, if it is in the history.
+    pub fn epoch(&self, epoch: u64) -> Option<&StakeTableSnapshot> {
+        self.snapshots
+            .iter()
+            .find(|snapshot| snapshot.epoch == epoch)
+    }
+
+    /// The snapshot of the most recent epoch whose stake table is committed to by `state`, if it is
+    /// in the history.
+    pub fn get(&self, state: &StakeTableState) -> Option<&StakeTableSnapshot> {
+        self.snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.state == *state)
+    }
+}
+
+/// A proof of a light client state, which can be submitted to any light client contract expecting
+/// the stake table state it was proven against.
+#[derive(Debug)]
+pub struct StateProof {
+    pub state: LightClientState,
+    pub stake_table_state: StakeTableState,
+    /// The stake table the signers handed over to, which the contract expects for the next update.
+    pub next_stake_table_state: StakeTableState,
+    pub proof: Proof,
+    pub public_input: PublicInput,
+    /// Time taken to generate the proof.
+    pub proving_time: Duration,
+    /// When the proof was generated.
+    pub generated_at: Instant,
+}
+
+/// Recently generated proofs.
+///
+/// When the prover submits to several light client contracts with different update intervals, a
+/// contract which falls due shortly after another was updated can reuse the same proof, instead of
+/// waiting for a new one to be generated for a slightly newer state.
+#[derive(Debug, Default)]
+pub struct ProofCache {
+    /// Proofs, oldest first.
+    proofs: VecDeque<Arc<StateProof>>,
+}
+
+impl ProofCache {
+    /// The newest proof against `stake_table_state`, handing over to `next_stake_table_state`,
+    /// which would advance a contract currently at `block_height`, and was generated at most
+    /// `max_age` ago.
+    pub fn get(
+        &self,
+        stake_table_state: &StakeTableState,
+        next_stake_table_state: &StakeTableState,
+        block_height: usize,
+        max_age: Duration,
+    ) -> Option<Arc<StateProof>> {
+        self.proofs
+            .iter()
+            .rev()
+            .find(|proof| {
+                proof.stake_table_state == *stake_table_state
+                    && proof.next_stake_table_state == *next_stake_table_state
+                    && proof.state.block_height > block_height
+                    && proof.generated_at.elapsed() <= max_age
+            })
+            .cloned()
+    }
+
+    /// Record a newly generated proof, replacing any older proof for the same stake table
+    /// transition.
+    pub fn insert(&mut self, proof: Arc<StateProof>) {
+        self.proofs.retain(|cached| {
+            cached.stake_table_state != proof.stake_table_state
+                || cached.next_stake_table_state != proof.next_stake_table_state
+        });
+        self.proofs.push_back(proof);
+        while self.proofs.len() > STAKE_TABLE_HISTORY_CAPACITY {
+            self.proofs.pop_front();
+        }
+    }
+}
+
+/// Compute the stake table state committing to the latest epoch of `st`.
+pub fn stake_table_state(
+    st: &StakeTable<BLSPubKey, StateVerKey, CircuitField>,
+) -> Result<ParsedStakeTableState, StakeTableError> {
+    let (bls_comm, schnorr_comm, stake_comm) = st.commitment(SnapshotVersion::LastEpochStart)?;
+    let threshold = one_honest_threshold(st.total_stake(SnapshotVersion::LastEpochStart)?);
+    Ok(ParsedStakeTableState {
+        bls_key_comm: field_to_u256(bls_comm),
+        schnorr_key_comm: field_to_u256(schnorr_comm),
+        amount_comm: field_to_u256(stake_comm),
+        threshold,
+    })
+}
+
 /// Returns both genesis light client state and stake table state
 pub async fn light_client_genesis(
     sequencer_url: &Url,
@@ -194,59 +464,110 @@ pub async fn light_client_genesis(
 pub fn light_client_genesis_from_stake_table(
     st: StakeTable<BLSPubKey, StateVerKey, CircuitField>,
 ) -> anyhow::Result<(ParsedLightClientState, ParsedStakeTableState)> {
-    let (bls_comm, schnorr_comm, stake_comm) = st
-        .commitment(SnapshotVersion::LastEpochStart)
-        .expect("Commitment computation shouldn't fail.");
-    let threshold = one_honest_threshold(st.total_stake(SnapshotVersion::LastEpochStart)?);
-
     Ok((
         ParsedLightClientState {
             view_num: 0,
             block_height: 0,
             block_comm_root: U256::from(0u32),
         },
-        ParsedStakeTableState {
-            bls_key_comm: field_to_u256(bls_comm),
-            schnorr_key_comm: field_to_u256(schnorr_comm),
-            amount_comm: field_to_u256(stake_comm),
-            threshold,
-        },
+        stake_table_state(&st)?,
     ))
 }
 
-pub fn load_proving_key(stake_table_capacity: usize) -> ProvingKey {
-    let srs = {
-        let num_gates = crate::circuit::build_for_preprocessing::<
-            CircuitField,
-            ark_ed_on_bn254::EdwardsConfig,
-        >(stake_table_capacity)
-        .unwrap()
-        .0
-        .num_gates();
-
-        std::println!("Loading SRS from Aztec's ceremony...");
-        let srs_timer = Instant::now();
-        let srs = ark_srs::kzg10::aztec20::setup(num_gates + 2).expect("Aztec SRS fail to load");
-        let srs_elapsed = Instant::now().signed_duration_since(srs_timer);
-        std::println!("Done in {srs_elapsed:.3}");
-
-        // convert to Jellyfish type
-        // TODO: (alex) use constructor instead https://github.com/EspressoSystems/jellyfish/issues/440
-        UnivariateUniversalParams {
-            powers_of_g: srs.powers_of_g,
-            h: srs.h,
-            beta_h: srs.beta_h,
-            powers_of_h: vec![srs.h, srs.beta_h],
-        }
-    };
+/// Load the universal SRS from Aztec's ceremony, large enough for a stake table of the given
+/// capacity.
+fn load_srs(stake_table_capacity: usize) -> UniversalSrs {
+    let num_gates = crate::circuit::build_for_preprocessing::<
+        CircuitField,
+        ark_ed_on_bn254::EdwardsConfig,
+    >(stake_table_capacity)
+    .unwrap()
+    .0
+    .num_gates();
+
+    std::println!("Loading SRS from Aztec's ceremony...");
+    let srs_timer = Instant::now();
+    let srs = ark_srs::kzg10::aztec20::setup(num_gates + 2).expect("Aztec SRS fail to load");
+    let srs_elapsed = Instant::now().signed_duration_since(srs_timer);
+    std::println!("Done in {srs_elapsed:.3}");
+
+    // convert to Jellyfish type
+    // TODO: (alex) use constructor instead https://github.com/EspressoSystems/jellyfish/issues/440
+    UnivariateUniversalParams {
+        powers_of_g: srs.powers_of_g,
+        h: srs.h,
+        beta_h: srs.beta_h,
+        powers_of_h: vec![srs.h, srs.beta_h],
+    }
+}
 
+fn generate_keys(srs: &UniversalSrs, stake_table_capacity: usize) -> (ProvingKey, VerifyingKey) {
     std::println!("Generating proving key and verification key.");
     let key_gen_timer = Instant::now();
-    let (pk, _) = crate::snark::preprocess(&srs, stake_table_capacity)
+    let keys = crate::snark::preprocess(srs, stake_table_capacity)
         .expect("Fail to preprocess state prover circuit");
     let key_gen_elapsed = Instant::now().signed_duration_since(key_gen_timer);
     std::println!("Done in {key_gen_elapsed:.3}");
-    pk
+    keys
+}
+
+/// Load the proving and verifying keys for a stake table of the given capacity.
+///
+/// If a `key_cache` is given, the keys are loaded from it if possible. Otherwise, the keys are
+/// generated and saved to the cache for next time.
+pub fn load_keys(
+    stake_table_capacity: usize,
+    key_cache: Option<&KeyCache>,
+) -> (ProvingKey, VerifyingKey) {
+    let srs = load_srs(stake_table_capacity);
+    let Some(key_cache) = key_cache else {
+        return generate_keys(&srs, stake_table_capacity);
+    };
+
+    let digest = srs_digest(&srs).expect("SRS serialization shouldn't fail");
+    match key_cache.load(stake_table_capacity, &digest) {
+        Ok(Some(keys)) => {
+            tracing::info!("Loaded keys from cache");
+            return keys;
+        }
+        Ok(None) => tracing::info!("Keys are not cached"),
+        Err(err) => tracing::warn!("Ignoring invalid key cache: {err:#}"),
+    }
+
+    let (pk, vk) = generate_keys(&srs, stake_table_capacity);
+    match key_cache.store(stake_table_capacity, &digest, &pk, &vk) {
+        Ok(path) => tracing::info!("Saved keys to {}", path.display()),
+        Err(err) => tracing::warn!("Failed to save keys to cache: {err:#}"),
+    }
+    (pk, vk)
+}
+
+/// Load the proving key for a stake table of the given capacity.
+///
+/// See [`load_keys`].
+pub fn load_proving_key(stake_table_capacity: usize, key_cache: Option<&KeyCache>) -> ProvingKey {
+    load_keys(stake_table_capacity, key_cache).0
+}
+
+/// Generate the keys for a stake table of the given capacity and save them in `key_cache`.
+///
+/// Does nothing if valid keys are already cached.
+pub fn precompute_keys(stake_table_capacity: usize, key_cache: &KeyCache) -> Result<()> {
+    let srs = load_srs(stake_table_capacity);
+    let digest = srs_digest(&srs)?;
+    match key_cache.load(stake_table_capacity, &digest) {
+        Ok(Some(_)) => {
+            tracing::info!("Keys are already cached");
+            return Ok(());
+        }
+        Ok(None) => {}
+        Err(err) => tracing::warn!("Replacing invalid key cache: {err:#}"),
+    }
+
+    let (pk, vk) = generate_keys(&srs, stake_table_capacity);
+    let path = key_cache.store(stake_table_capacity, &digest, &pk, &vk)?;
+    tracing::info!("Saved keys to {}", path.display());
+    Ok(())
 }
 
 pub async fn fetch_latest_state<ApiVer: StaticVersionType>(
@@ -274,7 +595,13 @@ async fn prepare_contract(
     Ok(contract)
 }
 
-/// get the `finalizedState` from the LightClient contract storage on L1
+/// get the `finalizedState` from the LightClient contract storage on L1, along with the stake table
+/// state that proofs must be generated against
+///
+/// The contract verifies each update against `votingStakeTableState`, the stake table committed to
+/// by the previous update, and then replaces it with the next stake table committed to by the new
+/// update. Contracts upgraded from a version without this field have not recorded a voting stake
+/// table yet, and verify against `genesisStakeTableState` until their first update.
 pub async fn read_contract_state(
     provider: Url,
     key: SigningKey,
@@ -288,6 +615,19 @@ pub async fn read_contract_state(
             return Err(ProverError::ContractError(e.into()));
         }
     };
+    let st_state: ParsedStakeTableState = match contract.voting_stake_table_state().call().await {
+        Ok(s) => s.into(),
+        Err(e) => {
+            tracing::error!(
+                "unable to read voting_stake_table_state from contract: {}",
+                e
+            );
+            return Err(ProverError::ContractError(e.into()));
+        }
+    };
+    if !st_state.threshold.is_zero() {
+        return Ok((state.into(), st_state.into()));
+    }
     let st_state: ParsedStakeTableState = match contract.genesis_stake_table_state().call().await {
         Ok(s) => s.into(),
         Err(e) => {
@@ -303,66 +643,67 @@ pub async fn read_contract_state(
 }
 
 /// submit the latest finalized state along with a proof to the L1 LightClient contract
+///
+/// Returns the receipt of the submitted transaction and the L1 block which included it.
 pub async fn submit_state_and_proof(
     proof: Proof,
     public_input: PublicInput,
     provider: Url,
     key: SigningKey,
     light_client_address: Address,
-) -> Result<(), ProverError> {
+    submission: &SubmissionConfig,
+) -> Result<(TransactionReceipt, u64), ProverError> {
     let contract = prepare_contract(provider, key, light_client_address).await?;
 
     // prepare the input the contract call and the tx itself
     let proof: ParsedPlonkProof = proof.into();
-    let new_state: ParsedLightClientState = public_input.into();
-    let tx = contract.new_finalized_state(new_state.into(), proof.into());
+    let new_state: ParsedLightClientState = public_input.lc_state().clone().into();
+    let next_stake_table: ParsedStakeTableState = (*public_input.next_stake_table_state()).into();
+    let block_height = new_state.block_height;
+    let tx = contract.new_finalized_state(new_state.into(), next_stake_table.into(), proof.into());
 
     // send the tx
-    let (receipt, included_block) = sequencer_utils::contract_send::<_, _, LightClientErrors>(&tx)
-        .await
-        .map_err(ProverError::ContractError)?;
+    let (receipt, included_block) = submit_update(&contract, tx, block_height, submission).await?;
 
     tracing::info!(
         "Submitted state and proof to L1: tx={:x} block={included_block}",
         receipt.transaction_hash,
     );
 
-    Ok(())
+    Ok((receipt, included_block))
 }
 
-pub async fn sync_state<ApiVer: StaticVersionType>(
-    st: &StakeTable<BLSPubKey, StateVerKey, CircuitField>,
+/// Generate a proof of `bundle.state` against the stake table committed to by `st_state`.
+///
+/// The proof hands over to the latest stake table in `stake_tables`, which the signers must have
+/// committed to along with the state. Only the signers of the voting stake table count towards
+/// the threshold, so a validator set change can be proven as long as the validators remaining from
+/// the voting stake table hold enough of its stake.
+async fn generate_proof(
+    bundle: &StateSignaturesBundle,
+    st_state: StakeTableState,
+    stake_tables: &StakeTableHistory,
     proving_key: Arc<ProvingKey>,
-    relay_server_client: &Client<ServerError, ApiVer>,
-    config: &StateProverConfig,
-) -> Result<(), ProverError> {
-    let light_client_address = config.light_client_address;
-    let provider = config.provider.clone();
-    let key = config.signing_key.clone();
-
-    tracing::info!(
-        ?light_client_address,
-        "Start syncing light client state for provider: {}",
-        provider,
-    );
-
-    let bundle = fetch_latest_state(relay_server_client).await?;
-    tracing::info!("Bundle accumulated weight: {}", bundle.accumulated_weight);
-    tracing::info!("Latest HotShot block height: {}", bundle.state.block_height);
-    let (old_state, st_state) =
-        read_contract_state(provider.clone(), key.clone(), light_client_address).await?;
-    tracing::info!(
-        "Current HotShot block height on contract: {}",
-        old_state.block_height
-    );
-    if old_state.block_height >= bundle.state.block_height {
-        tracing::info!("No update needed.");
-        return Ok(());
+    stake_table_capacity: usize,
+    monitor: &RwLock<ProverMonitor>,
+) -> Result<StateProof, ProverError> {
+    // Prove against the stake table the contract expects, which may not be the latest one.
+    let Some(snapshot) = stake_tables.get(&st_state) else {
+        return Err(ProverError::UnknownStakeTable);
+    };
+    let latest = stake_tables.latest();
+    let next_st_state = latest.state;
+    if latest.epoch != snapshot.epoch {
+        tracing::info!(
+            epoch = snapshot.epoch,
+            latest_epoch = latest.epoch,
+            "Proving a handover to the latest stake table"
+        );
     }
-    tracing::debug!("Old state: {old_state:?}");
-    tracing::debug!("New state: {:?}", bundle.state);
+    let state_msg = state_signature_message(&bundle.state, &next_st_state);
 
-    let entries = st
+    let entries = snapshot
+        .stake_table
         .try_iter(SnapshotVersion::LastEpochStart)
         .unwrap()
         .map(|(_, stake_amount, state_key)| (state_key, stake_amount))
@@ -373,7 +714,6 @@ pub async fn sync_state<ApiVer: StaticVersionType>(
     entries.iter().enumerate().for_each(|(i, (key, stake))| {
         if let Some(sig) = bundle.signatures.get(key) {
             // Check if the signature is valid
-            let state_msg: [FieldType; 3] = (&bundle.state).into();
             if key.verify(&state_msg, sig, CS_ID_SCHNORR).is_ok() {
                 signer_bit_vec[i] = true;
                 signatures[i] = sig.clone();
@@ -389,45 +729,300 @@ pub async fn sync_state<ApiVer: StaticVersionType>(
     }
 
     tracing::info!("Collected latest state and signatures. Start generating SNARK proof.");
+    monitor.write().await.set_phase(ProverPhase::Proving);
     let proof_gen_start = Instant::now();
-    let proving_key_clone = proving_key.clone();
-    let stake_table_capacity = config.stake_table_capacity;
+    let state = bundle.state.clone();
+    let new_state = bundle.state.clone();
     let (proof, public_input) = spawn_blocking(move || {
         generate_state_update_proof::<_, _, _, _>(
             &mut ark_std::rand::thread_rng(),
-            &proving_key_clone,
+            &proving_key,
             &entries,
             signer_bit_vec,
             signatures,
-            &bundle.state,
+            &new_state,
             &st_state,
+            &next_st_state,
             stake_table_capacity,
         )
     })
     .await?;
+    let proving_time = proof_gen_start.elapsed();
     let proof_gen_elapsed = Instant::now().signed_duration_since(proof_gen_start);
     tracing::info!("Proof generation completed. Elapsed: {proof_gen_elapsed:.3}");
+    monitor.write().await.record_proving_time(proving_time);
+
+    Ok(StateProof {
+        state,
+        stake_table_state: st_state,
+        next_stake_table_state: next_st_state,
+        proof,
+        public_input,
+        proving_time,
+        generated_at: Instant::now(),
+    })
+}
 
-    submit_state_and_proof(proof, public_input, provider, key, light_client_address).await?;
+/// Submit `proof` to the light client contract of `target`, if it still needs it.
+async fn submit_to_target(
+    index: usize,
+    target: &ProverTarget,
+    proof: &StateProof,
+    submission: &SubmissionConfig,
+    monitor: &RwLock<ProverMonitor>,
+) -> Result<(), ProverError> {
+    // Proof generation takes a while, so make sure the contract still needs this update before
+    // paying for a transaction.
+    let (current_state, _) = read_contract_state(
+        target.provider.clone(),
+        target.signing_key.clone(),
+        target.light_client_address,
+    )
+    .await?;
+    if current_state.block_height >= proof.state.block_height {
+        tracing::info!(
+            target_index = index,
+            "Light client contract already updated to block height {}, discarding proof.",
+            current_state.block_height
+        );
+        return Ok(());
+    }
+
+    let submit_start = Instant::now();
+    let (receipt, l1_block) = match submit_state_and_proof(
+        proof.proof.clone(),
+        proof.public_input.clone(),
+        target.provider.clone(),
+        target.signing_key.clone(),
+        target.light_client_address,
+        submission,
+    )
+    .await
+    {
+        Ok(res) => res,
+        Err(ProverError::StateSuperseded) => {
+            tracing::info!(
+                target_index = index,
+                "Light client contract was updated by another prover, discarding proof."
+            );
+            return Ok(());
+        }
+        Err(err) => return Err(err),
+    };
+    monitor.write().await.record_proof(
+        index,
+        ProofRecord {
+            state: proof.state.clone(),
+            stake_table_state: proof.stake_table_state,
+            tx_hash: receipt.transaction_hash,
+            l1_block,
+            proving_time: proof.proving_time,
+            submission_time: submit_start.elapsed(),
+        },
+    );
 
-    tracing::info!("Successfully synced light client state.");
+    tracing::info!(
+        target_index = index,
+        "Successfully synced light client state."
+    );
     Ok(())
 }
 
+/// Update the light client contracts of `targets`, given as indices into
+/// [`StateProverConfig::targets`].
+///
+/// A proof is generated at most once for each stake table state the contracts expect, and is then
+/// submitted to every contract which needs it. A contract may also be updated with a proof from
+/// `proofs` instead of the latest state, if that proof was generated within its update interval.
+///
+/// Failures which affect every target, like failing to fetch signatures from the relay server or to
+/// generate a proof, are returned directly. Otherwise, the result of updating each target is
+/// returned, in the order of `targets`.
+pub async fn sync_state<ApiVer: StaticVersionType>(
+    stake_tables: &StakeTableHistory,
+    proving_key: Arc<ProvingKey>,
+    relay_server_client: &Client<ServerError, ApiVer>,
+    config: &StateProverConfig,
+    targets: &[usize],
+    proofs: &mut ProofCache,
+    monitor: &RwLock<ProverMonitor>,
+) -> Result<Vec<Result<(), ProverError>>, ProverError> {
+    let all_targets = config.targets();
+    let round_start = Instant::now();
+    let mut bundle = None;
+    let mut results = Vec::with_capacity(targets.len());
+    let mut submissions = vec![];
+    for &index in targets {
+        let target = &all_targets[index];
+        tracing::info!(
+            target_index = index,
+            light_client_address = ?target.light_client_address,
+            "Start syncing light client state for provider: {}",
+            target.provider,
+        );
+
+        let (old_state, st_state) = match read_contract_state(
+            target.provider.clone(),
+            target.signing_key.clone(),
+            target.light_client_address,
+        )
+        .await
+        {
+            Ok(res) => res,
+            Err(err) => {
+                results.push(Err(err));
+                continue;
+            }
+        };
+        tracing::info!(
+            target_index = index,
+            "Current HotShot block height on contract: {}",
+            old_state.block_height
+        );
+        if stake_tables.get(&st_state).is_none() {
+            results.push(Err(ProverError::UnknownStakeTable));
+            continue;
+        }
+
+        // Any proof generated during this round is recent enough.
+        let max_age = target.update_interval.max(round_start.elapsed());
+        let next_st_state = stake_tables.latest().state;
+        let proof = match proofs.get(&st_state, &next_st_state, old_state.block_height, max_age) {
+            Some(proof) => {
+                tracing::info!(
+                    target_index = index,
+                    "Reusing proof for block height {}",
+                    proof.state.block_height
+                );
+                proof
+            }
+            None => {
+                // Fetch the latest state once, and only if some contract needs a new proof.
+                if bundle.is_none() {
+                    let latest = fetch_latest_state(relay_server_client).await?;
+                    tracing::info!("Bundle accumulated weight: {}", latest.accumulated_weight);
+                    tracing::info!("Latest HotShot block height: {}", latest.state.block_height);
+                    bundle = Some(latest);
+                }
+                let bundle = bundle.as_ref().unwrap();
+                if old_state.block_height >= bundle.state.block_height {
+                    tracing::info!(target_index = index, "No update needed.");
+                    results.push(Ok(()));
+                    continue;
+                }
+                tracing::debug!("Old state: {old_state:?}");
+                tracing::debug!("New state: {:?}", bundle.state);
+
+                let proof = Arc::new(
+                    generate_proof(
+                        bundle,
+                        st_state,
+                        stake_tables,
+                        proving_key.clone(),
+                        config.stake_table_capacity,
+                        monitor,
+                    )
+                    .await?,
+                );
+                proofs.insert(proof.clone());
+                proof
+            }
+        };
+        submissions.push((results.len(), index, proof));
+        results.push(Ok(()));
+    }
+
+    if !submissions.is_empty() {
+        monitor.write().await.set_phase(ProverPhase::Submitting);
+        let outcomes = join_all(submissions.iter().map(|(_, index, proof)| {
+            submit_to_target(
+                *index,
+                &all_targets[*index],
+                proof,
+                &config.submission,
+                monitor,
+            )
+        }))
+        .await;
+        for ((pos, _, _), res) in submissions.iter().zip(outcomes) {
+            results[*pos] = res;
+        }
+    }
+    Ok(results)
+}
+
+/// The target selected by the optional `:target` parameter of a request, by default the primary
+/// one.
+fn target_param(req: &RequestParams, num_targets: usize) -> Result<usize, ServerError> {
+    let target = req
+        .opt_integer_param("target")
+        .map_err(ServerError::from_request_error)?
+        .unwrap_or(0);
+    if target >= num_targets {
+        return Err(ServerError::catch_all(
+            StatusCode::NOT_FOUND,
+            format!("The prover has no target {target}."),
+        ));
+    }
+    Ok(target)
+}
+
 fn start_http_server<ApiVer: StaticVersionType + 'static>(
     port: u16,
-    light_client_address: Address,
+    light_client_addresses: Vec<Address>,
+    monitor: Arc<RwLock<ProverMonitor>>,
     bind_version: ApiVer,
 ) -> io::Result<()> {
-    let mut app = tide_disco::App::<_, ServerError>::with_state(());
+    let mut app = tide_disco::App::<_, ServerError>::with_state(monitor);
     let toml = toml::from_str::<toml::value::Value>(include_str!("../api/prover-service.toml"))
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
     let mut api = Api::<_, ServerError, ApiVer>::new(toml)
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
-    api.get("getlightclientcontract", move |_, _| {
-        async move { Ok(light_client_address) }.boxed()
+    let targets = light_client_addresses.clone();
+    api.get("getlightclientcontract", move |req, _| {
+        let res = target_param(&req, light_client_addresses.len())
+            .map(|target| light_client_addresses[target]);
+        async move { res }.boxed()
+    })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    .get("gettargets", move |_, _| {
+        let targets = targets.clone();
+        async move { Ok(targets) }.boxed()
+    })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    .get("getstatus", |req, monitor| {
+        async move {
+            let target = target_param(&req, monitor.num_targets())?;
+            Ok(monitor.status(target).unwrap_or_default())
+        }
+        .boxed()
+    })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    .get("getlatestproof", |req, monitor| {
+        async move {
+            let target = target_param(&req, monitor.num_targets())?;
+            Ok(monitor.status(target).and_then(|status| status.last_proof))
+        }
+        .boxed()
+    })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    .get("getproofs", |req, monitor| {
+        async move {
+            let target = target_param(&req, monitor.num_targets())?;
+            Ok(monitor
+                .history(target)
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect::<Vec<_>>())
+        }
+        .boxed()
+    })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    .metrics("metrics", |_, monitor| {
+        async move { Ok(Cow::Borrowed(monitor.metrics())) }.boxed()
     })
     .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
     app.register_module("api", api)
@@ -443,46 +1038,155 @@ pub async fn run_prover_service<ApiVer: StaticVersionType + 'static>(
 ) -> Result<()> {
     let stake_table_capacity = config.stake_table_capacity;
     tracing::info!("Stake table capacity: {}", stake_table_capacity);
-    // TODO(#1022): maintain the following stake table
     let st = Arc::new(
-        init_stake_table_from_sequencer(&config.sequencer_url, stake_table_capacity)
+        load_initial_stake_table(&config)
             .await
             .with_context(|| "Failed to initialize stake table")?,
     );
-    run_prover_service_with_stake_table(config, bind_version, st).await
+    let stake_table_file = config.network_config_file.clone();
+    run_prover_loop(
+        config,
+        bind_version,
+        StakeTableHistory::new(st)?,
+        stake_table_file,
+    )
+    .await
 }
 
+/// Run the prover service with a fixed stake table.
 pub async fn run_prover_service_with_stake_table<ApiVer: StaticVersionType + 'static>(
     config: StateProverConfig,
     bind_version: ApiVer,
     st: Arc<StakeTable<BLSPubKey, StateVerKey, CircuitField>>,
 ) -> Result<()> {
-    tracing::info!("Light client address: {:?}", config.light_client_address);
+    run_prover_loop(config, bind_version, StakeTableHistory::new(st)?, None).await
+}
+
+/// Reload the stake table from the network config file and record it in `stake_tables`.
+fn refresh_stake_table(
+    stake_tables: &mut StakeTableHistory,
+    path: &Path,
+    capacity: usize,
+) -> Result<()> {
+    let st = load_stake_table_from_file(path, capacity)?;
+    if stake_tables.update(Arc::new(st))? {
+        let latest = stake_tables.latest();
+        tracing::info!(
+            epoch = latest.epoch,
+            threshold = %field_to_u256(latest.state.threshold),
+            "Stake table changed"
+        );
+    }
+    Ok(())
+}
+
+async fn run_prover_loop<ApiVer: StaticVersionType + 'static>(
+    config: StateProverConfig,
+    bind_version: ApiVer,
+    mut stake_tables: StakeTableHistory,
+    stake_table_file: Option<PathBuf>,
+) -> Result<()> {
+    let targets = config.targets();
+    for (index, target) in targets.iter().enumerate() {
+        tracing::info!(
+            target_index = index,
+            "Light client address: {:?}, provider: {}",
+            target.light_client_address,
+            target.provider
+        );
+    }
 
     let relay_server_client = Arc::new(Client::<ServerError, ApiVer>::new(
         config.relay_server.clone(),
     ));
 
     // Start the HTTP server to get a functioning healthcheck before any heavy computations.
+    let monitor = Arc::new(RwLock::new(ProverMonitor::new(targets.len())));
     if let Some(port) = config.port {
-        if let Err(err) = start_http_server(port, config.light_client_address, bind_version) {
+        if let Err(err) = start_http_server(
+            port,
+            targets
+                .iter()
+                .map(|target| target.light_client_address)
+                .collect(),
+            monitor.clone(),
+            bind_version,
+        ) {
             tracing::error!("Error starting http server: {}", err);
         }
     }
 
-    let proving_key =
-        spawn_blocking(move || Arc::new(load_proving_key(config.stake_table_capacity))).await;
+    let stake_table_capacity = config.stake_table_capacity;
+    let key_cache = config.key_cache_dir.clone().map(KeyCache::new);
+    let proving_key = spawn_blocking(move || {
+        Arc::new(load_proving_key(stake_table_capacity, key_cache.as_ref()))
+    })
+    .await;
 
-    let update_interval = config.update_interval;
-    let retry_interval = config.retry_interval;
+    // Each target is updated on its own schedule.
+    let mut next_update = vec![Instant::now(); targets.len()];
+    let mut proofs = ProofCache::default();
     loop {
-        if let Err(err) = sync_state(&st, proving_key.clone(), &relay_server_client, &config).await
-        {
-            tracing::error!("Cannot sync the light client state, will retry: {}", err);
-            sleep(retry_interval).await;
-        } else {
-            tracing::info!("Sleeping for {:?}", update_interval);
-            sleep(update_interval).await;
+        let now = Instant::now();
+        let due = (0..targets.len())
+            .filter(|&index| next_update[index] <= now)
+            .collect::<Vec<_>>();
+        if due.is_empty() {
+            let next = next_update.iter().min().unwrap();
+            sleep(next.saturating_duration_since(now)).await;
+            continue;
+        }
+
+        if let Some(path) = &stake_table_file {
+            if let Err(err) =
+                refresh_stake_table(&mut stake_tables, path, config.stake_table_capacity)
+            {
+                tracing::warn!("Cannot refresh the stake table: {err:#}");
+            }
+        }
+        let results = sync_state(
+            &stake_tables,
+            proving_key.clone(),
+            &relay_server_client,
+            &config,
+            &due,
+            &mut proofs,
+            &monitor,
+        )
+        .await;
+
+        let mut status = monitor.write().await;
+        status.set_phase(ProverPhase::Idle);
+        let results: Vec<Result<(), Arc<ProverError>>> = match results {
+            Ok(results) => results
+                .into_iter()
+                .map(|res| res.map_err(Arc::new))
+                .collect(),
+            // The error affects every target we were trying to update.
+            Err(err) => vec![Err(Arc::new(err)); due.len()],
+        };
+        for (index, res) in due.into_iter().zip(results) {
+            status.record_result(index, &res);
+            let target = &targets[index];
+            let interval = match res {
+                Ok(()) => {
+                    tracing::info!(
+                        target_index = index,
+                        "Next update in {:?}",
+                        target.update_interval
+                    );
+                    target.update_interval
+                }
+                Err(err) => {
+                    tracing::error!(
+                        target_index = index,
+                        "Cannot sync the light client state, will retry: {}",
+                        err
+                    );
+                    target.retry_interval
+                }
+            };
+            next_update[index] = Instant::now() + interval;
         }
     }
 }
@@ -492,17 +1196,36 @@ pub async fn run_prover_once<ApiVer: StaticVersionType>(
     config: StateProverConfig,
     _: ApiVer,
 ) -> Result<()> {
-    let st = init_stake_table_from_sequencer(&config.sequencer_url, config.stake_table_capacity)
+    let st = load_initial_stake_table(&config)
         .await
         .with_context(|| "Failed to initialize stake table")?;
+    let stake_tables = StakeTableHistory::new(Arc::new(st))?;
     let stake_table_capacity = config.stake_table_capacity;
-    let proving_key =
-        spawn_blocking(move || Arc::new(load_proving_key(stake_table_capacity))).await;
+    let key_cache = config.key_cache_dir.clone().map(KeyCache::new);
+    let proving_key = spawn_blocking(move || {
+        Arc::new(load_proving_key(stake_table_capacity, key_cache.as_ref()))
+    })
+    .await;
     let relay_server_client = Client::<ServerError, ApiVer>::new(config.relay_server.clone());
 
-    sync_state(&st, proving_key, &relay_server_client, &config)
-        .await
-        .expect("Error syncing the light client state.");
+    let targets = (0..config.targets().len()).collect::<Vec<_>>();
+    let monitor = RwLock::new(ProverMonitor::new(targets.len()));
+    let results = sync_state(
+        &stake_tables,
+        proving_key,
+        &relay_server_client,
+        &config,
+        &targets,
+        &mut ProofCache::default(),
+        &monitor,
+    )
+    .await
+    .expect("Error syncing the light client state.");
+    for (index, res) in results.into_iter().enumerate() {
+        if let Err(err) = res {
+            panic!("Error syncing the light client state of target {index}: {err}");
+        }
+    }
 
     Ok(())
 }
@@ -517,8 +1240,16 @@ pub enum ProverError {
     RelayServerError(ServerError),
     /// Internal error with the stake table
     StakeTableError(StakeTableError),
+    /// The light client contract expects a stake table unknown to the prover
+    UnknownStakeTable,
     /// Internal error when generating the SNARK proof
     PlonkError(PlonkError),
+    /// The light client contract was already updated past this state
+    StateSuperseded,
+    /// Transaction {0:?} was reverted
+    TransactionReverted(H256),
+    /// Transaction was not included after {0} attempts
+    TransactionTimeout(usize),
     /// Internal error
     Internal(String),
 }
@@ -562,7 +1293,10 @@ mod test {
         LightClientConstructorArgs, ParsedStakeTableState,
     };
     use hotshot_stake_table::vec_based::StakeTable;
-    use hotshot_types::light_client::StateSignKey;
+    use hotshot_types::{
+        light_client::{StateKeyPair, StateSignKey},
+        traits::signature_key::SignatureKey,
+    };
     use jf_signature::{schnorr::SchnorrSignatureScheme, SignatureScheme};
     use jf_utils::test_rng;
     use sequencer_utils::{deployer, test_utils::setup_test};
@@ -604,7 +1338,7 @@ mod test {
         (genesis, stake_genesis, qc_keys, state_keys, st)
     }
 
-    // everybody signs, then generate a proof
+    // everybody signs, then generate a proof which keeps the same stake table
     fn gen_state_proof(
         new_state: ParsedLightClientState,
         genesis_stake_state: &ParsedStakeTableState,
@@ -613,11 +1347,9 @@ mod test {
     ) -> (PublicInput, Proof) {
         let mut rng = test_rng();
 
-        let new_state_msg: [CircuitField; 3] = {
-            // sorry for the complicated .into() conversion chain, might improve in the future
-            let pi_msg: LightClientState = new_state.clone().into();
-            pi_msg.into()
-        };
+        let new_state: LightClientState = new_state.into();
+        let stake_state: StakeTableState = genesis_stake_state.clone().into();
+        let new_state_msg = state_signature_message(&new_state, &stake_state);
         let bit_vec = vec![true; st.len(SnapshotVersion::LastEpochStart).unwrap()];
         let sigs = state_keypairs
             .iter()
@@ -627,21 +1359,7 @@ mod test {
             .collect::<Result<Vec<_>, _>>()
             .unwrap();
 
-        let srs = {
-            // load SRS from Aztec's ceremony
-            let srs = ark_srs::kzg10::aztec20::setup(2u64.pow(16) as usize + 2)
-                .expect("Aztec SRS fail to load");
-            // convert to Jellyfish type
-            // TODO: (alex) use constructor instead https://github.com/EspressoSystems/jellyfish/issues/440
-            UnivariateUniversalParams {
-                powers_of_g: srs.powers_of_g,
-                h: srs.h,
-                beta_h: srs.beta_h,
-                powers_of_h: vec![srs.h, srs.beta_h],
-            }
-        };
-        let (pk, _) = crate::preprocess(&srs, STAKE_TABLE_CAPACITY_FOR_TEST)
-            .expect("Fail to preprocess state prover circuit");
+        let pk = proving_key_for_test();
         let stake_table_entries = st
             .try_iter(SnapshotVersion::LastEpochStart)
             .unwrap()
@@ -653,8 +1371,9 @@ mod test {
             &stake_table_entries,
             &bit_vec,
             &sigs,
-            &new_state.into(),
-            &genesis_stake_state.clone().into(),
+            &new_state,
+            &stake_state,
+            &stake_state,
             STAKE_TABLE_CAPACITY_FOR_TEST,
         )
         .expect("Fail to generate state proof");
@@ -662,6 +1381,26 @@ mod test {
         (pi, proof)
     }
 
+    /// preprocess the state prover circuit for [`STAKE_TABLE_CAPACITY_FOR_TEST`]
+    fn proving_key_for_test() -> ProvingKey {
+        let srs = {
+            // load SRS from Aztec's ceremony
+            let srs = ark_srs::kzg10::aztec20::setup(2u64.pow(16) as usize + 2)
+                .expect("Aztec SRS fail to load");
+            // convert to Jellyfish type
+            // TODO: (alex) use constructor instead https://github.com/EspressoSystems/jellyfish/issues/440
+            UnivariateUniversalParams {
+                powers_of_g: srs.powers_of_g,
+                h: srs.h,
+                beta_h: srs.beta_h,
+                powers_of_h: vec![srs.h, srs.beta_h],
+            }
+        };
+        let (pk, _) = crate::preprocess(&srs, STAKE_TABLE_CAPACITY_FOR_TEST)
+            .expect("Fail to preprocess state prover circuit");
+        pk
+    }
+
     /// deploy LightClientMock.sol on local blockchain (via `anvil`) for testing
     /// return (signer-loaded wallet, contract instance)
     async fn deploy_contract_for_test(
@@ -712,12 +1451,301 @@ mod test {
                 light_client_address: Address::default(),
                 signing_key: SigningKey::random(&mut test_rng()),
                 sequencer_url: Url::parse("http://localhost").unwrap(),
+                network_config_file: None,
                 port: None,
                 stake_table_capacity: 10,
+                key_cache_dir: None,
+                submission: Default::default(),
+                alt_targets: vec![],
             }
         }
     }
 
+    #[test]
+    fn test_stake_table_history() {
+        setup_test();
+
+        let stake_table = |n: u64| {
+            let (bls_keys, state_keys): (Vec<_>, Vec<_>) = (0..n)
+                .map(|i| {
+                    (
+                        BLSPubKey::generated_from_seed_indexed([0; 32], i).0,
+                        StateKeyPair::generate_from_seed_indexed([0; 32], i).ver_key(),
+                    )
+                })
+                .unzip();
+            // Leave room for every stake table the history can hold.
+            Arc::new(
+                init_stake_table(&bls_keys, &state_keys, 2 * STAKE_TABLE_HISTORY_CAPACITY).unwrap(),
+            )
+        };
+        let state = |st: &StakeTable<BLSPubKey, StateVerKey, CircuitField>| -> StakeTableState {
+            stake_table_state(st).unwrap().into()
+        };
+
+        let st1 = stake_table(2);
+        let mut history = StakeTableHistory::new(st1.clone()).unwrap();
+        assert_eq!(history.latest().state, state(&st1));
+        assert_eq!(history.latest().epoch, 0);
+
+        // Recording the same stake table again is not a change.
+        assert!(!history.update(stake_table(2)).unwrap());
+        assert_eq!(history.latest().epoch, 0);
+
+        // A new validator set starts a new epoch, and the old one can still be found.
+        let st2 = stake_table(3);
+        assert!(history.update(st2.clone()).unwrap());
+        assert_eq!(history.latest().state, state(&st2));
+        assert_eq!(history.latest().epoch, 1);
+        assert!(Arc::ptr_eq(
+            &history.get(&state(&st1)).unwrap().stake_table,
+            &st1
+        ));
+        assert!(Arc::ptr_eq(
+            &history.get(&state(&st2)).unwrap().stake_table,
+            &st2
+        ));
+
+        // Returning to an earlier stake table starts yet another epoch, which is the one found by
+        // its state, while the earlier epoch is kept.
+        assert!(history.update(st1.clone()).unwrap());
+        assert_eq!(history.latest().state, state(&st1));
+        assert_eq!(history.latest().epoch, 2);
+        assert_eq!(history.get(&state(&st1)).unwrap().epoch, 2);
+        assert_eq!(history.epoch(0).unwrap().state, state(&st1));
+        assert_eq!(history.epoch(1).unwrap().state, state(&st2));
+
+        // Only a bounded number of epochs are remembered.
+        for n in 4..4 + STAKE_TABLE_HISTORY_CAPACITY as u64 {
+            assert!(history.update(stake_table(n)).unwrap());
+        }
+        assert!(history.epoch(0).is_none());
+        assert!(history.get(&state(&st1)).is_none());
+        assert!(history.get(&state(&st2)).is_none());
+    }
+
+    #[test]
+    fn test_stake_table_file_reload() {
+        setup_test();
+
+        let peers = |indices: std::ops::Range<u64>| {
+            indices
+                .map(|i| PeerConfig {
+                    stake_table_entry: BLSPubKey::generated_from_seed_indexed([0; 32], i)
+                        .0
+                        .stake_table_entry(1),
+                    state_ver_key: StateKeyPair::generate_from_seed_indexed([0; 32], i).ver_key(),
+                })
+                .collect::<Vec<_>>()
+        };
+        let write = |path: &Path, peers: Vec<PeerConfig<BLSPubKey>>| {
+            let config = serde_json::json!({ "config": { "known_nodes_with_stake": peers } });
+            std::fs::write(path, serde_json::to_vec(&config).unwrap()).unwrap();
+        };
+        let state = |peers: Vec<PeerConfig<BLSPubKey>>| -> StakeTableState {
+            let st = stake_table_from_config(
+                PublicHotShotConfig {
+                    known_nodes_with_stake: peers,
+                },
+                STAKE_TABLE_CAPACITY_FOR_TEST,
+            )
+            .unwrap();
+            stake_table_state(&st).unwrap().into()
+        };
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("network-config.json");
+        write(&path, peers(0..4));
+        let st = load_stake_table_from_file(&path, STAKE_TABLE_CAPACITY_FOR_TEST).unwrap();
+        let mut history = StakeTableHistory::new(Arc::new(st)).unwrap();
+        assert_eq!(history.latest().state, state(peers(0..4)));
+
+        // Reloading an unchanged file does not start a new epoch.
+        refresh_stake_table(&mut history, &path, STAKE_TABLE_CAPACITY_FOR_TEST).unwrap();
+        assert_eq!(history.latest().epoch, 0);
+
+        // Once the file is updated with a new validator set, the prover picks it up.
+        write(&path, peers(2..6));
+        refresh_stake_table(&mut history, &path, STAKE_TABLE_CAPACITY_FOR_TEST).unwrap();
+        assert_eq!(history.latest().epoch, 1);
+        assert_eq!(history.latest().state, state(peers(2..6)));
+
+        // A malformed file leaves the stake table unchanged.
+        std::fs::write(&path, "not a network config").unwrap();
+        refresh_stake_table(&mut history, &path, STAKE_TABLE_CAPACITY_FOR_TEST).unwrap_err();
+        assert_eq!(history.latest().epoch, 1);
+    }
+
+    #[async_std::test]
+    async fn test_stake_table_rotation() -> Result<()> {
+        setup_test();
+
+        // Six validators a to f, each with one unit of stake.
+        let validators = (0..6)
+            .map(|i| {
+                (
+                    BLSPubKey::generated_from_seed_indexed([0; 32], i).0,
+                    StateKeyPair::generate_from_seed_indexed([0; 32], i),
+                )
+            })
+            .collect::<Vec<_>>();
+        let stake_table = |validators: &[(BLSPubKey, StateKeyPair)]| {
+            let (bls_keys, state_keys): (Vec<_>, Vec<_>) = validators
+                .iter()
+                .map(|(bls_key, state_key)| (*bls_key, state_key.ver_key()))
+                .unzip();
+            Arc::new(
+                init_stake_table(&bls_keys, &state_keys, STAKE_TABLE_CAPACITY_FOR_TEST).unwrap(),
+            )
+        };
+        let sign = |state: &LightClientState,
+                    next_st_state: &StakeTableState,
+                    validators: &[(BLSPubKey, StateKeyPair)]| {
+            let msg = state_signature_message(state, next_st_state);
+            StateSignaturesBundle {
+                state: state.clone(),
+                signatures: validators
+                    .iter()
+                    .map(|(_, key)| {
+                        let sig = SchnorrSignatureScheme::<EdwardsConfig>::sign(
+                            &(),
+                            key.sign_key_ref(),
+                            msg,
+                            &mut test_rng(),
+                        )
+                        .unwrap();
+                        (key.ver_key(), sig)
+                    })
+                    .collect(),
+                accumulated_weight: U256::from(validators.len()),
+            }
+        };
+
+        // The contract is deployed with the validator set {a, b, c, d}, whose threshold is 2.
+        let old_validators = &validators[..4];
+        let old_st = stake_table(old_validators);
+        let mut history = StakeTableHistory::new(old_st.clone())?;
+        let genesis = ParsedLightClientState::dummy_genesis();
+        let stake_genesis: ParsedStakeTableState = stake_table_state(&old_st)?;
+
+        let anvil = Anvil::new().spawn();
+        let (_wallet, contract) =
+            deploy_contract_for_test(&anvil, genesis.clone(), stake_genesis.clone()).await?;
+        let mut config = StateProverConfig::default();
+        config.update_l1_info(&anvil, contract.address());
+
+        // a and b leave, and e and f join.
+        let new_validators = &validators[2..];
+        let new_st = stake_table(new_validators);
+        let new_st_state: StakeTableState = stake_table_state(&new_st)?.into();
+        assert!(history.update(new_st)?);
+        assert_eq!(history.latest().epoch, 1);
+
+        let (_, st_state) = super::read_contract_state(
+            config.provider.clone(),
+            config.signing_key.clone(),
+            config.light_client_address,
+        )
+        .await?;
+        assert_eq!(history.get(&st_state).unwrap().epoch, 0);
+
+        let mut state1: LightClientState = genesis.into();
+        state1.view_number = 5;
+        state1.block_height = 1;
+
+        let pk = Arc::new(proving_key_for_test());
+        let monitor = RwLock::new(ProverMonitor::new(1));
+
+        // Signatures which don't commit to the new stake table can't hand over to it.
+        let err = generate_proof(
+            &sign(&state1, &st_state, new_validators),
+            st_state,
+            &history,
+            pk.clone(),
+            STAKE_TABLE_CAPACITY_FOR_TEST,
+            &monitor,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ProverError::InvalidState(_)), "{err}");
+
+        // The new validators commit to their stake table. c and d are also in the stake table the
+        // contract expects, and their signatures reach its threshold.
+        let proof = generate_proof(
+            &sign(&state1, &new_st_state, new_validators),
+            st_state,
+            &history,
+            pk.clone(),
+            STAKE_TABLE_CAPACITY_FOR_TEST,
+            &monitor,
+        )
+        .await?;
+        assert_eq!(proof.stake_table_state, st_state);
+        assert_eq!(proof.next_stake_table_state, new_st_state);
+        super::submit_state_and_proof(
+            proof.proof,
+            proof.public_input,
+            config.provider.clone(),
+            config.signing_key.clone(),
+            config.light_client_address,
+            &config.submission,
+        )
+        .await?;
+        let finalized: ParsedLightClientState = contract.finalized_state().await?.into();
+        assert_eq!(LightClientState::from(finalized), state1);
+
+        // From now on, the contract expects the new stake table.
+        let (_, st_state) = super::read_contract_state(
+            config.provider.clone(),
+            config.signing_key.clone(),
+            config.light_client_address,
+        )
+        .await?;
+        assert_eq!(st_state, new_st_state);
+
+        let mut state2 = state1.clone();
+        state2.view_number = 6;
+        state2.block_height = 2;
+
+        // The departed validators can no longer update the contract...
+        let err = generate_proof(
+            &sign(&state2, &new_st_state, &validators[..2]),
+            st_state,
+            &history,
+            pk.clone(),
+            STAKE_TABLE_CAPACITY_FOR_TEST,
+            &monitor,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ProverError::InvalidState(_)), "{err}");
+
+        // ...but the validators who joined can, without the old ones.
+        let proof = generate_proof(
+            &sign(&state2, &new_st_state, &validators[4..]),
+            st_state,
+            &history,
+            pk,
+            STAKE_TABLE_CAPACITY_FOR_TEST,
+            &monitor,
+        )
+        .await?;
+        assert_eq!(proof.stake_table_state, new_st_state);
+        assert_eq!(proof.next_stake_table_state, new_st_state);
+        super::submit_state_and_proof(
+            proof.proof,
+            proof.public_input,
+            config.provider,
+            config.signing_key,
+            config.light_client_address,
+            &config.submission,
+        )
+        .await?;
+        let finalized: ParsedLightClientState = contract.finalized_state().await?.into();
+        assert_eq!(LightClientState::from(finalized), state2);
+        Ok(())
+    }
+
     #[async_std::test]
     async fn test_validate_light_contract_is_proxy() {
         // set the light client address to an address known to be a proxy
@@ -780,6 +1808,11 @@ mod test {
             contract.genesis_stake_table_state().await?.into();
         assert_eq!(stake_genesis, dummy_stake_genesis);
 
+        // Until the first update, the genesis stake table is the one which votes.
+        let voting_stake_table: ParsedStakeTableState =
+            contract.voting_stake_table_state().await?.into();
+        assert_eq!(voting_stake_table, dummy_stake_genesis);
+
         let mut config = StateProverConfig::default();
         config.update_l1_info(&anvil, contract.address());
         let (state, st_state) = super::read_contract_state(
@@ -823,6 +1856,7 @@ mod test {
             config.provider,
             config.signing_key,
             config.light_client_address,
+            &config.submission,
         )
         .await?;
         tracing::info!("Successfully submitted new finalized state to L1.");
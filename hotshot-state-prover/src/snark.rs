@@ -7,9 +7,7 @@ use ark_std::{
 use ethers::types::U256;
 /// BLS verification key, base field and Schnorr verification key
 pub use hotshot_stake_table::vec_based::config::QCVerKey;
-use hotshot_types::light_client::{
-    CircuitField, LightClientState, PublicInput, StakeTableState, StateVerKey,
-};
+use hotshot_types::light_client::{CircuitField, LightClientState, StakeTableState, StateVerKey};
 use jf_plonk::{
     errors::PlonkError,
     proof_system::{PlonkKzgSnark, UniversalSNARK},
@@ -24,6 +22,8 @@ pub type VerifyingKey = jf_plonk::proof_system::structs::VerifyingKey<Bn254>;
 pub type Proof = jf_plonk::proof_system::structs::Proof<Bn254>;
 /// Universal SRS
 pub type UniversalSrs = jf_plonk::proof_system::structs::UniversalSrs<Bn254>;
+/// Public input of the state update circuit
+pub type PublicInput = crate::circuit::GenericPublicInput<CircuitField>;
 
 /// Given a SRS, returns the proving key and verifying key for state update
 /// # Errors
@@ -43,13 +43,14 @@ pub fn preprocess(
 /// - a list of stake table entries (`Vec<(BLSVerKey, Amount, SchnorrVerKey)>`)
 /// - a list of schnorr signatures of the updated states (`Vec<SchnorrSignature>`), default if the node doesn't sign the state
 /// - updated light client state (`(view_number, block_height, block_comm_root)`)
-/// - the static stake table state (containing 3 commitments to the 3 columns of the stake table and a threshold)
+/// - the voting stake table state (containing 3 commitments to the 3 columns of the stake table and a threshold)
+/// - the next stake table state, which the signers committed to
 /// - a bit vector indicates the signers
 ///
 /// Returns error or a pair `(proof, public_inputs)` asserting that
 /// - the signer's accumulated weight exceeds the quorum threshold
 /// - the stake table corresponds to the one committed in the light client state
-/// - all schnorr signatures over the light client state are valid
+/// - all schnorr signatures over the light client state and the next stake table state are valid
 ///
 /// # Errors
 /// Errors if unable to generate proof
@@ -65,6 +66,7 @@ pub fn generate_state_update_proof<STIter, R, BitIter, SigIter>(
     signatures: SigIter,
     lightclient_state: &LightClientState,
     stake_table_state: &StakeTableState,
+    next_stake_table_state: &StakeTableState,
     stake_table_capacity: usize,
 ) -> Result<(Proof, PublicInput), PlonkError>
 where
@@ -93,6 +95,7 @@ where
         signatures,
         lightclient_state,
         stake_table_state,
+        next_stake_table_state,
         stake_table_capacity,
     )?;
     let proof = PlonkKzgSnark::<Bn254>::prove::<_, _, SolidityTranscript>(rng, &circuit, pk, None)?;
@@ -102,12 +105,7 @@ where
 #[cfg(test)]
 mod tests {
     use ark_bn254::Bn254;
-    use ark_ec::pairing::Pairing;
     use ark_ed_on_bn254::EdwardsConfig as Config;
-    use ark_std::{
-        rand::{CryptoRng, RngCore},
-        One,
-    };
     use hotshot_types::{
         light_client::LightClientState,
         traits::stake_table::{SnapshotVersion, StakeTableScheme},
@@ -125,69 +123,17 @@ mod tests {
     };
     use jf_utils::test_rng;
 
-    use super::{generate_state_update_proof, preprocess, CircuitField, UniversalSrs};
+    use super::{generate_state_update_proof, preprocess, CircuitField};
     use crate::{
-        circuit::build_for_preprocessing,
-        test_utils::{genesis_stake_table_state, key_pairs_for_testing, stake_table_for_testing},
+        circuit::{build_for_preprocessing, state_signature_message},
+        test_utils::{
+            genesis_stake_table_state, key_pairs_for_testing, stake_table_for_testing,
+            universal_setup_for_testing,
+        },
     };
 
     const ST_CAPACITY: usize = 20;
 
-    // FIXME(Chengyu): see <https://github.com/EspressoSystems/jellyfish/issues/249>
-    #[allow(clippy::unnecessary_wraps)]
-    fn universal_setup_for_testing<R>(
-        max_degree: usize,
-        rng: &mut R,
-    ) -> anyhow::Result<UniversalSrs>
-    where
-        R: RngCore + CryptoRng,
-    {
-        use ark_ec::{scalar_mul::fixed_base::FixedBase, CurveGroup};
-        use ark_ff::PrimeField;
-        use ark_std::{end_timer, start_timer, UniformRand};
-
-        let setup_time = start_timer!(|| format!("KZG10::Setup with degree {}", max_degree));
-        let beta = <Bn254 as Pairing>::ScalarField::rand(rng);
-        let g = <Bn254 as Pairing>::G1::rand(rng);
-        let h = <Bn254 as Pairing>::G2::rand(rng);
-
-        let mut powers_of_beta = vec![<Bn254 as Pairing>::ScalarField::one()];
-
-        let mut cur = beta;
-        for _ in 0..max_degree {
-            powers_of_beta.push(cur);
-            cur *= &beta;
-        }
-
-        let window_size = FixedBase::get_mul_window_size(max_degree + 1);
-
-        let scalar_bits = <Bn254 as Pairing>::ScalarField::MODULUS_BIT_SIZE as usize;
-        let g_time = start_timer!(|| "Generating powers of G");
-        // TODO: parallelization
-        let g_table = FixedBase::get_window_table(scalar_bits, window_size, g);
-        let powers_of_g = FixedBase::msm::<<Bn254 as Pairing>::G1>(
-            scalar_bits,
-            window_size,
-            &g_table,
-            &powers_of_beta,
-        );
-        end_timer!(g_time);
-
-        let powers_of_g = <Bn254 as Pairing>::G1::normalize_batch(&powers_of_g);
-
-        let h = h.into_affine();
-        let beta_h = (h * beta).into_affine();
-
-        let pp = UniversalSrs {
-            powers_of_g,
-            h,
-            beta_h,
-            powers_of_h: vec![h, beta_h],
-        };
-        end_timer!(setup_time);
-        Ok(pp)
-    }
-
     #[test]
     fn test_proof_generation() {
         let num_validators = 10;
@@ -214,7 +160,7 @@ mod tests {
             block_height: 73,
             block_comm_root,
         };
-        let state_msg: [CircuitField; 3] = lightclient_state.clone().into();
+        let state_msg = state_signature_message(&lightclient_state, &st_state);
 
         let sigs = schnorr_keys
             .iter()
@@ -259,6 +205,7 @@ mod tests {
             &bit_masked_sigs,
             &lightclient_state,
             &st_state,
+            &st_state,
             ST_CAPACITY,
         );
         assert!(result.is_ok());
@@ -266,7 +213,7 @@ mod tests {
         let (proof, public_inputs) = result.unwrap();
         assert!(PlonkKzgSnark::<Bn254>::verify::<SolidityTranscript>(
             &vk,
-            public_inputs.as_ref(),
+            &public_inputs.to_vec(),
             &proof,
             None
         )
@@ -283,6 +230,7 @@ mod tests {
             &bit_masked_sigs,
             &lightclient_state,
             &bad_st_state,
+            &st_state,
             ST_CAPACITY,
         );
         assert!(result.is_err());
@@ -0,0 +1,272 @@
+//! Status and metrics of a running prover service.
+//!
+//! The prover records what it is doing in a [`ProverMonitor`], which is served by the prover's HTTP
+//! API and exported as Prometheus metrics, so that operators can tell from outside whether the
+//! prover is stuck or slow. Proof generation is shared by all the light client contracts the prover
+//! submits to, but submissions and their results are tracked separately for each contract.
+
+use std::{collections::VecDeque, time::Duration};
+
+use ethers::types::H256;
+use hotshot_query_service::metrics::PrometheusMetrics;
+use hotshot_types::{
+    light_client::{LightClientState, StakeTableState},
+    traits::metrics::{Counter, Gauge, Histogram, Metrics as _},
+};
+use serde::{Deserialize, Serialize};
+
+/// Number of recent proofs kept by a [`ProverMonitor`].
+pub const PROOF_HISTORY_CAPACITY: usize = 100;
+
+/// What the prover is currently doing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProverPhase {
+    /// Waiting for the next update.
+    #[default]
+    Idle,
+    /// Generating a proof for a new state.
+    Proving,
+    /// Submitting a proof to the light client contract.
+    Submitting,
+}
+
+/// A proof which was generated and accepted by the light client contract.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ProofRecord {
+    /// The light client state proven, part of the public input.
+    pub state: LightClientState,
+    /// The stake table state the proof was verified against, part of the public input.
+    pub stake_table_state: StakeTableState,
+    /// Hash of the L1 transaction which submitted the proof.
+    pub tx_hash: H256,
+    /// The L1 block which included the transaction.
+    pub l1_block: u64,
+    /// Time taken to generate the proof.
+    pub proving_time: Duration,
+    /// Time taken to submit the proof and have the transaction included.
+    pub submission_time: Duration,
+}
+
+/// Current status of the prover, with respect to one light client contract.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct ProverStatus {
+    pub phase: ProverPhase,
+    /// The error from the last attempt to update this contract, if it failed.
+    pub last_error: Option<String>,
+    /// The most recent proof successfully submitted to this contract.
+    pub last_proof: Option<ProofRecord>,
+}
+
+#[derive(Debug)]
+struct ProverMetrics {
+    phase: Box<dyn Gauge>,
+    proving_time: Box<dyn Histogram>,
+    submission_time: Box<dyn Histogram>,
+}
+
+/// Metrics for one light client contract, labeled by the index of its target.
+#[derive(Debug)]
+struct TargetMetrics {
+    block_height: Box<dyn Gauge>,
+    proofs: Box<dyn Counter>,
+    failures: Box<dyn Counter>,
+}
+
+#[derive(Debug)]
+struct TargetMonitor {
+    last_error: Option<String>,
+    history: VecDeque<ProofRecord>,
+    metrics: TargetMetrics,
+}
+
+/// Tracks the status of the prover and its recent proofs for each of its targets.
+#[derive(Debug)]
+pub struct ProverMonitor {
+    phase: ProverPhase,
+    targets: Vec<TargetMonitor>,
+    registry: PrometheusMetrics,
+    metrics: ProverMetrics,
+}
+
+impl ProverMonitor {
+    /// Create a monitor for a prover submitting to `num_targets` light client contracts.
+    pub fn new(num_targets: usize) -> Self {
+        let registry = PrometheusMetrics::default();
+        let metrics = ProverMetrics {
+            phase: registry.create_gauge("phase".into(), None),
+            proving_time: registry.create_histogram("proving_time".into(), Some("s".into())),
+            submission_time: registry.create_histogram("submission_time".into(), Some("s".into())),
+        };
+
+        let block_height =
+            registry.gauge_family("last_proven_block_height".into(), vec!["target".into()]);
+        let proofs = registry.counter_family("proofs_submitted".into(), vec!["target".into()]);
+        let failures = registry.counter_family("update_failures".into(), vec!["target".into()]);
+        let targets = (0..num_targets)
+            .map(|i| {
+                let label = vec![i.to_string()];
+                TargetMonitor {
+                    last_error: None,
+                    history: Default::default(),
+                    metrics: TargetMetrics {
+                        block_height: block_height.create(label.clone()),
+                        proofs: proofs.create(label.clone()),
+                        failures: failures.create(label),
+                    },
+                }
+            })
+            .collect();
+
+        Self {
+            phase: Default::default(),
+            targets,
+            registry,
+            metrics,
+        }
+    }
+
+    /// The number of light client contracts the prover submits to.
+    pub fn num_targets(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// The status of the prover with respect to the contract of `target`.
+    pub fn status(&self, target: usize) -> Option<ProverStatus> {
+        let target = self.targets.get(target)?;
+        Some(ProverStatus {
+            phase: self.phase,
+            last_error: target.last_error.clone(),
+            last_proof: target.history.back().cloned(),
+        })
+    }
+
+    /// Recent proofs submitted to the contract of `target`, oldest first.
+    pub fn history(&self, target: usize) -> Option<impl Iterator<Item = &ProofRecord>> {
+        Some(self.targets.get(target)?.history.iter())
+    }
+
+    /// The Prometheus metrics of the prover.
+    pub fn metrics(&self) -> &PrometheusMetrics {
+        &self.registry
+    }
+
+    pub fn set_phase(&mut self, phase: ProverPhase) {
+        self.phase = phase;
+        self.metrics.phase.set(phase as usize);
+    }
+
+    /// Record the time taken to generate a proof.
+    ///
+    /// This is recorded once per proof, no matter how many contracts it is submitted to.
+    pub fn record_proving_time(&mut self, proving_time: Duration) {
+        self.metrics
+            .proving_time
+            .add_point(proving_time.as_secs_f64());
+    }
+
+    /// Record a proof successfully submitted to the contract of `target`.
+    pub fn record_proof(&mut self, target: usize, proof: ProofRecord) {
+        self.metrics
+            .submission_time
+            .add_point(proof.submission_time.as_secs_f64());
+
+        let target = &mut self.targets[target];
+        target.metrics.proofs.add(1);
+        target.metrics.block_height.set(proof.state.block_height);
+        target.last_error = None;
+        target.history.push_back(proof);
+        while target.history.len() > PROOF_HISTORY_CAPACITY {
+            target.history.pop_front();
+        }
+    }
+
+    /// Record the result of an attempt to update the contract of `target`.
+    pub fn record_result<E: std::fmt::Display>(&mut self, target: usize, res: &Result<(), E>) {
+        let target = &mut self.targets[target];
+        match res {
+            Ok(()) => target.last_error = None,
+            Err(err) => {
+                target.metrics.failures.add(1);
+                target.last_error = Some(err.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn proof(block_height: usize) -> ProofRecord {
+        ProofRecord {
+            state: LightClientState {
+                view_number: block_height,
+                block_height,
+                block_comm_root: Default::default(),
+            },
+            stake_table_state: StakeTableState {
+                bls_key_comm: Default::default(),
+                schnorr_key_comm: Default::default(),
+                amount_comm: Default::default(),
+                threshold: Default::default(),
+            },
+            tx_hash: H256::from_low_u64_be(block_height as u64),
+            l1_block: block_height as u64,
+            proving_time: Duration::from_secs(60),
+            submission_time: Duration::from_secs(12),
+        }
+    }
+
+    #[test]
+    fn test_prover_monitor() {
+        let mut monitor = ProverMonitor::new(2);
+        assert_eq!(monitor.num_targets(), 2);
+        assert_eq!(monitor.status(0), Some(ProverStatus::default()));
+        assert_eq!(monitor.status(2), None);
+
+        // The phase is shared by all targets.
+        monitor.set_phase(ProverPhase::Proving);
+        assert_eq!(monitor.status(0).unwrap().phase, ProverPhase::Proving);
+        assert_eq!(monitor.status(1).unwrap().phase, ProverPhase::Proving);
+        monitor.set_phase(ProverPhase::Idle);
+
+        // A failed attempt is reported until the next success, only for its own target.
+        monitor.record_result(0, &Err("relay server unavailable"));
+        assert_eq!(
+            monitor.status(0).unwrap().last_error.as_deref(),
+            Some("relay server unavailable")
+        );
+        assert_eq!(monitor.status(1).unwrap().last_error, None);
+        monitor.record_proof(0, proof(1));
+        monitor.record_result::<String>(0, &Ok(()));
+        assert_eq!(monitor.status(0).unwrap().last_error, None);
+        assert_eq!(monitor.status(0).unwrap().last_proof, Some(proof(1)));
+        assert_eq!(monitor.status(1).unwrap().last_proof, None);
+
+        // Only a bounded number of proofs are kept.
+        for height in 2..=PROOF_HISTORY_CAPACITY + 1 {
+            monitor.record_proof(0, proof(height));
+        }
+        let heights = monitor
+            .history(0)
+            .unwrap()
+            .map(|proof| proof.state.block_height)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            heights,
+            (2..=PROOF_HISTORY_CAPACITY + 1).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            monitor
+                .status(0)
+                .unwrap()
+                .last_proof
+                .unwrap()
+                .state
+                .block_height,
+            PROOF_HISTORY_CAPACITY + 1
+        );
+        assert_eq!(monitor.history(1).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,457 @@
+//! Submission of light client updates to layer 1.
+//!
+//! An update is sent as an EIP-1559 transaction with configurable fee caps. If it is not included
+//! within a timeout, it is replaced by a transaction with the same nonce and higher fees, up to a
+//! bounded number of attempts. Before each replacement the contract is checked again, so that no
+//! more fees are spent once another prover has already advanced it past the proven state.
+//!
+//! If an update is still pending when the next one is submitted, the next one takes its nonce and
+//! replaces it, outbidding it if necessary, rather than queueing behind it.
+
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use async_std::task::sleep;
+use clap::Args;
+use contract_bindings::light_client::{LightClient, LightClientErrors};
+use espresso_types::parse_duration;
+use ethers::{
+    contract::ContractCall,
+    providers::Middleware,
+    signers::Signer,
+    types::{BlockNumber, Eip1559TransactionRequest, TransactionReceipt, H256, U256},
+};
+use hotshot_contract_adapter::light_client::ParsedLightClientState;
+
+use crate::service::{ProverError, SignerWallet};
+
+/// How often to check whether a submitted transaction has been included.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Policy for submitting light client updates to layer 1.
+#[derive(Clone, Debug, Args)]
+pub struct SubmissionConfig {
+    /// Maximum fee per gas, in gwei, to pay for a light client update.
+    ///
+    /// If not set, the fee estimated by the layer 1 provider is used, and bumped without limit
+    /// when replacing stuck transactions.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_MAX_FEE_PER_GAS")]
+    pub max_fee_per_gas: Option<u64>,
+
+    /// Maximum priority fee per gas, in gwei, to pay for a light client update.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_MAX_PRIORITY_FEE_PER_GAS")]
+    pub max_priority_fee_per_gas: Option<u64>,
+
+    /// How long to wait for a transaction to be included before replacing it with higher fees.
+    #[clap(long, value_parser = parse_duration, default_value = "2m", env = "ESPRESSO_STATE_PROVER_TX_TIMEOUT")]
+    pub tx_timeout: Duration,
+
+    /// Percentage by which to increase fees when replacing a stuck transaction.
+    ///
+    /// Most nodes reject replacements which increase fees by less than 10%.
+    #[clap(
+        long,
+        default_value = "20",
+        env = "ESPRESSO_STATE_PROVER_FEE_BUMP_PERCENT"
+    )]
+    pub fee_bump_percent: u64,
+
+    /// Maximum number of transactions to send for one update, including replacements.
+    #[clap(
+        long,
+        default_value = "5",
+        env = "ESPRESSO_STATE_PROVER_MAX_SUBMISSION_ATTEMPTS"
+    )]
+    pub max_submission_attempts: usize,
+}
+
+impl Default for SubmissionConfig {
+    fn default() -> Self {
+        Self {
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            tx_timeout: Duration::from_secs(120),
+            fee_bump_percent: 20,
+            max_submission_attempts: 5,
+        }
+    }
+}
+
+impl SubmissionConfig {
+    /// Apply the configured caps to a pair of fees `(max_fee, max_priority_fee)`.
+    fn cap_fees(&self, max_fee: U256, priority_fee: U256) -> (U256, U256) {
+        let max_fee = match self.max_fee_per_gas {
+            Some(cap) => max_fee.min(gwei(cap)),
+            None => max_fee,
+        };
+        let priority_fee = match self.max_priority_fee_per_gas {
+            Some(cap) => priority_fee.min(gwei(cap)),
+            None => priority_fee,
+        };
+        (max_fee, priority_fee.min(max_fee))
+    }
+
+    fn bump(&self, fee: U256) -> U256 {
+        fee * (100 + self.fee_bump_percent) / 100
+    }
+}
+
+fn gwei(amount: u64) -> U256 {
+    U256::from(amount) * U256::exp10(9)
+}
+
+fn provider_error(err: impl std::fmt::Display) -> ProverError {
+    ProverError::ContractError(anyhow!("{err}"))
+}
+
+/// Read the block height of the latest state in the light client contract.
+async fn finalized_block_height(contract: &LightClient<SignerWallet>) -> Result<u64, ProverError> {
+    let state: ParsedLightClientState = contract
+        .finalized_state()
+        .call()
+        .await
+        .map_err(|err| ProverError::ContractError(err.into()))?
+        .into();
+    Ok(state.block_height)
+}
+
+/// Submit a light client update for a state at `block_height`.
+///
+/// Returns the receipt of the transaction which was included, and the block which included it.
+pub(crate) async fn submit_update(
+    contract: &LightClient<SignerWallet>,
+    call: ContractCall<SignerWallet, ()>,
+    block_height: u64,
+    config: &SubmissionConfig,
+) -> Result<(TransactionReceipt, u64), ProverError> {
+    let client = contract.client();
+
+    // Simulating the call also tells us if it would revert, without spending any gas.
+    let gas = call.estimate_gas().await.map_err(|err| {
+        match err.decode_contract_revert::<LightClientErrors>() {
+            Some(LightClientErrors::OutdatedState(_)) => ProverError::StateSuperseded,
+            Some(revert) => ProverError::ContractError(anyhow!("contract revert: {revert:?}")),
+            None => ProverError::ContractError(anyhow!("error estimating gas: {err}")),
+        }
+    })?;
+    let data = call
+        .calldata()
+        .ok_or_else(|| ProverError::Internal("contract call has no calldata".into()))?;
+    let (max_fee, priority_fee) = client
+        .estimate_eip1559_fees(None)
+        .await
+        .map_err(provider_error)?;
+    let (mut max_fee, mut priority_fee) = config.cap_fees(max_fee, priority_fee);
+
+    // Take the next nonce after the included transactions, not after the pending ones, so that an
+    // update from an earlier round which is stuck in the mempool is replaced by this one instead of
+    // blocking it. Every attempt below reuses this nonce, so that the replacements of this update
+    // are mutually exclusive.
+    let from = client.address();
+    let nonce = client
+        .get_transaction_count(from, Some(BlockNumber::Latest.into()))
+        .await
+        .map_err(provider_error)?;
+
+    let mut sent: Vec<H256> = vec![];
+    for attempt in 1..=config.max_submission_attempts {
+        let mut send = true;
+        if attempt > 1 {
+            if finalized_block_height(contract).await? >= block_height {
+                return Err(ProverError::StateSuperseded);
+            }
+            let (new_max_fee, new_priority_fee) =
+                config.cap_fees(config.bump(max_fee), config.bump(priority_fee));
+            if new_max_fee > max_fee && new_priority_fee > priority_fee {
+                max_fee = new_max_fee;
+                priority_fee = new_priority_fee;
+            } else if sent.is_empty() {
+                // Nothing we could wait for, the stuck transaction outbids us.
+                return Err(ProverError::ContractError(anyhow!(
+                    "fee cap reached before replacing pending transaction {nonce}"
+                )));
+            } else {
+                // At the fee cap, a replacement would be rejected, so just keep waiting.
+                tracing::warn!(attempt, %max_fee, "fee cap reached, not replacing transaction");
+                send = false;
+            }
+        }
+
+        if send {
+            let tx = Eip1559TransactionRequest::new()
+                .from(from)
+                .to(contract.address())
+                .data(data.clone())
+                .gas(gas)
+                .nonce(nonce)
+                .max_fee_per_gas(max_fee)
+                .max_priority_fee_per_gas(priority_fee)
+                .chain_id(client.signer().chain_id());
+            match client.send_transaction(tx, None).await {
+                Ok(pending) => {
+                    tracing::info!(
+                        attempt,
+                        %max_fee,
+                        %priority_fee,
+                        "submitted light client update: tx={:x}",
+                        pending.tx_hash()
+                    );
+                    sent.push(pending.tx_hash());
+                }
+                // If a transaction from an earlier round is pending with this nonce, we have to
+                // outbid it, so try again right away with higher fees.
+                Err(err)
+                    if sent.is_empty()
+                        && attempt < config.max_submission_attempts
+                        && err.to_string().contains("underpriced") =>
+                {
+                    tracing::warn!(
+                        attempt,
+                        "fees too low to replace pending transaction: {err}"
+                    );
+                    continue;
+                }
+                // If nothing was sent, there is nothing to wait for.
+                Err(err) if sent.is_empty() => return Err(provider_error(err)),
+                // Otherwise, an earlier transaction may have been included in the meantime, which
+                // we will find out below.
+                Err(err) => tracing::warn!(attempt, "error replacing transaction: {err}"),
+            }
+        }
+
+        // Wait for any of the transactions sent so far to be included.
+        let deadline = Instant::now() + config.tx_timeout;
+        loop {
+            for hash in &sent {
+                let Some(receipt) = client
+                    .get_transaction_receipt(*hash)
+                    .await
+                    .map_err(provider_error)?
+                else {
+                    continue;
+                };
+                if receipt.status != Some(1.into()) {
+                    if finalized_block_height(contract).await? >= block_height {
+                        return Err(ProverError::StateSuperseded);
+                    }
+                    return Err(ProverError::TransactionReverted(*hash));
+                }
+                // If a transaction is included and we get a receipt for it, the block number
+                // should always be set.
+                let block = receipt
+                    .block_number
+                    .ok_or_else(|| ProverError::Internal("receipt has no block number".into()))?
+                    .as_u64();
+                return Ok((receipt, block));
+            }
+            if Instant::now() >= deadline {
+                break;
+            }
+            sleep(RECEIPT_POLL_INTERVAL).await;
+        }
+        tracing::warn!(
+            attempt,
+            "light client update not included after {:?}",
+            config.tx_timeout
+        );
+    }
+
+    Err(ProverError::TransactionTimeout(
+        config.max_submission_attempts,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use contract_bindings::light_client_mock::LightClientMock;
+    use ethers::{
+        providers::{Http, Provider},
+        signers::{LocalWallet, Wallet},
+        utils::{Anvil, AnvilInstance},
+    };
+    use futures::join;
+    use sequencer_utils::{deployer, test_utils::setup_test};
+
+    use super::*;
+
+    #[test]
+    fn test_fee_policy() {
+        let config = SubmissionConfig {
+            max_fee_per_gas: Some(100),
+            max_priority_fee_per_gas: Some(2),
+            ..Default::default()
+        };
+
+        // Fees below the caps are unchanged.
+        assert_eq!(config.cap_fees(gwei(50), gwei(1)), (gwei(50), gwei(1)));
+
+        // Fees are capped.
+        assert_eq!(config.cap_fees(gwei(150), gwei(5)), (gwei(100), gwei(2)));
+
+        // The priority fee never exceeds the max fee.
+        let config = SubmissionConfig {
+            max_fee_per_gas: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(config.cap_fees(gwei(3), gwei(2)), (gwei(1), gwei(1)));
+
+        // Bumps are proportional.
+        assert_eq!(config.bump(gwei(10)), gwei(12));
+        let (max_fee, priority_fee) = config.cap_fees(config.bump(gwei(1)), config.bump(gwei(1)));
+        assert_eq!((max_fee, priority_fee), (gwei(1), gwei(1)));
+    }
+
+    /// Connect to `anvil` with the wallet of its `i`-th account.
+    async fn wallet(anvil: &AnvilInstance, i: usize) -> Result<Arc<SignerWallet>> {
+        let provider = Provider::<Http>::try_from(anvil.endpoint())?;
+        let signer: LocalWallet =
+            Wallet::from(anvil.keys()[i].clone()).with_chain_id(anvil.chain_id());
+        Ok(Arc::new(SignerWallet::new(provider, signer)))
+    }
+
+    /// Deploy a mock light client contract, then stop mining blocks, so that submitted
+    /// transactions stay pending until [`mine`] is called.
+    async fn deploy_stalled(
+        anvil: &AnvilInstance,
+    ) -> Result<(LightClient<SignerWallet>, LightClientMock<SignerWallet>)> {
+        let wallet = wallet(anvil, 0).await?;
+        let address = deployer::deploy_mock_light_client_contract(
+            wallet.clone(),
+            &mut Default::default(),
+            None,
+        )
+        .await?;
+        wallet
+            .provider()
+            .request::<_, ()>("evm_setAutomine", [false])
+            .await?;
+        Ok((
+            LightClient::new(address, wallet.clone()),
+            LightClientMock::new(address, wallet),
+        ))
+    }
+
+    async fn mine(contract: &LightClient<SignerWallet>) -> Result<()> {
+        contract
+            .client()
+            .provider()
+            .request::<_, String>("evm_mine", ())
+            .await?;
+        Ok(())
+    }
+
+    fn state(block_height: u64) -> ParsedLightClientState {
+        ParsedLightClientState {
+            block_height,
+            ..ParsedLightClientState::dummy_genesis()
+        }
+    }
+
+    #[async_std::test]
+    async fn test_submit_update_replacement() -> Result<()> {
+        setup_test();
+
+        let anvil = Anvil::new().spawn();
+        let (contract, mock) = deploy_stalled(&anvil).await?;
+        let client = contract.client();
+        let nonce = client
+            .get_transaction_count(client.address(), Some(BlockNumber::Pending.into()))
+            .await?;
+        let (max_fee, priority_fee) = client.estimate_eip1559_fees(None).await?;
+
+        // While no blocks are mined, each attempt times out and is replaced, until we give up.
+        let config = SubmissionConfig {
+            tx_timeout: Duration::from_secs(1),
+            max_submission_attempts: 3,
+            ..Default::default()
+        };
+        let call = mock.set_finalized_state(state(10).into());
+        let err = submit_update(&contract, call, 10, &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProverError::TransactionTimeout(3)), "{err}");
+
+        // The next round replaces the stuck update instead of queueing behind it. Its first
+        // attempts, at the fees the last replacement already paid or less, are rejected, so it
+        // bumps the fees right away until it outbids the stuck transaction.
+        let config = SubmissionConfig {
+            tx_timeout: Duration::from_secs(5),
+            max_submission_attempts: 4,
+            ..Default::default()
+        };
+        let call = mock.set_finalized_state(state(11).into());
+        let (res, mined) = join!(submit_update(&contract, call, 11, &config), async {
+            sleep(Duration::from_secs(1)).await;
+            mine(&contract).await
+        });
+        mined?;
+        let (receipt, _) = res?;
+
+        // Only the replacement from the second round is mined: it reuses the nonce of the first
+        // round, with the fees bumped once more than the last replacement of the first round.
+        let block = client
+            .get_block_with_txs(BlockNumber::Latest)
+            .await?
+            .unwrap();
+        assert_eq!(block.transactions.len(), 1);
+        let tx = &block.transactions[0];
+        assert_eq!(tx.hash, receipt.transaction_hash);
+        assert_eq!(tx.nonce, nonce);
+        let bumped = |fee| config.bump(config.bump(config.bump(fee)));
+        assert_eq!(tx.max_fee_per_gas, Some(bumped(max_fee)));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(bumped(priority_fee)));
+        assert_eq!(finalized_block_height(&contract).await?, 11);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_submit_update_superseded() -> Result<()> {
+        setup_test();
+
+        let anvil = Anvil::new().spawn();
+        let (contract, mock) = deploy_stalled(&anvil).await?;
+        let other_prover = LightClientMock::new(contract.address(), wallet(&anvil, 1).await?);
+
+        let config = SubmissionConfig {
+            tx_timeout: Duration::from_secs(1),
+            ..Default::default()
+        };
+        let call = mock.set_finalized_state(state(10).into());
+        let (res, advanced) = join!(submit_update(&contract, call, 10, &config), async {
+            // While our update is stuck, it is evicted and another prover advances the contract
+            // past it.
+            sleep(Duration::from_millis(500)).await;
+            contract
+                .client()
+                .provider()
+                .request::<_, ()>("anvil_dropAllTransactions", ())
+                .await?;
+            other_prover
+                .set_finalized_state(state(20).into())
+                .send()
+                .await?;
+            mine(&contract).await
+        });
+        advanced?;
+
+        // Instead of replacing our update, we find that it is no longer needed, which the prover
+        // service treats as success.
+        assert!(matches!(res, Err(ProverError::StateSuperseded)), "{res:?}");
+        assert_eq!(finalized_block_height(&contract).await?, 20);
+
+        // No replacement was sent.
+        mine(&contract).await?;
+        let block = contract
+            .client()
+            .get_block(BlockNumber::Latest)
+            .await?
+            .unwrap();
+        assert!(block.transactions.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+//! Offline verification of light client state update proofs.
+//!
+//! The light client contract only reports that a proof was rejected. Running the same checks in
+//! Rust, one at a time, tells us which one failed.
+
+use ark_bn254::Bn254;
+use displaydoc::Display;
+use ethers::{abi::AbiEncode, types::U256};
+use hotshot_contract_adapter::{
+    jellyfish::{field_to_u256, ParsedPlonkProof},
+    light_client::{ParsedLightClientState, ParsedStakeTableState},
+};
+use hotshot_types::light_client::CircuitField;
+use jf_plonk::{
+    errors::PlonkError,
+    proof_system::{PlonkKzgSnark, UniversalSNARK},
+    transcript::SolidityTranscript,
+};
+
+use crate::{
+    circuit::u256_to_field,
+    snark::{Proof, PublicInput, VerifyingKey},
+};
+
+/// Names of the commitments in a proof, in the order of [`commitments`].
+const COMMITMENT_NAMES: [&str; 13] = [
+    "wire_0",
+    "wire_1",
+    "wire_2",
+    "wire_3",
+    "wire_4",
+    "prod_perm",
+    "split_0",
+    "split_1",
+    "split_2",
+    "split_3",
+    "split_4",
+    "zeta",
+    "zeta_omega",
+];
+
+/// The check a state update proof failed.
+#[derive(Debug, Display)]
+pub enum VerificationFailure {
+    /// public input `{0}` is not a canonical field element
+    NonCanonicalPublicInput(&'static str),
+    /// proof commitment `{0}` is not a valid curve point
+    InvalidCommitment(&'static str),
+    /// proof contains a value which is not a canonical field element
+    NonCanonicalProof,
+    /// verifying key expects {0} public inputs, but a state update has {1}
+    WrongVerifyingKey(usize, usize),
+    /// proof does not verify against the public input: {0}
+    InvalidProof(PlonkError),
+}
+
+impl std::error::Error for VerificationFailure {}
+
+fn is_canonical(value: U256) -> bool {
+    field_to_u256(u256_to_field::<CircuitField>(&value)) == value
+}
+
+fn commitments(proof: &Proof) -> impl Iterator<Item = &ark_bn254::G1Affine> {
+    proof
+        .wires_poly_comms
+        .iter()
+        .chain([&proof.prod_perm_poly_comm])
+        .chain(&proof.split_quot_poly_comms)
+        .chain([&proof.opening_proof, &proof.shifted_opening_proof])
+        .map(|comm| &comm.0)
+}
+
+/// Verify a proof that `state` and `next_stake_table_state` were signed by a quorum of the stake
+/// table committed to by `stake_table_state`.
+///
+/// This performs the same checks as the light client contract, in order, and reports the first one
+/// which fails.
+pub fn verify_state_update(
+    vk: &VerifyingKey,
+    proof: &ParsedPlonkProof,
+    state: &ParsedLightClientState,
+    stake_table_state: &ParsedStakeTableState,
+    next_stake_table_state: &ParsedStakeTableState,
+) -> Result<(), VerificationFailure> {
+    for (name, value) in [
+        ("block_comm_root", state.block_comm_root),
+        ("bls_key_comm", stake_table_state.bls_key_comm),
+        ("schnorr_key_comm", stake_table_state.schnorr_key_comm),
+        ("amount_comm", stake_table_state.amount_comm),
+        ("threshold", stake_table_state.threshold),
+        ("next_bls_key_comm", next_stake_table_state.bls_key_comm),
+        (
+            "next_schnorr_key_comm",
+            next_stake_table_state.schnorr_key_comm,
+        ),
+        ("next_amount_comm", next_stake_table_state.amount_comm),
+        ("next_threshold", next_stake_table_state.threshold),
+    ] {
+        if !is_canonical(value) {
+            return Err(VerificationFailure::NonCanonicalPublicInput(name));
+        }
+    }
+
+    let parsed = proof.clone();
+    let proof = Proof::from(parsed.clone());
+    for (name, point) in COMMITMENT_NAMES.into_iter().zip(commitments(&proof)) {
+        if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(VerificationFailure::InvalidCommitment(name));
+        }
+    }
+    // Converting the proof reduces each coordinate and evaluation modulo the field size. If that
+    // changed anything, the encoding was not canonical.
+    if ParsedPlonkProof::from(proof.clone()).encode() != parsed.encode() {
+        return Err(VerificationFailure::NonCanonicalProof);
+    }
+
+    let inputs = PublicInput::new(
+        state.clone().into(),
+        stake_table_state.clone().into(),
+        next_stake_table_state.clone().into(),
+    )
+    .to_vec();
+    if vk.num_inputs != inputs.len() {
+        return Err(VerificationFailure::WrongVerifyingKey(
+            vk.num_inputs,
+            inputs.len(),
+        ));
+    }
+    PlonkKzgSnark::<Bn254>::verify::<SolidityTranscript>(vk, &inputs, &proof, None)
+        .map_err(VerificationFailure::InvalidProof)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_ed_on_bn254::EdwardsConfig;
+    use hotshot_types::{
+        light_client::LightClientState,
+        traits::stake_table::{SnapshotVersion, StakeTableScheme},
+    };
+    use jf_relation::Circuit as _;
+    use jf_signature::{schnorr::SchnorrSignatureScheme, SignatureScheme};
+    use jf_utils::test_rng;
+
+    use super::*;
+    use crate::{
+        circuit::{build_for_preprocessing, state_signature_message},
+        generate_state_update_proof, preprocess,
+        test_utils::{
+            genesis_stake_table_state, key_pairs_for_testing, stake_table_for_testing,
+            universal_setup_for_testing,
+        },
+    };
+
+    const ST_CAPACITY: usize = 4;
+
+    #[test]
+    fn test_verify_state_update() {
+        let mut prng = test_rng();
+
+        let (bls_keys, schnorr_keys) = key_pairs_for_testing(ST_CAPACITY, &mut prng);
+        let st = stake_table_for_testing(ST_CAPACITY, &bls_keys, &schnorr_keys);
+        let st_state = genesis_stake_table_state(&st);
+        let stake_table_entries = st
+            .try_iter(SnapshotVersion::LastEpochStart)
+            .unwrap()
+            .map(|(_, stake_amount, schnorr_key)| (schnorr_key, stake_amount))
+            .collect::<Vec<_>>();
+
+        let state = LightClientState {
+            view_number: 10,
+            block_height: 5,
+            block_comm_root: CircuitField::from(3u32),
+        };
+        let (next_bls_keys, next_schnorr_keys) = key_pairs_for_testing(ST_CAPACITY, &mut prng);
+        let next_st = stake_table_for_testing(ST_CAPACITY, &next_bls_keys, &next_schnorr_keys);
+        let next_st_state = genesis_stake_table_state(&next_st);
+        let state_msg = state_signature_message(&state, &next_st_state);
+        let sigs = schnorr_keys
+            .iter()
+            .map(|(key, _)| {
+                SchnorrSignatureScheme::<EdwardsConfig>::sign(&(), key, state_msg, &mut prng)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let num_gates = build_for_preprocessing::<CircuitField, EdwardsConfig>(ST_CAPACITY)
+            .unwrap()
+            .0
+            .num_gates();
+        let srs = universal_setup_for_testing(num_gates + 2, &mut prng).unwrap();
+        let (pk, vk) = preprocess(&srs, ST_CAPACITY).unwrap();
+        let (proof, _) = generate_state_update_proof::<_, _, _, _>(
+            &mut prng,
+            &pk,
+            &stake_table_entries,
+            [true; ST_CAPACITY],
+            &sigs,
+            &state,
+            &st_state,
+            &next_st_state,
+            ST_CAPACITY,
+        )
+        .unwrap();
+
+        let proof = ParsedPlonkProof::from(proof);
+        let state = ParsedLightClientState::from(state);
+        let st_state = ParsedStakeTableState::from(st_state);
+        let next_st_state = ParsedStakeTableState::from(next_st_state);
+        verify_state_update(&vk, &proof, &state, &st_state, &next_st_state).unwrap();
+
+        // A proof does not verify for a different state.
+        let mut wrong_state = state.clone();
+        wrong_state.block_height += 1;
+        assert!(matches!(
+            verify_state_update(&vk, &proof, &wrong_state, &st_state, &next_st_state),
+            Err(VerificationFailure::InvalidProof(_))
+        ));
+
+        // Nor for a different next stake table.
+        assert!(matches!(
+            verify_state_update(&vk, &proof, &state, &st_state, &st_state),
+            Err(VerificationFailure::InvalidProof(_))
+        ));
+
+        // Public inputs must be canonical.
+        let mut wrong_state = state.clone();
+        wrong_state.block_comm_root = U256::MAX;
+        assert!(matches!(
+            verify_state_update(&vk, &proof, &wrong_state, &st_state, &next_st_state),
+            Err(VerificationFailure::NonCanonicalPublicInput(
+                "block_comm_root"
+            ))
+        ));
+    }
+}
@@ -69,7 +69,12 @@ pub fn build_instance_state<V: Versions>(
     l1_params: L1Params,
     state_peers: Vec<Url>,
 ) -> anyhow::Result<NodeState> {
-    let l1_client = L1Client::new(l1_params.url, l1_params.events_max_block_range);
+    let mut l1_client = L1Client::from_urls(l1_params.urls, l1_params.events_max_block_range)?
+        .with_finalized_quorum(l1_params.finalized_quorum)
+        .with_cache(l1_params.cache_size, None);
+    if let Some(url) = l1_params.ws_url {
+        l1_client = l1_client.with_ws_subscription(url);
+    }
 
     let instance_state = NodeState::new(
         u64::MAX, // dummy node ID, only used for debugging
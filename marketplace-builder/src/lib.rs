@@ -67,7 +67,7 @@ use sequencer::{
     catchup::StatePeers,
     context::{Consensus, SequencerContext},
     network,
-    state_signature::{static_stake_table_commitment, StakeTableCommitmentType, StateSigner},
+    state_signature::{static_stake_table_state, StateSigner},
     L1Params, NetworkParams, Node, SequencerApiVersion,
 };
 use surf_disco::Client;
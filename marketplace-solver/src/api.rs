@@ -4,8 +4,8 @@ use std::{
 };
 
 use espresso_types::{
-    v0_3::{BidTx, RollupRegistration, RollupUpdate},
-    NamespaceId,
+    v0_3::{BidTx, RollupRegistration, RollupUpdate, TransferTx},
+    FeeAccount, NamespaceId,
 };
 use futures::FutureExt;
 use hotshot_types::{data::ViewNumber, traits::node_implementation::ConsensusTime};
@@ -31,6 +31,10 @@ pub enum SolverError {
     SignatureKeysMismatch(String),
     #[error("Signature key {0} does not match signatures in the database")]
     SignatureDatabaseKeysMismatch(String),
+    #[error("transfer nonce {nonce} from {account} has already been used")]
+    TransferNonceUsed { account: FeeAccount, nonce: u64 },
+    #[error("too many pending transfers: {0}")]
+    TooManyPendingTransfers(String),
     #[error("bincode err: {0}")]
     BincodeError(String),
     #[error("database err: {0}")]
@@ -101,6 +105,13 @@ where
         }
         .boxed()
     })?
+    .post("submit_transfer", |req, state| {
+        async move {
+            let transfer = req.body_json::<TransferTx>()?;
+            state.submit_transfer_tx(transfer).await
+        }
+        .boxed()
+    })?
     .get("auction_results", |req, state| {
         async move {
             let view_num: u64 = req.integer_param("view_number")?;
@@ -6,6 +6,7 @@ use espresso_types::SeqTypes;
 use futures::{Stream, StreamExt as _};
 use hotshot::types::Event;
 use hotshot_events_service::{events, events_source::StartupInfo};
+use hotshot_types::traits::block_contents::BlockHeader;
 use surf_disco::Client;
 use tide_disco::Url;
 use vbs::version::StaticVersion;
@@ -47,18 +48,34 @@ impl EventsServiceClient {
 
 pub async fn handle_events(
     mut stream: Pin<Box<dyn Stream<Item = Result<Event<SeqTypes>, events::Error>> + Send>>,
-    _state: Arc<RwLock<GlobalState>>,
+    state: Arc<RwLock<GlobalState>>,
 ) -> anyhow::Result<()> {
     while let Some(event) = stream.next().await {
         let event = event?;
 
         tracing::debug!("received event {:?}", event.event);
 
-        // TODO ED: Remove this lint later
-        #[allow(clippy::single_match)]
         match event.event {
             hotshot::types::EventType::ViewFinished { view_number } => {
-                tracing::debug!("received view finished event {view_number:?}")
+                tracing::debug!("received view finished event {view_number:?}");
+                state
+                    .write()
+                    .await
+                    .solver_mut()
+                    .transfer_txs
+                    .update_view(view_number);
+            }
+            hotshot::types::EventType::Decide { leaf_chain, .. } => {
+                let mut state = state.write().await;
+                // Leaves are in reverse chronological order.
+                for leaf_info in leaf_chain.iter().rev() {
+                    if let Some(results) = leaf_info.leaf.block_header().get_auction_results() {
+                        state
+                            .solver_mut()
+                            .transfer_txs
+                            .decide(leaf_info.leaf.view_number(), results.transfers());
+                    }
+                }
             }
             _ => (),
         }
@@ -1,13 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use async_trait::async_trait;
 use committable::Committable;
 use espresso_types::{
     v0_3::{
         BidTx, RollupRegistration, RollupRegistrationBody, RollupUpdate, RollupUpdatebody,
-        SolverAuctionResults,
+        SolverAuctionResults, TransferTx,
     },
-    PubKey, SeqTypes,
+    FeeAccount, PubKey, SeqTypes,
     Update::Set,
 };
 use hotshot::types::SignatureKey;
@@ -31,6 +31,10 @@ impl GlobalState {
     pub fn database(&self) -> &PgPool {
         self.database.pool()
     }
+
+    pub fn solver_mut(&mut self) -> &mut SolverState {
+        &mut self.solver
+    }
 }
 
 impl GlobalState {
@@ -45,6 +49,136 @@ impl GlobalState {
 pub struct SolverState {
     pub stake_table: StakeTable,
     pub bid_txs: HashMap<ViewNumber, HashMap<<SeqTypes as NodeType>::BuilderSignatureKey, BidTx>>,
+    /// Transfers waiting to be included in a block.
+    pub transfer_txs: TransferPool,
+}
+
+/// The maximum number of pending transfers from a single account.
+pub const MAX_PENDING_TRANSFERS_PER_ACCOUNT: usize = 16;
+
+/// The maximum number of pending transfers from all accounts.
+pub const MAX_PENDING_TRANSFERS: usize = 1024;
+
+/// The number of decided blocks a pending transfer may be left out of before it is dropped.
+///
+/// The leader drops any transfer it cannot execute, for example because the sender's balance is
+/// too low, so a transfer which keeps being left out will most likely never execute.
+pub const MAX_MISSED_BLOCKS: usize = 3;
+
+/// A transfer waiting to be included in a block.
+#[derive(Clone, Debug)]
+pub struct PendingTransfer {
+    pub tx: TransferTx,
+    /// The latest view seen when the transfer was submitted.
+    pub submitted_view: ViewNumber,
+    /// The number of decided blocks which could have included the transfer, but did not.
+    pub missed_blocks: usize,
+}
+
+/// Transfers waiting to be included in a block, by sender and nonce.
+#[derive(Clone, Debug, Default)]
+pub struct TransferPool {
+    pending: BTreeMap<(FeeAccount, u64), PendingTransfer>,
+    /// The nonce expected in the next transfer from each account, as far as we know from the
+    /// transfers decided so far.
+    next_nonces: HashMap<FeeAccount, u64>,
+    /// The latest view seen in consensus events.
+    view: ViewNumber,
+}
+
+impl TransferPool {
+    /// Add a transfer to the pool, replacing any pending transfer with the same sender and nonce.
+    ///
+    /// The signature is checked against the sender, so only the sender can replace one of its own
+    /// pending transfers.
+    pub fn insert(&mut self, tx: TransferTx) -> SolverResult<()> {
+        tx.verify()
+            .map_err(|_| SolverError::InvalidSignature(tx.signature().to_string()))?;
+
+        let from = tx.from();
+        let next_nonce = self.next_nonces.get(&from).copied().unwrap_or_default();
+        if tx.nonce() < next_nonce {
+            return Err(SolverError::TransferNonceUsed {
+                account: from,
+                nonce: tx.nonce(),
+            });
+        }
+
+        let key = (from, tx.nonce());
+        if !self.pending.contains_key(&key) {
+            let pending_from = self.pending.range((from, 0)..=(from, u64::MAX)).count();
+            if pending_from >= MAX_PENDING_TRANSFERS_PER_ACCOUNT {
+                return Err(SolverError::TooManyPendingTransfers(format!(
+                    "{from} already has {pending_from} pending transfers"
+                )));
+            }
+            if self.pending.len() >= MAX_PENDING_TRANSFERS {
+                return Err(SolverError::TooManyPendingTransfers(format!(
+                    "{} transfers are already pending",
+                    self.pending.len()
+                )));
+            }
+        }
+
+        self.pending.insert(
+            key,
+            PendingTransfer {
+                tx,
+                submitted_view: self.view,
+                missed_blocks: 0,
+            },
+        );
+        Ok(())
+    }
+
+    /// The pending transfers, ordered by sender and nonce.
+    pub fn transfers(&self) -> impl Iterator<Item = &TransferTx> {
+        self.pending.values().map(|pending| &pending.tx)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Record the latest view seen in consensus events.
+    pub fn update_view(&mut self, view: ViewNumber) {
+        self.view = self.view.max(view);
+    }
+
+    /// Update the pool after the block proposed in `view` is decided, executing `executed`.
+    ///
+    /// Transfers whose nonce has been used by a decided transfer are removed. Any other transfer
+    /// which was pending before `view` and has now been left out of [`MAX_MISSED_BLOCKS`] blocks
+    /// is removed as well.
+    pub fn decide(&mut self, view: ViewNumber, executed: &[TransferTx]) {
+        self.update_view(view);
+        for transfer in executed {
+            let next_nonce = self.next_nonces.entry(transfer.from()).or_default();
+            *next_nonce = (*next_nonce).max(transfer.nonce() + 1);
+        }
+
+        let next_nonces = &self.next_nonces;
+        self.pending.retain(|(from, nonce), pending| {
+            if *nonce < next_nonces.get(from).copied().unwrap_or_default() {
+                return false;
+            }
+            if pending.submitted_view < view {
+                pending.missed_blocks += 1;
+                if pending.missed_blocks >= MAX_MISSED_BLOCKS {
+                    tracing::info!(
+                        tx = ?pending.tx,
+                        "dropping transfer which was left out of {MAX_MISSED_BLOCKS} blocks"
+                    );
+                    return false;
+                }
+            }
+            true
+        });
+    }
 }
 
 pub struct StakeTable {
@@ -55,6 +189,8 @@ pub struct StakeTable {
 pub trait UpdateSolverState {
     async fn submit_bid_tx(&mut self, bid_tx: BidTx) -> SolverResult<()>;
 
+    async fn submit_transfer_tx(&mut self, transfer_tx: TransferTx) -> SolverResult<()>;
+
     async fn register_rollup(
         &self,
         registration: RollupRegistration,
@@ -89,6 +225,10 @@ impl UpdateSolverState for GlobalState {
         Ok(())
     }
 
+    async fn submit_transfer_tx(&mut self, transfer_tx: TransferTx) -> SolverResult<()> {
+        self.solver.transfer_txs.insert(transfer_tx)
+    }
+
     async fn register_rollup(
         &self,
         registration: RollupRegistration,
@@ -275,7 +415,8 @@ impl UpdateSolverState for GlobalState {
                 .into_iter()
                 .filter_map(|r| Some((r.body.namespace_id, r.body.reserve_url?)))
                 .collect(),
-        );
+        )
+        .with_transfers(self.solver.transfer_txs.transfers().cloned().collect());
 
         Ok(results)
     }
@@ -296,7 +437,8 @@ impl UpdateSolverState for GlobalState {
                 .into_iter()
                 .filter_map(|r| Some((r.body.namespace_id, r.body.reserve_url?)))
                 .collect(),
-        );
+        )
+        .with_transfers(self.solver.transfer_txs.transfers().cloned().collect());
 
         Ok(results)
     }
@@ -348,6 +490,7 @@ impl SolverState {
                 known_nodes_with_stake: crate::mock::generate_stake_table(),
             },
             bid_txs: Default::default(),
+            transfer_txs: Default::default(),
         }
     }
 }
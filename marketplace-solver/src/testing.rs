@@ -66,6 +66,7 @@ impl MockSolver {
                 known_nodes_with_stake: startup_info.known_node_with_stake,
             },
             bid_txs: Default::default(),
+            transfer_txs: Default::default(),
         };
 
         let state = Arc::new(RwLock::new(
@@ -121,16 +122,27 @@ mod test {
 
     use committable::Committable;
     use espresso_types::{
-        v0_3::{BidTx, RollupRegistration, RollupRegistrationBody, RollupUpdate, RollupUpdatebody},
-        FeeAccount, MarketplaceVersion, SeqTypes,
+        eth_signature_key::EthKeyPair,
+        v0_3::{
+            BidTx, RollupRegistration, RollupRegistrationBody, RollupUpdate, RollupUpdatebody,
+            SolverAuctionResults, TransferTxBody,
+        },
+        ChainConfig, FeeAccount, MarketplaceVersion, SeqTypes,
         Update::{Set, Skip},
     };
     use hotshot::types::{BLSPubKey, SignatureKey};
-    use hotshot_types::traits::node_implementation::NodeType;
+    use hotshot_types::{
+        data::ViewNumber,
+        traits::node_implementation::{ConsensusTime, NodeType},
+    };
     use std::{str::FromStr, time::Duration};
     use tide_disco::Url;
 
-    use crate::{testing::MockSolver, SolverError};
+    use crate::{
+        state::{TransferPool, MAX_MISSED_BLOCKS, MAX_PENDING_TRANSFERS_PER_ACCOUNT},
+        testing::MockSolver,
+        SolverError,
+    };
 
     async fn register_rollup_helper(
         namespace_id: u64,
@@ -515,6 +527,128 @@ mod test {
             .unwrap();
     }
 
+    #[async_std::test]
+    async fn test_transfer_submission() {
+        let mock_solver = MockSolver::init().await;
+
+        let solver_api = mock_solver.solver_api();
+
+        let client = surf_disco::Client::<SolverError, MarketplaceVersion>::new(solver_api);
+        client.connect(None).await;
+
+        let key = FeeAccount::test_key_pair();
+        let tx = TransferTxBody::new(
+            key.fee_account(),
+            FeeAccount::default(),
+            10.into(),
+            1.into(),
+            0,
+            ChainConfig::default().chain_id,
+        )
+        .signed(&key)
+        .unwrap();
+
+        client
+            .post::<()>("submit_transfer")
+            .body_json(&tx)
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+
+        // The transfer is included in the auction results until it is decided.
+        let results: SolverAuctionResults = client.get("auction_results/1").send().await.unwrap();
+        assert_eq!(results.transfers(), [tx.clone()]);
+
+        // A transfer signed by someone other than the sender is rejected, and does not replace
+        // the pending transfer with the same sender and nonce.
+        let forged = TransferTxBody::new(
+            key.fee_account(),
+            FeeAccount::default(),
+            0.into(),
+            0.into(),
+            0,
+            ChainConfig::default().chain_id,
+        )
+        .signed(&EthKeyPair::random())
+        .unwrap();
+        client
+            .post::<()>("submit_transfer")
+            .body_json(&forged)
+            .unwrap()
+            .send()
+            .await
+            .unwrap_err();
+        let results: SolverAuctionResults = client.get("auction_results/1").send().await.unwrap();
+        assert_eq!(results.transfers(), [tx.clone()]);
+
+        mock_solver
+            .state
+            .write()
+            .await
+            .solver_mut()
+            .transfer_txs
+            .decide(ViewNumber::new(1), &[tx.clone()]);
+        let results: SolverAuctionResults = client.get("auction_results/2").send().await.unwrap();
+        assert!(results.transfers().is_empty());
+
+        // The transfer cannot be submitted again once its nonce has been used.
+        client
+            .post::<()>("submit_transfer")
+            .body_json(&tx)
+            .unwrap()
+            .send()
+            .await
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_transfer_pool() {
+        let key = EthKeyPair::random();
+        let transfer = |nonce| {
+            TransferTxBody::new(
+                key.fee_account(),
+                FeeAccount::default(),
+                10.into(),
+                1.into(),
+                nonce,
+                ChainConfig::default().chain_id,
+            )
+            .signed(&key)
+            .unwrap()
+        };
+
+        // The number of pending transfers from one account is limited.
+        let mut pool = TransferPool::default();
+        for nonce in 0..MAX_PENDING_TRANSFERS_PER_ACCOUNT as u64 {
+            pool.insert(transfer(nonce)).unwrap();
+        }
+        pool.insert(transfer(MAX_PENDING_TRANSFERS_PER_ACCOUNT as u64))
+            .unwrap_err();
+        // The sender can still replace one of its pending transfers.
+        pool.insert(transfer(0)).unwrap();
+        assert_eq!(pool.len(), MAX_PENDING_TRANSFERS_PER_ACCOUNT);
+
+        // Deciding a transfer removes every pending transfer with the same or a lower nonce.
+        pool.update_view(ViewNumber::new(5));
+        pool.decide(ViewNumber::new(5), &[transfer(1)]);
+        assert_eq!(
+            pool.transfers().map(|tx| tx.nonce()).collect::<Vec<_>>(),
+            (2..MAX_PENDING_TRANSFERS_PER_ACCOUNT as u64).collect::<Vec<_>>()
+        );
+
+        // A transfer submitted later is dropped once it has been left out of enough blocks.
+        let mut pool = TransferPool::default();
+        pool.update_view(ViewNumber::new(10));
+        pool.insert(transfer(0)).unwrap();
+        for view in 11..10 + MAX_MISSED_BLOCKS as u64 {
+            pool.decide(ViewNumber::new(view), &[]);
+            assert_eq!(pool.len(), 1);
+        }
+        pool.decide(ViewNumber::new(10 + MAX_MISSED_BLOCKS as u64), &[]);
+        assert!(pool.is_empty());
+    }
+
     #[async_std::test]
     async fn test_database_state() {
         // Initialize a mock solver and register two rollups
@@ -9,7 +9,10 @@ use crate::service::{
         ProcessDistributeBlockDetailHandlingTask, ProcessDistributeNodeIdentityHandlingTask,
         ProcessDistributeVotersHandlingTask,
     },
-    data_state::{DataState, ProcessLeafStreamTask, ProcessNodeIdentityStreamTask},
+    data_state::{
+        DataState, DataStateStorage, ProcessLeafStreamTask, ProcessNodeIdentityStreamTask,
+        StorageError,
+    },
     server_message::ServerMessage,
 };
 use async_std::{sync::RwLock, task::JoinHandle};
@@ -32,16 +35,25 @@ pub struct NodeValidatorAPI<K> {
     pub process_node_identity_stream_handle: Option<ProcessNodeIdentityStreamTask>,
     pub process_url_stream_handle: Option<ProcessNodeIdentityUrlStreamTask>,
     pub url_sender: K,
+
+    /// The height of the most recent block restored from storage, if any.
+    /// The leaf stream should resume after this block.
+    pub last_stored_block_height: Option<u64>,
 }
 
 pub struct NodeValidatorConfig {
     pub stake_table_url_base: Url,
     pub initial_node_public_base_urls: Vec<Url>,
+
+    /// storage is the optional storage that the data state will be restored
+    /// from, and persisted to.
+    pub storage: Option<DataStateStorage>,
 }
 
 #[derive(Debug)]
 pub enum CreateNodeValidatorProcessingError {
     FailedToGetStakeTable(hotshot_query_service::Error),
+    FailedToLoadStorage(StorageError),
 }
 
 /// An external message that can be sent to or received from a node
@@ -294,12 +306,33 @@ pub async fn create_node_validator_processing(
         .await
         .map_err(CreateNodeValidatorProcessingError::FailedToGetStakeTable)?;
 
-    let data_state = DataState::new(
-        Default::default(),
-        Default::default(),
-        stake_table,
-        Default::default(),
-    );
+    let (data_state, last_stored_block_height) = match config.storage {
+        Some(storage) => {
+            let stored = storage
+                .load()
+                .await
+                .map_err(CreateNodeValidatorProcessingError::FailedToLoadStorage)?;
+            let last_stored_block_height = stored.last_block_height();
+            tracing::info!(
+                "restored {} retained blocks and {} node identities from storage",
+                stored.latest_blocks.len(),
+                stored.node_identity.len()
+            );
+            (
+                DataState::from_storage(stored, stake_table, storage),
+                last_stored_block_height,
+            )
+        }
+        None => (
+            DataState::new(
+                Default::default(),
+                Default::default(),
+                stake_table,
+                Default::default(),
+            ),
+            None,
+        ),
+    };
 
     let data_state = Arc::new(RwLock::new(data_state));
     let client_thread_state = Arc::new(RwLock::new(client_thread_state));
@@ -367,6 +400,7 @@ pub async fn create_node_validator_processing(
         process_node_identity_stream_handle: Some(process_node_identity_stream_handle),
         process_url_stream_handle: Some(process_url_stream_handle),
         url_sender: url_sender.clone(),
+        last_stored_block_height,
     })
 }
 
@@ -420,6 +454,7 @@ mod test {
                     .parse()
                     .unwrap(),
             ),
+            None,
             leaf_sender,
         );
 
@@ -442,6 +477,7 @@ mod test {
                         .parse()
                         .unwrap(),
                 ],
+                storage: None,
             },
             internal_client_message_receiver,
             leaf_receiver,
@@ -2,7 +2,7 @@ pub mod cdn;
 pub mod create_node_validator_api;
 
 use crate::service::client_message::{ClientMessage, InternalClientMessage};
-use crate::service::data_state::{LocationDetails, NodeIdentity};
+use crate::service::data_state::{LocationDetails, NodeIdentity, ParticipationSnapshot};
 use crate::service::server_message::ServerMessage;
 use async_std::task::JoinHandle;
 use espresso_types::SeqTypes;
@@ -25,6 +25,7 @@ use std::future::Future;
 use std::io::BufRead;
 use std::pin::Pin;
 use std::str::FromStr;
+use std::sync::Arc;
 use tide_disco::socket::Connection;
 use tide_disco::{api::ApiError, Api};
 use url::Url;
@@ -166,6 +167,57 @@ pub trait StateClientMessageSender<K> {
 #[derive(Debug)]
 pub enum EndpointError {}
 
+/// [request_participation_snapshot] registers a short-lived client with the
+/// Server through the given [Sender], and requests a
+/// [ParticipationSnapshot] on its behalf.
+async fn request_participation_snapshot(
+    mut internal_client_message_sender: Sender<InternalClientMessage<Sender<ServerMessage>>>,
+) -> Result<Arc<ParticipationSnapshot>, Error> {
+    let internal_error = |msg: &str| {
+        Error::UnhandledTideDisco(
+            tide_disco::StatusCode::INTERNAL_SERVER_ERROR,
+            msg.to_string(),
+        )
+    };
+    let (server_message_sender, mut server_message_receiver) = mpsc::channel(1);
+
+    internal_client_message_sender
+        .send(InternalClientMessage::Connected(server_message_sender))
+        .await
+        .map_err(|_| internal_error("client message sender is closed"))?;
+
+    let client_id =
+        if let Some(ServerMessage::YouAre(client_id)) = server_message_receiver.next().await {
+            client_id
+        } else {
+            return Err(internal_error(
+                "server message receiver closed before first message",
+            ));
+        };
+
+    let result = match internal_client_message_sender
+        .send(InternalClientMessage::Request(
+            client_id,
+            ClientMessage::RequestParticipationSnapshot,
+        ))
+        .await
+    {
+        Ok(_) => match server_message_receiver.next().await {
+            Some(ServerMessage::ParticipationSnapshot(snapshot)) => Ok(snapshot),
+            _ => Err(internal_error("no participation snapshot received")),
+        },
+        Err(_) => Err(internal_error("client message sender is closed")),
+    };
+
+    // We don't actually care if this fails or not, as this client is done
+    // either way.
+    _ = internal_client_message_sender
+        .send(InternalClientMessage::Disconnected(client_id))
+        .await;
+
+    result
+}
+
 pub fn define_api<State>() -> Result<Api<State, Error, Version01>, DefineApiError>
 where
     State: StateClientMessageSender<Sender<ServerMessage>> + Send + Sync + 'static,
@@ -290,6 +342,10 @@ where
             .boxed()
         },
     )?;
+
+    api.at("participation", |_req, state| {
+        request_participation_snapshot(state.sender()).boxed()
+    })?;
     Ok(api)
 }
 
@@ -481,12 +537,10 @@ impl LeafStreamRetriever for HotshotQueryServiceLeafStreamRetriever {
                 }
             };
 
+            // Resume from the known height, so that no blocks are missed,
+            // otherwise start with the most recent blocks.
             let latest_block_start = block_height.saturating_sub(50);
-            let start_block_height = if let Some(known_height) = current_block_height {
-                std::cmp::min(known_height, latest_block_start)
-            } else {
-                latest_block_start
-            };
+            let start_block_height = current_block_height.unwrap_or(latest_block_start);
 
             let leaves_stream_result = client
                 .socket(&format!(
@@ -521,10 +575,18 @@ impl ProcessProduceLeafStreamTask {
     /// [new] creates a new [ProcessConsumeLeafStreamTask] that produces a
     /// stream of [Leaf]s from the Hotshot Query Service.
     ///
+    /// The stream will start from `current_block_height` if it is given,
+    /// for example to resume after the blocks that were restored from
+    /// storage.
+    ///
     /// Calling this function will create an async task that will start
     /// processing immediately.  The task's handle will be stored in the
     /// returned state.
-    pub fn new<R, K>(leaf_stream_retriever: R, leaf_sender: K) -> Self
+    pub fn new<R, K>(
+        leaf_stream_retriever: R,
+        current_block_height: Option<u64>,
+        leaf_sender: K,
+    ) -> Self
     where
         R: LeafStreamRetriever<Item = Leaf<SeqTypes>> + 'static,
         K: Sink<Leaf<SeqTypes>, Error = SendError> + Clone + Send + Sync + Unpin + 'static,
@@ -532,6 +594,7 @@ impl ProcessProduceLeafStreamTask {
         // let future = Self::process_consume_leaf_stream(leaf_stream_retriever, leaf_sender);
         let task_handle = async_std::task::spawn(Self::process_consume_leaf_stream(
             leaf_stream_retriever,
+            current_block_height,
             leaf_sender,
         ));
 
@@ -545,15 +608,20 @@ impl ProcessProduceLeafStreamTask {
     /// Hotshot Query Service and then send them to the [Sink] provided.  If the
     /// [Sink] is closed, or if the Stream ends prematurely, then the function
     /// will return.
-    async fn process_consume_leaf_stream<R, K>(leaf_stream_retriever: R, leaf_sender: K)
-    where
+    async fn process_consume_leaf_stream<R, K>(
+        leaf_stream_retriever: R,
+        current_block_height: Option<u64>,
+        leaf_sender: K,
+    ) where
         R: LeafStreamRetriever<Item = Leaf<SeqTypes>>,
         K: Sink<Leaf<SeqTypes>, Error = SendError> + Clone + Send + Sync + Unpin + 'static,
     {
         // Alright, let's start processing leaves
         // TODO: implement retry logic with backoff and ultimately fail if
         //       unable to retrieve the stream within a time frame.
-        let leaves_stream_result = leaf_stream_retriever.retrieve_stream(None).await;
+        let leaves_stream_result = leaf_stream_retriever
+            .retrieve_stream(current_block_height)
+            .await;
         let leaves_stream = match leaves_stream_result {
             Ok(leaves_stream) => leaves_stream,
             Err(err) => {
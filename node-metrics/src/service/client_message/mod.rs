@@ -14,6 +14,7 @@ pub enum ClientMessage {
     RequestNodeIdentitySnapshot,
     RequestHistogramSnapshot,
     RequestVotersSnapshot,
+    RequestParticipationSnapshot,
 }
 
 /// InternalClientMessage represents the message requests that the client can
@@ -69,6 +70,7 @@ mod tests {
             ClientMessage::RequestBlocksSnapshot,
             ClientMessage::RequestNodeIdentitySnapshot,
             ClientMessage::RequestHistogramSnapshot,
+            ClientMessage::RequestParticipationSnapshot,
         ];
 
         for (l, r) in zip(messages.iter(), messages.iter()) {
@@ -94,6 +96,7 @@ mod tests {
             ClientMessage::RequestBlocksSnapshot,
             ClientMessage::RequestNodeIdentitySnapshot,
             ClientMessage::RequestHistogramSnapshot,
+            ClientMessage::RequestParticipationSnapshot,
         ];
 
         for message in messages.iter() {
@@ -113,6 +116,7 @@ mod tests {
             ClientMessage::RequestBlocksSnapshot,
             ClientMessage::RequestNodeIdentitySnapshot,
             ClientMessage::RequestHistogramSnapshot,
+            ClientMessage::RequestParticipationSnapshot,
         ];
 
         for message in messages.iter() {
@@ -131,6 +135,7 @@ mod tests {
             ClientMessage::RequestBlocksSnapshot,
             ClientMessage::RequestNodeIdentitySnapshot,
             ClientMessage::RequestHistogramSnapshot,
+            ClientMessage::RequestParticipationSnapshot,
         ];
 
         for message in messages {
@@ -175,6 +180,10 @@ mod tests {
                 ClientId::from_count(1),
                 ClientMessage::RequestHistogramSnapshot,
             ),
+            InternalClientMessage::Request(
+                ClientId::from_count(1),
+                ClientMessage::RequestParticipationSnapshot,
+            ),
         ];
 
         for (l, r) in zip(messages.iter(), messages.iter()) {
@@ -217,6 +226,10 @@ mod tests {
                     ClientId::from_count(j),
                     ClientMessage::RequestHistogramSnapshot,
                 ),
+                InternalClientMessage::Request(
+                    ClientId::from_count(j),
+                    ClientMessage::RequestParticipationSnapshot,
+                ),
             ];
 
             // We skip the first message, as we don't want to include the
@@ -512,6 +512,67 @@ where
     Ok(())
 }
 
+#[derive(Debug)]
+pub enum HandleRequestParticipationSnapshotError {
+    ClientSendError(SendError),
+}
+
+impl std::fmt::Display for HandleRequestParticipationSnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandleRequestParticipationSnapshotError::ClientSendError(err) => {
+                write!(
+                    f,
+                    "handle request participation snapshot error: client send error: {}",
+                    err
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for HandleRequestParticipationSnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HandleRequestParticipationSnapshotError::ClientSendError(err) => Some(err),
+        }
+    }
+}
+
+/// [handle_client_message_request_participation_snapshot] is a function that
+/// processes the client message request for a participation snapshot.
+pub async fn handle_client_message_request_participation_snapshot<K>(
+    client_id: ClientId,
+    data_state: Arc<RwLock<DataState>>,
+    client_thread_state: Arc<RwLock<ClientThreadState<K>>>,
+) -> Result<(), HandleRequestParticipationSnapshotError>
+where
+    K: Sink<ServerMessage, Error = SendError> + Clone + Unpin,
+{
+    let (client_thread_state_read_lock_guard, data_state_read_lock_guard) =
+        futures::join!(client_thread_state.read(), data_state.read());
+
+    let participation_data = data_state_read_lock_guard.participation_snapshot();
+
+    if let Some(client) = client_thread_state_read_lock_guard.clients.get(&client_id) {
+        let mut sender = client.sender.clone();
+        drop(client_thread_state_read_lock_guard);
+
+        if let Err(err) = sender
+            .send(ServerMessage::ParticipationSnapshot(participation_data))
+            .await
+        {
+            drop_client_no_lock_guard(&client_id, client_thread_state.clone()).await;
+            return Err(HandleRequestParticipationSnapshotError::ClientSendError(
+                err,
+            ));
+        }
+
+        return Ok(());
+    }
+    Ok(())
+}
+
 /// [ProcessClientMessageError] represents the scope of errors that can be
 /// returned from the [process_client_message] function.
 #[derive(Debug)]
@@ -521,6 +582,7 @@ pub enum ProcessClientMessageError {
     NodeIdentitySnapshot(HandleRequestNodeIdentitySnapshotError),
     HistogramSnapshot(HandleRequestHistogramSnapshotError),
     VotersSnapshot(HandleRequestVotersSnapshotError),
+    ParticipationSnapshot(HandleRequestParticipationSnapshotError),
 }
 
 impl From<HandleConnectedError> for ProcessClientMessageError {
@@ -553,6 +615,12 @@ impl From<HandleRequestVotersSnapshotError> for ProcessClientMessageError {
     }
 }
 
+impl From<HandleRequestParticipationSnapshotError> for ProcessClientMessageError {
+    fn from(err: HandleRequestParticipationSnapshotError) -> Self {
+        ProcessClientMessageError::ParticipationSnapshot(err)
+    }
+}
+
 impl std::fmt::Display for ProcessClientMessageError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -579,6 +647,13 @@ impl std::fmt::Display for ProcessClientMessageError {
             ProcessClientMessageError::VotersSnapshot(err) => {
                 write!(f, "process client message error: voters snapshot: {}", err)
             }
+            ProcessClientMessageError::ParticipationSnapshot(err) => {
+                write!(
+                    f,
+                    "process client message error: participation snapshot: {}",
+                    err
+                )
+            }
         }
     }
 }
@@ -591,6 +666,7 @@ impl std::error::Error for ProcessClientMessageError {
             ProcessClientMessageError::NodeIdentitySnapshot(err) => Some(err),
             ProcessClientMessageError::HistogramSnapshot(err) => Some(err),
             ProcessClientMessageError::VotersSnapshot(err) => Some(err),
+            ProcessClientMessageError::ParticipationSnapshot(err) => Some(err),
         }
     }
 }
@@ -677,6 +753,16 @@ where
             .await?;
             Ok(())
         }
+
+        InternalClientMessage::Request(client_id, ClientMessage::RequestParticipationSnapshot) => {
+            handle_client_message_request_participation_snapshot(
+                client_id,
+                data_state,
+                client_thread_state,
+            )
+            .await?;
+            Ok(())
+        }
     }
 }
 
@@ -1365,6 +1451,58 @@ pub mod tests {
         }
     }
 
+    #[async_std::test]
+    async fn test_process_client_handling_stream_request_participation_snapshot() {
+        let (_, _, _, data_state) = create_test_data_state();
+        let client_thread_state = Arc::new(RwLock::new(create_test_client_thread_state()));
+        let participation_snapshot = data_state.participation_snapshot();
+
+        let data_state = Arc::new(RwLock::new(data_state));
+
+        let (internal_client_message_sender, internal_client_message_receiver) = mpsc::channel(1);
+        let (server_message_sender_1, mut server_message_receiver_1) = mpsc::channel(1);
+        let mut process_internal_client_message_handle = InternalClientMessageProcessingTask::new(
+            internal_client_message_receiver,
+            data_state,
+            client_thread_state,
+        );
+
+        // Send a Connected Message to the server
+        let mut internal_client_message_sender_1 = internal_client_message_sender;
+        assert_eq!(
+            internal_client_message_sender_1
+                .send(InternalClientMessage::Connected(server_message_sender_1))
+                .await,
+            Ok(())
+        );
+
+        assert_eq!(
+            server_message_receiver_1.next().await,
+            Some(ServerMessage::YouAre(ClientId::from_count(2))),
+        );
+
+        let client_1_id = ClientId::from_count(2);
+
+        assert_eq!(
+            internal_client_message_sender_1
+                .send(InternalClientMessage::Request(
+                    client_1_id,
+                    ClientMessage::RequestParticipationSnapshot
+                ))
+                .await,
+            Ok(()),
+        );
+
+        assert_eq!(
+            server_message_receiver_1.next().await,
+            Some(ServerMessage::ParticipationSnapshot(participation_snapshot)),
+        );
+
+        if let Some(task_handle) = process_internal_client_message_handle.task_handle.take() {
+            assert_eq!(task_handle.cancel().await, None);
+        }
+    }
+
     #[async_std::test]
     #[cfg(feature = "testing")]
     async fn test_process_client_handling_stream_request_latest_blocks_snapshot() {
@@ -1,5 +1,7 @@
 pub mod location_details;
 pub mod node_identity;
+pub mod participation;
+pub mod storage;
 
 use async_std::{sync::RwLock, task::JoinHandle};
 use bitvec::vec::BitVec;
@@ -17,13 +19,20 @@ use hotshot_types::{
     signature_key::BLSPubKey,
     traits::{
         block_contents::BlockHeader,
+        node_implementation::ConsensusTime,
         stake_table::{SnapshotVersion, StakeTableScheme},
         BlockPayload,
     },
+    vote::HasViewNumber,
 };
 pub use location_details::LocationDetails;
 pub use node_identity::NodeIdentity;
+pub use participation::{
+    LeafParticipation, ParticipationSnapshot, ParticipationTracker, ParticipationWindow,
+    ValidatorParticipation,
+};
 use std::{collections::HashSet, iter::zip, sync::Arc};
+pub use storage::{DataStateStorage, StorageError, StoredDataState};
 use time::OffsetDateTime;
 
 /// MAX_HISTORY represents the last N records that are stored within the
@@ -39,6 +48,8 @@ pub struct DataState {
     stake_table: StakeTable<BLSPubKey, StateVerKey, CircuitField>,
     // Do we need any other data at the moment?
     node_identity: Vec<NodeIdentity>,
+    participation: ParticipationTracker,
+    storage: Option<DataStateStorage>,
 }
 
 impl DataState {
@@ -53,6 +64,34 @@ impl DataState {
             latest_voters,
             stake_table,
             node_identity,
+            participation: Default::default(),
+            storage: None,
+        }
+    }
+
+    /// [from_storage] creates a new [DataState] from the information that was
+    /// restored from [DataStateStorage].  Only the most recent blocks and
+    /// voters are kept in memory, and the participation statistics are
+    /// rebuilt from the restored participation of each block.  Any further
+    /// blocks, voters and node identities that are added will be persisted to
+    /// the given storage.
+    pub fn from_storage(
+        stored: StoredDataState,
+        stake_table: StakeTable<BLSPubKey, StateVerKey, CircuitField>,
+        storage: DataStateStorage,
+    ) -> Self {
+        let skip = stored.latest_blocks.len().saturating_sub(MAX_HISTORY);
+        let mut participation = ParticipationTracker::default();
+        for leaf_participation in stored.participation {
+            participation.add_leaf(leaf_participation);
+        }
+        Self {
+            latest_blocks: stored.latest_blocks.into_iter().skip(skip).collect(),
+            latest_voters: stored.latest_voters.into_iter().skip(skip).collect(),
+            stake_table,
+            node_identity: stored.node_identity,
+            participation,
+            storage: Some(storage),
         }
     }
 
@@ -72,6 +111,20 @@ impl DataState {
         self.node_identity.iter()
     }
 
+    /// [participation_snapshot] computes the participation statistics of the
+    /// validators in the current stake table over the recent blocks.  The
+    /// statistics are only recomputed once a new block has been added.
+    pub fn participation_snapshot(&self) -> Arc<ParticipationSnapshot> {
+        let stake_table_keys = self
+            .stake_table
+            .try_iter(SnapshotVersion::LastEpochStart)
+            .map_or(vec![], |into_iter| {
+                into_iter.map(|(key, _, _)| key).collect::<Vec<_>>()
+            });
+
+        self.participation.snapshot(&stake_table_keys)
+    }
+
     pub fn replace_stake_table(
         &mut self,
         stake_table: StakeTable<BLSPubKey, StateVerKey, CircuitField>,
@@ -111,7 +164,9 @@ impl DataState {
         self.latest_voters.push_back(voters);
     }
 
-    pub fn add_node_identity(&mut self, identity: NodeIdentity) {
+    /// [add_node_identity] adds the given [NodeIdentity], or replaces the
+    /// existing one with the same public key, and returns its index.
+    pub fn add_node_identity(&mut self, identity: NodeIdentity) -> usize {
         // We need to check to see if this identity is already in the list,
         // if it is, we will want to replace it.
 
@@ -131,11 +186,12 @@ impl DataState {
 
         if let Some((index, _)) = existing_node_identity_option {
             self.node_identity[index] = identity;
-            return;
+            return index;
         }
 
         // This entry doesn't appear in our table, so let's add it.
         self.node_identity.push(identity);
+        self.node_identity.len() - 1
     }
 }
 
@@ -244,6 +300,15 @@ where
     // exception of the genesis block.
     let stake_table_voters_bit_vec = signatures.map_or(Default::default(), |sig| sig.1.clone());
 
+    // Only leaves with a signed Quorum Certificate tell us anything about
+    // the participation of the validators.
+    let leaf_participation = signatures.map(|sig| LeafParticipation {
+        height: block_detail.height,
+        view: leaf.view_number().u64(),
+        timestamp: leaf.block_header().timestamp(),
+        voters: sig.1.clone(),
+    });
+
     // This BitVec should be in the same order as the Stake Table.
     // The StakeTable will be able to change its order between epochs,
     // which means that its order can change between blocks.
@@ -287,15 +352,37 @@ where
         },
     );
 
+    let storage = data_state_write_lock_guard.storage.clone();
     data_state_write_lock_guard
         .latest_blocks
         .push_back(block_detail);
     data_state_write_lock_guard
         .latest_voters
         .push_back(voters_bitvec.clone());
+    let leaf_participation_copy = leaf_participation.clone();
+    if let Some(leaf_participation) = leaf_participation {
+        data_state_write_lock_guard
+            .participation
+            .add_leaf(leaf_participation);
+    }
 
     drop(data_state_write_lock_guard);
 
+    // Failing to persist the block is not fatal, we will only be missing it
+    // from the history after a restart.
+    if let Some(storage) = storage {
+        if let Err(err) = storage
+            .store_block(
+                &block_detail_copy,
+                &voters_bitvec,
+                leaf_participation_copy.as_ref(),
+            )
+            .await
+        {
+            tracing::error!("process leaf: error storing block detail: {}", err);
+        }
+    }
+
     if let Err(err) = block_sender.send(block_detail_copy).await {
         // We have an error that prevents us from continuing
         return Err(ProcessLeafError::BlockSendError(err));
@@ -451,7 +538,19 @@ where
     NISink: Sink<NodeIdentity, Error = SendError> + Unpin,
 {
     let mut data_state_write_lock_guard = data_state.write().await;
-    data_state_write_lock_guard.add_node_identity(node_identity.clone());
+    let index = data_state_write_lock_guard.add_node_identity(node_identity.clone());
+    let storage = data_state_write_lock_guard.storage.clone();
+    drop(data_state_write_lock_guard);
+
+    if let Some(storage) = storage {
+        if let Err(err) = storage.store_node_identity(index, &node_identity).await {
+            tracing::error!(
+                "process node identity: error storing node identity: {}",
+                err
+            );
+        }
+    }
+
     node_identity_sender.send(node_identity).await?;
 
     Ok(())
@@ -613,6 +712,7 @@ mod tests {
             block_merkle_tree: BlockMerkleTree::new(32),
             fee_merkle_tree: FeeMerkleTree::new(32),
             chain_config: ChainConfig::default().into(),
+            ..Default::default()
         };
         let instance_state = NodeState::mock();
 
@@ -0,0 +1,365 @@
+use bitvec::vec::BitVec;
+use hotshot_types::signature_key::BLSPubKey;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+/// PARTICIPATION_WINDOWS represents the sizes, in blocks, of the rolling
+/// windows that participation statistics are computed over.
+pub const PARTICIPATION_WINDOWS: [usize; 3] = [100, 1_000, 10_000];
+
+/// UPTIME_SLOT_SECONDS represents the length of the time slots that uptime
+/// is measured in.  A validator is considered to be up during a slot if it
+/// voted for any of the blocks that were decided within that slot.
+pub const UPTIME_SLOT_SECONDS: u64 = 60;
+
+/// [LeafParticipation] represents the participation information that is
+/// recorded for a single decided leaf.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeafParticipation {
+    pub height: u64,
+    pub view: u64,
+    pub timestamp: u64,
+
+    /// voters is the voter participation of the Quorum Certificate for this
+    /// leaf, in stake table order.
+    pub voters: BitVec<u16>,
+}
+
+/// [ParticipationTracker] keeps the participation information of the most
+/// recent leaves, enough to compute statistics over the largest of the
+/// [PARTICIPATION_WINDOWS].
+#[derive(Debug, Default)]
+pub struct ParticipationTracker {
+    leaves: VecDeque<LeafParticipation>,
+
+    /// snapshot is the most recently computed [ParticipationSnapshot],
+    /// along with the stake table keys it was computed for.  It is cleared
+    /// whenever a leaf is added, so that the statistics are computed at most
+    /// once per block, no matter how many clients request them.
+    snapshot: Mutex<Option<(Vec<BLSPubKey>, Arc<ParticipationSnapshot>)>>,
+}
+
+/// [ValidatorParticipation] represents the participation statistics of a
+/// single validator over a window of blocks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorParticipation {
+    pub public_key: BLSPubKey,
+
+    /// votes is the number of blocks in the window whose Quorum Certificate
+    /// this validator signed.
+    pub votes: u64,
+
+    /// vote_participation_rate is the fraction of the blocks in the window
+    /// whose Quorum Certificate this validator signed.
+    pub vote_participation_rate: f64,
+
+    /// current_missed_vote_streak is the number of most recent consecutive
+    /// blocks that this validator did not vote for.
+    pub current_missed_vote_streak: u64,
+
+    /// longest_missed_vote_streak is the longest run of consecutive blocks
+    /// in the window that this validator did not vote for.
+    pub longest_missed_vote_streak: u64,
+
+    /// proposals is the number of blocks in the window that were proposed by
+    /// this validator as leader.
+    pub proposals: u64,
+
+    /// expected_proposals is the number of views covered by the window in
+    /// which this validator was the leader.
+    pub expected_proposals: u64,
+
+    /// uptime is the fraction of time slots in the window, in which any
+    /// block was decided, where this validator voted for at least one block.
+    pub uptime: f64,
+}
+
+/// [ParticipationWindow] represents the participation statistics of all
+/// of the validators in the stake table over a window of recent blocks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParticipationWindow {
+    /// num_blocks is the size of the window requested.  Fewer blocks may
+    /// have been observed so far.
+    pub num_blocks: usize,
+
+    /// first_height and last_height are the heights of the oldest and most
+    /// recent blocks within the window, if any.
+    pub first_height: Option<u64>,
+    pub last_height: Option<u64>,
+
+    /// validators are the statistics of each validator, in stake table order.
+    pub validators: Vec<ValidatorParticipation>,
+}
+
+/// [ParticipationSnapshot] represents the participation statistics of the
+/// validators over each of the [PARTICIPATION_WINDOWS].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParticipationSnapshot {
+    pub windows: Vec<ParticipationWindow>,
+}
+
+impl ParticipationTracker {
+    /// [add_leaf] records the participation information of a newly decided
+    /// leaf, dropping the oldest leaf if the largest window is full.
+    pub fn add_leaf(&mut self, leaf: LeafParticipation) {
+        self.leaves.push_back(leaf);
+        while self.leaves.len() > max_window() {
+            self.leaves.pop_front();
+        }
+        *self.snapshot.get_mut().unwrap() = None;
+    }
+
+    /// [snapshot] computes the participation statistics for the validators
+    /// with the given public keys, which are expected to be in stake table
+    /// order, over each of the [PARTICIPATION_WINDOWS].
+    ///
+    /// The leader of each view is expected to be chosen round robin from the
+    /// stake table, as is done by the static committee.
+    ///
+    /// The statistics are cached until the next leaf is added, or they are
+    /// requested for a different stake table.
+    pub fn snapshot(&self, stake_table_keys: &[BLSPubKey]) -> Arc<ParticipationSnapshot> {
+        let mut cache = self.snapshot.lock().unwrap();
+        if let Some((keys, snapshot)) = &*cache {
+            if keys == stake_table_keys {
+                return snapshot.clone();
+            }
+        }
+
+        let snapshot = Arc::new(ParticipationSnapshot {
+            windows: PARTICIPATION_WINDOWS
+                .iter()
+                .map(|num_blocks| self.window(*num_blocks, stake_table_keys))
+                .collect(),
+        });
+        *cache = Some((stake_table_keys.to_vec(), snapshot.clone()));
+        snapshot
+    }
+
+    fn window(&self, num_blocks: usize, stake_table_keys: &[BLSPubKey]) -> ParticipationWindow {
+        let leaves = self
+            .leaves
+            .range(self.leaves.len().saturating_sub(num_blocks)..)
+            .collect::<Vec<_>>();
+        let num_validators = stake_table_keys.len();
+
+        // The time slots in which any block was decided.
+        let slots = leaves
+            .iter()
+            .map(|leaf| leaf.timestamp / UPTIME_SLOT_SECONDS)
+            .collect::<HashSet<_>>();
+
+        let validators = stake_table_keys
+            .iter()
+            .enumerate()
+            .map(|(index, public_key)| {
+                let mut votes = 0;
+                let mut current_missed_vote_streak = 0;
+                let mut longest_missed_vote_streak = 0;
+                let mut proposals = 0;
+                let mut slots_voted = HashSet::new();
+
+                for leaf in &leaves {
+                    if leaf.voters.get(index).is_some_and(|voted| *voted) {
+                        votes += 1;
+                        current_missed_vote_streak = 0;
+                        slots_voted.insert(leaf.timestamp / UPTIME_SLOT_SECONDS);
+                    } else {
+                        current_missed_vote_streak += 1;
+                        longest_missed_vote_streak =
+                            longest_missed_vote_streak.max(current_missed_vote_streak);
+                    }
+
+                    if leader_index(leaf.view, num_validators) == Some(index) {
+                        proposals += 1;
+                    }
+                }
+
+                let expected_proposals = match (leaves.first(), leaves.last()) {
+                    (Some(first), Some(last)) => {
+                        views_led(first.view, last.view, index, num_validators)
+                    }
+                    _ => 0,
+                };
+
+                ValidatorParticipation {
+                    public_key: *public_key,
+                    votes,
+                    vote_participation_rate: ratio(votes, leaves.len() as u64),
+                    current_missed_vote_streak,
+                    longest_missed_vote_streak,
+                    proposals,
+                    expected_proposals,
+                    uptime: ratio(slots_voted.len() as u64, slots.len() as u64),
+                }
+            })
+            .collect();
+
+        ParticipationWindow {
+            num_blocks,
+            first_height: leaves.first().map(|leaf| leaf.height),
+            last_height: leaves.last().map(|leaf| leaf.height),
+            validators,
+        }
+    }
+}
+
+/// [max_window] returns the size, in blocks, of the largest of the
+/// [PARTICIPATION_WINDOWS].
+pub fn max_window() -> usize {
+    PARTICIPATION_WINDOWS
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or_default()
+}
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    if denominator == 0 {
+        return 0.0;
+    }
+
+    numerator as f64 / denominator as f64
+}
+
+/// [leader_index] returns the index, in the stake table, of the leader of
+/// the given view.
+fn leader_index(view: u64, num_validators: usize) -> Option<usize> {
+    if num_validators == 0 {
+        return None;
+    }
+
+    Some((view % num_validators as u64) as usize)
+}
+
+/// [views_led] returns the number of views between `first_view` and
+/// `last_view`, inclusive, in which the validator at `index` is the leader.
+fn views_led(first_view: u64, last_view: u64, index: usize, num_validators: usize) -> u64 {
+    if num_validators == 0 || last_view < first_view {
+        return 0;
+    }
+
+    // The number of views v in [0, view) with v % n == index.
+    let led_before = |view: u64| {
+        let n = num_validators as u64;
+        view / n + u64::from(view % n > index as u64)
+    };
+
+    led_before(last_view + 1) - led_before(first_view)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        views_led, LeafParticipation, ParticipationTracker, PARTICIPATION_WINDOWS,
+        UPTIME_SLOT_SECONDS,
+    };
+    use bitvec::vec::BitVec;
+    use hotshot_types::{signature_key::BLSPubKey, traits::signature_key::SignatureKey};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_views_led() {
+        // Views 0 through 9, with 3 validators.
+        assert_eq!(views_led(0, 9, 0, 3), 4);
+        assert_eq!(views_led(0, 9, 1, 3), 3);
+        assert_eq!(views_led(0, 9, 2, 3), 3);
+
+        // A single view.
+        assert_eq!(views_led(4, 4, 1, 3), 1);
+        assert_eq!(views_led(4, 4, 0, 3), 0);
+
+        // No validators.
+        assert_eq!(views_led(0, 9, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_participation_snapshot() {
+        let keys = (0..3)
+            .map(|i| BLSPubKey::generated_from_seed_indexed([0; 32], i).0)
+            .collect::<Vec<_>>();
+
+        let mut tracker = ParticipationTracker::default();
+        // Validator 0 always votes, validator 1 misses every other block,
+        // and validator 2 stops voting after the first minute.  View 3 fails,
+        // so validator 0 misses a proposal.
+        for (height, view) in [(0, 0), (1, 1), (2, 2), (3, 4), (4, 5), (5, 6)] {
+            let mut voters = BitVec::repeat(false, 3);
+            voters.set(0, true);
+            voters.set(1, height % 2 == 0);
+            voters.set(2, height < 3);
+            tracker.add_leaf(LeafParticipation {
+                height,
+                view,
+                timestamp: height * UPTIME_SLOT_SECONDS / 3,
+                voters,
+            });
+        }
+
+        let snapshot = tracker.snapshot(&keys);
+        assert_eq!(snapshot.windows.len(), PARTICIPATION_WINDOWS.len());
+
+        let window = &snapshot.windows[0];
+        assert_eq!(window.first_height, Some(0));
+        assert_eq!(window.last_height, Some(5));
+
+        let [validator_0, validator_1, validator_2] = &window.validators[..] else {
+            panic!("expected 3 validators");
+        };
+
+        assert_eq!(validator_0.public_key, keys[0]);
+        assert_eq!(validator_0.votes, 6);
+        assert_eq!(validator_0.vote_participation_rate, 1.0);
+        assert_eq!(validator_0.longest_missed_vote_streak, 0);
+        assert_eq!(validator_0.proposals, 2);
+        assert_eq!(validator_0.expected_proposals, 3);
+        assert_eq!(validator_0.uptime, 1.0);
+
+        assert_eq!(validator_1.votes, 3);
+        assert_eq!(validator_1.vote_participation_rate, 0.5);
+        assert_eq!(validator_1.current_missed_vote_streak, 1);
+        assert_eq!(validator_1.longest_missed_vote_streak, 1);
+        assert_eq!(validator_1.proposals, 2);
+        assert_eq!(validator_1.expected_proposals, 2);
+        assert_eq!(validator_1.uptime, 1.0);
+
+        assert_eq!(validator_2.votes, 3);
+        assert_eq!(validator_2.current_missed_vote_streak, 3);
+        assert_eq!(validator_2.longest_missed_vote_streak, 3);
+        assert_eq!(validator_2.proposals, 2);
+        assert_eq!(validator_2.expected_proposals, 2);
+        assert_eq!(validator_2.uptime, 0.5);
+    }
+
+    #[test]
+    fn test_participation_snapshot_cache() {
+        let keys = (0..2)
+            .map(|i| BLSPubKey::generated_from_seed_indexed([0; 32], i).0)
+            .collect::<Vec<_>>();
+        let leaf = |height: u64| LeafParticipation {
+            height,
+            view: height,
+            timestamp: height,
+            voters: BitVec::repeat(true, 2),
+        };
+
+        let mut tracker = ParticipationTracker::default();
+        tracker.add_leaf(leaf(0));
+
+        // The snapshot is only computed once per block.
+        let snapshot = tracker.snapshot(&keys);
+        assert!(Arc::ptr_eq(&snapshot, &tracker.snapshot(&keys)));
+
+        // A different stake table gets its own statistics.
+        let other = tracker.snapshot(&keys[..1]);
+        assert_eq!(other.windows[0].validators.len(), 1);
+
+        // A new block invalidates the snapshot.
+        tracker.add_leaf(leaf(1));
+        let snapshot = tracker.snapshot(&keys);
+        assert_eq!(snapshot.windows[0].last_height, Some(1));
+    }
+}
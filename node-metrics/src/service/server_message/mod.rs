@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use super::{client_id::ClientId, data_state::NodeIdentity};
+use super::{
+    client_id::ClientId,
+    data_state::{NodeIdentity, ParticipationSnapshot},
+};
 use bitvec::vec::BitVec;
 use espresso_types::SeqTypes;
 use hotshot_query_service::explorer::{BlockDetail, ExplorerHistograms};
@@ -40,6 +43,11 @@ pub enum ServerMessage {
     /// VotersSnapshot is a message that is sent in response to a request for
     /// the snapshot of the current voters information.
     VotersSnapshot(Arc<Vec<BitVec<u16>>>),
+
+    /// ParticipationSnapshot is a message that is sent in response to a
+    /// request for the snapshot of the current vote participation, proposal
+    /// and uptime statistics of the validators.
+    ParticipationSnapshot(Arc<ParticipationSnapshot>),
 }
 
 impl PartialEq for ServerMessage {
@@ -53,6 +61,7 @@ impl PartialEq for ServerMessage {
             (Self::NodeIdentitySnapshot(lhs), Self::NodeIdentitySnapshot(rhs)) => lhs == rhs,
             (Self::HistogramSnapshot(_), Self::HistogramSnapshot(_)) => false,
             (Self::VotersSnapshot(lhs), Self::VotersSnapshot(rhs)) => lhs == rhs,
+            (Self::ParticipationSnapshot(lhs), Self::ParticipationSnapshot(rhs)) => lhs == rhs,
             _ => false,
         }
     }
@@ -0,0 +1,9 @@
+CREATE TABLE bootstrap_state (
+    -- The ID is always set to 0. Setting it explicitly allows us to enforce with every insert or
+    -- update that there is only a single entry in this table: the state imported from the most
+    -- recent snapshot.
+    id INT PRIMARY KEY,
+
+    height BIGINT NOT NULL,
+    state  BYTEA NOT NULL
+);
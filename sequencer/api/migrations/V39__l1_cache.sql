@@ -0,0 +1,16 @@
+-- Finalized L1 data cached by the L1 client. Since this data is finalized, it never changes, so it
+-- can be kept across restarts without invalidation. Old entries are pruned as new ones are saved.
+CREATE TABLE l1_block (
+    number BIGINT PRIMARY KEY,
+    data   JSONB NOT NULL
+);
+
+CREATE TABLE l1_deposits (
+    fee_contract VARCHAR NOT NULL,
+    from_block   BIGINT NOT NULL,
+    to_block     BIGINT NOT NULL,
+    deposits     JSONB NOT NULL,
+    PRIMARY KEY (fee_contract, from_block, to_block)
+);
+
+CREATE INDEX l1_deposits_to_block_idx ON l1_deposits (to_block);
@@ -0,0 +1,13 @@
+-- Fee deposits applied to the fee state, indexed by the Espresso block in which they took effect.
+CREATE TABLE fee_deposit (
+    height         BIGINT NOT NULL,
+    l1_block       BIGINT NOT NULL,
+    log_index      BIGINT NOT NULL,
+    l1_transaction VARCHAR NOT NULL,
+    account        VARCHAR NOT NULL,
+    data           JSONB NOT NULL,
+    PRIMARY KEY (l1_block, log_index)
+);
+
+CREATE INDEX fee_deposit_account_height_idx ON fee_deposit (account, height);
+CREATE INDEX fee_deposit_height_idx ON fee_deposit (height);
@@ -0,0 +1,3 @@
+-- Index fee state entries by account, so we can efficiently look up the history of a single
+-- account's balance.
+CREATE INDEX fee_merkle_tree_index ON fee_merkle_tree (index, created) WHERE index IS NOT NULL;
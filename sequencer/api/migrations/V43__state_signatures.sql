@@ -0,0 +1,10 @@
+-- Light client state signatures generated by this node, by block height.
+CREATE TABLE state_signature (
+    height    BIGINT PRIMARY KEY,
+    signature BYTEA NOT NULL,
+    -- Whether the signature has been accepted by the state relay server. Undelivered signatures are
+    -- retried, including after a restart.
+    delivered BOOLEAN NOT NULL DEFAULT false
+);
+
+CREATE INDEX state_signature_undelivered_idx ON state_signature (height) WHERE NOT delivered;
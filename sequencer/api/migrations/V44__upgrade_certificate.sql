@@ -0,0 +1,8 @@
+CREATE TABLE upgrade_certificate (
+    -- The ID is always set to 0. Setting it explicitly allows us to enforce with every insert or
+    -- update that there is only a single entry in this table: the most recently decided upgrade
+    -- certificate.
+    id INT PRIMARY KEY,
+
+    data BYTEA NOT NULL
+);
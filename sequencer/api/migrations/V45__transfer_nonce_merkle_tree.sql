@@ -0,0 +1,26 @@
+CREATE TABLE transfer_nonce_merkle_tree (
+  path INTEGER[] NOT NULL,
+  created BIGINT NOT NULL,
+  hash_id INT NOT NULL REFERENCES hash (id),
+  children INT[],
+  children_bitvec BIT(256),
+  index JSONB,
+  entry JSONB
+);
+
+ALTER TABLE
+  transfer_nonce_merkle_tree
+ADD
+  CONSTRAINT transfer_nonce_merkle_tree_pk PRIMARY KEY (path, created);
+
+-- Index nonce entries by account, so we can efficiently look up the latest nonce of every account.
+CREATE INDEX transfer_nonce_merkle_tree_index ON transfer_nonce_merkle_tree (index, created)
+  WHERE index IS NOT NULL;
+
+-- Only headers from version 0.3 on have a transfer nonce root, so unlike the other Merkle root
+-- columns, this one is nullable.
+ALTER TABLE header
+ADD column transfer_nonce_merkle_tree_root text
+GENERATED ALWAYS AS (data->'fields'->>'transfer_nonce_merkle_tree_root') STORED;
+
+CREATE INDEX header_transfer_nonce_merkle_tree_root_idx ON header (transfer_nonce_merkle_tree_root);
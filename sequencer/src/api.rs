@@ -5,11 +5,14 @@ use async_once_cell::Lazy;
 use async_std::sync::{Arc, RwLock};
 use async_trait::async_trait;
 use committable::Commitment;
-use data_source::{CatchupDataSource, SubmitDataSource};
+use data_source::{CatchupDataSource, DepositDataSource, FeeHistoryDataSource, SubmitDataSource};
 use derivative::Derivative;
 use espresso_types::{
-    v0::traits::SequencerPersistence, v0_3::ChainConfig, AccountQueryData, BlockMerkleTree,
-    FeeAccountProof, MockSequencerVersions, NodeState, PubKey, Transaction,
+    remember_transfer_nonces,
+    v0::traits::SequencerPersistence,
+    v0_3::{ChainConfig, TransferNonceMerkleTree},
+    AccountQueryData, BalanceHistoryEntry, BlockMerkleTree, DepositQueryData, FeeAccount,
+    FeeAccountProof, FeeMerkleTree, MockSequencerVersions, NodeState, PubKey, Transaction,
 };
 use ethers::prelude::Address;
 use futures::{
@@ -28,12 +31,15 @@ use hotshot_types::{
     light_client::StateSignatureRequestBody,
     traits::{network::ConnectedNetwork, node_implementation::Versions},
 };
-use jf_merkle_tree::MerkleTreeScheme;
+use jf_merkle_tree::{ForgetableMerkleTreeScheme, MerkleTreeScheme};
 
 use self::data_source::{HotShotConfigDataSource, PublicNetworkConfig, StateSignatureDataSource};
 use crate::{
-    context::Consensus, network, state_signature::StateSigner, SeqTypes, SequencerApiVersion,
-    SequencerContext,
+    context::Consensus,
+    network,
+    state_signature::StateSigner,
+    upgrade_status::{UpgradeStatus, UpgradeTracker},
+    SeqTypes, SequencerApiVersion, SequencerContext,
 };
 
 pub mod data_source;
@@ -53,6 +59,7 @@ type BoxLazy<T> = Pin<Arc<Lazy<T, BoxFuture<'static, T>>>>;
 #[derivative(Debug(bound = ""))]
 struct ConsensusState<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> {
     state_signer: Arc<StateSigner<SequencerApiVersion>>,
+    upgrade_tracker: Arc<UpgradeTracker>,
     event_streamer: Arc<RwLock<EventsStreamer<SeqTypes>>>,
     node_state: NodeState,
     config: NetworkConfig<PubKey>,
@@ -67,6 +74,7 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions>
     fn from(ctx: &SequencerContext<N, P, V>) -> Self {
         Self {
             state_signer: ctx.state_signer(),
+            upgrade_tracker: ctx.upgrade_tracker(),
             event_streamer: ctx.event_streamer(),
             node_state: ctx.node_state(),
             config: ctx.config(),
@@ -97,6 +105,16 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> ApiState
         &self.consensus.as_ref().get().await.get_ref().state_signer
     }
 
+    async fn upgrade_tracker(&self) -> &UpgradeTracker {
+        &self
+            .consensus
+            .as_ref()
+            .get()
+            .await
+            .get_ref()
+            .upgrade_tracker
+    }
+
     async fn event_streamer(&self) -> &RwLock<EventsStreamer<SeqTypes>> {
         &self.consensus.as_ref().get().await.get_ref().event_streamer
     }
@@ -215,6 +233,50 @@ impl<
         self.inner().get_account(height, view, account).await
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn get_accounts(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<FeeMerkleTree> {
+        // Check if we have the desired state in memory.
+        match self.as_ref().get_accounts(height, view, accounts).await {
+            Ok(snapshot) => return Ok(snapshot),
+            Err(err) => {
+                tracing::info!("accounts are not in memory, trying storage: {err:#}");
+            }
+        }
+
+        // Try storage.
+        self.inner().get_accounts(height, view, accounts).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn get_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<TransferNonceMerkleTree> {
+        // Check if we have the desired state in memory.
+        match self
+            .as_ref()
+            .get_transfer_nonces(height, view, accounts)
+            .await
+        {
+            Ok(snapshot) => return Ok(snapshot),
+            Err(err) => {
+                tracing::info!("transfer nonces are not in memory, trying storage: {err:#}");
+            }
+        }
+
+        // Try storage.
+        self.inner()
+            .get_transfer_nonces(height, view, accounts)
+            .await
+    }
+
     #[tracing::instrument(skip(self))]
     async fn get_frontier(&self, height: u64, view: ViewNumber) -> anyhow::Result<BlocksFrontier> {
         // Check if we have the desired state in memory.
@@ -246,6 +308,46 @@ impl<
     }
 }
 
+impl<
+        N: ConnectedNetwork<PubKey>,
+        V: Versions,
+        P: SequencerPersistence,
+        D: DepositDataSource + Send + Sync,
+    > DepositDataSource for StorageState<N, P, D, V>
+{
+    async fn get_deposits_for_account(
+        &self,
+        account: FeeAccount,
+    ) -> anyhow::Result<Vec<DepositQueryData>> {
+        self.inner().get_deposits_for_account(account).await
+    }
+
+    async fn get_deposits_in_range(
+        &self,
+        from: u64,
+        until: u64,
+    ) -> anyhow::Result<Vec<DepositQueryData>> {
+        self.inner().get_deposits_in_range(from, until).await
+    }
+}
+
+impl<
+        N: ConnectedNetwork<PubKey>,
+        V: Versions,
+        P: SequencerPersistence,
+        D: FeeHistoryDataSource + Send + Sync,
+    > FeeHistoryDataSource for StorageState<N, P, D, V>
+{
+    async fn get_balance_history(
+        &self,
+        account: FeeAccount,
+        from: u64,
+        until: u64,
+    ) -> anyhow::Result<Vec<BalanceHistoryEntry>> {
+        self.inner().get_balance_history(account, from, until).await
+    }
+}
+
 // #[async_trait]
 // impl<
 //         N: ConnectedNetwork<PubKey>,
@@ -291,6 +393,65 @@ impl<N: ConnectedNetwork<PubKey>, V: Versions, P: SequencerPersistence> CatchupD
         Ok(AccountQueryData { balance, proof })
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn get_accounts(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<FeeMerkleTree> {
+        let state = self
+            .consensus()
+            .await
+            .read()
+            .await
+            .state(view)
+            .await
+            .context(format!(
+                "state not available for height {height}, view {view:?}"
+            ))?;
+
+        // Build a sparse copy of the fee state containing only the requested accounts.
+        let mut snapshot = FeeMerkleTree::from_commitment(state.fee_merkle_tree.commitment());
+        for account in accounts {
+            let (proof, _) = FeeAccountProof::prove(&state.fee_merkle_tree, (*account).into())
+                .context(format!(
+                    "account {account} not available for height {height}, view {view:?}"
+                ))?;
+            proof
+                .remember(&mut snapshot)
+                .context(format!("invalid proof for account {account}"))?;
+        }
+        Ok(snapshot)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn get_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<TransferNonceMerkleTree> {
+        let state = self
+            .consensus()
+            .await
+            .read()
+            .await
+            .state(view)
+            .await
+            .context(format!(
+                "state not available for height {height}, view {view:?}"
+            ))?;
+
+        // Build a sparse copy of the transfer nonces containing only the requested accounts.
+        let tree = &state.transfer_nonce_merkle_tree;
+        let mut snapshot = TransferNonceMerkleTree::from_commitment(tree.commitment());
+        remember_transfer_nonces(&mut snapshot, tree, accounts).context(format!(
+            "transfer nonces not available for height {height}, view {view:?}"
+        ))?;
+        Ok(snapshot)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn get_frontier(&self, height: u64, view: ViewNumber) -> anyhow::Result<BlocksFrontier> {
         let state = self
@@ -329,6 +490,10 @@ impl<N: ConnectedNetwork<PubKey>, D: Sync, V: Versions, P: SequencerPersistence>
     async fn get_config(&self) -> PublicNetworkConfig {
         self.as_ref().network_config().await.into()
     }
+
+    async fn get_upgrade_status(&self) -> UpgradeStatus {
+        self.as_ref().get_upgrade_status().await
+    }
 }
 
 impl<N: ConnectedNetwork<PubKey>, V: Versions, P: SequencerPersistence> HotShotConfigDataSource
@@ -337,6 +502,14 @@ impl<N: ConnectedNetwork<PubKey>, V: Versions, P: SequencerPersistence> HotShotC
     async fn get_config(&self) -> PublicNetworkConfig {
         self.network_config().await.into()
     }
+
+    async fn get_upgrade_status(&self) -> UpgradeStatus {
+        let decided_leaf = self.consensus().await.read().await.decided_leaf().await;
+        self.upgrade_tracker()
+            .await
+            .status(&self.node_state().await.upgrades, &decided_leaf)
+            .await
+    }
 }
 
 #[async_trait]
@@ -406,6 +579,8 @@ pub mod test_helpers {
         pub server: SequencerContext<network::Memory, P::Persistence, V>,
         pub peers: Vec<SequencerContext<network::Memory, P::Persistence, V>>,
         pub cfg: TestConfig<{ NUM_NODES }>,
+        /// The fallback builder nodes were started with, for restarting nodes.
+        pub marketplace_builder_url: Url,
     }
 
     pub struct TestNetworkConfig<const NUM_NODES: usize, P, C>
@@ -618,6 +793,7 @@ pub mod test_helpers {
                 server,
                 peers,
                 cfg: cfg.network_config,
+                marketplace_builder_url,
             }
         }
 
@@ -842,6 +1018,31 @@ pub mod test_helpers {
             0.into()
         );
 
+        // Undecided fee state: batch of accounts.
+        let accounts = vec![
+            FeeAccount::default(),
+            FeeAccount::test_key_pair().fee_account(),
+        ];
+        let snapshot = client
+            .post::<FeeMerkleTree>(&format!("catchup/{height}/{}/accounts", view.u64()))
+            .body_binary(&accounts)
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+        let root = network
+            .server
+            .state(view)
+            .await
+            .unwrap()
+            .fee_merkle_tree
+            .commitment();
+        assert_eq!(snapshot.commitment(), root);
+        for account in accounts {
+            let (proof, balance) = FeeAccountProof::prove(&snapshot, account.into()).unwrap();
+            assert_eq!(proof.verify(&root).unwrap(), balance);
+        }
+
         // Undecided block state.
         let res = client
             .get::<BlocksFrontier>(&format!("catchup/{height}/{}/blocks", view.u64()))
@@ -874,6 +1075,15 @@ pub mod test_helpers {
             }
             .boxed()
         })?
+        .at("accounts", |_req, _state: &()| {
+            async move {
+                Result::<FeeMerkleTree, _>::Err(hotshot_query_service::Error::catch_all(
+                    StatusCode::BAD_REQUEST,
+                    "no accounts found".to_string(),
+                ))
+            }
+            .boxed()
+        })?
         .get("blocks", |_req, _state| {
             async move {
                 Result::<BlocksFrontier, _>::Err(hotshot_query_service::Error::catch_all(
@@ -1074,8 +1284,8 @@ mod test {
         mock::MockStateCatchup,
         traits::NullEventConsumer,
         v0_1::{UpgradeMode, ViewBasedUpgrade},
-        BackoffParams, FeeAccount, FeeAmount, Header, MockSequencerVersions, SequencerVersions,
-        TimeBasedUpgrade, Timestamp, Upgrade, UpgradeType, ValidatedState,
+        BackoffParams, BalanceChange, FeeAccount, FeeAmount, Header, MockSequencerVersions,
+        SequencerVersions, TimeBasedUpgrade, Timestamp, Upgrade, UpgradeType, ValidatedState,
     };
     use ethers::utils::Anvil;
     use futures::{
@@ -1114,6 +1324,7 @@ mod test {
         catchup::StatePeers,
         persistence::no_storage,
         testing::{TestConfig, TestConfigBuilder},
+        upgrade_status::UpgradePhase,
     };
 
     #[async_std::test]
@@ -1202,6 +1413,27 @@ mod test {
         sleep(Duration::from_secs(5)).await;
         network.stop_consensus().await;
 
+        // The builder account was funded at genesis, and its balance has not changed since, since
+        // the test chain config has no base fee.
+        let account = TestConfig::<5>::builder_key().fee_account();
+        let history = client
+            .get::<Vec<BalanceHistoryEntry>>(&format!(
+                "fee-history/balance/{account}/0/{}",
+                blocks.len() + 1
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1, "{history:?}");
+        assert_eq!(history[0].height, 0);
+        assert!(history[0].account.balance > 0.into());
+        assert_eq!(
+            history[0].changes,
+            [BalanceChange::Genesis(FeeAmount(
+                history[0].account.balance
+            ))]
+        );
+
         for block in blocks {
             let i = block.height();
             tracing::info!(i, "get block state");
@@ -1677,7 +1909,8 @@ mod test {
                     max_connections: None,
                 })
                 .catchup(Default::default())
-                .status(Default::default()),
+                .status(Default::default())
+                .config(Default::default()),
             )
             .catchups(std::array::from_fn(|_| {
                 StatePeers::<SequencerApiVersion>::from_urls(
@@ -1755,6 +1988,22 @@ mod test {
             sleep(Duration::from_millis(200)).await;
         }
 
+        // The upgrade is reported as active by the config API.
+        let status = client
+            .get::<UpgradeStatus>("config/upgrades")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(status.upgrades.len(), 1);
+        let upgrade = &status.upgrades[0];
+        assert_eq!(
+            upgrade.version,
+            <MockSeqVersions as Versions>::Upgrade::VERSION
+        );
+        assert_eq!(upgrade.phase, UpgradePhase::Active);
+        assert!(upgrade.new_version_first_view.is_some());
+        assert_eq!(status.chain_config, chain_config_upgrade.commit());
+
         network.server.shut_down().await;
     }
 
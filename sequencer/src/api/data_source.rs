@@ -5,8 +5,8 @@ use async_trait::async_trait;
 use committable::Commitment;
 use espresso_types::{
     v0::traits::{PersistenceOptions, SequencerPersistence},
-    v0_3::ChainConfig,
-    PubKey, Transaction,
+    v0_3::{ChainConfig, TransferNonceMerkleTree},
+    BalanceHistoryEntry, DepositQueryData, FeeAccount, FeeMerkleTree, PubKey, Transaction,
 };
 use ethers::prelude::Address;
 use futures::future::Future;
@@ -37,6 +37,7 @@ use super::{
 };
 use crate::{
     persistence::{self},
+    upgrade_status::UpgradeStatus,
     SeqTypes, SequencerApiVersion,
 };
 
@@ -102,6 +103,9 @@ pub(crate) trait SubmitDataSource<N: ConnectedNetwork<PubKey>, P: SequencerPersi
 
 pub(crate) trait HotShotConfigDataSource {
     fn get_config(&self) -> impl Send + Future<Output = PublicNetworkConfig>;
+
+    /// The progress of each upgrade this node is configured for.
+    fn get_upgrade_status(&self) -> impl Send + Future<Output = UpgradeStatus>;
 }
 
 #[async_trait]
@@ -131,6 +135,38 @@ pub(crate) trait CatchupDataSource {
         }
     }
 
+    /// Get the state of several `accounts` at once.
+    ///
+    /// Returns a snapshot of the fee Merkle tree at the given height and view, in which the paths to
+    /// each of the requested accounts are remembered. The same restrictions on `height` and `view`
+    /// apply as for [`get_account`](Self::get_account).
+    fn get_accounts(
+        &self,
+        _height: u64,
+        _view: ViewNumber,
+        _accounts: &[FeeAccount],
+    ) -> impl Send + Future<Output = anyhow::Result<FeeMerkleTree>> {
+        async {
+            bail!("merklized state catchup is not supported for this data source");
+        }
+    }
+
+    /// Get the transfer nonces of several `accounts` at once.
+    ///
+    /// Returns a snapshot of the transfer nonce Merkle tree at the given height and view, in which
+    /// the paths to each of the requested accounts are remembered. The same restrictions on
+    /// `height` and `view` apply as for [`get_account`](Self::get_account).
+    fn get_transfer_nonces(
+        &self,
+        _height: u64,
+        _view: ViewNumber,
+        _accounts: &[FeeAccount],
+    ) -> impl Send + Future<Output = anyhow::Result<TransferNonceMerkleTree>> {
+        async {
+            bail!("merklized state catchup is not supported for this data source");
+        }
+    }
+
     /// Get the blocks Merkle tree frontier.
     ///
     /// The state is fetched from a snapshot at the given height and view, which _must_ correspond!
@@ -163,6 +199,57 @@ pub(crate) trait CatchupDataSource {
 
 impl CatchupDataSource for MetricsDataSource {}
 
+/// The maximum number of blocks which can be requested in a single deposit history range query.
+pub(crate) const MAX_DEPOSIT_RANGE: u64 = 10_000;
+
+pub(crate) trait DepositDataSource {
+    /// Get all fee deposits made to `account`, in the order they took effect.
+    fn get_deposits_for_account(
+        &self,
+        _account: FeeAccount,
+    ) -> impl Send + Future<Output = anyhow::Result<Vec<DepositQueryData>>> {
+        // Deposits are indexed by the merklized state update loop, so the index is only available
+        // for persistence backends that provide merklized state storage.
+        async {
+            bail!("deposit history is not supported for this data source");
+        }
+    }
+
+    /// Get all fee deposits which took effect in blocks with heights in `from..until`.
+    ///
+    /// The range may be at most [`MAX_DEPOSIT_RANGE`] blocks.
+    fn get_deposits_in_range(
+        &self,
+        _from: u64,
+        _until: u64,
+    ) -> impl Send + Future<Output = anyhow::Result<Vec<DepositQueryData>>> {
+        async {
+            bail!("deposit history is not supported for this data source");
+        }
+    }
+}
+
+/// The maximum number of blocks which can be requested in a single balance history query.
+pub(crate) const MAX_BALANCE_HISTORY_RANGE: u64 = 10_000;
+
+pub(crate) trait FeeHistoryDataSource {
+    /// Get every change to the balance of `account` in blocks with heights in `from..until`.
+    ///
+    /// The range may be at most [`MAX_BALANCE_HISTORY_RANGE`] blocks.
+    fn get_balance_history(
+        &self,
+        _account: FeeAccount,
+        _from: u64,
+        _until: u64,
+    ) -> impl Send + Future<Output = anyhow::Result<Vec<BalanceHistoryEntry>>> {
+        // Balance history is derived from the merklized fee state, so it is only available for
+        // persistence backends that provide merklized state storage.
+        async {
+            bail!("balance history is not supported for this data source");
+        }
+    }
+}
+
 /// This struct defines the public Hotshot validator configuration.
 /// Private key and state key pairs are excluded for security reasons.
 
@@ -387,6 +474,11 @@ impl From<NetworkConfig<PubKey>> for PublicNetworkConfig {
 }
 
 impl PublicNetworkConfig {
+    /// The staked nodes in the network.
+    pub fn known_nodes_with_stake(&self) -> &[PeerConfig<PubKey>] {
+        &self.config.known_nodes_with_stake
+    }
+
     pub fn into_network_config(
         self,
         my_own_validator_config: ValidatorConfig<PubKey>,
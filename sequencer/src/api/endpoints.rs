@@ -7,7 +7,7 @@ use std::{
 
 use anyhow::Result;
 use committable::Committable;
-use espresso_types::{NamespaceId, NsProof, PubKey, Transaction};
+use espresso_types::{FeeAccount, NamespaceId, NsProof, PubKey, Transaction};
 use futures::{try_join, FutureExt};
 use hotshot_query_service::{
     availability::{self, AvailabilityDataSource, CustomSnafu, FetchBlockSnafu},
@@ -33,8 +33,8 @@ use vbs::version::StaticVersionType;
 
 use super::{
     data_source::{
-        CatchupDataSource, HotShotConfigDataSource, SequencerDataSource, StateSignatureDataSource,
-        SubmitDataSource,
+        CatchupDataSource, DepositDataSource, FeeHistoryDataSource, HotShotConfigDataSource,
+        SequencerDataSource, StateSignatureDataSource, SubmitDataSource, MAX_DEPOSIT_RANGE,
     },
     StorageState,
 };
@@ -246,6 +246,58 @@ where
         }
         .boxed()
     })?
+    .at("accounts", |req, state| {
+        async move {
+            let height = req
+                .integer_param("height")
+                .map_err(Error::from_request_error)?;
+            let view = req
+                .integer_param("view")
+                .map_err(Error::from_request_error)?;
+            let accounts = req
+                .body_auto::<Vec<FeeAccount>, ApiVer>(ApiVer::instance())
+                .map_err(Error::from_request_error)?;
+
+            state
+                .read(|state| {
+                    async move {
+                        state
+                            .get_accounts(height, ViewNumber::new(view), &accounts)
+                            .await
+                    }
+                    .boxed()
+                })
+                .await
+                .map_err(|err| Error::catch_all(StatusCode::NOT_FOUND, format!("{err:#}")))
+        }
+        .boxed()
+    })?
+    .at("transfer-nonces", |req, state| {
+        async move {
+            let height = req
+                .integer_param("height")
+                .map_err(Error::from_request_error)?;
+            let view = req
+                .integer_param("view")
+                .map_err(Error::from_request_error)?;
+            let accounts = req
+                .body_auto::<Vec<FeeAccount>, ApiVer>(ApiVer::instance())
+                .map_err(Error::from_request_error)?;
+
+            state
+                .read(|state| {
+                    async move {
+                        state
+                            .get_transfer_nonces(height, ViewNumber::new(view), &accounts)
+                            .await
+                    }
+                    .boxed()
+                })
+                .await
+                .map_err(|err| Error::catch_all(StatusCode::NOT_FOUND, format!("{err:#}")))
+        }
+        .boxed()
+    })?
     .get("blocks", |req, state| {
         async move {
             let height = req
@@ -279,6 +331,111 @@ where
     Ok(api)
 }
 
+pub(super) fn deposits<S, ApiVer: StaticVersionType + 'static>(
+    _: ApiVer,
+) -> Result<Api<S, Error, ApiVer>>
+where
+    S: 'static + Send + Sync + ReadState,
+    S::State: Send + Sync + DepositDataSource,
+{
+    let toml = toml::from_str::<toml::Value>(include_str!("../../api/deposits.toml"))?;
+    let mut api = Api::<S, Error, ApiVer>::new(toml)?;
+
+    api.get("account", |req, state| {
+        async move {
+            let account = req
+                .string_param("address")
+                .map_err(Error::from_request_error)?;
+            let account: FeeAccount = account.parse().map_err(|err| {
+                Error::catch_all(
+                    StatusCode::BAD_REQUEST,
+                    format!("malformed account {account}: {err}"),
+                )
+            })?;
+
+            state
+                .get_deposits_for_account(account)
+                .await
+                .map_err(|err| Error::catch_all(StatusCode::NOT_FOUND, format!("{err:#}")))
+        }
+        .boxed()
+    })?
+    .get("range", |req, state| {
+        async move {
+            let from = req
+                .integer_param("from")
+                .map_err(Error::from_request_error)?;
+            let until = req
+                .integer_param("until")
+                .map_err(Error::from_request_error)?;
+            if from > until {
+                return Err(Error::catch_all(
+                    StatusCode::BAD_REQUEST,
+                    format!("invalid range {from}..{until}"),
+                ));
+            }
+            if until - from > MAX_DEPOSIT_RANGE {
+                return Err(Error::catch_all(
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "requested range {from}..{until} exceeds the maximum of \
+                         {MAX_DEPOSIT_RANGE} blocks"
+                    ),
+                ));
+            }
+
+            state
+                .get_deposits_in_range(from, until)
+                .await
+                .map_err(|err| {
+                    Error::catch_all(StatusCode::INTERNAL_SERVER_ERROR, format!("{err:#}"))
+                })
+        }
+        .boxed()
+    })?;
+
+    Ok(api)
+}
+
+pub(super) fn fee_history<S, ApiVer: StaticVersionType + 'static>(
+    _: ApiVer,
+) -> Result<Api<S, Error, ApiVer>>
+where
+    S: 'static + Send + Sync + ReadState,
+    S::State: Send + Sync + FeeHistoryDataSource,
+{
+    let toml = toml::from_str::<toml::Value>(include_str!("../../api/fee_history.toml"))?;
+    let mut api = Api::<S, Error, ApiVer>::new(toml)?;
+
+    api.get("balance", |req, state| {
+        async move {
+            let account = req
+                .string_param("address")
+                .map_err(Error::from_request_error)?;
+            let account: FeeAccount = account.parse().map_err(|err| {
+                Error::catch_all(
+                    StatusCode::BAD_REQUEST,
+                    format!("malformed account {account}: {err}"),
+                )
+            })?;
+            let from = req
+                .integer_param("from")
+                .map_err(Error::from_request_error)?;
+            let until = req
+                .integer_param("until")
+                .map_err(Error::from_request_error)?;
+
+            state
+                .get_balance_history(account, from, until)
+                .await
+                .map_err(|err| Error::catch_all(StatusCode::NOT_FOUND, format!("{err:#}")))
+        }
+        .boxed()
+    })?;
+
+    Ok(api)
+}
+
 type MerklizedStateApi<N, P, D, V, ApiVer> =
     Api<AvailState<N, P, D, V>, merklized_state::Error, ApiVer>;
 pub(super) fn merklized_state<N, P, D, S, V: Versions, const ARITY: usize>(
@@ -320,6 +477,9 @@ where
     api.get("hotshot", |_, state| {
         async move { Ok(state.get_config().await) }.boxed()
     })?
+    .get("upgrades", |_, state| {
+        async move { Ok(state.get_upgrade_status().await) }.boxed()
+    })?
     .get("env", move |_, _| {
         {
             let env_variables = env_variables.clone();
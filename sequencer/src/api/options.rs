@@ -5,6 +5,7 @@ use async_std::sync::{Arc, RwLock};
 use clap::Parser;
 use espresso_types::{
     v0::traits::{EventConsumer, NullEventConsumer, SequencerPersistence},
+    v0_3::TransferNonceMerkleTree,
     BlockMerkleTree, FeeMerkleTree, PubKey,
 };
 use futures::{
@@ -358,6 +359,14 @@ impl Options {
                 "fee-state",
                 endpoints::merklized_state::<N, P, _, FeeMerkleTree, _, 256>()?,
             )?;
+            // Initialize merklized state module for transfer nonce merkle tree
+            app.register_module(
+                "transfer-nonce-state",
+                endpoints::merklized_state::<N, P, _, TransferNonceMerkleTree, _, 256>()?,
+            )?;
+            // Deposits are indexed as a side effect of updating the merklized state.
+            app.register_module("deposits", endpoints::deposits(bind_version)?)?;
+            app.register_module("fee-history", endpoints::fee_history(bind_version)?)?;
 
             let state = state.clone();
             let get_node_state = async move { state.node_state().await.clone() };
@@ -1,7 +1,16 @@
-use anyhow::{bail, Context};
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, ensure, Context};
+use async_std::stream::StreamExt;
 use async_trait::async_trait;
 use committable::Commitment;
-use espresso_types::{v0_3::ChainConfig, BlockMerkleTree, FeeAccountProof, FeeMerkleTree};
+use espresso_types::{
+    v0_3::{
+        ChainConfig, FullNetworkTx, TransferNonceMerkleTree, TRANSFER_NONCE_MERKLE_TREE_HEIGHT,
+    },
+    BalanceChange, BalanceHistoryEntry, BlockMerkleTree, DepositQueryData, FeeAccount,
+    FeeAccountProof, FeeAmount, FeeInfo, FeeMerkleTree, Header, L1Deposit,
+};
 use ethers::prelude::Address;
 use hotshot_query_service::{
     data_source::{
@@ -12,17 +21,23 @@ use hotshot_query_service::{
     merklized_state::{MerklizedStateDataSource, Snapshot},
     Resolvable,
 };
-use hotshot_types::data::ViewNumber;
-use jf_merkle_tree::{prelude::MerkleNode, MerkleTreeScheme};
+use hotshot_types::{data::ViewNumber, traits::node_implementation::ConsensusTime};
+use jf_merkle_tree::{
+    prelude::MerkleNode, ForgetableMerkleTreeScheme, ForgetableUniversalMerkleTreeScheme,
+    MerkleCommitment, MerkleTreeScheme,
+};
 
 use super::{
-    data_source::{CatchupDataSource, Provider, SequencerDataSource},
+    data_source::{
+        CatchupDataSource, DepositDataSource, FeeHistoryDataSource, Provider, SequencerDataSource,
+        MAX_BALANCE_HISTORY_RANGE, MAX_DEPOSIT_RANGE,
+    },
     AccountQueryData, BlocksFrontier,
 };
 use crate::{
     persistence::{
         sql::{sql_param, Options},
-        ChainConfigPersistence,
+        ChainConfigPersistence, DepositPersistence,
     },
     SeqTypes,
 };
@@ -78,23 +93,117 @@ impl CatchupDataSource for SqlStorage {
             )
             .await
             .context(format!("fetching account {account}; height {height}"))?;
+        account_query_data(account.into(), proof)
+            .context(format!("account {account}; height {height}"))
+    }
 
-        match proof.proof.first().context(format!(
-            "empty proof for account {account}; height {height}"
-        ))? {
-            MerkleNode::Leaf { pos, elem, .. } => Ok(AccountQueryData {
-                balance: (*elem).into(),
-                proof: FeeAccountProof::presence(*pos, proof),
-            }),
-
-            MerkleNode::Empty => Ok(AccountQueryData {
-                balance: 0_u64.into(),
-                proof: FeeAccountProof::absence(account.into(), proof),
-            }),
-            _ => {
-                bail!("Invalid proof");
+    async fn get_accounts(
+        &self,
+        height: u64,
+        _view: ViewNumber,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<FeeMerkleTree> {
+        let mut tx = self.read().await.context(format!(
+            "opening transaction to fetch accounts; height {height}"
+        ))?;
+
+        // Get the root of the fee state at this height, so we can build a snapshot to remember the
+        // requested paths in.
+        let row = tx
+            .query_one(
+                "SELECT data FROM header WHERE height = $1",
+                [&(height as i64)],
+            )
+            .await
+            .context(format!("fetching header; height {height}"))?;
+        let header: Header = serde_json::from_value(row.try_get("data")?)
+            .context(format!("malformed header; height {height}"))?;
+        let mut snapshot = FeeMerkleTree::from_commitment(header.fee_merkle_tree_root());
+
+        for account in accounts {
+            let proof = tx
+                .get_path(
+                    Snapshot::<SeqTypes, FeeMerkleTree, { FeeMerkleTree::ARITY }>::Index(height),
+                    *account,
+                )
+                .await
+                .context(format!("fetching account {account}; height {height}"))?;
+            match proof.proof.first().context(format!(
+                "empty proof for account {account}; height {height}"
+            ))? {
+                MerkleNode::Leaf { pos, elem, .. } => snapshot
+                    .remember(*pos, *elem, proof.clone())
+                    .context(format!("invalid proof for account {account}"))?,
+                MerkleNode::Empty => snapshot
+                    .non_membership_remember(*account, proof.clone())
+                    .context(format!("invalid proof for account {account}"))?,
+                _ => {
+                    bail!("Invalid proof");
+                }
             }
         }
+
+        Ok(snapshot)
+    }
+
+    async fn get_transfer_nonces(
+        &self,
+        height: u64,
+        _view: ViewNumber,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<TransferNonceMerkleTree> {
+        let mut tx = self.read().await.context(format!(
+            "opening transaction to fetch transfer nonces; height {height}"
+        ))?;
+
+        let row = tx
+            .query_one(
+                "SELECT data FROM header WHERE height = $1",
+                [&(height as i64)],
+            )
+            .await
+            .context(format!("fetching header; height {height}"))?;
+        let header: Header = serde_json::from_value(row.try_get("data")?)
+            .context(format!("malformed header; height {height}"))?;
+        let root = header
+            .transfer_nonce_merkle_tree_root()
+            .context(format!("header has no transfer nonces; height {height}"))?;
+        if root.size() == 0 {
+            // No transfer has been executed yet, so there is nothing stored to look up.
+            return Ok(TransferNonceMerkleTree::new(
+                TRANSFER_NONCE_MERKLE_TREE_HEIGHT,
+            ));
+        }
+        let mut snapshot = TransferNonceMerkleTree::from_commitment(root);
+
+        for account in accounts {
+            let snapshot_index = Snapshot::<
+                SeqTypes,
+                TransferNonceMerkleTree,
+                { TransferNonceMerkleTree::ARITY },
+            >::Index(height);
+            let proof = tx
+                .get_path(snapshot_index, *account)
+                .await
+                .context(format!(
+                    "fetching transfer nonce {account}; height {height}"
+                ))?;
+            match proof.proof.first().context(format!(
+                "empty proof for transfer nonce {account}; height {height}"
+            ))? {
+                MerkleNode::Leaf { pos, elem, .. } => snapshot
+                    .remember(*pos, *elem, proof.clone())
+                    .context(format!("invalid proof for transfer nonce {account}"))?,
+                MerkleNode::Empty => snapshot
+                    .non_membership_remember(*account, proof.clone())
+                    .context(format!("invalid proof for transfer nonce {account}"))?,
+                _ => {
+                    bail!("Invalid proof");
+                }
+            }
+        }
+
+        Ok(snapshot)
     }
 
     async fn get_frontier(&self, height: u64, _view: ViewNumber) -> anyhow::Result<BlocksFrontier> {
@@ -133,6 +242,372 @@ impl CatchupDataSource for SqlStorage {
     }
 }
 
+impl DepositDataSource for SqlStorage {
+    async fn get_deposits_for_account(
+        &self,
+        account: FeeAccount,
+    ) -> anyhow::Result<Vec<DepositQueryData>> {
+        self.read()
+            .await
+            .context(format!(
+                "opening transaction to fetch deposits for account {account}"
+            ))?
+            .query(
+                "SELECT height, data FROM fee_deposit WHERE account = $1
+                    ORDER BY height, l1_block, log_index",
+                [&account.to_string()],
+            )
+            .await?
+            .map(|row| {
+                let row = row?;
+                let height: i64 = row.try_get("height")?;
+                let deposit = serde_json::from_value(row.try_get("data")?)?;
+                Ok(DepositQueryData {
+                    height: height as u64,
+                    deposit,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await
+            .context(format!("fetching deposits for account {account}"))
+    }
+
+    async fn get_deposits_in_range(
+        &self,
+        from: u64,
+        until: u64,
+    ) -> anyhow::Result<Vec<DepositQueryData>> {
+        ensure!(
+            until.saturating_sub(from) <= MAX_DEPOSIT_RANGE,
+            "requested range {from}..{until} exceeds the maximum of {MAX_DEPOSIT_RANGE} blocks"
+        );
+        self.read()
+            .await
+            .context(format!(
+                "opening transaction to fetch deposits in range {from}..{until}"
+            ))?
+            .query(
+                "SELECT height, data FROM fee_deposit WHERE height >= $1 AND height < $2
+                    ORDER BY height, l1_block, log_index",
+                [&(from as i64), &(until as i64)],
+            )
+            .await?
+            .map(|row| {
+                let row = row?;
+                let height: i64 = row.try_get("height")?;
+                let deposit = serde_json::from_value(row.try_get("data")?)?;
+                Ok(DepositQueryData {
+                    height: height as u64,
+                    deposit,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await
+            .context(format!("fetching deposits in range {from}..{until}"))
+    }
+}
+
+impl FeeHistoryDataSource for SqlStorage {
+    async fn get_balance_history(
+        &self,
+        account: FeeAccount,
+        from: u64,
+        until: u64,
+    ) -> anyhow::Result<Vec<BalanceHistoryEntry>> {
+        ensure!(
+            until.saturating_sub(from) <= MAX_BALANCE_HISTORY_RANGE,
+            "requested range {from}..{until} exceeds the maximum of {MAX_BALANCE_HISTORY_RANGE} \
+             blocks"
+        );
+        let index = serde_json::to_value(account)?;
+        let (from_param, until_param) = (from as i64, until as i64);
+
+        let mut tx = self.read().await.context(format!(
+            "opening transaction to fetch balance history for account {account}"
+        ))?;
+
+        // The leaf for this account in the fee state is rewritten at every height where the account
+        // is touched. An empty entry means the account was removed from the tree, which happens
+        // when its balance drops to 0.
+        let mut prev = match tx
+            .query_opt(
+                "SELECT entry FROM fee_merkle_tree WHERE index = $1 AND created < $2
+                    ORDER BY created DESC LIMIT 1",
+                [sql_param(&index), sql_param(&from_param)],
+            )
+            .await?
+        {
+            Some(row) => parse_balance(row.try_get("entry")?)?,
+            None => FeeAmount::default(),
+        };
+
+        // Fetch every update to the account in the range along with the header of the block that
+        // made it, in a single query.
+        let updates = tx
+            .query(
+                "SELECT f.created, f.entry, h.data FROM fee_merkle_tree AS f
+                    JOIN header AS h ON h.height = f.created
+                    WHERE f.index = $1 AND f.created >= $2 AND f.created < $3
+                    ORDER BY f.created",
+                [
+                    sql_param(&index),
+                    sql_param(&from_param),
+                    sql_param(&until_param),
+                ],
+            )
+            .await?
+            .map(|row| {
+                let row = row?;
+                let height = row.try_get::<i64, _>("created")? as u64;
+                let balance = parse_balance(row.try_get("entry")?)?;
+                let header: Header = serde_json::from_value(row.try_get("data")?)
+                    .context(format!("malformed header; height {height}"))?;
+                Ok((height, balance, header))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await
+            .context(format!("fetching fee state updates for account {account}"))?;
+
+        // Collect the deposits to this account in the range, so we can attribute balance changes to
+        // them.
+        let mut deposits = BTreeMap::<u64, Vec<L1Deposit>>::new();
+        let rows = tx
+            .query(
+                "SELECT height, data FROM fee_deposit
+                    WHERE account = $1 AND height >= $2 AND height < $3
+                    ORDER BY height, l1_block, log_index",
+                [
+                    sql_param(&account.to_string()),
+                    sql_param(&from_param),
+                    sql_param(&until_param),
+                ],
+            )
+            .await?
+            .map(|row| {
+                let row = row?;
+                let height: i64 = row.try_get("height")?;
+                let deposit = serde_json::from_value(row.try_get("data")?)?;
+                Ok((height as u64, deposit))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await
+            .context(format!("fetching deposits for account {account}"))?;
+        for (height, deposit) in rows {
+            deposits.entry(height).or_default().push(deposit);
+        }
+
+        // The chain config rarely changes, so only look up each one once.
+        let mut chain_configs = HashMap::<Commitment<ChainConfig>, ChainConfig>::new();
+
+        let mut history = vec![];
+        for (height, balance, header) in updates {
+            // Touching an account does not necessarily change its balance, e.g. a builder paying
+            // a fee of 0.
+            if balance == prev {
+                continue;
+            }
+            prev = balance;
+
+            let chain_config = match header.chain_config().resolve() {
+                Some(chain_config) => chain_config,
+                None => {
+                    let commitment = header.chain_config().commit();
+                    match chain_configs.get(&commitment) {
+                        Some(chain_config) => *chain_config,
+                        None => {
+                            let row = tx
+                                .query_one(
+                                    "SELECT data FROM chain_config WHERE commitment = $1",
+                                    [&commitment.to_string()],
+                                )
+                                .await
+                                .context(format!("fetching chain config {commitment}"))?;
+                            let data: Vec<u8> = row.try_get("data")?;
+                            let chain_config: ChainConfig = bincode::deserialize(&data)
+                                .context(format!("malformed chain config {commitment}"))?;
+                            chain_configs.insert(commitment, chain_config);
+                            chain_config
+                        }
+                    }
+                }
+            };
+
+            let mut changes = balance_changes(
+                account,
+                &chain_config,
+                &header.fee_info(),
+                &header.full_network_txs(),
+                deposits.remove(&height).unwrap_or_default(),
+            );
+            if height == 0 {
+                changes.push(BalanceChange::Genesis(balance));
+            }
+            if changes.is_empty() {
+                tracing::warn!(
+                    %account,
+                    height,
+                    "unable to attribute change in balance to any event in the block"
+                );
+            }
+
+            let proof = tx
+                .get_path(
+                    Snapshot::<SeqTypes, FeeMerkleTree, { FeeMerkleTree::ARITY }>::Index(height),
+                    account,
+                )
+                .await
+                .context(format!("fetching account {account}; height {height}"))?;
+            history.push(BalanceHistoryEntry {
+                height,
+                account: account_query_data(account, proof)
+                    .context(format!("account {account}; height {height}"))?,
+                changes,
+            });
+        }
+        Ok(history)
+    }
+}
+
+/// The balance of `account` along with a proof, from its path in the fee state.
+fn account_query_data(
+    account: FeeAccount,
+    proof: <FeeMerkleTree as MerkleTreeScheme>::MembershipProof,
+) -> anyhow::Result<AccountQueryData> {
+    match proof.proof.first().context("empty proof")? {
+        MerkleNode::Leaf { pos, elem, .. } => Ok(AccountQueryData {
+            balance: (*elem).into(),
+            proof: FeeAccountProof::presence(*pos, proof),
+        }),
+
+        MerkleNode::Empty => Ok(AccountQueryData {
+            balance: 0_u64.into(),
+            proof: FeeAccountProof::absence(account, proof),
+        }),
+        _ => {
+            bail!("Invalid proof");
+        }
+    }
+}
+
+/// The events in a block which changed the balance of `account`.
+///
+/// `fee_info` are the builder fees charged by the block, and `full_network_txs` the transactions
+/// it executed afterwards. A charge of 0 does not change any balance, so it is left out.
+fn balance_changes(
+    account: FeeAccount,
+    chain_config: &ChainConfig,
+    fee_info: &[FeeInfo],
+    full_network_txs: &[FullNetworkTx],
+    deposits: Vec<L1Deposit>,
+) -> Vec<BalanceChange> {
+    let mut changes = deposits
+        .into_iter()
+        .map(BalanceChange::Deposit)
+        .collect::<Vec<_>>();
+    let mut charge = |fee: FeeInfo,
+                      recipient: FeeAccount,
+                      paid: fn(FeeInfo) -> BalanceChange,
+                      received: fn(FeeInfo) -> BalanceChange| {
+        if fee.amount() == FeeAmount::default() {
+            return;
+        }
+        if fee.account() == account {
+            changes.push(paid(fee));
+        }
+        if recipient == account {
+            changes.push(received(fee));
+        }
+    };
+
+    for fee in fee_info {
+        charge(
+            *fee,
+            chain_config.fee_recipient,
+            BalanceChange::FeePaid,
+            BalanceChange::FeeReceived,
+        );
+    }
+    for tx in full_network_txs {
+        match tx {
+            FullNetworkTx::Bid(bid) => {
+                // A bid cannot execute without a bid recipient.
+                if let Some(bid_recipient) = chain_config.bid_recipient {
+                    charge(
+                        FeeInfo::new(
+                            bid.account(),
+                            FeeAmount(bid.amount().0.saturating_add(bid.gas_price().0)),
+                        ),
+                        bid_recipient,
+                        BalanceChange::BidPaid,
+                        BalanceChange::BidReceived,
+                    );
+                }
+            }
+            FullNetworkTx::Transfer(transfer) => {
+                charge(
+                    FeeInfo::new(transfer.from(), transfer.fee()),
+                    chain_config.fee_recipient,
+                    BalanceChange::FeePaid,
+                    BalanceChange::FeeReceived,
+                );
+                if transfer.amount() == FeeAmount::default() {
+                    continue;
+                }
+                if transfer.from() == account {
+                    changes.push(BalanceChange::TransferSent {
+                        to: transfer.to(),
+                        amount: transfer.amount(),
+                    });
+                }
+                if transfer.to() == account {
+                    changes.push(BalanceChange::TransferReceived {
+                        from: transfer.from(),
+                        amount: transfer.amount(),
+                    });
+                }
+            }
+        }
+    }
+    changes
+}
+
+fn parse_balance(entry: Option<serde_json::Value>) -> anyhow::Result<FeeAmount> {
+    match entry {
+        Some(entry) => Ok(serde_json::from_value(entry).context("malformed fee state entry")?),
+        None => Ok(FeeAmount::default()),
+    }
+}
+
+impl FeeHistoryDataSource for DataSource {
+    async fn get_balance_history(
+        &self,
+        account: FeeAccount,
+        from: u64,
+        until: u64,
+    ) -> anyhow::Result<Vec<BalanceHistoryEntry>> {
+        self.as_ref()
+            .get_balance_history(account, from, until)
+            .await
+    }
+}
+
+impl DepositDataSource for DataSource {
+    async fn get_deposits_for_account(
+        &self,
+        account: FeeAccount,
+    ) -> anyhow::Result<Vec<DepositQueryData>> {
+        self.as_ref().get_deposits_for_account(account).await
+    }
+
+    async fn get_deposits_in_range(
+        &self,
+        from: u64,
+        until: u64,
+    ) -> anyhow::Result<Vec<DepositQueryData>> {
+        self.as_ref().get_deposits_in_range(from, until).await
+    }
+}
+
 impl CatchupDataSource for DataSource {
     async fn get_account(
         &self,
@@ -143,6 +618,26 @@ impl CatchupDataSource for DataSource {
         self.as_ref().get_account(height, view, account).await
     }
 
+    async fn get_accounts(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<FeeMerkleTree> {
+        self.as_ref().get_accounts(height, view, accounts).await
+    }
+
+    async fn get_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<TransferNonceMerkleTree> {
+        self.as_ref()
+            .get_transfer_nonces(height, view, accounts)
+            .await
+    }
+
     async fn get_frontier(&self, height: u64, view: ViewNumber) -> anyhow::Result<BlocksFrontier> {
         self.as_ref().get_frontier(height, view).await
     }
@@ -164,6 +659,55 @@ impl<'a> ChainConfigPersistence for Transaction<'a> {
     }
 }
 
+#[async_trait]
+impl<'a> DepositPersistence for Transaction<'a> {
+    async fn insert_deposits(&mut self, height: u64, deposits: &[L1Deposit]) -> anyhow::Result<()> {
+        if deposits.is_empty() {
+            return Ok(());
+        }
+        let height = height as i64;
+        let values = deposits
+            .iter()
+            .map(|deposit| {
+                Ok((
+                    deposit.l1_block as i64,
+                    deposit.log_index as i64,
+                    format!("{:#x}", deposit.l1_transaction),
+                    deposit.account.to_string(),
+                    serde_json::to_value(deposit)?,
+                ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let rows = values
+            .iter()
+            .map(|(l1_block, log_index, tx, account, data)| {
+                [
+                    sql_param(&height),
+                    sql_param(l1_block),
+                    sql_param(log_index),
+                    sql_param(tx),
+                    sql_param(account),
+                    sql_param(data),
+                ]
+            });
+        self.upsert(
+            "fee_deposit",
+            [
+                "height",
+                "l1_block",
+                "log_index",
+                "l1_transaction",
+                "account",
+                "data",
+            ],
+            ["l1_block", "log_index"],
+            rows,
+        )
+        .await
+        .map_err(Into::into)
+    }
+}
+
 #[cfg(any(test, feature = "testing"))]
 mod impl_testable_data_source {
 
@@ -209,3 +753,180 @@ mod generic_tests {
 
     instantiate_api_tests!(DataSource);
 }
+
+#[cfg(test)]
+mod test {
+    use espresso_types::{
+        eth_signature_key::EthKeyPair,
+        v0_3::{BidTxBody, TransferTxBody},
+    };
+    use ethers::types::H256;
+    use hotshot_query_service::data_source::Transaction as _;
+    use sequencer_utils::test_utils::setup_test;
+
+    use super::*;
+    use crate::api::data_source::testing::TestableSequencerDataSource;
+
+    #[async_std::test]
+    async fn test_deposit_index() {
+        setup_test();
+
+        let storage = DataSource::create_storage().await;
+        let ds = DataSource::create(
+            DataSource::persistence_options(&storage),
+            Default::default(),
+            false,
+        )
+        .await
+        .unwrap();
+
+        let alice = FeeAccount::from(Address::random());
+        let bob = FeeAccount::from(Address::random());
+        let deposit = |account, l1_block, log_index| L1Deposit {
+            account,
+            amount: 100u64.into(),
+            l1_block,
+            l1_transaction: H256::random(),
+            log_index,
+        };
+        let deposits = [
+            (1, deposit(alice, 10, 0)),
+            (1, deposit(bob, 10, 1)),
+            (3, deposit(alice, 12, 0)),
+        ];
+
+        let mut tx = ds.write().await.unwrap();
+        tx.insert_deposits(1, &[deposits[0].1, deposits[1].1])
+            .await
+            .unwrap();
+        tx.insert_deposits(3, &[deposits[2].1]).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let expected = |indices: &[usize]| {
+            indices
+                .iter()
+                .map(|&i| DepositQueryData {
+                    height: deposits[i].0,
+                    deposit: deposits[i].1,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ds.get_deposits_for_account(alice).await.unwrap(),
+            expected(&[0, 2])
+        );
+        assert_eq!(
+            ds.get_deposits_for_account(bob).await.unwrap(),
+            expected(&[1])
+        );
+        assert_eq!(
+            ds.get_deposits_in_range(0, 10).await.unwrap(),
+            expected(&[0, 1, 2])
+        );
+        assert_eq!(
+            ds.get_deposits_in_range(2, 4).await.unwrap(),
+            expected(&[2])
+        );
+        assert_eq!(
+            ds.get_deposits_in_range(4, 10).await.unwrap(),
+            expected(&[])
+        );
+        ds.get_deposits_in_range(0, MAX_DEPOSIT_RANGE + 1)
+            .await
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_balance_changes() {
+        let alice = FeeAccount::from(Address::random());
+        let bob = FeeAccount::from(Address::random());
+        let fee_recipient = FeeAccount::from(Address::random());
+        let bid_recipient = FeeAccount::from(Address::random());
+        let chain_config = ChainConfig {
+            fee_recipient,
+            bid_recipient: Some(bid_recipient),
+            ..Default::default()
+        };
+
+        // A deposit.
+        let deposit = L1Deposit {
+            account: alice,
+            amount: 100u64.into(),
+            l1_block: 10,
+            l1_transaction: H256::random(),
+            log_index: 0,
+        };
+        assert_eq!(
+            balance_changes(alice, &chain_config, &[], &[], vec![deposit]),
+            [BalanceChange::Deposit(deposit)]
+        );
+
+        // A builder fee is paid by the builder and received by the fee recipient. A fee of 0 does
+        // not change any balance.
+        let fee = FeeInfo::new(alice, 10u64);
+        let fees = [fee, FeeInfo::new(bob, 0u64)];
+        assert_eq!(
+            balance_changes(alice, &chain_config, &fees, &[], vec![]),
+            [BalanceChange::FeePaid(fee)]
+        );
+        assert_eq!(
+            balance_changes(fee_recipient, &chain_config, &fees, &[], vec![]),
+            [BalanceChange::FeeReceived(fee)]
+        );
+        assert!(balance_changes(bob, &chain_config, &fees, &[], vec![]).is_empty());
+
+        // A winning bid, including gas, is paid by the bidder and received by the bid recipient.
+        let key = EthKeyPair::random();
+        let bidder = key.fee_account();
+        let bid = BidTxBody::new(
+            bidder,
+            30.into(),
+            ViewNumber::genesis(),
+            vec![],
+            "https://sequencer:3939".parse().unwrap(),
+            5.into(),
+        )
+        .signed(&key)
+        .unwrap();
+        let txs = [FullNetworkTx::Bid(bid)];
+        assert_eq!(
+            balance_changes(bidder, &chain_config, &[], &txs, vec![]),
+            [BalanceChange::BidPaid(FeeInfo::new(bidder, 35u64))]
+        );
+        assert_eq!(
+            balance_changes(bid_recipient, &chain_config, &[], &txs, vec![]),
+            [BalanceChange::BidReceived(FeeInfo::new(bidder, 35u64))]
+        );
+        assert!(balance_changes(fee_recipient, &chain_config, &[], &txs, vec![]).is_empty());
+
+        // A transfer moves funds between the accounts, and pays a fee to the fee recipient.
+        let key = EthKeyPair::random();
+        let sender = key.fee_account();
+        let transfer =
+            TransferTxBody::new(sender, alice, 50.into(), 1.into(), 0, chain_config.chain_id)
+                .signed(&key)
+                .unwrap();
+        let txs = [FullNetworkTx::Transfer(transfer)];
+        assert_eq!(
+            balance_changes(sender, &chain_config, &[], &txs, vec![]),
+            [
+                BalanceChange::FeePaid(FeeInfo::new(sender, 1u64)),
+                BalanceChange::TransferSent {
+                    to: alice,
+                    amount: 50.into()
+                }
+            ]
+        );
+        assert_eq!(
+            balance_changes(alice, &chain_config, &[], &txs, vec![]),
+            [BalanceChange::TransferReceived {
+                from: sender,
+                amount: 50.into()
+            }]
+        );
+        assert_eq!(
+            balance_changes(fee_recipient, &chain_config, &[], &txs, vec![]),
+            [BalanceChange::FeeReceived(FeeInfo::new(sender, 1u64))]
+        );
+    }
+}
@@ -1,12 +1,17 @@
-use std::{io, time::Duration};
+use std::{borrow::Cow, io, path::PathBuf, time::Duration};
 
-use async_std::task::spawn;
+use async_std::{
+    sync::{Arc, RwLock},
+    task::spawn,
+};
 use clap::Parser;
 use espresso_types::parse_duration;
 use ethers::prelude::*;
 use futures::FutureExt;
 use sequencer::{
-    hotshot_commitment::{run_hotshot_commitment_task, CommitmentTaskOptions},
+    hotshot_commitment::{
+        run_hotshot_commitment_task, CommitmentTaskMonitor, CommitmentTaskOptions, StakeTableSource,
+    },
     SequencerApiVersion,
 };
 use sequencer_utils::logging;
@@ -47,7 +52,8 @@ pub struct Options {
 
     /// If provided, the service will run a basic HTTP server on the given port.
     ///
-    /// The server provides healthcheck and version endpoints.
+    /// The server provides healthcheck and version endpoints, as well as the status and metrics of
+    /// the commitment task.
     #[clap(short, long, env = "ESPRESSO_COMMITMENT_TASK_PORT")]
     pub port: Option<u16>,
 
@@ -59,6 +65,39 @@ pub struct Options {
     #[clap(long, name = "DELAY", value_parser = parse_duration, env = "ESPRESSO_COMMITMENT_TASK_DELAY")]
     pub delay: Option<Duration>,
 
+    /// Path to the genesis file of the Espresso chain.
+    ///
+    /// The genesis leaf is computed from this file, rather than trusted from the sequencer node.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_GENESIS_FILE")]
+    pub genesis_file: PathBuf,
+
+    /// Path to the public network config of the Espresso chain, as served by the `config/hotshot`
+    /// endpoint of a trusted node.
+    ///
+    /// Leaves are checked against the stake table in this file, rather than the stake table of the
+    /// sequencer node they are fetched from. Exactly one of this and `--stake-table-url` must be
+    /// given.
+    #[clap(
+        long,
+        env = "ESPRESSO_COMMITMENT_TASK_NETWORK_CONFIG_FILE",
+        required_unless_present = "stake_table_url",
+        conflicts_with = "stake_table_url"
+    )]
+    pub network_config_file: Option<PathBuf>,
+
+    /// URL of a trusted sequencer node to load the stake table from.
+    ///
+    /// This should be a different node than `--sequencer-url`, which leaves are fetched from.
+    #[clap(long, env = "ESPRESSO_COMMITMENT_TASK_STAKE_TABLE_URL")]
+    pub stake_table_url: Option<Url>,
+
+    /// Directory to save the progress of the task in.
+    ///
+    /// If given, a restarted task resumes from the last height it submitted. Otherwise it starts
+    /// from the block height of the HotShot contract.
+    #[clap(long, env = "ESPRESSO_COMMITMENT_TASK_STORAGE_PATH")]
+    pub storage_path: Option<PathBuf>,
+
     #[clap(flatten)]
     logging: logging::Config,
 }
@@ -67,8 +106,15 @@ async fn main() {
     let opt = Options::parse();
     opt.logging.init();
 
+    let monitor = Arc::new(RwLock::new(CommitmentTaskMonitor::default()));
     if let Some(port) = opt.port {
-        start_http_server(port, opt.hotshot_address, SequencerApiVersion::instance()).unwrap();
+        start_http_server(
+            port,
+            opt.hotshot_address,
+            monitor.clone(),
+            SequencerApiVersion::instance(),
+        )
+        .unwrap();
     }
 
     let hotshot_contract_options = CommitmentTaskOptions {
@@ -80,26 +126,42 @@ async fn main() {
         sequencer_account_index: opt.hotshot_account_index,
         request_timeout: opt.request_timeout,
         query_service_url: Some(opt.sequencer_url),
+        genesis_file: opt.genesis_file,
+        stake_table: match (opt.network_config_file, opt.stake_table_url) {
+            (Some(path), _) => StakeTableSource::File(path),
+            (None, Some(url)) => StakeTableSource::Url(url),
+            (None, None) => unreachable!("clap requires a stake table source"),
+        },
+        storage_path: opt.storage_path,
     };
     tracing::info!("Launching HotShot commitment task..");
-    run_hotshot_commitment_task::<SequencerApiVersion>(&hotshot_contract_options).await;
+    run_hotshot_commitment_task::<SequencerApiVersion>(&hotshot_contract_options, monitor).await;
 }
 
 fn start_http_server<ApiVer: StaticVersionType + 'static>(
     port: u16,
     hotshot_address: Address,
+    monitor: Arc<RwLock<CommitmentTaskMonitor>>,
     bind_version: ApiVer,
 ) -> io::Result<()> {
-    let mut app = tide_disco::App::<(), ServerError>::with_state(());
+    let mut app = tide_disco::App::<_, ServerError>::with_state(monitor);
     let toml = toml::from_str::<toml::value::Value>(include_str!("../../api/commitment_task.toml"))
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
-    let mut api = Api::<(), ServerError, ApiVer>::new(toml)
+    let mut api = Api::<_, ServerError, ApiVer>::new(toml)
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
     api.get("gethotshotcontract", move |_, _| {
         async move { Ok(hotshot_address) }.boxed()
     })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    .get("getstatus", |_, monitor| {
+        async move { Ok(monitor.status().clone()) }.boxed()
+    })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    .metrics("metrics", |_, monitor| {
+        async move { Ok(Cow::Borrowed(monitor.metrics())) }.boxed()
+    })
     .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
     app.register_module("api", api)
@@ -111,8 +173,12 @@ fn start_http_server<ApiVer: StaticVersionType + 'static>(
 
 #[cfg(test)]
 mod test {
+    use async_std::sync::{Arc, RwLock};
     use portpicker::pick_unused_port;
-    use sequencer::SequencerApiVersion;
+    use sequencer::{
+        hotshot_commitment::{CommitmentTaskMonitor, CommitmentTaskStatus},
+        SequencerApiVersion,
+    };
     use sequencer_utils::test_utils::setup_test;
     use surf_disco::Client;
     use vbs::version::StaticVersionType;
@@ -127,8 +193,13 @@ mod test {
         let expected_addr = "0xED15E1FE0789c524398137a066ceb2EF9884E5D8"
             .parse::<Address>()
             .unwrap();
-        start_http_server(port, expected_addr, SequencerApiVersion::instance())
-            .expect("Failed to start the server");
+        start_http_server(
+            port,
+            expected_addr,
+            Arc::new(RwLock::new(CommitmentTaskMonitor::default())),
+            SequencerApiVersion::instance(),
+        )
+        .expect("Failed to start the server");
 
         let client: Client<ServerError, SequencerApiVersion> =
             Client::new(format!("http://localhost:{port}").parse().unwrap());
@@ -137,5 +208,9 @@ mod test {
         let addr: Address = client.get("api/hotshot_contract").send().await.unwrap();
 
         assert_eq!(addr, expected_addr);
+
+        // Nothing has been submitted yet.
+        let status: CommitmentTaskStatus = client.get("api/status").send().await.unwrap();
+        assert_eq!(status, CommitmentTaskStatus::default());
     }
 }
@@ -3,14 +3,17 @@ use async_std::sync::Arc;
 use clap::{Parser, Subcommand};
 use client::SequencerClient;
 use contract_bindings::fee_contract::FeeContract;
-use espresso_types::{eth_signature_key::EthKeyPair, Header};
+use espresso_types::{
+    eth_signature_key::EthKeyPair, v0_3::TransferTxBody, FeeAmount, Header, MarketplaceVersion,
+};
 use ethers::{
     middleware::{Middleware, SignerMiddleware},
     providers::Provider,
     types::{Address, BlockId, U256},
 };
 use futures::stream::StreamExt;
-use sequencer_utils::logging;
+use marketplace_solver::{SolverError, SOLVER_API_PATH};
+use sequencer_utils::{logging, ser::FromStringOrInteger};
 use surf_disco::Url;
 
 /// Command-line utility for working with the Espresso bridge.
@@ -26,6 +29,7 @@ struct Options {
 #[derive(Debug, Subcommand)]
 enum Command {
     Deposit(Deposit),
+    Transfer(Transfer),
     Balance(Balance),
     L1Balance(L1Balance),
 }
@@ -67,6 +71,53 @@ struct Deposit {
     confirmations: usize,
 }
 
+/// Transfer ETH from one Espresso account to another.
+///
+/// This builds and signs a transfer from the account derived from MNEMONIC, and submits it to the
+/// marketplace solver, which passes it on to the leaders of upcoming views for sequencing.
+///
+/// To prevent replays, each transfer carries a nonce, which must equal the number of transfers
+/// previously executed from the sending account.
+#[derive(Debug, Parser)]
+struct Transfer {
+    /// Espresso query service provider.
+    ///
+    /// This must point to an Espresso node running the node and Merklized state APIs.
+    #[clap(short, long, env = "ESPRESSO_PROVIDER")]
+    espresso_provider: Url,
+
+    /// Marketplace solver to submit the transfer to.
+    #[clap(short, long, env = "SOLVER_URL")]
+    solver_url: Url,
+
+    /// Mnemonic to generate the account from which to transfer.
+    #[clap(short, long, env = "MNEMONIC")]
+    mnemonic: String,
+
+    /// Account index when deriving an account from MNEMONIC.
+    #[clap(short = 'i', long, env = "ACCOUNT_INDEX", default_value = "0")]
+    account_index: u32,
+
+    /// Account to receive the funds.
+    #[clap(short, long, env = "TO")]
+    to: Address,
+
+    /// Amount to transfer, in WEI unless a unit is given (e.g. "1 gwei").
+    #[clap(short, long, env = "AMOUNT", value_parser = parse_fee_amount)]
+    amount: FeeAmount,
+
+    /// Amount to pay the fee recipient for sequencing the transfer, in WEI unless a unit is given.
+    #[clap(short, long, env = "FEE", default_value = "0", value_parser = parse_fee_amount)]
+    fee: FeeAmount,
+
+    /// Nonce of the transfer (default: the current nonce of the sending account).
+    ///
+    /// This must be set explicitly to submit a transfer while earlier transfers from the same
+    /// account are still waiting to be sequenced.
+    #[clap(short, long, env = "NONCE")]
+    nonce: Option<u64>,
+}
+
 /// Check the balance (in ETH) of an Espresso account.
 #[derive(Debug, Parser)]
 struct Balance {
@@ -230,6 +281,53 @@ async fn deposit(opt: Deposit) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn transfer(opt: Transfer) -> anyhow::Result<()> {
+    // Derive the account to transfer from.
+    let key_pair = EthKeyPair::from_mnemonic(opt.mnemonic, opt.account_index)?;
+    let from = key_pair.fee_account();
+
+    let espresso = SequencerClient::new(opt.espresso_provider);
+    let balance = espresso.get_espresso_balance(from.address(), None).await?;
+    let (amount, fee) = (opt.amount, opt.fee);
+    let required = amount
+        .0
+        .checked_add(fee.0)
+        .context("transfer amount plus fee overflows")?;
+    ensure!(
+        balance.0 >= required,
+        "insufficient balance {balance} for transfer of {amount} plus fee {fee}"
+    );
+
+    let nonce = match opt.nonce {
+        Some(nonce) => nonce,
+        None => espresso.get_transfer_nonce(from.address(), None).await?,
+    };
+
+    let chain_id = espresso.get_chain_id().await?;
+
+    tracing::info!(%from, to = %opt.to, %amount, %fee, nonce, %balance, %chain_id, "signing transfer");
+    let tx = TransferTxBody::new(from, opt.to.into(), amount, fee, nonce, chain_id)
+        .signed(&key_pair)
+        .context("signing transfer")?;
+
+    let solver = surf_disco::Client::<SolverError, MarketplaceVersion>::new(
+        opt.solver_url.join(SOLVER_API_PATH)?,
+    );
+    solver
+        .post::<()>("submit_transfer")
+        .body_json(&tx)?
+        .send()
+        .await
+        .context("submitting transfer")?;
+    tracing::info!(nonce, "submitted transfer");
+
+    Ok(())
+}
+
+fn parse_fee_amount(s: &str) -> anyhow::Result<FeeAmount> {
+    FeeAmount::from_string(s.to_string())
+}
+
 async fn balance(opt: Balance) -> anyhow::Result<()> {
     // Derive the address to look up.
     let address = if let Some(address) = opt.address {
@@ -283,6 +381,7 @@ async fn main() -> anyhow::Result<()> {
 
     match opt.command {
         Command::Deposit(opt) => deposit(opt).await,
+        Command::Transfer(opt) => transfer(opt).await,
         Command::Balance(opt) => balance(opt).await,
         Command::L1Balance(opt) => l1_balance(opt).await,
     }
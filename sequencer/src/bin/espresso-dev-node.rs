@@ -13,9 +13,8 @@ use ethers::{
 };
 use futures::{future::BoxFuture, stream::FuturesUnordered, FutureExt, StreamExt};
 use hotshot_state_prover::service::{
-    one_honest_threshold, run_prover_service_with_stake_table, StateProverConfig,
+    run_prover_service_with_stake_table, ProverTarget, StateProverConfig,
 };
-use hotshot_types::traits::stake_table::{SnapshotVersion, StakeTableScheme};
 use portpicker::pick_unused_port;
 use sequencer::{
     api::{
@@ -23,7 +22,7 @@ use sequencer::{
         test_helpers::{TestNetwork, TestNetworkConfigBuilder, STAKE_TABLE_CAPACITY_FOR_TEST},
     },
     persistence,
-    state_signature::relay_server::run_relay_server,
+    state_signature::relay_server::{run_relay_server, LightClientContract, StakeTableSource},
     testing::TestConfigBuilder,
     SequencerApiVersion,
 };
@@ -208,7 +207,6 @@ async fn main() -> anyhow::Result<()> {
     let network =
         TestNetwork::new(config, SequencerVersions::<MarketplaceVersion, V0_1>::new()).await;
     let st = network.cfg.stake_table();
-    let total_stake = st.total_stake(SnapshotVersion::LastEpochStart).unwrap();
     let config = network.cfg.hotshot_config();
 
     tracing::info!("Hotshot config {config:?}");
@@ -217,7 +215,7 @@ async fn main() -> anyhow::Result<()> {
 
     let contracts = Contracts::new();
     let mut light_client_addresses = vec![];
-    let mut prover_ports = Vec::new();
+    let mut prover_targets = vec![];
     let mut mock_contracts = BTreeMap::new();
     let mut handles = FuturesUnordered::new();
     // deploy contract for L1 and each alt chain
@@ -304,34 +302,57 @@ async fn main() -> anyhow::Result<()> {
         );
         light_client_addresses.push((chain_id, light_client_address));
 
-        let prover_port = prover_port.unwrap_or_else(|| pick_unused_port().unwrap());
-
-        prover_ports.push(prover_port);
-
-        let prover_config = StateProverConfig {
-            relay_server: relay_server_url.clone(),
-            update_interval,
-            retry_interval,
-            sequencer_url: "http://localhost".parse().unwrap(),
-            port: Some(prover_port),
-            stake_table_capacity: STAKE_TABLE_CAPACITY_FOR_TEST as usize,
+        prover_targets.push(ProverTarget {
             provider: url.clone(),
             light_client_address,
             signing_key: wallet.signer().clone(),
-        };
-
-        let prover_handle = spawn(run_prover_service_with_stake_table(
-            prover_config,
-            SequencerApiVersion::instance(),
-            Arc::new(st.clone()),
-        ));
-        handles.push(prover_handle);
+            update_interval,
+            retry_interval,
+        });
     }
 
+    // The relay server keeps signatures until they have been used to update every chain.
+    let light_clients = prover_targets
+        .iter()
+        .map(|target| LightClientContract {
+            provider: target.provider.clone(),
+            address: target.light_client_address,
+        })
+        .collect();
+
+    // A single prover generates each proof once and submits it to every chain.
+    let prover_port = prover_port.unwrap_or_else(|| pick_unused_port().unwrap());
+    let l1_target = prover_targets.remove(0);
+    let prover_config = StateProverConfig {
+        relay_server: relay_server_url.clone(),
+        update_interval: l1_target.update_interval,
+        retry_interval: l1_target.retry_interval,
+        sequencer_url: "http://localhost".parse().unwrap(),
+        network_config_file: None,
+        port: Some(prover_port),
+        stake_table_capacity: STAKE_TABLE_CAPACITY_FOR_TEST as usize,
+        key_cache_dir: None,
+        submission: Default::default(),
+        provider: l1_target.provider,
+        light_client_address: l1_target.light_client_address,
+        signing_key: l1_target.signing_key,
+        alt_targets: prover_targets,
+    };
+    let prover_handle = spawn(run_prover_service_with_stake_table(
+        prover_config,
+        SequencerApiVersion::instance(),
+        Arc::new(st.clone()),
+    ));
+    handles.push(prover_handle);
+
+    let peers = config.known_nodes_with_stake.clone();
     let relay_server_handle = spawn(async move {
         let _ = run_relay_server(
             None,
-            one_honest_threshold(total_stake),
+            StakeTableSource::Static(peers),
+            STAKE_TABLE_CAPACITY_FOR_TEST as usize,
+            None,
+            light_clients,
             format!("http://0.0.0.0:{relay_server_port}")
                 .parse()
                 .unwrap(),
@@ -346,22 +367,18 @@ async fn main() -> anyhow::Result<()> {
     // we remove the first entry which is for L1 light client contract
     // so only alt chain light client addresses are left
     let (_, l1_lc) = light_client_addresses.remove(0);
-    let l1_prover_port = prover_ports.remove(0);
-    // we remove the first entry which is for primary L1 chain signing key
-    // so only alt signing keys are left
 
     let dev_info = DevInfo {
         builder_url: network.cfg.hotshot_config().builder_urls[0].clone(),
         sequencer_api_port,
-        l1_prover_port,
+        l1_prover_port: prover_port,
         l1_url,
         l1_light_client_address: l1_lc,
         alt_chains: alt_chain_providers
             .into_iter()
             .zip(light_client_addresses)
-            .zip(prover_ports)
             .map(
-                |((provider_url, (chain_id, light_client_address)), prover_port)| AltChainInfo {
+                |(provider_url, (chain_id, light_client_address))| AltChainInfo {
                     chain_id,
                     provider_url,
                     light_client_address,
@@ -1,8 +1,15 @@
+use std::{path::PathBuf, time::Duration};
+
 use clap::Parser;
-use ethers::types::U256;
-use hotshot_state_prover::service::one_honest_threshold;
-use sequencer::{state_signature::relay_server::run_relay_server, SequencerApiVersion};
+use espresso_types::parse_duration;
+use ethers::types::Address;
+use hotshot_stake_table::config::STAKE_TABLE_CAPACITY;
+use sequencer::{
+    state_signature::relay_server::{run_relay_server, LightClientContract, StakeTableSource},
+    SequencerApiVersion,
+};
 use sequencer_utils::logging;
+use url::Url;
 use vbs::version::StaticVersionType;
 
 #[derive(Parser)]
@@ -16,15 +23,86 @@ struct Args {
     )]
     port: u16,
 
-    /// Total amount of stake.
-    /// WARNING: this is a temporary flag, should remove after integrating with stake table.
-    /// Related issue: [https://github.com/EspressoSystems/espresso-sequencer/issues/1022]
+    /// URL of a sequencer node running the config API, to load the stake table from.
+    ///
+    /// Only signatures from state keys in this stake table are accepted, and a light client state
+    /// is ready once it has been signed by more than a third of the total stake.
+    #[clap(
+        long,
+        env = "ESPRESSO_SEQUENCER_URL",
+        required_unless_present = "network_config_file"
+    )]
+    sequencer_url: Option<Url>,
+
+    /// Public network config file to load the stake table from, instead of a sequencer node.
+    ///
+    /// The file is in the format served by the `config/hotshot` endpoint. It is reloaded
+    /// periodically, so that the relay server follows validator set changes made by updating it.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_RELAY_SERVER_NETWORK_CONFIG_FILE",
+        conflicts_with = "sequencer_url"
+    )]
+    network_config_file: Option<PathBuf>,
+
+    /// How often to reload the stake table.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_RELAY_SERVER_STAKE_TABLE_REFRESH_INTERVAL",
+        default_value = "1m",
+        value_parser = parse_duration
+    )]
+    stake_table_refresh_interval: Duration,
+
+    /// Capacity of the stake table.
+    ///
+    /// This must match the capacity used by the sequencer nodes and the prover.
     #[clap(
         long,
-        env = "ESPRESSO_STATE_SIGNATURE_TOTAL_STAKE",
-        default_value = "5"
+        env = "ESPRESSO_SEQUENCER_STAKE_TABLE_CAPACITY",
+        default_value_t = STAKE_TABLE_CAPACITY
     )]
-    total_stake: u64,
+    stake_table_capacity: usize,
+
+    /// Directory to store signature bundles in.
+    ///
+    /// If given, collected signatures and recent available bundles survive a restart of the relay
+    /// server. Otherwise they are kept only in memory.
+    #[clap(long, env = "ESPRESSO_STATE_RELAY_SERVER_STORAGE_PATH")]
+    storage_path: Option<PathBuf>,
+
+    /// URL of layer 1 Ethereum JSON-RPC provider.
+    ///
+    /// If given along with `--light-client-address`, signature bundles are kept until the light
+    /// client contract has been updated past them. Otherwise a fixed number of recent bundles is
+    /// kept.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_L1_PROVIDER")]
+    l1_provider: Option<Url>,
+
+    /// Address of LightClient contract on layer 1.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_LIGHTCLIENT_ADDRESS")]
+    light_client_address: Option<Address>,
+
+    /// URLs of JSON-RPC providers for alternate chains with light client contracts.
+    ///
+    /// Signature bundles are kept until the light client contracts on every chain have been
+    /// updated past them.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_PROVER_ALT_CHAIN_PROVIDERS",
+        num_args = 1..,
+        value_delimiter = ','
+    )]
+    alt_chain_providers: Vec<Url>,
+
+    /// Addresses of the LightClient contracts on the alternate chains, one per chain.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_PROVER_ALT_LIGHTCLIENT_ADDRESSES",
+        num_args = 1..,
+        value_delimiter = ','
+    )]
+    alt_light_client_addresses: Vec<Address>,
 
     #[clap(flatten)]
     logging: logging::Config,
@@ -35,15 +113,45 @@ async fn main() {
     let args = Args::parse();
     args.logging.init();
 
-    let threshold = one_honest_threshold(U256::from(args.total_stake));
+    tracing::info!(port = args.port, "starting state relay server");
+    if args.alt_chain_providers.len() != args.alt_light_client_addresses.len() {
+        tracing::error!(
+            "Expected a light client address for each of the {} alternate chains, got {}",
+            args.alt_chain_providers.len(),
+            args.alt_light_client_addresses.len()
+        );
+        std::process::exit(1);
+    }
+    let light_clients = args
+        .l1_provider
+        .into_iter()
+        .zip(args.light_client_address)
+        .chain(
+            args.alt_chain_providers
+                .into_iter()
+                .zip(args.alt_light_client_addresses),
+        )
+        .map(|(provider, address)| LightClientContract { provider, address })
+        .collect();
+
+    let stake_table = match (args.network_config_file, args.sequencer_url) {
+        (Some(path), _) => StakeTableSource::File {
+            path,
+            refresh_interval: args.stake_table_refresh_interval,
+        },
+        (None, Some(url)) => StakeTableSource::Sequencer {
+            url,
+            refresh_interval: args.stake_table_refresh_interval,
+        },
+        (None, None) => unreachable!("clap requires a stake table source"),
+    };
 
-    tracing::info!(
-        port = args.port,
-        "starting state relay server, quorum threshold: {threshold}"
-    );
     run_relay_server(
         None,
-        threshold,
+        stake_table,
+        args.stake_table_capacity,
+        args.storage_path,
+        light_clients,
         format!("http://0.0.0.0:{}", args.port).parse().unwrap(),
         SequencerApiVersion::instance(),
     )
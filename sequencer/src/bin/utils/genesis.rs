@@ -0,0 +1,290 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use committable::Committable;
+use espresso_types::{
+    v0_3::ChainConfig, BlockSize, ChainConfigUpgradeActivation, ChainId, FeeAccount, FeeAmount,
+    GenesisHeader, Timestamp,
+};
+use ethers::types::Address;
+use hotshot_stake_table::config::STAKE_TABLE_CAPACITY;
+use sequencer::genesis::{Genesis, L1Finalized, ScheduledChainConfig, StakeTableConfig};
+use sequencer_utils::ser::FromStringOrInteger;
+use serde_json::Value;
+use url::Url;
+use vbs::version::Version;
+
+/// Validate, inspect, compare and generate genesis files.
+#[derive(Clone, Debug, Subcommand)]
+pub enum Commands {
+    /// Check a genesis file for mistakes.
+    ///
+    /// This checks versions, upgrade windows and chain configs. If an L1 provider is given, it also
+    /// checks that each configured fee contract is a proxy contract on the L1.
+    Validate(Validate),
+    /// Print the chain config in effect at each point in the upgrade schedule of a genesis file.
+    Inspect(Inspect),
+    /// Compare two genesis files, including the chain config commitments they result in.
+    Diff(Diff),
+    /// Generate a new genesis file without upgrades.
+    New(New),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Validate {
+    /// Genesis file to validate.
+    path: PathBuf,
+
+    /// L1 provider to check fee contracts against.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_L1_PROVIDER")]
+    l1_provider: Option<Url>,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Inspect {
+    /// Genesis file to inspect.
+    path: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Diff {
+    /// The old genesis file.
+    old: PathBuf,
+    /// The new genesis file.
+    new: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct New {
+    /// Protocol version the chain starts at.
+    #[clap(long, default_value = "0.2", value_parser = parse_version)]
+    base_version: Version,
+
+    /// Protocol version the chain is prepared to upgrade to.
+    #[clap(long, default_value = "0.3", value_parser = parse_version)]
+    upgrade_version: Version,
+
+    /// Chain ID, in decimal or 0x-prefixed hex.
+    #[clap(long, value_parser = parse::<ChainId>)]
+    chain_id: ChainId,
+
+    /// Maximum block size, e.g. "1mb".
+    #[clap(long, default_value = "1mb", value_parser = parse::<BlockSize>)]
+    max_block_size: BlockSize,
+
+    /// Minimum fee per byte of block payload, e.g. "1 wei".
+    #[clap(long, default_value = "0 wei", value_parser = parse::<FeeAmount>)]
+    base_fee: FeeAmount,
+
+    /// Account which receives block fees.
+    #[clap(long, default_value = "0x0000000000000000000000000000000000000000")]
+    fee_recipient: FeeAccount,
+
+    /// Address of the fee contract on the L1.
+    #[clap(long)]
+    fee_contract: Option<Address>,
+
+    /// Account which receives auction bids.
+    #[clap(long)]
+    bid_recipient: Option<FeeAccount>,
+
+    /// Capacity of the stake table.
+    #[clap(long, default_value_t = STAKE_TABLE_CAPACITY as u64)]
+    stake_table_capacity: u64,
+
+    /// Number of the finalized L1 block the chain starts syncing from.
+    #[clap(long, default_value = "0")]
+    l1_finalized: u64,
+
+    /// Timestamp of the genesis block, in RFC 3339 format.
+    #[clap(long, default_value = "1970-01-01T00:00:00Z", value_parser = parse::<Timestamp>)]
+    timestamp: Timestamp,
+
+    /// Prefund an account at genesis, as ADDRESS=AMOUNT.
+    ///
+    /// May be given multiple times.
+    #[clap(long = "account", value_parser = parse_account)]
+    accounts: Vec<(FeeAccount, FeeAmount)>,
+
+    /// File to write the genesis to. If not given, the genesis is printed to stdout.
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn parse<T: FromStringOrInteger>(s: &str) -> anyhow::Result<T> {
+    T::from_string(s.to_string())
+}
+
+fn parse_version(s: &str) -> anyhow::Result<Version> {
+    let (major, minor) = s.split_once('.').context("version must be MAJOR.MINOR")?;
+    Ok(Version {
+        major: major.parse().context("invalid major version")?,
+        minor: minor.parse().context("invalid minor version")?,
+    })
+}
+
+fn parse_account(s: &str) -> anyhow::Result<(FeeAccount, FeeAmount)> {
+    let (account, amount) = s
+        .split_once('=')
+        .context("account must be ADDRESS=AMOUNT")?;
+    Ok((
+        account.parse().context("invalid account address")?,
+        parse(amount)?,
+    ))
+}
+
+pub async fn run(opt: Commands) -> anyhow::Result<()> {
+    match opt {
+        Commands::Validate(opt) => {
+            let genesis = Genesis::from_file(&opt.path)?;
+            genesis.validate()?;
+            if let Some(l1) = opt.l1_provider {
+                genesis.validate_fee_contract(l1.to_string()).await?;
+            } else {
+                tracing::warn!("no L1 provider given, not checking fee contracts");
+            }
+            println!("{} is valid", opt.path.display());
+            Ok(())
+        }
+        Commands::Inspect(opt) => {
+            let genesis = Genesis::from_file(&opt.path)?;
+            for scheduled in genesis.chain_config_schedule() {
+                println!("{}", describe(&scheduled));
+                println!("commitment = {}", scheduled.chain_config.commit());
+                println!("{}", toml::to_string_pretty(&scheduled.chain_config)?);
+            }
+            Ok(())
+        }
+        Commands::Diff(opt) => {
+            let old = Genesis::from_file(&opt.old)?;
+            let new = Genesis::from_file(&opt.new)?;
+
+            let mut changes = vec![];
+            diff_values(
+                "",
+                &serde_json::to_value(&old)?,
+                &serde_json::to_value(&new)?,
+                &mut changes,
+            );
+            if changes.is_empty() {
+                println!("no differences");
+                return Ok(());
+            }
+            for change in changes {
+                println!("{change}");
+            }
+
+            println!();
+            println!("chain config commitments:");
+            let old_schedule = old.chain_config_schedule();
+            let new_schedule = new.chain_config_schedule();
+            for i in 0..old_schedule.len().max(new_schedule.len()) {
+                match (old_schedule.get(i), new_schedule.get(i)) {
+                    (Some(old), Some(new)) => {
+                        let (old_commit, new_commit) =
+                            (old.chain_config.commit(), new.chain_config.commit());
+                        if (old.version, old.activation) != (new.version, new.activation) {
+                            println!("- {}: {old_commit}", describe(old));
+                            println!("+ {}: {new_commit}", describe(new));
+                        } else if old_commit != new_commit {
+                            println!("~ {}: {old_commit} -> {new_commit}", describe(old));
+                        } else {
+                            println!("  {}: {old_commit}", describe(old));
+                        }
+                    }
+                    (Some(old), None) => {
+                        println!("- {}: {}", describe(old), old.chain_config.commit())
+                    }
+                    (None, Some(new)) => {
+                        println!("+ {}: {}", describe(new), new.chain_config.commit())
+                    }
+                    (None, None) => unreachable!(),
+                }
+            }
+            Ok(())
+        }
+        Commands::New(opt) => {
+            let genesis = Genesis {
+                base_version: opt.base_version,
+                upgrade_version: opt.upgrade_version,
+                chain_config: ChainConfig {
+                    chain_id: opt.chain_id,
+                    max_block_size: opt.max_block_size,
+                    base_fee: opt.base_fee,
+                    fee_contract: opt.fee_contract,
+                    fee_recipient: opt.fee_recipient,
+                    bid_recipient: opt.bid_recipient,
+                },
+                stake_table: StakeTableConfig {
+                    capacity: opt.stake_table_capacity,
+                },
+                accounts: opt.accounts.into_iter().collect::<HashMap<_, _>>(),
+                l1_finalized: L1Finalized::Number {
+                    number: opt.l1_finalized,
+                },
+                header: GenesisHeader {
+                    timestamp: opt.timestamp,
+                },
+                upgrades: Default::default(),
+                chain_config_upgrades: Default::default(),
+            };
+            genesis.validate()?;
+
+            match opt.output {
+                Some(path) => {
+                    genesis.to_file(&path)?;
+                    tracing::info!("wrote genesis to {}", path.display());
+                }
+                None => print!("{}", toml::to_string_pretty(&genesis)?),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// A short description of when a chain config takes effect.
+fn describe(scheduled: &ScheduledChainConfig) -> String {
+    match scheduled.activation {
+        None => format!("version {}", scheduled.version),
+        Some(ChainConfigUpgradeActivation::View(view)) => {
+            format!("version {} from view {view}", scheduled.version)
+        }
+        Some(ChainConfigUpgradeActivation::Time(time)) => {
+            format!("version {} from {time}", scheduled.version)
+        }
+    }
+}
+
+/// Collect a line for each leaf value which differs between `old` and `new`.
+fn diff_values(path: &str, old: &Value, new: &Value, changes: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = format!("{path}.{key}");
+                match (old.get(key), new.get(key)) {
+                    (Some(old), Some(new)) => diff_values(&path, old, new, changes),
+                    (Some(old), None) => changes.push(format!("- {path} = {old}")),
+                    (None, Some(new)) => changes.push(format!("+ {path} = {new}")),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                let path = format!("{path}[{i}]");
+                match (old.get(i), new.get(i)) {
+                    (Some(old), Some(new)) => diff_values(&path, old, new, changes),
+                    (Some(old), None) => changes.push(format!("- {path} = {old}")),
+                    (None, Some(new)) => changes.push(format!("+ {path} = {new}")),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        _ if old != new => changes.push(format!("~ {path}: {old} -> {new}")),
+        _ => {}
+    }
+}
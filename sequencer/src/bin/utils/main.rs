@@ -3,9 +3,11 @@
 use clap::{Parser, Subcommand};
 
 use sequencer_utils::logging;
+mod genesis;
 mod keygen;
 mod pubkey;
 mod reset_storage;
+mod snapshot;
 
 #[derive(Debug, Parser)]
 struct Options {
@@ -18,10 +20,14 @@ struct Options {
 
 #[derive(Debug, Subcommand)]
 enum Command {
+    #[command(subcommand)]
+    Genesis(genesis::Commands),
     Keygen(keygen::Options),
     Pubkey(pubkey::Options),
     #[command(subcommand)]
     ResetStorage(reset_storage::Commands),
+    #[command(subcommand)]
+    Snapshot(snapshot::Commands),
 }
 
 #[async_std::main]
@@ -30,11 +36,13 @@ async fn main() -> anyhow::Result<()> {
     opt.logging.init();
 
     match opt.command {
+        Command::Genesis(opt) => genesis::run(opt).await,
         Command::Keygen(opt) => keygen::run(opt),
         Command::Pubkey(opt) => {
             pubkey::run(opt);
             Ok(())
         }
         Command::ResetStorage(opt) => reset_storage::run(opt).await,
+        Command::Snapshot(opt) => snapshot::run(opt).await,
     }
 }
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use espresso_types::traits::PersistenceOptions;
+use sequencer::{persistence, snapshot::StateSnapshot};
+
+use crate::reset_storage::SequencerStorage;
+
+/// Export or import state snapshots for bootstrapping nodes.
+///
+/// A snapshot contains a decided leaf, its QC, and the full state after that leaf. A node whose
+/// storage is seeded with a snapshot starts from the snapshot leaf instead of genesis, without
+/// catching up state from its peers. Do not run these programs while the sequencer is running on
+/// the same storage.
+#[derive(Clone, Debug, Subcommand)]
+pub enum Commands {
+    /// Export a snapshot from the SQL storage of a running node.
+    Export(Export),
+    /// Import a snapshot into the storage of a new node.
+    Import(Import),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Export {
+    /// Height of the decided leaf to snapshot.
+    #[clap(long)]
+    height: u64,
+
+    /// File to write the snapshot to.
+    #[clap(short, long)]
+    output: PathBuf,
+
+    #[clap(flatten)]
+    storage: Box<persistence::sql::Options>,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Import {
+    /// File to read the snapshot from.
+    #[clap(short, long)]
+    input: PathBuf,
+
+    /// Import the snapshot even if the storage already has a more recent decided leaf.
+    #[clap(long)]
+    force: bool,
+
+    #[command(subcommand)]
+    storage: SequencerStorage,
+}
+
+pub async fn run(opt: Commands) -> anyhow::Result<()> {
+    match opt {
+        Commands::Export(opt) => {
+            tracing::info!(height = opt.height, "exporting state snapshot");
+            let snapshot = StateSnapshot::export_sql(*opt.storage, opt.height).await?;
+            snapshot.to_file(&opt.output)?;
+            tracing::info!("wrote snapshot to {}", opt.output.display());
+            Ok(())
+        }
+        Commands::Import(opt) => {
+            let snapshot = StateSnapshot::from_file(&opt.input)?;
+            tracing::info!(
+                height = snapshot.height,
+                view = snapshot.view,
+                "importing state snapshot"
+            );
+            match opt.storage {
+                SequencerStorage::Fs(storage) => import(storage, &snapshot, opt.force).await,
+                SequencerStorage::Sql(storage) => snapshot.import_sql(*storage, opt.force).await,
+            }
+        }
+    }
+}
+
+async fn import(
+    opt: impl PersistenceOptions,
+    snapshot: &StateSnapshot,
+    force: bool,
+) -> anyhow::Result<()> {
+    let persistence = opt.create().await?;
+    snapshot.import(&persistence, force).await
+}
@@ -1,26 +1,37 @@
-use std::sync::Arc;
+use std::time::Instant;
 
-use anyhow::{bail, Context};
+use anyhow::{anyhow, bail, ensure, Context};
+use async_std::sync::{Arc, RwLock};
 use async_trait::async_trait;
 use committable::Commitment;
 use committable::Committable;
 use espresso_types::{
+    remember_transfer_nonces,
     v0::traits::{PersistenceOptions, StateCatchup},
-    v0_3::ChainConfig,
-    AccountQueryData, BackoffParams, BlockMerkleTree, FeeAccount, FeeMerkleCommitment,
+    v0_3::{ChainConfig, TransferNonceMerkleTree},
+    AccountQueryData, BackoffParams, BlockMerkleTree, FeeAccount, FeeAccountProof,
+    FeeMerkleCommitment, FeeMerkleTree,
+};
+use futures::{
+    future::{BoxFuture, FutureExt},
+    stream::{FuturesUnordered, StreamExt},
 };
-use futures::future::FutureExt;
 use hotshot_orchestrator::config::NetworkConfig;
 use hotshot_types::{
-    data::ViewNumber, traits::node_implementation::ConsensusTime as _, ValidatorConfig,
+    data::ViewNumber,
+    traits::{metrics::Metrics, node_implementation::ConsensusTime as _},
+    ValidatorConfig,
+};
+use jf_merkle_tree::{
+    prelude::MerkleNode, ForgetableMerkleTreeScheme, MerkleCommitment, MerkleTreeScheme,
 };
-use jf_merkle_tree::{prelude::MerkleNode, ForgetableMerkleTreeScheme, MerkleTreeScheme};
 use serde::de::DeserializeOwned;
 use surf_disco::Request;
 use tide_disco::error::ServerError;
 use url::Url;
 use vbs::version::StaticVersionType;
 
+use self::scores::{Outcome, PeerScores};
 use crate::{
     api::{
         data_source::{CatchupDataSource, PublicNetworkConfig},
@@ -29,6 +40,10 @@ use crate::{
     PubKey,
 };
 
+pub use self::scores::PeerScoringParams;
+
+mod scores;
+
 // This newtype is probably not worth having. It's only used to be able to log
 // URLs before doing requests.
 #[derive(Debug, Clone)]
@@ -48,6 +63,41 @@ impl<ApiVer: StaticVersionType> Client<ServerError, ApiVer> {
     pub fn get<T: DeserializeOwned>(&self, route: &str) -> Request<T, ServerError, ApiVer> {
         self.inner.get(route)
     }
+
+    pub fn post<T: DeserializeOwned>(&self, route: &str) -> Request<T, ServerError, ApiVer> {
+        self.inner.post(route)
+    }
+}
+
+/// Extract and verify the requested accounts from a fee state snapshot sent by a peer.
+///
+/// Each account's proof is checked against `fee_merkle_tree_root`, so a peer cannot get away with
+/// forging part of the snapshot, even though we never recompute the snapshot's root ourselves.
+fn accounts_from_snapshot(
+    snapshot: &FeeMerkleTree,
+    fee_merkle_tree_root: FeeMerkleCommitment,
+    accounts: &[FeeAccount],
+) -> anyhow::Result<Vec<AccountQueryData>> {
+    ensure!(
+        snapshot.commitment() == fee_merkle_tree_root,
+        "snapshot has wrong root: expected {fee_merkle_tree_root:?}, got {:?}",
+        snapshot.commitment()
+    );
+    accounts
+        .iter()
+        .map(|account| {
+            let (proof, balance) = FeeAccountProof::prove(snapshot, (*account).into())
+                .context(format!("snapshot is missing account {account}"))?;
+            ensure!(
+                proof
+                    .verify(&fee_merkle_tree_root)
+                    .context(format!("invalid proof for account {account}"))?
+                    == balance,
+                "balance for account {account} does not match proof"
+            );
+            Ok(AccountQueryData { balance, proof })
+        })
+        .collect()
 }
 
 /// A catchup implementation that falls back to a remote provider, but prefers a local provider when
@@ -65,10 +115,20 @@ pub(crate) async fn local_and_remote(
     }
 }
 
+/// The reason a request to a peer did not produce a usable response.
+enum PeerError {
+    /// The request itself failed, e.g. because the peer is unreachable or missing the data.
+    Request(anyhow::Error),
+    /// The peer responded, but with data that failed verification.
+    Invalid(anyhow::Error),
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct StatePeers<ApiVer: StaticVersionType> {
     clients: Vec<Client<ServerError, ApiVer>>,
     backoff: BackoffParams,
+    scoring: PeerScoringParams,
+    scores: Arc<RwLock<PeerScores>>,
 }
 
 impl<ApiVer: StaticVersionType> StatePeers<ApiVer> {
@@ -78,8 +138,26 @@ impl<ApiVer: StaticVersionType> StatePeers<ApiVer> {
         }
 
         Self {
+            scores: Arc::new(RwLock::new(PeerScores::new(urls.len()))),
             clients: urls.into_iter().map(Client::new).collect(),
             backoff,
+            scoring: Default::default(),
+        }
+    }
+
+    /// Configure how peers are ranked and queried.
+    pub fn with_scoring(mut self, scoring: PeerScoringParams) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Export per-peer scores as metrics.
+    pub fn with_metrics(self, metrics: &dyn Metrics) -> Self {
+        let scores = PeerScores::new(self.clients.len())
+            .with_metrics(metrics, self.clients.iter().map(|client| &client.url));
+        Self {
+            scores: Arc::new(RwLock::new(scores)),
+            ..self
         }
     }
 
@@ -113,6 +191,58 @@ impl<ApiVer: StaticVersionType> StatePeers<ApiVer> {
             })
             .await
     }
+
+    /// Fetch and verify a resource from the best available peers.
+    ///
+    /// Peers are queried in order of their score, `parallelism` at a time, skipping banned peers
+    /// unless every peer is banned.
+    /// The first response that passes verification in `fetch` is returned, and any requests still
+    /// in flight are dropped. Every completed request updates the score of the peer that served it.
+    async fn fetch_from_peers<'a, T>(
+        &'a self,
+        what: &str,
+        fetch: impl Fn(&'a Client<ServerError, ApiVer>) -> BoxFuture<'a, Result<T, PeerError>>,
+    ) -> anyhow::Result<T> {
+        let ranked = self.scores.read().await.ranked();
+        for batch in ranked.chunks(self.scoring.parallelism.max(1)) {
+            let mut requests = batch
+                .iter()
+                .map(|&i| {
+                    let client = &self.clients[i];
+                    tracing::info!("Fetching {what} from {}", client.url);
+                    let req = fetch(client);
+                    async move {
+                        let start = Instant::now();
+                        let res = req.await;
+                        (i, start.elapsed(), res)
+                    }
+                })
+                .collect::<FuturesUnordered<_>>();
+
+            while let Some((i, elapsed, res)) = requests.next().await {
+                let url = &self.clients[i].url;
+                let outcome = match &res {
+                    Ok(_) => Outcome::Ok,
+                    Err(PeerError::Request(err)) => {
+                        tracing::warn!("Error fetching {what} from {url}: {err:#}");
+                        Outcome::Failed
+                    }
+                    Err(PeerError::Invalid(err)) => {
+                        tracing::error!("Peer {url} served invalid {what}, banning: {err:#}");
+                        Outcome::Invalid
+                    }
+                };
+                self.scores
+                    .write()
+                    .await
+                    .record(i, outcome, elapsed, &self.scoring);
+                if let Ok(res) = res {
+                    return Ok(res);
+                }
+            }
+        }
+        bail!("Could not fetch {what} from any peer");
+    }
 }
 
 #[async_trait]
@@ -125,26 +255,50 @@ impl<ApiVer: StaticVersionType> StateCatchup for StatePeers<ApiVer> {
         fee_merkle_tree_root: FeeMerkleCommitment,
         account: FeeAccount,
     ) -> anyhow::Result<AccountQueryData> {
-        for client in self.clients.iter() {
-            tracing::info!("Fetching account {account:?} from {}", client.url);
-            match client
-                .get::<AccountQueryData>(&format!(
-                    "catchup/{height}/{}/account/{account}",
-                    view.u64(),
-                ))
-                .send()
-                .await
-            {
-                Ok(res) => match res.proof.verify(&fee_merkle_tree_root) {
-                    Ok(_) => return Ok(res),
-                    Err(err) => tracing::warn!("Error verifying account proof: {}", err),
-                },
-                Err(err) => {
-                    tracing::warn!("Error fetching account from peer: {}", err);
-                }
+        self.fetch_from_peers(&format!("account {account}"), |client| {
+            async move {
+                let res = client
+                    .get::<AccountQueryData>(&format!(
+                        "catchup/{height}/{}/account/{account}",
+                        view.u64(),
+                    ))
+                    .send()
+                    .await
+                    .map_err(|err| PeerError::Request(anyhow!("{err}")))?;
+                res.proof
+                    .verify(&fee_merkle_tree_root)
+                    .context("verifying account proof")
+                    .map_err(PeerError::Invalid)?;
+                Ok(res)
             }
-        }
-        bail!("Could not fetch account from any peer");
+            .boxed()
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn try_fetch_accounts(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        fee_merkle_tree_root: FeeMerkleCommitment,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<Vec<AccountQueryData>> {
+        self.fetch_from_peers(&format!("{} accounts", accounts.len()), |client| {
+            async move {
+                let snapshot = client
+                    .post::<FeeMerkleTree>(&format!("catchup/{height}/{}/accounts", view.u64()))
+                    .body_binary(&accounts)
+                    .map_err(|err| PeerError::Request(anyhow!("{err}")))?
+                    .send()
+                    .await
+                    .map_err(|err| PeerError::Request(anyhow!("{err}")))?;
+                accounts_from_snapshot(&snapshot, fee_merkle_tree_root, accounts)
+                    .map_err(PeerError::Invalid)
+            }
+            .boxed()
+        })
+        .await
     }
 
     #[tracing::instrument(skip(self, mt), height = mt.num_leaves())]
@@ -154,63 +308,101 @@ impl<ApiVer: StaticVersionType> StateCatchup for StatePeers<ApiVer> {
         view: ViewNumber,
         mt: &mut BlockMerkleTree,
     ) -> anyhow::Result<()> {
-        for client in self.clients.iter() {
-            tracing::info!("Fetching frontier from {}", client.url);
-            match client
-                .get::<BlocksFrontier>(&format!("catchup/{height}/{}/blocks", view.u64()))
-                .send()
-                .await
-            {
-                Ok(frontier) => {
-                    let Some(elem) = frontier.elem() else {
-                        tracing::warn!("Provided frontier is missing leaf element");
-                        continue;
-                    };
-                    match mt.remember(mt.num_leaves() - 1, *elem, &frontier) {
-                        Ok(_) => return Ok(()),
-                        Err(err) => {
-                            tracing::warn!("Error verifying block proof: {}", err);
-                            continue;
-                        }
+        let root = mt.commitment();
+        let index = mt.num_leaves() - 1;
+        let frontier = self
+            .fetch_from_peers("frontier", |client| {
+                async move {
+                    let frontier = client
+                        .get::<BlocksFrontier>(&format!("catchup/{height}/{}/blocks", view.u64()))
+                        .send()
+                        .await
+                        .map_err(|err| PeerError::Request(anyhow!("{err}")))?;
+                    if frontier.elem().is_none() {
+                        return Err(PeerError::Invalid(anyhow!(
+                            "provided frontier is missing leaf element"
+                        )));
+                    }
+                    match BlockMerkleTree::verify(root.digest(), index, &frontier) {
+                        Ok(Ok(())) => Ok(frontier),
+                        Ok(Err(())) => Err(PeerError::Invalid(anyhow!("invalid block proof"))),
+                        Err(err) => Err(PeerError::Invalid(anyhow!(
+                            "error verifying block proof: {err}"
+                        ))),
                     }
                 }
-                Err(err) => {
-                    tracing::warn!("Error fetching blocks from peer: {}", err);
+                .boxed()
+            })
+            .await?;
+
+        let elem = frontier
+            .elem()
+            .context("frontier is missing leaf element")?;
+        mt.remember(index, *elem, &frontier)
+            .context("remembering verified frontier")
+    }
+
+    #[tracing::instrument(skip(self, tree))]
+    async fn try_remember_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        tree: &mut TransferNonceMerkleTree,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<()> {
+        let root = tree.commitment();
+        let snapshot = self
+            .fetch_from_peers(&format!("{} transfer nonces", accounts.len()), |client| {
+                async move {
+                    let snapshot = client
+                        .post::<TransferNonceMerkleTree>(&format!(
+                            "catchup/{height}/{}/transfer-nonces",
+                            view.u64()
+                        ))
+                        .body_binary(&accounts)
+                        .map_err(|err| PeerError::Request(anyhow!("{err}")))?
+                        .send()
+                        .await
+                        .map_err(|err| PeerError::Request(anyhow!("{err}")))?;
+                    // Check the snapshot against the root before accepting it, so that a peer
+                    // serving an invalid one is penalized.
+                    remember_transfer_nonces(
+                        &mut TransferNonceMerkleTree::from_commitment(root),
+                        &snapshot,
+                        accounts,
+                    )
+                    .map_err(PeerError::Invalid)?;
+                    Ok(snapshot)
                 }
-            }
-        }
-        bail!("Could not fetch frontier from any peer");
+                .boxed()
+            })
+            .await?;
+        remember_transfer_nonces(tree, &snapshot, accounts)
     }
 
     async fn try_fetch_chain_config(
         &self,
         commitment: Commitment<ChainConfig>,
     ) -> anyhow::Result<ChainConfig> {
-        for client in self.clients.iter() {
-            tracing::info!("Fetching chain config from {}", client.url);
-            match client
-                .get::<ChainConfig>(&format!("catchup/chain-config/{}", commitment))
-                .send()
-                .await
-            {
-                Ok(cf) => {
-                    if cf.commit() == commitment {
-                        return Ok(cf);
-                    } else {
-                        tracing::error!(
-                            "Received chain config with mismatched commitment from {}: expected {}, got {}",
-                            client.url,
-                            commitment,
-                            cf.commit(),
-                        );
-                    }
-                }
-                Err(err) => {
-                    tracing::warn!("Error fetching chain config from peer: {}", err);
+        self.fetch_from_peers("chain config", |client| {
+            async move {
+                let cf = client
+                    .get::<ChainConfig>(&format!("catchup/chain-config/{}", commitment))
+                    .send()
+                    .await
+                    .map_err(|err| PeerError::Request(anyhow!("{err}")))?;
+                if cf.commit() != commitment {
+                    return Err(PeerError::Invalid(anyhow!(
+                        "received chain config with mismatched commitment: expected {}, got {}",
+                        commitment,
+                        cf.commit(),
+                    )));
                 }
+                Ok(cf)
             }
-        }
-        bail!("Could not fetch chain config from any peer");
+            .boxed()
+        })
+        .await
     }
 
     fn backoff(&self) -> &BackoffParams {
@@ -248,6 +440,18 @@ where
             .await
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn try_fetch_accounts(
+        &self,
+        block_height: u64,
+        view: ViewNumber,
+        fee_merkle_tree_root: FeeMerkleCommitment,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<Vec<AccountQueryData>> {
+        let snapshot = self.db.get_accounts(block_height, view, accounts).await?;
+        accounts_from_snapshot(&snapshot, fee_merkle_tree_root, accounts)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn try_remember_blocks_merkle_tree(
         &self,
@@ -272,6 +476,21 @@ where
         }
     }
 
+    #[tracing::instrument(skip(self, tree))]
+    async fn try_remember_transfer_nonces(
+        &self,
+        block_height: u64,
+        view: ViewNumber,
+        tree: &mut TransferNonceMerkleTree,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<()> {
+        let snapshot = self
+            .db
+            .get_transfer_nonces(block_height, view, accounts)
+            .await?;
+        remember_transfer_nonces(tree, &snapshot, accounts)
+    }
+
     async fn try_fetch_chain_config(
         &self,
         commitment: Commitment<ChainConfig>,
@@ -283,3 +502,136 @@ where
         &self.backoff
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{sync::Mutex, time::Duration};
+
+    use async_std::task::sleep;
+
+    use super::*;
+    use crate::SequencerApiVersion;
+
+    fn peers(num_peers: usize, parallelism: usize) -> StatePeers<SequencerApiVersion> {
+        StatePeers::from_urls(
+            (0..num_peers)
+                .map(|i| format!("http://peer{i}").parse().unwrap())
+                .collect(),
+            Default::default(),
+        )
+        .with_scoring(PeerScoringParams {
+            parallelism,
+            ..Default::default()
+        })
+    }
+
+    /// Fetch from `peers`, with each peer responding after the delay and with the result given by
+    /// `respond`. Returns the result along with the peers that were queried, in order.
+    async fn fetch(
+        peers: &StatePeers<SequencerApiVersion>,
+        respond: impl Fn(usize) -> (Duration, Result<usize, PeerError>),
+    ) -> (anyhow::Result<usize>, Vec<usize>) {
+        let queried = Mutex::new(vec![]);
+        let res = peers
+            .fetch_from_peers("test", |client| {
+                let i: usize = client.url.host_str().unwrap()["peer".len()..]
+                    .parse()
+                    .unwrap();
+                queried.lock().unwrap().push(i);
+                let (delay, res) = respond(i);
+                async move {
+                    sleep(delay).await;
+                    res
+                }
+                .boxed()
+            })
+            .await;
+        (res, queried.into_inner().unwrap())
+    }
+
+    fn fail() -> Result<usize, PeerError> {
+        Err(PeerError::Request(anyhow!("unavailable")))
+    }
+
+    #[async_std::test]
+    async fn test_fetch_from_peers_order() {
+        let peers = peers(3, 1);
+
+        // Untried peers are queried in order.
+        let (res, queried) = fetch(&peers, |i| (Duration::from_millis(200), Ok(i))).await;
+        assert_eq!(res.unwrap(), 0);
+        assert_eq!(queried, [0]);
+
+        // A slow peer ranks below untried ones.
+        let (res, queried) = fetch(&peers, |i| (Duration::ZERO, Ok(i))).await;
+        assert_eq!(res.unwrap(), 1);
+        assert_eq!(queried, [1]);
+
+        // If the best peer serves invalid data, the next one is queried.
+        let (res, queried) = fetch(&peers, |i| {
+            if i == 1 {
+                (Duration::ZERO, Err(PeerError::Invalid(anyhow!("invalid"))))
+            } else {
+                (Duration::ZERO, Ok(i))
+            }
+        })
+        .await;
+        assert_eq!(res.unwrap(), 2);
+        assert_eq!(queried, [1, 2]);
+
+        // The banned peer is not queried, even when every other peer fails.
+        let (res, queried) = fetch(&peers, |_| (Duration::ZERO, fail())).await;
+        res.unwrap_err();
+        assert_eq!(queried, [2, 0]);
+    }
+
+    #[async_std::test]
+    async fn test_fetch_from_banned_peers() {
+        let peers = peers(2, 1);
+
+        // Ban both peers.
+        let (res, queried) = fetch(&peers, |_| {
+            (Duration::ZERO, Err(PeerError::Invalid(anyhow!("invalid"))))
+        })
+        .await;
+        res.unwrap_err();
+        assert_eq!(queried, [0, 1]);
+
+        // With no unbanned peer left, the banned ones are still queried.
+        let (res, queried) = fetch(&peers, |i| (Duration::ZERO, Ok(i))).await;
+        assert_eq!(res.unwrap(), 0);
+        assert_eq!(queried, [0]);
+    }
+
+    #[async_std::test]
+    async fn test_fetch_from_peers_race() {
+        let peers = peers(3, 2);
+
+        // The two best peers are queried at once, and the first to respond wins.
+        let (res, mut queried) = fetch(&peers, |i| {
+            let delay = if i == 0 {
+                Duration::from_secs(60)
+            } else {
+                Duration::ZERO
+            };
+            (delay, Ok(i))
+        })
+        .await;
+        assert_eq!(res.unwrap(), 1);
+        queried.sort();
+        assert_eq!(queried, [0, 1]);
+
+        // If the whole batch fails, the next batch is tried.
+        let (res, queried) = fetch(&peers, |i| {
+            if i == 2 {
+                (Duration::ZERO, Ok(i))
+            } else {
+                (Duration::ZERO, fail())
+            }
+        })
+        .await;
+        assert_eq!(res.unwrap(), 2);
+        assert_eq!(queried.len(), 3);
+        assert_eq!(queried[2], 2);
+    }
+}
@@ -0,0 +1,287 @@
+//! Bookkeeping for ranking catchup peers.
+//!
+//! [`StatePeers`](super::StatePeers) records the outcome of every request it makes to a peer. Peers
+//! which respond quickly and correctly are queried first, peers which fail often sink to the bottom
+//! of the list, and peers which serve data that fails verification are banned for a while.
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use espresso_types::parse_duration;
+use hotshot_types::traits::metrics::{Counter, Gauge, Metrics};
+use url::Url;
+
+/// Weight of the most recent sample in the moving average of a peer's latency.
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Latency assumed for a peer we have not heard from yet.
+///
+/// This is optimistic, so that new peers get tried early and earn a real score.
+const DEFAULT_LATENCY: Duration = Duration::from_millis(100);
+
+/// How much a peer's effective latency is inflated per unit of error rate.
+const ERROR_PENALTY: f64 = 10.0;
+
+#[derive(Clone, Copy, Debug, Parser)]
+pub struct PeerScoringParams {
+    /// Number of the best scoring catchup peers to query in parallel.
+    ///
+    /// The first verified response wins. If all of them fail, the next batch of peers is tried.
+    #[clap(
+        long = "catchup-parallelism",
+        env = "ESPRESSO_SEQUENCER_CATCHUP_PARALLELISM",
+        default_value = "2"
+    )]
+    pub parallelism: usize,
+
+    /// How long to stop querying a catchup peer after it serves an invalid response.
+    #[clap(
+        long = "catchup-ban-duration",
+        env = "ESPRESSO_SEQUENCER_CATCHUP_BAN_DURATION",
+        default_value = "10m",
+        value_parser = parse_duration
+    )]
+    pub ban_duration: Duration,
+}
+
+impl Default for PeerScoringParams {
+    fn default() -> Self {
+        Self {
+            parallelism: 2,
+            ban_duration: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+/// The outcome of a single request to a peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Outcome {
+    /// The peer responded with data that passed verification.
+    Ok,
+    /// The request failed, for example due to a network error or a missing resource.
+    Failed,
+    /// The peer responded with data that failed verification.
+    Invalid,
+}
+
+#[derive(Default)]
+struct PeerMetrics {
+    latency: Option<Box<dyn Gauge>>,
+    score: Option<Box<dyn Gauge>>,
+    banned: Option<Box<dyn Gauge>>,
+    failures: Option<Box<dyn Counter>>,
+    invalid: Option<Box<dyn Counter>>,
+}
+
+impl Debug for PeerMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PeerMetrics").finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Default)]
+pub(super) struct PeerScore {
+    /// Exponential moving average of the latency of successful requests.
+    latency: Option<Duration>,
+    requests: u64,
+    failures: u64,
+    invalid: u64,
+    banned_until: Option<Instant>,
+    metrics: PeerMetrics,
+}
+
+impl PeerScore {
+    /// The score of this peer; lower is better.
+    ///
+    /// This is the peer's expected latency, inflated by its (smoothed) error rate, so a fast but
+    /// flaky peer can rank below a slower reliable one.
+    pub(super) fn score(&self) -> f64 {
+        let latency = self.latency.unwrap_or(DEFAULT_LATENCY).as_secs_f64();
+        let error_rate = (self.failures + self.invalid + 1) as f64 / (self.requests + 2) as f64;
+        latency * (1.0 + ERROR_PENALTY * error_rate)
+    }
+
+    pub(super) fn is_banned(&self, now: Instant) -> bool {
+        self.banned_until.is_some_and(|until| until > now)
+    }
+
+    pub(super) fn record(
+        &mut self,
+        outcome: Outcome,
+        elapsed: Duration,
+        params: &PeerScoringParams,
+    ) {
+        self.requests += 1;
+        match outcome {
+            Outcome::Ok => {
+                self.latency = Some(match self.latency {
+                    Some(latency) => {
+                        latency.mul_f64(1.0 - LATENCY_SMOOTHING)
+                            + elapsed.mul_f64(LATENCY_SMOOTHING)
+                    }
+                    None => elapsed,
+                });
+            }
+            Outcome::Failed => {
+                self.failures += 1;
+                if let Some(counter) = &self.metrics.failures {
+                    counter.add(1);
+                }
+            }
+            Outcome::Invalid => {
+                self.invalid += 1;
+                self.banned_until = Some(Instant::now() + params.ban_duration);
+                if let Some(counter) = &self.metrics.invalid {
+                    counter.add(1);
+                }
+            }
+        }
+        self.update_metrics();
+    }
+
+    fn update_metrics(&self) {
+        if let (Some(gauge), Some(latency)) = (&self.metrics.latency, self.latency) {
+            gauge.set(latency.as_millis() as usize);
+        }
+        if let Some(gauge) = &self.metrics.score {
+            // Report the score in milliseconds, since gauges only hold integers.
+            gauge.set((self.score() * 1000.0) as usize);
+        }
+        if let Some(gauge) = &self.metrics.banned {
+            gauge.set(self.is_banned(Instant::now()) as usize);
+        }
+    }
+}
+
+/// Scores for a list of peers, indexed the same as the peers themselves.
+#[derive(Debug, Default)]
+pub(super) struct PeerScores {
+    scores: Vec<PeerScore>,
+}
+
+impl PeerScores {
+    pub(super) fn new(num_peers: usize) -> Self {
+        Self {
+            scores: std::iter::repeat_with(Default::default)
+                .take(num_peers)
+                .collect(),
+        }
+    }
+
+    /// Register metrics for each peer, labeled by the peer's URL.
+    pub(super) fn with_metrics<'a>(
+        mut self,
+        metrics: &dyn Metrics,
+        urls: impl IntoIterator<Item = &'a Url>,
+    ) -> Self {
+        let metrics = metrics.subgroup("catchup".into());
+        let latency = metrics.gauge_family("peer_latency_ms".into(), vec!["peer".into()]);
+        let score = metrics.gauge_family("peer_score".into(), vec!["peer".into()]);
+        let banned = metrics.gauge_family("peer_banned".into(), vec!["peer".into()]);
+        let failures = metrics.counter_family("peer_failures".into(), vec!["peer".into()]);
+        let invalid = metrics.counter_family("peer_invalid_responses".into(), vec!["peer".into()]);
+        for (peer, url) in self.scores.iter_mut().zip(urls) {
+            let label = vec![url.to_string()];
+            peer.metrics = PeerMetrics {
+                latency: Some(latency.create(label.clone())),
+                score: Some(score.create(label.clone())),
+                banned: Some(banned.create(label.clone())),
+                failures: Some(failures.create(label.clone())),
+                invalid: Some(invalid.create(label)),
+            };
+            peer.update_metrics();
+        }
+        self
+    }
+
+    /// The indices of all peers that are not currently banned, best first.
+    ///
+    /// A banned peer is not consulted until its ban expires, as long as any other peer is not
+    /// banned. If every peer is banned, all of them are ranked, so that catchup can still make
+    /// progress if the bans were caused by our own verification failing.
+    pub(super) fn ranked(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut indices = (0..self.scores.len())
+            .filter(|&i| !self.scores[i].is_banned(now))
+            .collect::<Vec<_>>();
+        if indices.is_empty() {
+            indices = (0..self.scores.len()).collect();
+        }
+        indices.sort_by(|&i, &j| self.scores[i].score().total_cmp(&self.scores[j].score()));
+        indices
+    }
+
+    pub(super) fn record(
+        &mut self,
+        peer: usize,
+        outcome: Outcome,
+        elapsed: Duration,
+        params: &PeerScoringParams,
+    ) {
+        self.scores[peer].record(outcome, elapsed, params);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_peer_ranking() {
+        let params = PeerScoringParams::default();
+        let mut scores = PeerScores::new(3);
+
+        // Untried peers keep their original order.
+        assert_eq!(scores.ranked(), [0, 1, 2]);
+
+        // A slow peer ranks below a fast one.
+        scores.record(0, Outcome::Ok, Duration::from_secs(2), &params);
+        scores.record(1, Outcome::Ok, Duration::from_millis(10), &params);
+        assert_eq!(scores.ranked(), [1, 2, 0]);
+
+        // A failing peer ranks below a reliable one with similar latency.
+        scores.record(2, Outcome::Ok, Duration::from_millis(10), &params);
+        for _ in 0..5 {
+            scores.record(1, Outcome::Failed, Duration::from_millis(10), &params);
+        }
+        assert_eq!(scores.ranked(), [2, 1, 0]);
+
+        // A peer serving invalid data is banned, and excluded regardless of latency.
+        scores.record(2, Outcome::Invalid, Duration::from_millis(1), &params);
+        assert_eq!(scores.ranked(), [1, 0]);
+
+        // Once every peer is banned, they are all ranked again by score.
+        scores.record(0, Outcome::Invalid, Duration::from_millis(1), &params);
+        scores.record(1, Outcome::Invalid, Duration::from_millis(1), &params);
+        let ranked = scores.ranked();
+        assert_eq!(ranked.len(), 3);
+        assert!(ranked
+            .windows(2)
+            .all(|w| scores.scores[w[0]].score() <= scores.scores[w[1]].score()));
+    }
+
+    #[test]
+    fn test_default_params() {
+        // The defaults match those of the command line options.
+        let params = PeerScoringParams::default();
+        let parsed = PeerScoringParams::parse_from(std::iter::empty::<String>());
+        assert_eq!(params.parallelism, parsed.parallelism);
+        assert_eq!(params.ban_duration, parsed.ban_duration);
+    }
+
+    #[test]
+    fn test_ban_expiry() {
+        let params = PeerScoringParams {
+            ban_duration: Duration::ZERO,
+            ..Default::default()
+        };
+        let mut scores = PeerScores::new(2);
+
+        // Once the ban expires, the peer is ranked again, taking its errors into account.
+        scores.record(0, Outcome::Invalid, Duration::from_millis(1), &params);
+        assert_eq!(scores.ranked(), [1, 0]);
+    }
+}
@@ -39,7 +39,9 @@ use url::Url;
 use crate::{
     external_event_handler::{self, ExternalEventHandler},
     state_signature::StateSigner,
-    static_stake_table_commitment, Node, SeqTypes, SequencerApiVersion,
+    static_stake_table_state,
+    upgrade_status::UpgradeTracker,
+    Node, SeqTypes, SequencerApiVersion,
 };
 
 /// The consensus handle
@@ -56,6 +58,9 @@ pub struct SequencerContext<N: ConnectedNetwork<PubKey>, P: SequencerPersistence
     /// Context for generating state signatures.
     state_signer: Arc<StateSigner<SequencerApiVersion>>,
 
+    /// Progress of scheduled upgrades.
+    upgrade_tracker: Arc<UpgradeTracker>,
+
     /// An orchestrator to wait for before starting consensus.
     #[derivative(Debug = "ignore")]
     wait_for_orchestrator: Option<Arc<OrchestratorClient>>,
@@ -120,7 +125,7 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
             da_membership,
         };
 
-        let stake_table_commit = static_stake_table_commitment(
+        let stake_table_state = static_stake_table_state(
             &config.known_nodes_with_stake,
             stake_table_capacity
                 .try_into()
@@ -133,6 +138,11 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
             0,
         )));
 
+        // Restore the progress of upgrades decided before a restart.
+        let upgrade_tracker = UpgradeTracker::restore(&persistence)
+            .await
+            .context("restoring upgrade progress")?;
+
         let persistence = Arc::new(persistence);
 
         let handle = SystemContext::init(
@@ -150,7 +160,7 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
         .await?
         .0;
 
-        let mut state_signer = StateSigner::new(state_key_pair, stake_table_commit);
+        let mut state_signer = StateSigner::new(state_key_pair, stake_table_state);
         if let Some(url) = state_relay_server {
             state_signer = state_signer.with_relay_server(url);
         }
@@ -169,6 +179,7 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
             handle,
             persistence,
             state_signer,
+            upgrade_tracker,
             external_event_handler,
             event_streamer,
             instance_state,
@@ -185,6 +196,7 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
         handle: Consensus<N, P, V>,
         persistence: Arc<P>,
         state_signer: StateSigner<SequencerApiVersion>,
+        upgrade_tracker: UpgradeTracker,
         external_event_handler: ExternalEventHandler<V>,
         event_streamer: Arc<RwLock<EventsStreamer<SeqTypes>>>,
         node_state: NodeState,
@@ -198,6 +210,7 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
         let mut ctx = Self {
             handle: Arc::new(RwLock::new(handle)),
             state_signer: Arc::new(state_signer),
+            upgrade_tracker: Arc::new(upgrade_tracker),
             tasks: Default::default(),
             detached: false,
             wait_for_orchestrator: None,
@@ -205,6 +218,12 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
             node_state,
             config,
         };
+        ctx.spawn(
+            "state signature delivery",
+            ctx.state_signer
+                .clone()
+                .deliver_signatures(persistence.clone()),
+        );
         ctx.spawn(
             "main event handler",
             handle_events(
@@ -212,6 +231,7 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
                 events,
                 persistence,
                 ctx.state_signer.clone(),
+                ctx.upgrade_tracker.clone(),
                 external_event_handler,
                 Some(event_streamer.clone()),
                 event_consumer,
@@ -239,6 +259,11 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
         self.state_signer.clone()
     }
 
+    /// Return a reference to the upgrade progress tracker.
+    pub fn upgrade_tracker(&self) -> Arc<UpgradeTracker> {
+        self.upgrade_tracker.clone()
+    }
+
     /// Stream consensus events.
     pub async fn event_stream(&self) -> impl Stream<Item = Event<SeqTypes>> {
         self.handle.read().await.event_stream()
@@ -354,6 +379,7 @@ async fn handle_events<V: Versions>(
     mut events: impl Stream<Item = Event<SeqTypes>> + Unpin,
     persistence: Arc<impl SequencerPersistence>,
     state_signer: Arc<StateSigner<SequencerApiVersion>>,
+    upgrade_tracker: Arc<UpgradeTracker>,
     external_event_handler: ExternalEventHandler<V>,
     events_streamer: Option<Arc<RwLock<EventsStreamer<SeqTypes>>>>,
     event_consumer: impl PersistenceEventConsumer + 'static,
@@ -379,7 +405,10 @@ async fn handle_events<V: Versions>(
         persistence.handle_event(&event, &event_consumer).await;
 
         // Generate state signature.
-        state_signer.handle_event(&event).await;
+        state_signer.handle_event(&event, &*persistence).await;
+
+        // Track upgrade progress.
+        upgrade_tracker.handle_event(&event, &*persistence).await;
 
         // Handle external messages
         if let EventType::ExternalMessageReceived(external_message_bytes) = &event.event {
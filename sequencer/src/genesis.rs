@@ -3,9 +3,10 @@ use std::{
     path::Path,
 };
 
-use anyhow::Context;
+use anyhow::{bail, ensure, Context};
 use espresso_types::{
-    v0_3::ChainConfig, FeeAccount, FeeAmount, GenesisHeader, L1BlockInfo, Upgrade, UpgradeType,
+    v0_3::ChainConfig, ChainConfigUpgrade, ChainConfigUpgradeActivation, FeeAccount, FeeAmount,
+    GenesisHeader, L1BlockInfo, Upgrade, UpgradeMode, UpgradeType,
 };
 use ethers::{
     providers::{Http, Provider},
@@ -56,6 +57,10 @@ pub struct Genesis {
     #[serde(rename = "upgrade", with = "upgrade_ser")]
     #[serde(default)]
     pub upgrades: BTreeMap<Version, Upgrade>,
+    /// Chain config changes which take effect without a protocol upgrade, by protocol version.
+    #[serde(rename = "chain_config_upgrade", with = "chain_config_upgrade_ser")]
+    #[serde(default)]
+    pub chain_config_upgrades: BTreeMap<Version, Vec<ChainConfigUpgrade>>,
 }
 
 impl Genesis {
@@ -74,11 +79,174 @@ impl Genesis {
                 }
             }
         }
+        for upgrade in self.chain_config_upgrades.values().flatten() {
+            base_fee = std::cmp::max(upgrade.chain_config.base_fee, base_fee);
+        }
 
         base_fee
     }
 }
 
+/// A chain config which takes effect at some point in a genesis upgrade schedule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScheduledChainConfig {
+    /// The protocol version at which this chain config is used.
+    pub version: Version,
+    /// The activation point, if this chain config is set by a chain config upgrade rather than at
+    /// genesis or by a protocol upgrade.
+    pub activation: Option<ChainConfigUpgradeActivation>,
+    pub chain_config: ChainConfig,
+}
+
+impl Genesis {
+    /// Every chain config this genesis schedules, in the order they take effect.
+    ///
+    /// This starts with the genesis chain config at the base version. Each protocol upgrade then
+    /// contributes the chain config it migrates to, followed by any chain config upgrades scheduled
+    /// for that version.
+    pub fn chain_config_schedule(&self) -> Vec<ScheduledChainConfig> {
+        let base = ScheduledChainConfig {
+            version: self.base_version,
+            activation: None,
+            chain_config: self.chain_config,
+        };
+        let upgrades = self
+            .upgrades
+            .iter()
+            .filter(|(version, _)| **version > self.base_version)
+            .map(|(version, upgrade)| ScheduledChainConfig {
+                version: *version,
+                activation: None,
+                chain_config: upgrade.upgrade_type.data(),
+            });
+
+        let mut schedule = vec![];
+        for scheduled in std::iter::once(base).chain(upgrades) {
+            schedule.push(scheduled);
+            for upgrade in self
+                .chain_config_upgrades
+                .get(&scheduled.version)
+                .into_iter()
+                .flatten()
+            {
+                schedule.push(ScheduledChainConfig {
+                    version: scheduled.version,
+                    activation: Some(upgrade.activation),
+                    chain_config: upgrade.chain_config,
+                });
+            }
+        }
+        schedule
+    }
+
+    /// Check for mistakes which can be detected without connecting to the L1.
+    ///
+    /// This catches misordered versions, empty upgrade windows, missing or zero fee contracts in
+    /// upgrades, chain config upgrades for versions the chain never reaches or whose activations are
+    /// out of order, and changes of chain ID. Use [`validate_fee_contract`](Self::validate_fee_contract) to check the fee contracts
+    /// against the L1.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.base_version <= self.upgrade_version,
+            "upgrade version {} is older than base version {}",
+            self.upgrade_version,
+            self.base_version
+        );
+
+        for (version, upgrade) in &self.upgrades {
+            ensure!(
+                *version > self.base_version,
+                "upgrade to {version} does not come after base version {}",
+                self.base_version
+            );
+            match &upgrade.mode {
+                UpgradeMode::View(v) => {
+                    ensure!(
+                        v.start_proposing_view <= v.stop_proposing_view,
+                        "upgrade to {version}: stop_proposing_view is less than start_proposing_view"
+                    );
+                    if let (Some(start), Some(stop)) = (v.start_voting_view, v.stop_voting_view) {
+                        ensure!(
+                            start <= stop,
+                            "upgrade to {version}: stop_voting_view is less than start_voting_view"
+                        );
+                    }
+                }
+                UpgradeMode::Time(t) => {
+                    ensure!(
+                        t.start_proposing_time.unix_timestamp()
+                            <= t.stop_proposing_time.unix_timestamp(),
+                        "upgrade to {version}: stop_proposing_time is less than start_proposing_time"
+                    );
+                    if let (Some(start), Some(stop)) = (t.start_voting_time, t.stop_voting_time) {
+                        ensure!(
+                            start.unix_timestamp() <= stop.unix_timestamp(),
+                            "upgrade to {version}: stop_voting_time is less than start_voting_time"
+                        );
+                    }
+                }
+            }
+            match upgrade.upgrade_type.data().fee_contract {
+                Some(address) => ensure!(
+                    address != H160::zero(),
+                    "upgrade to {version}: fee contract cannot use the zero address"
+                ),
+                None => bail!("upgrade to {version}: fee contract address is missing"),
+            }
+        }
+
+        for (version, upgrades) in &self.chain_config_upgrades {
+            ensure!(
+                *version == self.base_version || self.upgrades.contains_key(version),
+                "chain config upgrade for version {version}, which is neither the base version nor an upgrade"
+            );
+            // Within a version, upgrades take effect in the order they are listed, so activation
+            // points of the same kind must be strictly increasing.
+            let mut last_view = None;
+            let mut last_time = None;
+            for upgrade in upgrades {
+                ensure!(
+                    upgrade.chain_config.fee_contract != Some(H160::zero()),
+                    "chain config upgrade for version {version}: fee contract cannot use the zero address"
+                );
+                match upgrade.activation {
+                    ChainConfigUpgradeActivation::View(view) => {
+                        if let Some(last) = last_view {
+                            ensure!(
+                                view > last,
+                                "chain config upgrade for version {version}: activation view {view} does not come after {last}"
+                            );
+                        }
+                        last_view = Some(view);
+                    }
+                    ChainConfigUpgradeActivation::Time(time) => {
+                        let time = time.unix_timestamp();
+                        if let Some(last) = last_time {
+                            ensure!(
+                                time > last,
+                                "chain config upgrade for version {version}: activation time {time} does not come after {last}"
+                            );
+                        }
+                        last_time = Some(time);
+                    }
+                }
+            }
+        }
+
+        for scheduled in self.chain_config_schedule() {
+            ensure!(
+                scheduled.chain_config.chain_id == self.chain_config.chain_id,
+                "chain config at version {} changes chain ID from {} to {}",
+                scheduled.version,
+                self.chain_config.chain_id,
+                scheduled.chain_config.chain_id
+            );
+        }
+
+        Ok(())
+    }
+}
+
 impl Genesis {
     pub async fn validate_fee_contract(&self, l1_rpc_url: String) -> anyhow::Result<()> {
         let provider = Provider::<Http>::try_from(l1_rpc_url)?;
@@ -107,12 +275,24 @@ impl Genesis {
                 }
             }
         }
+
+        // Chain config upgrades may leave the fee contract unset, but if they set it, it must be
+        // a proxy.
+        for upgrade in self.chain_config_upgrades.values().flatten() {
+            if let Some(fee_contract_address) = upgrade.chain_config.fee_contract {
+                if fee_contract_address == H160::zero() {
+                    anyhow::bail!("Fee contract cannot use the zero address");
+                } else if !is_proxy_contract(provider.clone(), fee_contract_address).await? {
+                    anyhow::bail!("Fee contract's address is not a proxy");
+                }
+            }
+        }
         // TODO: it's optional for the fee contract to be included in a proxy in v1 so no need to panic but revisit this after v1 https://github.com/EspressoSystems/espresso-sequencer/pull/2000#discussion_r1765174702
         Ok(())
     }
 }
 
-mod version_ser {
+pub(crate) mod version_ser {
 
     use vbs::version::Version;
 
@@ -285,6 +465,87 @@ mod upgrade_ser {
     }
 }
 
+mod chain_config_upgrade_ser {
+    use std::collections::BTreeMap;
+
+    use espresso_types::{
+        v0_3::ChainConfig, ChainConfigUpgrade, ChainConfigUpgradeActivation, Timestamp,
+    };
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use vbs::version::Version;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Fields {
+        #[serde(with = "super::version_ser")]
+        version: Version,
+        // Both activation fields are optional so that we can raise an error if both or neither are
+        // given, rather than silently picking one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        activation_view: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        activation_time: Option<Timestamp>,
+        chain_config: ChainConfig,
+    }
+
+    pub fn serialize<S>(
+        map: &BTreeMap<Version, Vec<ChainConfigUpgrade>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        map.iter()
+            .flat_map(|(version, upgrades)| {
+                upgrades.iter().map(|upgrade| {
+                    let (activation_view, activation_time) = match upgrade.activation {
+                        ChainConfigUpgradeActivation::View(view) => (Some(view), None),
+                        ChainConfigUpgradeActivation::Time(time) => (None, Some(time)),
+                    };
+                    Fields {
+                        version: *version,
+                        activation_view,
+                        activation_time,
+                        chain_config: upgrade.chain_config,
+                    }
+                })
+            })
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<BTreeMap<Version, Vec<ChainConfigUpgrade>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut map = BTreeMap::<_, Vec<_>>::new();
+        for fields in Vec::<Fields>::deserialize(deserializer)? {
+            let activation = match (fields.activation_view, fields.activation_time) {
+                (Some(view), None) => ChainConfigUpgradeActivation::View(view),
+                (None, Some(time)) => ChainConfigUpgradeActivation::Time(time),
+                (Some(_), Some(_)) => {
+                    return Err(de::Error::custom(
+                        "both activation_view and activation_time are set",
+                    ))
+                }
+                (None, None) => {
+                    return Err(de::Error::custom(
+                        "no activation_view or activation_time provided",
+                    ))
+                }
+            };
+            map.entry(fields.version)
+                .or_default()
+                .push(ChainConfigUpgrade {
+                    activation,
+                    chain_config: fields.chain_config,
+                });
+        }
+        Ok(map)
+    }
+}
+
 impl Genesis {
     pub fn to_file(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
         let toml = toml::to_string_pretty(self)?;
@@ -304,7 +565,8 @@ impl Genesis {
 #[cfg(test)]
 mod test {
     use espresso_types::{
-        L1BlockInfo, TimeBasedUpgrade, Timestamp, UpgradeMode, UpgradeType, ViewBasedUpgrade,
+        ChainConfigUpgradeActivation, L1BlockInfo, TimeBasedUpgrade, Timestamp, UpgradeMode,
+        UpgradeType, ViewBasedUpgrade,
     };
     use ethers::prelude::{Address, H160, H256};
     use sequencer_utils::ser::FromStringOrInteger;
@@ -1122,4 +1384,275 @@ mod test {
 
         toml::from_str::<Genesis>(&toml).unwrap();
     }
+
+    #[test]
+    fn test_genesis_toml_chain_config_upgrade() {
+        let toml = toml! {
+            base_version = "0.2"
+            upgrade_version = "0.3"
+
+            [stake_table]
+            capacity = 10
+
+            [chain_config]
+            chain_id = 12345
+            max_block_size = 30000
+            base_fee = 1
+            fee_recipient = "0x0000000000000000000000000000000000000000"
+
+            [header]
+            timestamp = 123456
+
+            [l1_finalized]
+            number = 64
+
+            [[chain_config_upgrade]]
+            version = "0.2"
+            activation_view = 100
+
+            [chain_config_upgrade.chain_config]
+            chain_id = 12345
+            max_block_size = 60000
+            base_fee = 1
+            fee_recipient = "0x0000000000000000000000000000000000000000"
+
+            [[chain_config_upgrade]]
+            version = "0.2"
+            activation_time = "2030-01-01T00:00:00Z"
+
+            [chain_config_upgrade.chain_config]
+            chain_id = 12345
+            max_block_size = 60000
+            base_fee = 2
+            fee_recipient = "0x0000000000000000000000000000000000000000"
+        }
+        .to_string();
+
+        let genesis: Genesis = toml::from_str(&toml).unwrap_or_else(|err| panic!("{err:#}"));
+        let upgrades = &genesis.chain_config_upgrades[&Version { major: 0, minor: 2 }];
+        assert_eq!(
+            *upgrades,
+            [
+                ChainConfigUpgrade {
+                    activation: ChainConfigUpgradeActivation::View(100),
+                    chain_config: ChainConfig {
+                        max_block_size: 60000.into(),
+                        ..genesis.chain_config
+                    },
+                },
+                ChainConfigUpgrade {
+                    activation: ChainConfigUpgradeActivation::Time(
+                        Timestamp::from_string("2030-01-01T00:00:00Z".to_string()).unwrap()
+                    ),
+                    chain_config: ChainConfig {
+                        max_block_size: 60000.into(),
+                        base_fee: 2.into(),
+                        ..genesis.chain_config
+                    },
+                },
+            ]
+        );
+        assert_eq!(genesis.max_base_fee(), FeeAmount::from(2));
+
+        // The schedule survives a round trip through the genesis file format.
+        let round_trip: Genesis = toml::from_str(&toml::to_string(&genesis).unwrap()).unwrap();
+        assert_eq!(
+            round_trip.chain_config_upgrades,
+            genesis.chain_config_upgrades
+        );
+    }
+
+    #[test]
+    fn test_genesis_toml_chain_config_upgrade_view_and_time() {
+        // Setting both activation points is ambiguous, so it is an error.
+        let toml = toml! {
+            base_version = "0.2"
+            upgrade_version = "0.3"
+
+            [stake_table]
+            capacity = 10
+
+            [chain_config]
+            chain_id = 12345
+            max_block_size = 30000
+            base_fee = 1
+            fee_recipient = "0x0000000000000000000000000000000000000000"
+
+            [header]
+            timestamp = 123456
+
+            [l1_finalized]
+            number = 64
+
+            [[chain_config_upgrade]]
+            version = "0.2"
+            activation_view = 100
+            activation_time = 123456
+
+            [chain_config_upgrade.chain_config]
+            chain_id = 12345
+            max_block_size = 60000
+            base_fee = 1
+            fee_recipient = "0x0000000000000000000000000000000000000000"
+        }
+        .to_string();
+
+        toml::from_str::<Genesis>(&toml).unwrap_err();
+    }
+
+    #[test]
+    fn test_genesis_files_validate() {
+        for entry in std::fs::read_dir("../data/genesis").unwrap() {
+            let path = entry.unwrap().path();
+            let genesis = Genesis::from_file(&path).unwrap();
+            genesis
+                .validate()
+                .unwrap_or_else(|err| panic!("{}: {err:#}", path.display()));
+        }
+    }
+
+    #[test]
+    fn test_genesis_validate_errors() {
+        let genesis = Genesis::from_file("../data/genesis/demo.toml").unwrap();
+        genesis.validate().unwrap();
+
+        // An upgrade to a version which is not newer than the base version.
+        let mut bad = genesis.clone();
+        bad.base_version = Version { major: 0, minor: 2 };
+        bad.validate().unwrap_err();
+
+        // An upgrade which sets the fee contract to the zero address.
+        let mut bad = genesis.clone();
+        let upgrade = bad
+            .upgrades
+            .get_mut(&Version { major: 0, minor: 2 })
+            .unwrap();
+        upgrade.upgrade_type = UpgradeType::Fee {
+            chain_config: ChainConfig {
+                fee_contract: Some(H160::zero()),
+                ..upgrade.upgrade_type.data()
+            },
+        };
+        bad.validate().unwrap_err();
+
+        // A chain config upgrade which changes the chain ID.
+        let mut bad = genesis.clone();
+        bad.chain_config_upgrades.insert(
+            Version { major: 0, minor: 2 },
+            vec![ChainConfigUpgrade {
+                activation: ChainConfigUpgradeActivation::View(100),
+                chain_config: ChainConfig {
+                    chain_id: 1u64.into(),
+                    ..genesis.chain_config
+                },
+            }],
+        );
+        bad.validate().unwrap_err();
+
+        // A chain config upgrade for a version the chain never upgrades to.
+        let mut bad = genesis.clone();
+        bad.chain_config_upgrades.insert(
+            Version { major: 0, minor: 4 },
+            vec![ChainConfigUpgrade {
+                activation: ChainConfigUpgradeActivation::View(100),
+                chain_config: genesis.chain_config,
+            }],
+        );
+        bad.validate().unwrap_err();
+
+        // Chain config upgrades within a version must be in order of activation, without
+        // duplicates, but views and times are independent of each other.
+        let v2 = Version { major: 0, minor: 2 };
+        let upgrade = |activation| ChainConfigUpgrade {
+            activation,
+            chain_config: genesis.upgrades[&v2].upgrade_type.data(),
+        };
+        let time = |t| ChainConfigUpgradeActivation::Time(Timestamp::from_integer(t).unwrap());
+        let mut ok = genesis.clone();
+        ok.chain_config_upgrades.insert(
+            v2,
+            vec![
+                upgrade(ChainConfigUpgradeActivation::View(200)),
+                upgrade(time(100)),
+                upgrade(ChainConfigUpgradeActivation::View(300)),
+                upgrade(time(200)),
+            ],
+        );
+        ok.validate().unwrap();
+
+        let mut bad = genesis.clone();
+        bad.chain_config_upgrades.insert(
+            v2,
+            vec![
+                upgrade(ChainConfigUpgradeActivation::View(200)),
+                upgrade(ChainConfigUpgradeActivation::View(100)),
+            ],
+        );
+        bad.validate().unwrap_err();
+
+        let mut bad = genesis.clone();
+        bad.chain_config_upgrades.insert(
+            v2,
+            vec![
+                upgrade(ChainConfigUpgradeActivation::View(100)),
+                upgrade(ChainConfigUpgradeActivation::View(100)),
+            ],
+        );
+        bad.validate().unwrap_err();
+
+        let mut bad = genesis.clone();
+        bad.chain_config_upgrades
+            .insert(v2, vec![upgrade(time(200)), upgrade(time(100))]);
+        bad.validate().unwrap_err();
+
+        let mut bad = genesis.clone();
+        bad.chain_config_upgrades
+            .insert(v2, vec![upgrade(time(100)), upgrade(time(100))]);
+        bad.validate().unwrap_err();
+    }
+
+    #[test]
+    fn test_genesis_chain_config_schedule() {
+        let mut genesis = Genesis::from_file("../data/genesis/demo.toml").unwrap();
+        let v2 = Version { major: 0, minor: 2 };
+        let v3 = Version { major: 0, minor: 3 };
+        let cf = ChainConfig {
+            max_block_size: 1.into(),
+            ..genesis.upgrades[&v2].upgrade_type.data()
+        };
+        genesis.chain_config_upgrades.insert(
+            v2,
+            vec![ChainConfigUpgrade {
+                activation: ChainConfigUpgradeActivation::View(100),
+                chain_config: cf,
+            }],
+        );
+
+        let schedule = genesis.chain_config_schedule();
+        assert_eq!(
+            schedule,
+            [
+                ScheduledChainConfig {
+                    version: genesis.base_version,
+                    activation: None,
+                    chain_config: genesis.chain_config,
+                },
+                ScheduledChainConfig {
+                    version: v2,
+                    activation: None,
+                    chain_config: genesis.upgrades[&v2].upgrade_type.data(),
+                },
+                ScheduledChainConfig {
+                    version: v2,
+                    activation: Some(ChainConfigUpgradeActivation::View(100)),
+                    chain_config: cf,
+                },
+                ScheduledChainConfig {
+                    version: v3,
+                    activation: None,
+                    chain_config: genesis.upgrades[&v3].upgrade_type.data(),
+                },
+            ]
+        );
+    }
 }
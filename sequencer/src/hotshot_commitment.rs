@@ -1,24 +1,66 @@
-use std::{error::Error, time::Duration};
+//! Task which commits blocks sequenced by HotShot to the HotShot contract on layer 1.
+//!
+//! Leaves are fetched from a query service, but not trusted: each leaf's QC is checked against a
+//! stake table loaded from a trusted network config before the leaf is submitted, and the only leaf
+//! accepted without signatures is the genesis leaf computed locally from the genesis file. The task
+//! reports its progress, lag behind HotShot and gas spent in a [`CommitmentTaskMonitor`].
+//!
+//! If a storage directory is configured, the progress is also saved to disk after each submission,
+//! so a restarted task resumes from the last height it submitted instead of the contract's.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use anyhow::anyhow;
-use async_std::{sync::Arc, task::sleep};
+use anyhow::{anyhow, bail, ensure, Context};
+use async_std::{
+    sync::{Arc, RwLock},
+    task::{sleep, spawn_blocking},
+};
 use async_trait::async_trait;
+use committable::{Commitment, Committable};
 use contract_bindings::hot_shot::{HotShot, HotShotErrors, Qc};
-use espresso_types::Header;
+use espresso_types::{
+    FeeVersion, Header, L1Client, Leaf, MarketplaceVersion, NodeState, PubKey, SequencerVersions,
+    ValidatedState, V0_0, V0_1,
+};
 use ethers::prelude::*;
 use futures::{
     future,
     stream::{self, StreamExt},
 };
-use hotshot_query_service::{availability::LeafQueryData, types::HeightIndexed};
+use hotshot::traits::election::static_committee::GeneralStaticCommittee;
+use hotshot_query_service::{
+    availability::LeafQueryData, metrics::PrometheusMetrics, types::HeightIndexed,
+};
+use hotshot_types::{
+    data::ViewNumber,
+    message::UpgradeLock,
+    simple_certificate::QuorumCertificate,
+    traits::{
+        election::Membership,
+        metrics::{Counter, Gauge, Metrics as _},
+        network::Topic,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::{Certificate, HasViewNumber},
+    PeerConfig,
+};
 use rand::SeedableRng;
 use rand_chacha::ChaChaRng;
 use rand_distr::Distribution;
 use sequencer_utils::{commitment_to_u256, contract_send, init_signer, Signer};
+use serde::{Deserialize, Serialize};
 use surf_disco::Url;
-use vbs::version::StaticVersionType;
+use vbs::version::{StaticVersionType, Version};
 
-use crate::SeqTypes;
+use crate::{
+    api::data_source::PublicNetworkConfig, catchup::StatePeers, genesis::L1Finalized, Genesis,
+    SeqTypes, SequencerApiVersion,
+};
 
 const RETRY_DELAY: Duration = Duration::from_secs(1);
 
@@ -59,10 +101,360 @@ pub struct CommitmentTaskOptions {
 
     /// If specified, sequencing attempts will be delayed by duration sampled from an exponential distribution with mean DELAY.
     pub delay: Option<Duration>,
+
+    /// Genesis file of the Espresso chain.
+    ///
+    /// The genesis leaf, which is the only leaf valid without a signed QC, is computed locally from
+    /// this file.
+    pub genesis_file: PathBuf,
+
+    /// Where to load the stake table which leaf QCs are checked against.
+    pub stake_table: StakeTableSource,
+
+    /// Directory to save the progress of the task in.
+    ///
+    /// If given, the task resumes from the last height it submitted after a restart. Otherwise it
+    /// starts from the block height of the contract.
+    pub storage_path: Option<PathBuf>,
+}
+
+/// A trusted source of the stake table.
+///
+/// This must not be the query service leaves are fetched from, or a malicious query service could
+/// certify its own leaves.
+#[derive(Clone, Debug)]
+pub enum StakeTableSource {
+    /// A public network config file, in the format served by the `config/hotshot` endpoint.
+    File(PathBuf),
+    /// A trusted sequencer node, whose `config/hotshot` endpoint serves the stake table.
+    Url(Url),
+}
+
+impl StakeTableSource {
+    async fn load<ApiVer: StaticVersionType>(
+        &self,
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<PeerConfig<PubKey>>> {
+        let config = match self {
+            Self::File(path) => {
+                let bytes = fs::read(path).context(format!("reading {}", path.display()))?;
+                serde_json::from_slice::<PublicNetworkConfig>(&bytes)
+                    .context(format!("malformed network config in {}", path.display()))?
+            }
+            Self::Url(url) => {
+                HotShotClient::<ApiVer>::builder(url.clone())
+                    .set_timeout(Some(timeout))
+                    .build()
+                    .get::<PublicNetworkConfig>("config/hotshot")
+                    .send()
+                    .await?
+            }
+        };
+        Ok(config.known_nodes_with_stake().to_vec())
+    }
+}
+
+/// Progress of the commitment task, as reported by its status API.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CommitmentTaskStatus {
+    /// Number of blocks committed to the HotShot contract as of the last submission.
+    pub contract_block_height: u64,
+    /// Block height of HotShot as of the last submission.
+    pub hotshot_block_height: u64,
+    /// Hash of the last L1 transaction which submitted blocks.
+    pub last_tx: Option<H256>,
+    /// Total gas used by transactions submitting blocks, since the task started, or since storage
+    /// was first used if the task has storage.
+    pub gas_used: U256,
+    /// Total fees paid for transactions submitting blocks, in wei, since the task started, or since
+    /// storage was first used if the task has storage.
+    pub fees_paid: U256,
+    /// The error from the last attempt to submit blocks, if it failed.
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct CommitmentTaskMetrics {
+    contract_block_height: Box<dyn Gauge>,
+    hotshot_block_height: Box<dyn Gauge>,
+    lag: Box<dyn Gauge>,
+    gas_used: Box<dyn Counter>,
+    fees_paid: Box<dyn Counter>,
+    failures: Box<dyn Counter>,
+    rejected_leaves: Box<dyn Counter>,
+}
+
+/// Tracks the progress of the commitment task and exports it as Prometheus metrics.
+#[derive(Debug)]
+pub struct CommitmentTaskMonitor {
+    status: CommitmentTaskStatus,
+    registry: PrometheusMetrics,
+    metrics: CommitmentTaskMetrics,
+}
+
+impl Default for CommitmentTaskMonitor {
+    fn default() -> Self {
+        let registry = PrometheusMetrics::default();
+        let metrics = CommitmentTaskMetrics {
+            contract_block_height: registry.create_gauge("contract_block_height".into(), None),
+            hotshot_block_height: registry.create_gauge("hotshot_block_height".into(), None),
+            lag: registry.create_gauge("lag".into(), Some("blocks".into())),
+            gas_used: registry.create_counter("gas_used".into(), None),
+            fees_paid: registry.create_counter("fees_paid".into(), Some("gwei".into())),
+            failures: registry.create_counter("submission_failures".into(), None),
+            rejected_leaves: registry.create_counter("rejected_leaves".into(), None),
+        };
+        Self {
+            status: Default::default(),
+            registry,
+            metrics,
+        }
+    }
+}
+
+impl CommitmentTaskMonitor {
+    pub fn status(&self) -> &CommitmentTaskStatus {
+        &self.status
+    }
+
+    /// The Prometheus metrics of the commitment task.
+    pub fn metrics(&self) -> &PrometheusMetrics {
+        &self.registry
+    }
+
+    /// Resume from `status`, as saved by a previous run of the task.
+    fn restore(&mut self, status: CommitmentTaskStatus) {
+        self.set_heights(status.contract_block_height, status.hotshot_block_height);
+        self.metrics
+            .gas_used
+            .add(status.gas_used.low_u64() as usize);
+        self.metrics
+            .fees_paid
+            .add((status.fees_paid / U256::exp10(9)).low_u64() as usize);
+        self.status = status;
+    }
+
+    fn set_heights(&mut self, contract_block_height: u64, hotshot_block_height: u64) {
+        self.status.contract_block_height = contract_block_height;
+        self.status.hotshot_block_height = hotshot_block_height;
+        self.metrics
+            .contract_block_height
+            .set(contract_block_height as usize);
+        self.metrics
+            .hotshot_block_height
+            .set(hotshot_block_height as usize);
+        self.metrics
+            .lag
+            .set(hotshot_block_height.saturating_sub(contract_block_height) as usize);
+    }
+
+    fn record_submission(&mut self, submission: &Submission) {
+        self.set_heights(
+            submission.contract_block_height,
+            submission.hotshot_block_height,
+        );
+        self.status.last_tx = Some(submission.receipt.transaction_hash);
+        self.status.last_error = None;
+
+        let gas_used = submission.receipt.gas_used.unwrap_or_default();
+        let fee = gas_used * submission.receipt.effective_gas_price.unwrap_or_default();
+        self.status.gas_used += gas_used;
+        self.status.fees_paid += fee;
+        self.metrics.gas_used.add(gas_used.low_u64() as usize);
+        self.metrics
+            .fees_paid
+            .add((fee / U256::exp10(9)).low_u64() as usize);
+    }
+
+    fn record_error(&mut self, err: &SyncError) {
+        self.metrics.failures.add(1);
+        self.status.last_error = Some(match err {
+            SyncError::TransactionFailed { err, num_leaves } => {
+                format!("submitting {num_leaves} leaves: {err:#}")
+            }
+            SyncError::InvalidLeaf { height, err } => {
+                self.metrics.rejected_leaves.add(1);
+                format!("invalid leaf {height}: {err:#}")
+            }
+            SyncError::Other(err) => format!("{err:#}"),
+        });
+    }
+}
+
+/// On-disk record of the progress of the commitment task.
+#[derive(Clone, Debug)]
+pub struct CommitmentTaskStorage {
+    path: PathBuf,
+}
+
+impl CommitmentTaskStorage {
+    /// Open storage in the directory `path`, creating it if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        fs::create_dir_all(path).context(format!("creating {}", path.display()))?;
+        Ok(Self {
+            path: path.join("status.json"),
+        })
+    }
+
+    /// The status as of the last submission, if any submission has been saved.
+    pub fn load(&self) -> anyhow::Result<Option<CommitmentTaskStatus>> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).context(format!("reading {}", self.path.display())),
+        };
+        let status = serde_json::from_slice(&bytes)
+            .context(format!("malformed status in {}", self.path.display()))?;
+        Ok(Some(status))
+    }
+
+    pub async fn save(&self, status: &CommitmentTaskStatus) -> anyhow::Result<()> {
+        let path = self.path.clone();
+        let bytes = serde_json::to_vec(status)?;
+        spawn_blocking(move || {
+            // Write to a temporary file first, so that the status is replaced atomically.
+            let swap_path = path.with_extension("swp");
+            fs::write(&swap_path, bytes).context(format!("writing {}", swap_path.display()))?;
+            fs::rename(&swap_path, &path).context(format!("replacing {}", path.display()))
+        })
+        .await
+    }
+}
+
+/// Restore the progress saved in `storage` into `monitor`.
+///
+/// Returns the height to resume submitting from, if a previous run of the task saved one.
+async fn resume(
+    storage: &CommitmentTaskStorage,
+    monitor: &RwLock<CommitmentTaskMonitor>,
+) -> anyhow::Result<Option<u64>> {
+    let Some(status) = storage.load()? else {
+        return Ok(None);
+    };
+    let height = status.contract_block_height;
+    tracing::info!("resuming from block {height}");
+    monitor.write().await.restore(status);
+    Ok(Some(height))
+}
+
+/// Checks that leaves fetched from the query service were decided by a quorum of the stake table.
+#[derive(Clone)]
+pub struct QcVerifier {
+    membership: <SeqTypes as NodeType>::Membership,
+    genesis: Commitment<Leaf>,
+}
+
+impl QcVerifier {
+    /// A verifier for leaves decided by `known_nodes_with_stake`, descending from the genesis leaf
+    /// with commitment `genesis`.
+    pub fn new(known_nodes_with_stake: Vec<PeerConfig<PubKey>>, genesis: Commitment<Leaf>) -> Self {
+        Self {
+            membership: GeneralStaticCommittee::new(
+                known_nodes_with_stake.clone(),
+                known_nodes_with_stake,
+                Topic::Global,
+            ),
+            genesis,
+        }
+    }
+
+    /// Check that `leaf` is certified by a valid QC.
+    pub async fn verify(&self, leaf: &LeafQueryData<SeqTypes>) -> anyhow::Result<()> {
+        let qc = leaf.qc();
+        ensure!(
+            qc.data.leaf_commit == leaf.leaf().commit(),
+            "QC does not certify this leaf"
+        );
+        ensure!(
+            qc.view_number() == leaf.leaf().view_number(),
+            "QC is for view {:?}, but leaf is for view {:?}",
+            qc.view_number(),
+            leaf.leaf().view_number()
+        );
+
+        // The genesis QC is not signed, and HotShot accepts any QC for the genesis view, so the
+        // only leaf we accept in the genesis view is the one we computed ourselves.
+        if qc.view_number() == ViewNumber::genesis() || qc.signatures.is_none() {
+            ensure!(
+                leaf.height() == 0 && leaf.leaf().commit() == self.genesis,
+                "QC is not signed, but leaf {} is not the genesis leaf",
+                leaf.height()
+            );
+            return Ok(());
+        }
+
+        ensure!(
+            self.is_signed_by_quorum(qc, leaf.leaf().block_header().version())
+                .await?,
+            "QC is not signed by a quorum of the stake table"
+        );
+        Ok(())
+    }
+
+    /// Check the signatures on `qc`, which certifies a leaf with a header of version `version`.
+    ///
+    /// Votes are signed under the protocol version in effect in the view they are cast in, which
+    /// is the version of the header they vote for, so QCs remain verifiable across upgrades.
+    async fn is_signed_by_quorum(
+        &self,
+        qc: &QuorumCertificate<SeqTypes>,
+        version: Version,
+    ) -> anyhow::Result<bool> {
+        Ok(if version == V0_1::version() {
+            self.is_valid_cert::<V0_1>(qc).await
+        } else if version == FeeVersion::version() {
+            self.is_valid_cert::<FeeVersion>(qc).await
+        } else if version == MarketplaceVersion::version() {
+            self.is_valid_cert::<MarketplaceVersion>(qc).await
+        } else {
+            bail!("unsupported protocol version {version}");
+        })
+    }
+
+    async fn is_valid_cert<Ver: StaticVersionType + 'static>(
+        &self,
+        qc: &QuorumCertificate<SeqTypes>,
+    ) -> bool {
+        let upgrade_lock = UpgradeLock::<SeqTypes, SequencerVersions<Ver, V0_0>>::new();
+        qc.is_valid_cert(&self.membership, &upgrade_lock).await
+    }
+}
+
+/// Compute the genesis leaf of the chain started from `genesis`, as the sequencer nodes do.
+async fn genesis_leaf(genesis: Genesis, l1_client: L1Client) -> Leaf {
+    let mut genesis_state = ValidatedState {
+        chain_config: genesis.chain_config.into(),
+        ..Default::default()
+    };
+    for (address, amount) in genesis.accounts {
+        genesis_state.prefund_account(address, amount);
+    }
+    let l1_genesis = match genesis.l1_finalized {
+        L1Finalized::Block(b) => b,
+        L1Finalized::Number { number } => l1_client.wait_for_finalized_block(number).await,
+    };
+    // The genesis leaf does not depend on any state we would have to catch up on.
+    let mut instance = NodeState::new(
+        0,
+        genesis.chain_config,
+        l1_client,
+        StatePeers::<SequencerApiVersion>::default(),
+        genesis.base_version,
+    )
+    .with_genesis(genesis_state)
+    .with_upgrades(genesis.upgrades)
+    .with_chain_config_upgrades(genesis.chain_config_upgrades);
+    instance.genesis_header = genesis.header;
+    instance.l1_genesis = Some(l1_genesis);
+    Leaf::genesis(&instance.genesis_state, &instance).await
 }
 
 /// main logic for the commitment task, which sync the latest blocks from HotShot to L1 contracts
-pub async fn run_hotshot_commitment_task<ApiVer: StaticVersionType>(opt: &CommitmentTaskOptions) {
+pub async fn run_hotshot_commitment_task<ApiVer: StaticVersionType>(
+    opt: &CommitmentTaskOptions,
+    monitor: Arc<RwLock<CommitmentTaskMonitor>>,
+) {
     // init a client connecting to HotShot query service
     let hotshot = HotShotClient::<ApiVer>::builder(
         opt.query_service_url
@@ -73,6 +465,21 @@ pub async fn run_hotshot_commitment_task<ApiVer: StaticVersionType>(opt: &Commit
     .build();
     hotshot.connect(None).await;
 
+    // Leaves are verified against a stake table and genesis we trust, never against anything
+    // served by the node we are fetching them from.
+    let stake_table = loop {
+        match opt.stake_table.load::<ApiVer>(opt.request_timeout).await {
+            Ok(stake_table) => break stake_table,
+            Err(err) => {
+                tracing::error!("unable to load stake table: {err:#}");
+                sleep(RETRY_DELAY).await;
+            }
+        }
+    };
+    let genesis = Genesis::from_file(&opt.genesis_file).unwrap();
+    let genesis = genesis_leaf(genesis, L1Client::new(opt.l1_provider.clone(), 10000)).await;
+    let verifier = QcVerifier::new(stake_table, genesis.commit());
+
     // init a signer connecting to the HotShot contract
     let signer = init_signer(
         &opt.l1_provider,
@@ -84,13 +491,30 @@ pub async fn run_hotshot_commitment_task<ApiVer: StaticVersionType>(opt: &Commit
     .unwrap();
     let contract = HotShot::new(opt.hotshot_address, signer.clone());
 
-    sequence(hotshot, contract, opt.delay).await;
+    let storage = opt
+        .storage_path
+        .as_ref()
+        .map(CommitmentTaskStorage::open)
+        .transpose()
+        .unwrap();
+    sequence(
+        hotshot,
+        contract,
+        &verifier,
+        opt.delay,
+        &monitor,
+        storage.as_ref(),
+    )
+    .await;
 }
 
-async fn sequence<ApiVer: StaticVersionType>(
-    hotshot: HotShotClient<ApiVer>,
+async fn sequence(
+    hotshot: impl HotShotDataSource,
     contract: HotShot<Signer>,
+    verifier: &QcVerifier,
     delay: Option<Duration>,
+    monitor: &RwLock<CommitmentTaskMonitor>,
+    storage: Option<&CommitmentTaskStorage>,
 ) {
     // Get the maximum number of blocks the contract will allow at a time.
     let hard_block_limit = match contract.max_blocks().call().await {
@@ -106,31 +530,76 @@ async fn sequence<ApiVer: StaticVersionType>(
     // A gas limit exception and decrease the limit
     // If we succeed, we increase the limit towards the hard_block_limit
     let mut soft_block_limit = hard_block_limit;
+
+    // The height of the next block to submit. We only read it from the contract if we have no
+    // record of our own submissions, or if a submission failed, in which case our record may be
+    // out of date (e.g. another commitment task submitted the same blocks first).
+    let mut next_height = match storage {
+        Some(storage) => match resume(storage, monitor).await {
+            Ok(height) => height,
+            Err(err) => {
+                tracing::error!("unable to load saved progress: {err:#}");
+                panic!("hotshot commitment task will exit");
+            }
+        },
+        None => None,
+    };
+
     let mut rng = ChaChaRng::from_entropy();
     loop {
-        if let Err(sync_err) = sync_with_l1(soft_block_limit, &hotshot, &contract).await {
-            match sync_err {
-                SyncError::Other(err) => {
-                    tracing::error!("error synchronizing with HotShot contract: {err}");
-                }
-                SyncError::TransactionFailed { err, num_leaves } => {
-                    // Assume we have hit a gas limit exception, decrease the limit
-                    tracing::error!("error synchronizing with HotShot contract, leaf submission failed with {num_leaves}: {err}");
-                    soft_block_limit = std::cmp::max(num_leaves / 2, 1)
+        let from_height = match next_height {
+            Some(height) => Ok(height),
+            None => contract_block_height(&contract).await,
+        };
+        let res = match from_height {
+            Ok(height) => {
+                sync_with_l1(soft_block_limit, height, &hotshot, &contract, verifier).await
+            }
+            Err(err) => Err(err),
+        };
+        match res {
+            Err(sync_err) => {
+                monitor.write().await.record_error(&sync_err);
+                match sync_err {
+                    SyncError::Other(err) => {
+                        tracing::error!("error synchronizing with HotShot contract: {err}");
+                    }
+                    SyncError::InvalidLeaf { height, err } => {
+                        tracing::error!("refusing to submit leaf {height}: {err:#}");
+                    }
+                    SyncError::TransactionFailed { err, num_leaves } => {
+                        // Assume we have hit a gas limit exception, decrease the limit
+                        tracing::error!("error synchronizing with HotShot contract, leaf submission failed with {num_leaves}: {err}");
+                        soft_block_limit = std::cmp::max(num_leaves / 2, 1);
+                        next_height = None;
+                    }
                 }
+                // Wait a bit to avoid spam, then try again.
+                sleep(RETRY_DELAY).await;
             }
-            // Wait a bit to avoid spam, then try again.
-            sleep(RETRY_DELAY).await;
-        } else {
-            // If we succeed, increase the limit
-            soft_block_limit = std::cmp::min(soft_block_limit * 2, hard_block_limit);
-            if let Some(delay) = delay {
-                // Create an exponential distribution for sampling delay times. The distribution should have
-                // mean `delay`, or parameter `\lambda = 1 / delay`.
-                let delay_distr =
-                    rand_distr::Exp::<f64>::new(1f64 / delay.as_millis() as f64).unwrap();
-                let delay = Duration::from_millis(delay_distr.sample(&mut rng) as u64);
-                sleep(delay).await;
+            Ok(submission) => {
+                next_height = Some(submission.contract_block_height);
+                let status = {
+                    let mut monitor = monitor.write().await;
+                    monitor.record_submission(&submission);
+                    monitor.status().clone()
+                };
+                if let Some(storage) = storage {
+                    if let Err(err) = storage.save(&status).await {
+                        tracing::warn!("unable to save progress: {err:#}");
+                    }
+                }
+
+                // If we succeed, increase the limit
+                soft_block_limit = std::cmp::min(soft_block_limit * 2, hard_block_limit);
+                if let Some(delay) = delay {
+                    // Create an exponential distribution for sampling delay times. The distribution should have
+                    // mean `delay`, or parameter `\lambda = 1 / delay`.
+                    let delay_distr =
+                        rand_distr::Exp::<f64>::new(1f64 / delay.as_millis() as f64).unwrap();
+                    let delay = Duration::from_millis(delay_distr.sample(&mut rng) as u64);
+                    sleep(delay).await;
+                }
             }
         }
     }
@@ -177,21 +646,42 @@ enum SyncError {
         err: anyhow::Error,
         num_leaves: usize,
     },
+    /// The next leaf to submit is not certified by a valid QC.
+    InvalidLeaf {
+        height: u64,
+        err: anyhow::Error,
+    },
     Other(anyhow::Error),
 }
 
-/// main logic for catching up with HotShot contract on L1
-async fn sync_with_l1(
-    max_blocks: usize,
-    hotshot: &impl HotShotDataSource,
-    contract: &HotShot<Signer>,
-) -> Result<(), SyncError> {
-    let contract_block_height = contract
+/// A successful submission of leaves to the HotShot contract.
+#[derive(Debug)]
+struct Submission {
+    /// Number of blocks committed to the contract after the submission.
+    contract_block_height: u64,
+    /// Block height of HotShot at the time of the submission.
+    hotshot_block_height: u64,
+    receipt: TransactionReceipt,
+}
+
+async fn contract_block_height(contract: &HotShot<Signer>) -> Result<u64, SyncError> {
+    Ok(contract
         .block_height()
         .call()
         .await
         .map_err(|e| SyncError::Other(e.into()))?
-        .as_u64();
+        .as_u64())
+}
+
+/// main logic for catching up with HotShot contract on L1, starting from the block at
+/// `contract_block_height`
+async fn sync_with_l1(
+    max_blocks: usize,
+    contract_block_height: u64,
+    hotshot: &impl HotShotDataSource,
+    contract: &HotShot<Signer>,
+    verifier: &QcVerifier,
+) -> Result<Submission, SyncError> {
     let hotshot_block_height = loop {
         let height = hotshot
             .block_height()
@@ -214,20 +704,27 @@ async fn sync_with_l1(
     };
 
     // Download leaves between `contract_block_height` and `hotshot_block_height`.
+    let mut invalid = None;
     let leaves = stream::iter(contract_block_height..hotshot_block_height)
         .take(max_blocks)
-        .then(|height| hotshot.get_leaf(height))
-        // It is possible that we failed to fetch some leaves. But as long as we successfully
-        // fetched a prefix of the desired list (since leaves must be sent to the contract in order)
-        // we can make some progress.
-        .scan(contract_block_height, |height, leaf| {
+        .then(|height| async move {
+            let leaf = hotshot.get_leaf(height).await.map_err(|err| {
+                tracing::error!("error fetching leaf {height}: {err}");
+                None
+            })?;
+            if let Err(err) = verifier.verify(&leaf).await {
+                return Err(Some((height, err)));
+            }
+            Ok(leaf)
+        })
+        // It is possible that we failed to fetch or verify some leaves. But as long as we
+        // successfully fetched a prefix of the desired list (since leaves must be sent to the
+        // contract in order) we can make some progress.
+        .scan((), |_, leaf| {
             future::ready(match leaf {
-                Ok(leaf) => {
-                    *height += 1;
-                    Some(leaf)
-                }
+                Ok(leaf) => Some(leaf),
                 Err(err) => {
-                    tracing::error!("error fetching leaf {height}: {err}");
+                    invalid = err;
                     None
                 }
             })
@@ -235,7 +732,13 @@ async fn sync_with_l1(
         .collect::<Vec<_>>()
         .await;
     if leaves.is_empty() {
-        return Err(SyncError::Other(anyhow!("failed to fetch any leaves")));
+        return Err(match invalid {
+            Some((height, err)) => SyncError::InvalidLeaf { height, err },
+            None => SyncError::Other(anyhow!("failed to fetch any leaves")),
+        });
+    }
+    if let Some((height, err)) = invalid {
+        tracing::warn!("leaf {height} is invalid, submitting only the leaves before it: {err:#}");
     }
     let num_leaves = leaves.len();
     tracing::info!(
@@ -250,11 +753,15 @@ async fn sync_with_l1(
     // error. We will retry, and may end up changing the transaction we send if the contract state
     // has changed, which is one possible cause of the transaction failure. This can happen, for
     // example, if there are multiple commitment tasks racing.
-    contract_send::<_, _, HotShotErrors>(&txn)
+    let (receipt, _) = contract_send::<_, _, HotShotErrors>(&txn)
         .await
         .map_err(|e| SyncError::TransactionFailed { err: e, num_leaves })?;
 
-    Ok(())
+    Ok(Submission {
+        contract_block_height: contract_block_height + num_leaves as u64,
+        hotshot_block_height,
+        receipt,
+    })
 }
 
 /// prepare the transaction from new leaves (with QC) from HotShot
@@ -276,13 +783,18 @@ fn build_sequence_batches_txn<M: ethers::prelude::Middleware>(
 #[cfg(test)]
 mod test {
     use async_std::task::spawn;
-    use committable::Committable;
+    use bitvec::bitvec;
     use contract_bindings::hot_shot::{NewBlocksCall, NewBlocksFilter};
-    use espresso_types::{L1Client, Leaf, NodeState, ValidatedState};
+    use espresso_types::PrivKey;
     use ethers::{abi::AbiDecode, providers::Middleware};
     use futures::FutureExt;
     use hotshot_example_types::node_types::TestVersions;
-    use hotshot_types::simple_certificate::QuorumCertificate;
+    use hotshot_types::{
+        data::QuorumProposal,
+        light_client::StateKeyPair,
+        simple_vote::{QuorumData, VersionedVoteData},
+        traits::signature_key::SignatureKey,
+    };
     use sequencer_utils::{
         test_utils::{setup_test, TestL1System},
         AnvilOptions,
@@ -331,16 +843,93 @@ mod test {
         }
     }
 
+    const NUM_VALIDATORS: u64 = 5;
+
+    fn validators(seed: [u8; 32]) -> Vec<(PubKey, PrivKey)> {
+        (0..NUM_VALIDATORS)
+            .map(|i| PubKey::generated_from_seed_indexed(seed, i))
+            .collect()
+    }
+
+    fn stake_table(validators: &[(PubKey, PrivKey)]) -> Vec<PeerConfig<PubKey>> {
+        validators
+            .iter()
+            .enumerate()
+            .map(|(i, (pub_key, _))| PeerConfig {
+                stake_table_entry: pub_key.stake_table_entry(1),
+                state_ver_key: StateKeyPair::generate_from_seed_indexed([0; 32], i as u64)
+                    .ver_key(),
+            })
+            .collect()
+    }
+
+    /// A QC for `leaf` signed by all of `validators`.
+    async fn sign_qc(leaf: &Leaf, validators: &[(PubKey, PrivKey)]) -> QuorumCertificate<SeqTypes> {
+        let stake_table = stake_table(validators);
+        let membership = <SeqTypes as NodeType>::Membership::new(
+            stake_table.clone(),
+            stake_table,
+            Topic::Global,
+        );
+        let data = QuorumData {
+            leaf_commit: <Leaf as Committable>::commit(leaf),
+        };
+
+        // The mock leaves have version 0.1 headers, so votes are signed under version 0.1.
+        let upgrade_lock = UpgradeLock::<SeqTypes, SequencerVersions<V0_1, V0_0>>::new();
+        let vote_commit = VersionedVoteData::new(data.clone(), leaf.view_number(), &upgrade_lock)
+            .await
+            .unwrap()
+            .commit();
+        let signatures = validators
+            .iter()
+            .map(|(_, priv_key)| PubKey::sign(priv_key, vote_commit.as_ref()).unwrap())
+            .collect::<Vec<_>>();
+        let qc_pp = PubKey::public_parameter(
+            membership.stake_table(),
+            U256::from(membership.success_threshold().get()),
+        );
+        let signatures = PubKey::assemble(&qc_pp, &bitvec![1; validators.len()], &signatures);
+
+        QuorumCertificate::new(
+            data.clone(),
+            data.commit(),
+            leaf.view_number(),
+            Some(signatures),
+            Default::default(),
+        )
+    }
+
+    /// A leaf at `height`, certified by the test validators.
+    ///
+    /// Height 0 is the genesis leaf, certified by the unsigned genesis QC.
     async fn mock_leaf(height: u64, node_state: &NodeState) -> LeafQueryData<SeqTypes> {
-        let mut leaf = Leaf::genesis(&ValidatedState::default(), node_state).await;
-        let mut qc =
+        let genesis = Leaf::genesis(&ValidatedState::default(), node_state).await;
+        let genesis_qc =
             QuorumCertificate::genesis::<TestVersions>(&ValidatedState::default(), node_state)
                 .await;
-        *leaf.block_header_mut().height_mut() = height;
-        qc.data.leaf_commit = <Leaf as Committable>::commit(&leaf);
+        if height == 0 {
+            return LeafQueryData::new(genesis, genesis_qc).unwrap();
+        }
+
+        let mut block_header = genesis.block_header().clone();
+        *block_header.height_mut() = height;
+        let leaf = Leaf::from_quorum_proposal(&QuorumProposal {
+            block_header,
+            view_number: ViewNumber::new(height),
+            justify_qc: genesis_qc,
+            upgrade_certificate: None,
+            proposal_certificate: None,
+        });
+        let qc = sign_qc(&leaf, &validators([0; 32])).await;
         LeafQueryData::new(leaf, qc).unwrap()
     }
 
+    async fn verifier(node_state: &NodeState) -> QcVerifier {
+        let genesis = Leaf::genesis(&ValidatedState::default(), node_state).await;
+        QcVerifier::new(stake_table(&validators([0; 32])), genesis.commit())
+    }
+
     async fn wait_for_new_batches(
         l1: &TestL1System,
         from_block: u64,
@@ -403,7 +992,15 @@ mod test {
         assert!(size < 131072);
 
         // Sequence them in the HotShot contract.
-        sync_with_l1(num_batches, &data, &hotshot).await.unwrap();
+        sync_with_l1(
+            num_batches,
+            initial_batch_num.as_u64(),
+            &data,
+            &hotshot,
+            &verifier(&node_state).await,
+        )
+        .await
+        .unwrap();
 
         // Check the NewBatches event.
         let (event, meta) = wait_for_new_batches(&l1, l1_initial_block.as_u64()).await;
@@ -453,12 +1050,15 @@ mod test {
         let node_state =
             NodeState::mock().with_l1(L1Client::new(anvil.provider().url().clone(), 1));
         data.leaves.push(Some(mock_leaf(0, &node_state).await));
+        let verifier = verifier(&node_state).await;
 
         // Connect to the HotShot contract with the expected L1 client.
         let hotshot = HotShot::new(l1.hotshot.address(), adaptor_l1_signer);
 
         // Sequence them in the HotShot contract.
-        sync_with_l1(1, &data, &hotshot).await.unwrap();
+        sync_with_l1(1, 0, &data, &hotshot, &verifier)
+            .await
+            .unwrap();
 
         // Check the NewBatches event.
         let (event, meta) = wait_for_new_batches(&l1, from_block.as_u64()).await;
@@ -469,7 +1069,8 @@ mod test {
         let fut = {
             let data = data.clone();
             let hotshot = hotshot.clone();
-            spawn(async move { sync_with_l1(1, &data, &hotshot).await })
+            let verifier = verifier.clone();
+            spawn(async move { sync_with_l1(1, 1, &data, &hotshot, &verifier).await })
         };
         // Sleep for a few seconds and make sure nothing happened.
         sleep(Duration::from_secs(3)).await;
@@ -478,7 +1079,9 @@ mod test {
 
         // Once a new batch is available, we can sequence it.
         data.leaves.push(Some(mock_leaf(1, &node_state).await));
-        sync_with_l1(1, &data, &hotshot).await.unwrap();
+        sync_with_l1(1, 1, &data, &hotshot, &verifier)
+            .await
+            .unwrap();
         let (event, _) = wait_for_new_batches(&l1, from_block.as_u64()).await;
         assert_eq!(event.first_block_number.as_u64(), 1);
 
@@ -518,22 +1121,157 @@ mod test {
         let mut data = MockDataSource::default();
         data.leaves
             .extend([None, Some(mock_leaf(1, &node_state).await), None]);
+        let verifier = verifier(&node_state).await;
 
         // Connect to the HotShot contract with the expected L1 client.
         let hotshot = HotShot::new(l1.hotshot.address(), adaptor_l1_signer);
 
         // If the first leaf is missing, we cannot make any progress, and sync should fail.
-        sync_with_l1(3, &data, &hotshot).await.unwrap_err();
+        sync_with_l1(3, 0, &data, &hotshot, &verifier)
+            .await
+            .unwrap_err();
 
         // If the first leaf is present but subsequent leaves are missing, we should sequence the
         // leaves that are available.
 
         data.leaves[0] = Some(mock_leaf(0, &node_state).await);
-        sync_with_l1(3, &data, &hotshot).await.unwrap();
+        sync_with_l1(3, 0, &data, &hotshot, &verifier)
+            .await
+            .unwrap();
 
         // Check the NewBatches event.
         let event = wait_for_new_batches(&l1, l1_initial_block.as_u64()).await.0;
         assert_eq!(event.first_block_number, 0.into());
         assert_eq!(event.num_blocks, 2.into());
     }
+
+    #[async_std::test]
+    async fn test_resume_after_restart() {
+        setup_test();
+
+        let anvil = AnvilOptions::default().spawn().await;
+        let l1 = TestL1System::deploy(anvil.provider()).await.unwrap();
+        let adaptor_l1_signer = Arc::new(
+            init_signer(l1.provider.url(), TEST_MNEMONIC, l1.clients.funded[0].index)
+                .await
+                .unwrap(),
+        );
+        let hotshot = HotShot::new(l1.hotshot.address(), adaptor_l1_signer);
+
+        let node_state =
+            NodeState::mock().with_l1(L1Client::new(anvil.provider().url().clone(), 1));
+        let verifier = verifier(&node_state).await;
+        let mut data = MockDataSource::default();
+        for height in 0..2 {
+            data.leaves.push(Some(mock_leaf(height, &node_state).await));
+        }
+
+        let tmp = tempfile::tempdir().unwrap();
+        let storage = CommitmentTaskStorage::open(tmp.path()).unwrap();
+        assert_eq!(storage.load().unwrap(), None);
+
+        // Run the task until it has submitted and saved the available leaves.
+        let task = {
+            let data = data.clone();
+            let hotshot = hotshot.clone();
+            let verifier = verifier.clone();
+            let storage = storage.clone();
+            spawn(async move {
+                let monitor = RwLock::new(CommitmentTaskMonitor::default());
+                sequence(data, hotshot, &verifier, None, &monitor, Some(&storage)).await
+            })
+        };
+        let saved = loop {
+            match storage.load().unwrap() {
+                Some(status) if status.contract_block_height == 2 => break status,
+                _ => sleep(Duration::from_millis(100)).await,
+            }
+        };
+        task.cancel().await;
+        assert_eq!(saved.hotshot_block_height, 2);
+        assert!(saved.last_tx.is_some());
+        assert!(saved.gas_used > U256::zero());
+
+        // A restarted task picks up the saved progress.
+        let storage = CommitmentTaskStorage::open(tmp.path()).unwrap();
+        let monitor = Arc::new(RwLock::new(CommitmentTaskMonitor::default()));
+        assert_eq!(resume(&storage, &monitor).await.unwrap(), Some(2));
+        assert_eq!(*monitor.read().await.status(), saved);
+
+        // It continues with the next leaf, and accumulates gas on top of the saved total.
+        let from_block = l1.provider.get_block_number().await.unwrap();
+        data.leaves.push(Some(mock_leaf(2, &node_state).await));
+        let task = {
+            let monitor = monitor.clone();
+            let storage = storage.clone();
+            spawn(async move {
+                sequence(data, hotshot, &verifier, None, &monitor, Some(&storage)).await
+            })
+        };
+        let (event, _) = wait_for_new_batches(&l1, from_block.as_u64()).await;
+        assert_eq!(event.first_block_number.as_u64(), 2);
+        assert_eq!(event.num_blocks.as_u64(), 1);
+        let status = loop {
+            match storage.load().unwrap() {
+                Some(status) if status.contract_block_height == 3 => break status,
+                _ => sleep(Duration::from_millis(100)).await,
+            }
+        };
+        task.cancel().await;
+        assert!(status.gas_used > saved.gas_used);
+        assert_eq!(status, *monitor.read().await.status());
+    }
+
+    #[async_std::test]
+    async fn test_verify_leaf() {
+        setup_test();
+
+        let node_state = NodeState::mock();
+        let verifier = verifier(&node_state).await;
+
+        // The genesis leaf is valid with the unsigned genesis QC.
+        let genesis = mock_leaf(0, &node_state).await;
+        verifier.verify(&genesis).await.unwrap();
+
+        // Later leaves are valid when signed by a quorum of the stake table.
+        for height in 1..3 {
+            verifier
+                .verify(&mock_leaf(height, &node_state).await)
+                .await
+                .unwrap();
+        }
+
+        // A QC for a different view does not certify the leaf.
+        let mut qc = genesis.qc().clone();
+        qc.view_number = ViewNumber::new(1);
+        let leaf = LeafQueryData::new(genesis.leaf().clone(), qc).unwrap();
+        verifier.verify(&leaf).await.unwrap_err();
+
+        // A leaf forged in the genesis view, with an unsigned QC, is not valid at any other height.
+        let mut forged = genesis.leaf().clone();
+        *forged.block_header_mut().height_mut() = 1;
+        let mut qc = genesis.qc().clone();
+        qc.data.leaf_commit = <Leaf as Committable>::commit(&forged);
+        let forged = LeafQueryData::new(forged, qc).unwrap();
+        let err = verifier.verify(&forged).await.unwrap_err();
+        assert!(err.to_string().contains("not the genesis leaf"), "{err:#}");
+
+        // Nor is a different leaf at height 0.
+        let mut forged = genesis.leaf().clone();
+        *forged.block_header_mut().timestamp_mut() += 1;
+        let mut qc = genesis.qc().clone();
+        qc.data.leaf_commit = <Leaf as Committable>::commit(&forged);
+        let forged = LeafQueryData::new(forged, qc).unwrap();
+        verifier.verify(&forged).await.unwrap_err();
+
+        // A leaf signed by validators outside the stake table is not valid.
+        let leaf = mock_leaf(1, &node_state).await;
+        let qc = sign_qc(leaf.leaf(), &validators([1; 32])).await;
+        let leaf = LeafQueryData::new(leaf.leaf().clone(), qc).unwrap();
+        let err = verifier.verify(&leaf).await.unwrap_err();
+        assert!(
+            err.to_string().contains("not signed by a quorum"),
+            "{err:#}"
+        );
+    }
 }
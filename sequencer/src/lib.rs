@@ -10,9 +10,9 @@ pub mod state_signature;
 
 mod message_compat_tests;
 
-use anyhow::Context;
+use anyhow::{ensure, Context};
 use async_std::sync::RwLock;
-use catchup::StatePeers;
+use catchup::{PeerScoringParams, StatePeers};
 use context::SequencerContext;
 use espresso_types::{
     traits::EventConsumer, BackoffParams, L1Client, NodeState, PubKey, SeqTypes,
@@ -26,10 +26,14 @@ use genesis::L1Finalized;
 use libp2p::Multiaddr;
 use network::libp2p::split_off_peer_id;
 use options::Identity;
-use state_signature::static_stake_table_commitment;
+use state_signature::static_stake_table_state;
 use url::Url;
 pub mod persistence;
+pub mod snapshot;
 pub mod state;
+#[cfg(any(test, feature = "testing"))]
+pub mod upgrade_simulation;
+pub mod upgrade_status;
 
 #[cfg(feature = "libp2p")]
 use std::time::Duration;
@@ -111,6 +115,7 @@ pub struct NetworkParams {
     pub state_peers: Vec<Url>,
     pub config_peers: Option<Vec<Url>>,
     pub catchup_backoff: BackoffParams,
+    pub catchup_scoring: PeerScoringParams,
     /// The address to advertise as our public API's URL
     pub public_api_url: Option<Url>,
 
@@ -124,8 +129,12 @@ pub struct NetworkParams {
 }
 
 pub struct L1Params {
-    pub url: Url,
+    pub urls: Vec<Url>,
     pub events_max_block_range: u64,
+    pub finalized_quorum: usize,
+    pub ws_url: Option<Url>,
+    pub cache_size: usize,
+    pub persist_cache: bool,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -379,7 +388,28 @@ pub async fn init_node<P: PersistenceOptions, V: Versions>(
         genesis_state.prefund_account(address, amount);
     }
 
-    let l1_client = L1Client::new(l1_params.url, l1_params.events_max_block_range);
+    ensure!(!l1_params.urls.is_empty(), "no L1 providers configured");
+    ensure!(
+        (1..=l1_params.urls.len()).contains(&l1_params.finalized_quorum),
+        "L1 finalized quorum {} must be between 1 and the number of L1 providers ({})",
+        l1_params.finalized_quorum,
+        l1_params.urls.len()
+    );
+    let l1_cache_storage = if l1_params.persist_cache {
+        let storage = persistence_opt.clone().create_l1_cache_storage().await?;
+        if storage.is_none() {
+            tracing::warn!("persistent L1 cache is not supported by this storage");
+        }
+        storage
+    } else {
+        None
+    };
+    let mut l1_client = L1Client::from_urls(l1_params.urls, l1_params.events_max_block_range)?
+        .with_finalized_quorum(l1_params.finalized_quorum)
+        .with_cache(l1_params.cache_size, l1_cache_storage);
+    if let Some(url) = l1_params.ws_url {
+        l1_client = l1_client.with_ws_subscription(url);
+    }
     let l1_genesis = match genesis.l1_finalized {
         L1Finalized::Block(b) => b,
         L1Finalized::Number { number } => l1_client.wait_for_finalized_block(number).await,
@@ -395,11 +425,14 @@ pub async fn init_node<P: PersistenceOptions, V: Versions>(
             StatePeers::<SequencerApiVersion>::from_urls(
                 network_params.state_peers,
                 network_params.catchup_backoff,
-            ),
+            )
+            .with_scoring(network_params.catchup_scoring)
+            .with_metrics(metrics),
         )
         .await,
         node_id: node_index,
         upgrades: genesis.upgrades,
+        chain_config_upgrades: genesis.chain_config_upgrades,
         current_version: V::Base::VERSION,
     };
 
@@ -437,8 +470,8 @@ pub mod testing {
         eth_signature_key::EthKeyPair,
         mock::MockStateCatchup,
         v0::traits::{EventConsumer, NullEventConsumer, PersistenceOptions, StateCatchup},
-        Event, FeeAccount, Leaf, MarketplaceVersion, Payload, PubKey, SeqTypes, Transaction,
-        Upgrade,
+        ChainConfigUpgrade, Event, FeeAccount, Leaf, MarketplaceVersion, Payload, PubKey, SeqTypes,
+        Transaction, Upgrade,
     };
     use futures::{
         future::join_all,
@@ -628,6 +661,7 @@ pub mod testing {
         builder_port: Option<u16>,
         marketplace_builder_port: Option<u16>,
         upgrades: BTreeMap<Version, Upgrade>,
+        chain_config_upgrades: BTreeMap<Version, Vec<ChainConfigUpgrade>>,
     }
 
     impl<const NUM_NODES: usize> TestConfigBuilder<NUM_NODES> {
@@ -658,6 +692,14 @@ pub mod testing {
             self
         }
 
+        pub fn chain_config_upgrades(
+            mut self,
+            upgrades: BTreeMap<Version, Vec<ChainConfigUpgrade>>,
+        ) -> Self {
+            self.chain_config_upgrades = upgrades;
+            self
+        }
+
         pub fn build(self) -> TestConfig<NUM_NODES> {
             TestConfig {
                 config: self.config,
@@ -669,6 +711,7 @@ pub mod testing {
                 marketplace_builder_port: self.marketplace_builder_port,
                 builder_port: self.builder_port,
                 upgrades: self.upgrades,
+                chain_config_upgrades: self.chain_config_upgrades,
             }
         }
     }
@@ -742,6 +785,7 @@ pub mod testing {
                 builder_port: None,
                 marketplace_builder_port: None,
                 upgrades: Default::default(),
+                chain_config_upgrades: Default::default(),
             }
         }
     }
@@ -757,6 +801,7 @@ pub mod testing {
         builder_port: Option<u16>,
         marketplace_builder_port: Option<u16>,
         upgrades: BTreeMap<Version, Upgrade>,
+        chain_config_upgrades: BTreeMap<Version, Vec<ChainConfigUpgrade>>,
     }
 
     impl<const NUM_NODES: usize> TestConfig<NUM_NODES> {
@@ -885,7 +930,8 @@ pub mod testing {
             )
             .with_current_version(V::Base::version())
             .with_genesis(state)
-            .with_upgrades(upgrades);
+            .with_upgrades(upgrades)
+            .with_chain_config_upgrades(self.chain_config_upgrades.clone());
 
             tracing::info!(
                 i,
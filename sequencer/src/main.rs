@@ -1,5 +1,6 @@
 use std::{net::ToSocketAddrs, sync::Arc};
 
+use anyhow::Context;
 use clap::Parser;
 use espresso_types::{
     traits::NullEventConsumer, FeeVersion, MarketplaceVersion, SequencerVersions,
@@ -28,8 +29,12 @@ async fn main() -> anyhow::Result<()> {
     let genesis = Genesis::from_file(&opt.genesis_file)?;
 
     // validate that the fee contract is a proxy and panic otherwise
+    let l1_url = opt
+        .l1_provider_urls
+        .first()
+        .context("no L1 providers configured")?;
     genesis
-        .validate_fee_contract(opt.l1_provider_url.to_string())
+        .validate_fee_contract(l1_url.to_string())
         .await
         .unwrap();
 
@@ -149,8 +154,12 @@ where
 {
     let (private_staking_key, private_state_key) = opt.private_keys()?;
     let l1_params = L1Params {
-        url: opt.l1_provider_url,
+        urls: opt.l1_provider_urls,
         events_max_block_range: opt.l1_events_max_block_range,
+        finalized_quorum: opt.l1_finalized_quorum,
+        ws_url: opt.l1_ws_provider,
+        cache_size: opt.l1_cache_size,
+        persist_cache: opt.l1_cache_persist,
     };
 
     // Parse supplied Libp2p addresses to their socket form
@@ -182,6 +191,7 @@ where
         state_peers: opt.state_peers,
         config_peers: opt.config_peers,
         catchup_backoff: opt.catchup_backoff,
+        catchup_scoring: opt.catchup_scoring,
     };
 
     let marketplace_config = MarketplaceConfig {
@@ -309,6 +319,7 @@ mod test {
             l1_finalized: L1Finalized::Number { number: 0 },
             header: Default::default(),
             upgrades: Default::default(),
+            chain_config_upgrades: Default::default(),
             base_version: Version { major: 0, minor: 1 },
             upgrade_version: Version { major: 0, minor: 2 },
         };
@@ -16,7 +16,7 @@ use hotshot_types::{light_client::StateSignKey, signature_key::BLSPrivKey};
 use libp2p::Multiaddr;
 use url::Url;
 
-use crate::{api, persistence};
+use crate::{api, catchup::PeerScoringParams, persistence};
 
 // This options struct is a bit unconventional. The sequencer has multiple optional modules which
 // can be added, in any combination, to the service. These include, for example, the API server.
@@ -192,14 +192,45 @@ pub struct Options {
     #[clap(raw = true)]
     modules: Vec<String>,
 
-    /// Url we will use for RPC communication with L1.
+    /// Urls we will use for RPC communication with L1.
+    ///
+    /// Multiple providers can be given as a comma-separated list, in order of preference. Requests
+    /// go to the first healthy provider, failing over to the others if it stops responding.
     #[clap(
-        long,
+        long = "l1-provider-url",
         env = "ESPRESSO_SEQUENCER_L1_PROVIDER",
-        default_value = "http://localhost:8545"
+        default_value = "http://localhost:8545",
+        value_delimiter = ','
     )]
-    #[derivative(Debug(format_with = "Display::fmt"))]
-    pub l1_provider_url: Url,
+    #[derivative(Debug(format_with = "fmt_urls"))]
+    pub l1_provider_urls: Vec<Url>,
+
+    /// Number of L1 providers which must agree on the latest finalized L1 block.
+    ///
+    /// When this is greater than 1, every provider is queried for the finalized block and their
+    /// responses are cross-checked, logging any divergence.
+    #[clap(
+        long,
+        env = "ESPRESSO_SEQUENCER_L1_FINALIZED_QUORUM",
+        default_value = "1"
+    )]
+    pub l1_finalized_quorum: usize,
+
+    /// Optional WebSocket RPC endpoint for the L1.
+    ///
+    /// If provided, the sequencer subscribes to new L1 heads over this socket and keeps its view of
+    /// the L1 up to date in the background, instead of polling the L1 when proposing a block. If
+    /// the socket drops, it falls back to polling the HTTP providers until it reconnects.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_L1_WS_PROVIDER")]
+    pub l1_ws_provider: Option<Url>,
+
+    /// Number of finalized L1 blocks, and of ranges of L1 deposits, to cache in memory.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_L1_CACHE_SIZE", default_value = "100")]
+    pub l1_cache_size: usize,
+
+    /// Save cached finalized L1 data in the sequencer's storage, so it survives restarts.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_L1_CACHE_PERSIST", action)]
+    pub l1_cache_persist: bool,
 
     /// Maximum number of L1 blocks that can be scanned for events in a single query.
     #[clap(
@@ -232,6 +263,10 @@ pub struct Options {
     #[clap(flatten)]
     pub catchup_backoff: BackoffParams,
 
+    /// How peers are ranked and queried when fetching missing state.
+    #[clap(flatten)]
+    pub catchup_scoring: PeerScoringParams,
+
     #[clap(flatten)]
     pub logging: logging::Config,
 
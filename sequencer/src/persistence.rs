@@ -9,7 +9,7 @@
 //! persistence which is _required_ to run a node.
 
 use async_trait::async_trait;
-use espresso_types::v0_3::ChainConfig;
+use espresso_types::{v0_3::ChainConfig, L1Deposit};
 use hotshot_query_service::data_source::fetching;
 
 use crate::SeqTypes;
@@ -33,6 +33,22 @@ where
     }
 }
 
+#[async_trait]
+pub trait DepositPersistence: Sized + Send + Sync {
+    /// Record the fee deposits which took effect in the block at `height`.
+    async fn insert_deposits(&mut self, height: u64, deposits: &[L1Deposit]) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl<'a, T> DepositPersistence for fetching::Transaction<'a, SeqTypes, T>
+where
+    T: DepositPersistence,
+{
+    async fn insert_deposits(&mut self, height: u64, deposits: &[L1Deposit]) -> anyhow::Result<()> {
+        self.as_mut().insert_deposits(height, deposits).await
+    }
+}
+
 #[cfg(any(test, feature = "testing"))]
 mod testing {
 
@@ -58,23 +74,33 @@ mod persistence_tests {
     use async_std::sync::{Arc, RwLock};
     use committable::Committable;
     use espresso_types::{
-        traits::EventConsumer, Event, Leaf, NodeState, PubKey, SeqTypes, ValidatedState,
+        traits::EventConsumer, Event, FeeInfo, L1BlockInfo, Leaf, NodeState, PubKey, SeqTypes,
+        ValidatedState, L1_CACHE_RETENTION,
     };
+    use ethers::types::{Address, H256};
     use hotshot::types::{BLSPubKey, SignatureKey};
     use hotshot_example_types::node_types::TestVersions;
+    use hotshot_state_prover::circuit::state_signature_message;
+    use hotshot_types::light_client::{
+        LightClientState, StateKeyPair, StateSignatureRequestBody, StateSignatureScheme,
+    };
     use hotshot_types::{
         data::{DaProposal, QuorumProposal, VidDisperseShare, ViewNumber},
         event::{EventType, HotShotAction, LeafInfo},
         message::Proposal,
-        simple_certificate::QuorumCertificate,
+        simple_certificate::{QuorumCertificate, UpgradeCertificate},
+        simple_vote::UpgradeProposalData,
         traits::{node_implementation::ConsensusTime, EncodeBytes},
         vid::vid_scheme,
     };
+    use jf_signature::SignatureScheme;
     use jf_vid::VidScheme;
     use sequencer_utils::test_utils::setup_test;
     use testing::TestablePersistence;
+    use vbs::version::Version;
 
     use super::*;
+    use crate::state_signature::static_stake_table_state;
 
     #[derive(Clone, Debug, Default)]
     struct EventCollector {
@@ -132,6 +158,245 @@ mod persistence_tests {
         );
     }
 
+    #[async_std::test]
+    pub async fn test_bootstrap_state<P: TestablePersistence>() {
+        setup_test();
+
+        let tmp = P::tmp_storage().await;
+        let storage = P::connect(&tmp).await;
+
+        // Initially, there is no saved state.
+        assert_eq!(storage.load_bootstrap_state(1).await.unwrap(), None);
+
+        // Save a state and load it back.
+        let state = ValidatedState::default();
+        storage.save_bootstrap_state(1, &state).await.unwrap();
+        assert_eq!(
+            storage.load_bootstrap_state(1).await.unwrap(),
+            Some(state.clone())
+        );
+
+        // The state is only returned for the height it was saved at.
+        assert_eq!(storage.load_bootstrap_state(2).await.unwrap(), None);
+
+        // A newer state replaces the old one.
+        storage.save_bootstrap_state(2, &state).await.unwrap();
+        assert_eq!(storage.load_bootstrap_state(1).await.unwrap(), None);
+        assert_eq!(storage.load_bootstrap_state(2).await.unwrap(), Some(state));
+    }
+
+    #[async_std::test]
+    pub async fn test_upgrade_certificate<P: TestablePersistence>() {
+        setup_test();
+
+        let tmp = P::tmp_storage().await;
+        let storage = P::connect(&tmp).await;
+
+        // Initially, there is no saved certificate.
+        assert!(storage.load_upgrade_certificate().await.unwrap().is_none());
+
+        let certificate = |minor, first_view| {
+            let data = UpgradeProposalData {
+                old_version: Version { major: 0, minor: 1 },
+                new_version: Version { major: 0, minor },
+                decide_by: ViewNumber::new(first_view - 1),
+                new_version_hash: Default::default(),
+                old_version_last_view: ViewNumber::new(first_view - 1),
+                new_version_first_view: ViewNumber::new(first_view),
+            };
+            UpgradeCertificate::new(
+                data.clone(),
+                data.commit(),
+                ViewNumber::new(first_view - 1),
+                Default::default(),
+                Default::default(),
+            )
+        };
+
+        // Save a certificate and load it back.
+        storage
+            .save_upgrade_certificate(&certificate(2, 10))
+            .await
+            .unwrap();
+        let saved = storage.load_upgrade_certificate().await.unwrap().unwrap();
+        assert_eq!(saved.data, certificate(2, 10).data);
+
+        // A newer certificate replaces the old one.
+        storage
+            .save_upgrade_certificate(&certificate(3, 20))
+            .await
+            .unwrap();
+        let saved = storage.load_upgrade_certificate().await.unwrap().unwrap();
+        assert_eq!(saved.data, certificate(3, 20).data);
+
+        // The certificate survives reconnecting.
+        let storage = P::connect(&tmp).await;
+        let saved = storage.load_upgrade_certificate().await.unwrap().unwrap();
+        assert_eq!(saved.data, certificate(3, 20).data);
+    }
+
+    #[async_std::test]
+    pub async fn test_state_signatures<P: TestablePersistence>() {
+        setup_test();
+
+        let tmp = P::tmp_storage().await;
+        let storage = P::connect(&tmp).await;
+
+        let key = StateKeyPair::generate_from_seed_indexed([0; 32], 0);
+        let sign = |block_height: usize| {
+            let state = LightClientState {
+                view_number: block_height,
+                block_height,
+                block_comm_root: Default::default(),
+            };
+            let msg = state_signature_message(&state, &static_stake_table_state(&[], 10));
+            let signature =
+                StateSignatureScheme::sign(&(), key.sign_key_ref(), msg, &mut rand::thread_rng())
+                    .unwrap();
+            StateSignatureRequestBody {
+                key: key.ver_key(),
+                state,
+                signature,
+            }
+        };
+        let heights = |signatures: Vec<StateSignatureRequestBody>| {
+            signatures
+                .into_iter()
+                .map(|signature| signature.state.block_height)
+                .collect::<Vec<_>>()
+        };
+
+        // Initially, there are no saved signatures.
+        assert!(storage.load_state_signature(1).await.unwrap().is_none());
+        assert!(storage
+            .load_undelivered_state_signatures()
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Save some signatures and load them back.
+        for height in [3, 1, 2] {
+            storage.save_state_signature(&sign(height)).await.unwrap();
+        }
+        let signature = storage.load_state_signature(2).await.unwrap().unwrap();
+        assert_eq!(signature.state.block_height, 2);
+        assert_eq!(signature.key, key.ver_key());
+
+        // Undelivered signatures are loaded in order of height.
+        assert_eq!(
+            heights(storage.load_undelivered_state_signatures().await.unwrap()),
+            [1, 2, 3]
+        );
+
+        // Delivered signatures can still be loaded, but are not redelivered.
+        storage.mark_state_signature_delivered(2).await.unwrap();
+        assert!(storage.load_state_signature(2).await.unwrap().is_some());
+        assert_eq!(
+            heights(storage.load_undelivered_state_signatures().await.unwrap()),
+            [1, 3]
+        );
+
+        // Pruning deletes signatures below the given height, whether or not they were delivered.
+        storage.prune_state_signatures(3).await.unwrap();
+        assert!(storage.load_state_signature(1).await.unwrap().is_none());
+        assert!(storage.load_state_signature(2).await.unwrap().is_none());
+        assert_eq!(
+            heights(storage.load_undelivered_state_signatures().await.unwrap()),
+            [3]
+        );
+    }
+
+    #[async_std::test]
+    pub async fn test_l1_cache_storage<P: TestablePersistence>() {
+        setup_test();
+
+        let tmp = P::tmp_storage().await;
+        let storage = P::connect(&tmp)
+            .await
+            .into_l1_cache_storage()
+            .expect("persistence supports L1 cache");
+
+        // Blocks can be saved and loaded back by number.
+        let block = L1BlockInfo {
+            number: 5,
+            timestamp: 100.into(),
+            hash: H256::random(),
+        };
+        assert_eq!(storage.load_l1_block(5).await.unwrap(), None);
+        storage.save_l1_block(&block).await.unwrap();
+        assert_eq!(storage.load_l1_block(5).await.unwrap(), Some(block));
+        assert_eq!(storage.load_l1_block(6).await.unwrap(), None);
+
+        // Deposits are saved and loaded by exact range.
+        let contract = Address::random();
+        let deposits = vec![
+            FeeInfo::new(Address::random(), 1u64),
+            FeeInfo::new(Address::random(), 2u64),
+        ];
+        assert_eq!(
+            storage.load_l1_deposits(contract, 0, 9).await.unwrap(),
+            None
+        );
+        storage
+            .save_l1_deposits(contract, 0, 9, &deposits)
+            .await
+            .unwrap();
+        storage
+            .save_l1_deposits(contract, 10, 19, &[])
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.load_l1_deposits(contract, 0, 9).await.unwrap(),
+            Some(deposits)
+        );
+        assert_eq!(
+            storage.load_l1_deposits(contract, 10, 19).await.unwrap(),
+            Some(vec![])
+        );
+        assert_eq!(
+            storage.load_l1_deposits(contract, 0, 19).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            storage
+                .load_l1_deposits(Address::random(), 0, 9)
+                .await
+                .unwrap(),
+            None
+        );
+
+        // Saving newer data prunes data which is older than the retention period.
+        let number = 5 + L1_CACHE_RETENTION;
+        let recent = L1BlockInfo {
+            number,
+            timestamp: 200.into(),
+            hash: H256::random(),
+        };
+        storage.save_l1_block(&recent).await.unwrap();
+        assert_eq!(storage.load_l1_block(5).await.unwrap(), Some(block));
+        let newer = L1BlockInfo {
+            number: number + 1,
+            timestamp: 201.into(),
+            hash: H256::random(),
+        };
+        storage.save_l1_block(&newer).await.unwrap();
+        assert_eq!(storage.load_l1_block(5).await.unwrap(), None);
+        assert_eq!(storage.load_l1_block(number).await.unwrap(), Some(recent));
+
+        storage
+            .save_l1_deposits(contract, 20, 10 + L1_CACHE_RETENTION, &[])
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.load_l1_deposits(contract, 0, 9).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            storage.load_l1_deposits(contract, 10, 19).await.unwrap(),
+            Some(vec![])
+        );
+    }
+
     fn leaf_info(leaf: Leaf) -> LeafInfo<SeqTypes> {
         LeafInfo {
             leaf,
@@ -3,15 +3,18 @@ use async_std::sync::{Arc, RwLock};
 use async_trait::async_trait;
 use clap::Parser;
 use espresso_types::{
-    v0::traits::{EventConsumer, PersistenceOptions, SequencerPersistence},
-    Leaf, NetworkConfig, Payload, SeqTypes,
+    v0::traits::{EventConsumer, L1CacheStorage, PersistenceOptions, SequencerPersistence},
+    FeeInfo, L1BlockInfo, Leaf, NetworkConfig, Payload, SeqTypes, ValidatedState,
+    L1_CACHE_RETENTION,
 };
+use ethers::types::Address;
 use hotshot_types::{
     consensus::CommitmentMap,
     data::{DaProposal, QuorumProposal, VidDisperseShare},
     event::{Event, EventType, HotShotAction, LeafInfo},
+    light_client::StateSignatureRequestBody,
     message::Proposal,
-    simple_certificate::QuorumCertificate,
+    simple_certificate::{QuorumCertificate, UpgradeCertificate},
     traits::{block_contents::BlockPayload, node_implementation::ConsensusTime},
     utils::View,
     vote::HasViewNumber,
@@ -118,10 +121,138 @@ impl Inner {
         self.path.join("undecided_state")
     }
 
+    fn bootstrap_state_path(&self) -> PathBuf {
+        self.path.join("bootstrap_state")
+    }
+
+    fn upgrade_certificate_path(&self) -> PathBuf {
+        self.path.join("upgrade_certificate")
+    }
+
     fn quorum_proposals_dir_path(&self) -> PathBuf {
         self.path.join("quorum_proposals")
     }
 
+    fn l1_block_dir_path(&self) -> PathBuf {
+        self.path.join("l1_blocks")
+    }
+
+    fn l1_deposits_dir_path(&self) -> PathBuf {
+        self.path.join("l1_deposits")
+    }
+
+    fn state_signature_dir_path(&self) -> PathBuf {
+        self.path.join("state_signatures")
+    }
+
+    /// The path of the state signature at `height`.
+    ///
+    /// Whether the signature has been delivered to the relay server is recorded in the file
+    /// extension, so that it can be determined, and the signature pruned, without reading the file.
+    fn state_signature_path(&self, height: u64, delivered: bool) -> PathBuf {
+        self.state_signature_dir_path()
+            .join(height.to_string())
+            .with_extension(if delivered { "delivered" } else { "txt" })
+    }
+
+    /// List saved state signatures by block height, with whether each has been delivered and the
+    /// path of the file containing it.
+    ///
+    /// This only reads the directory, not the signatures themselves.
+    fn state_signature_files(&self) -> anyhow::Result<BTreeMap<u64, (bool, PathBuf)>> {
+        let dir_path = self.state_signature_dir_path();
+        if !dir_path.is_dir() {
+            return Ok(Default::default());
+        }
+        let mut files = BTreeMap::new();
+        for entry in fs::read_dir(dir_path)? {
+            let path = entry?.path();
+            let delivered = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("txt") => false,
+                Some("delivered") => true,
+                _ => continue,
+            };
+            let Some(height) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            files.insert(height, (delivered, path));
+        }
+        Ok(files)
+    }
+
+    fn read_state_signature(path: &Path) -> anyhow::Result<StateSignatureRequestBody> {
+        let bytes =
+            fs::read(path).context(format!("reading state signature {}", path.display()))?;
+        bincode::deserialize(&bytes).context("deserialize state signature")
+    }
+
+    fn write_state_signature(
+        &mut self,
+        signature: &StateSignatureRequestBody,
+    ) -> anyhow::Result<()> {
+        let dir_path = self.state_signature_dir_path();
+        fs::create_dir_all(&dir_path).context("failed to create state signature dir")?;
+
+        let height = signature.state.block_height as u64;
+        let path = self.state_signature_path(height, false);
+        self.replace(
+            &path,
+            |_| {
+                // Always overwrite the previous file.
+                Ok(true)
+            },
+            |mut file| {
+                let bytes = bincode::serialize(signature).context("serialize")?;
+                file.write_all(&bytes)?;
+                Ok(())
+            },
+        )?;
+
+        // A new signature for this height has not been delivered, even if an old one was.
+        let delivered_path = self.state_signature_path(height, true);
+        if delivered_path.is_file() {
+            fs::remove_file(delivered_path)?;
+        }
+        Ok(())
+    }
+
+    fn l1_deposits_path(&self, fee_contract: Address, from: u64, to: u64) -> PathBuf {
+        self.l1_deposits_dir_path()
+            .join(format!("{fee_contract:#x}-{from}-{to}"))
+            .with_extension("txt")
+    }
+
+    /// Remove cached L1 data in `dir_path` for blocks before `cutoff`.
+    ///
+    /// `block` extracts the L1 block number that a cache file covers from its name.
+    fn prune_l1_cache(
+        dir_path: &Path,
+        cutoff: u64,
+        block: impl Fn(&str) -> Option<u64>,
+    ) -> anyhow::Result<()> {
+        for entry in fs::read_dir(dir_path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("txt") {
+                continue;
+            }
+            let Some(number) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(&block)
+            else {
+                continue;
+            };
+            if number < cutoff {
+                fs::remove_file(&path).context(format!("removing {}", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+
     /// Overwrite a file if a condition is met.
     ///
     /// The file at `path`, if it exists, is opened in read mode and passed to `pred`. If `pred`
@@ -372,6 +503,10 @@ impl Inner {
 
 #[async_trait]
 impl SequencerPersistence for Persistence {
+    fn into_l1_cache_storage(self) -> Option<Arc<dyn L1CacheStorage>> {
+        Some(Arc::new(self))
+    }
+
     async fn load_config(&self) -> anyhow::Result<Option<NetworkConfig>> {
         let inner = self.inner.read().await;
         let path = inner.config_path();
@@ -503,6 +638,127 @@ impl SequencerPersistence for Persistence {
         Ok(Some(bincode::deserialize(&bytes).context("deserialize")?))
     }
 
+    async fn save_bootstrap_state(
+        &self,
+        height: u64,
+        state: &ValidatedState,
+    ) -> anyhow::Result<()> {
+        let mut inner = self.inner.write().await;
+        let path = &inner.bootstrap_state_path();
+        inner.replace(
+            path,
+            |_| {
+                // Always overwrite the previous file.
+                Ok(true)
+            },
+            |mut file| {
+                let bytes =
+                    bincode::serialize(&(height, state)).context("serializing bootstrap state")?;
+                file.write_all(&bytes)?;
+                Ok(())
+            },
+        )
+    }
+
+    async fn load_bootstrap_state(&self, height: u64) -> anyhow::Result<Option<ValidatedState>> {
+        let inner = self.inner.read().await;
+        let path = inner.bootstrap_state_path();
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path).context("read")?;
+        let (saved_height, state): (u64, ValidatedState) =
+            bincode::deserialize(&bytes).context("deserialize")?;
+        Ok((saved_height == height).then_some(state))
+    }
+
+    async fn save_upgrade_certificate(
+        &self,
+        certificate: &UpgradeCertificate<SeqTypes>,
+    ) -> anyhow::Result<()> {
+        let mut inner = self.inner.write().await;
+        let path = &inner.upgrade_certificate_path();
+        inner.replace(
+            path,
+            |_| {
+                // Always overwrite the previous file.
+                Ok(true)
+            },
+            |mut file| {
+                let bytes =
+                    bincode::serialize(certificate).context("serializing upgrade certificate")?;
+                file.write_all(&bytes)?;
+                Ok(())
+            },
+        )
+    }
+
+    async fn load_upgrade_certificate(
+        &self,
+    ) -> anyhow::Result<Option<UpgradeCertificate<SeqTypes>>> {
+        let inner = self.inner.read().await;
+        let path = inner.upgrade_certificate_path();
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path).context("read")?;
+        Ok(Some(bincode::deserialize(&bytes).context("deserialize")?))
+    }
+
+    async fn save_state_signature(
+        &self,
+        signature: &StateSignatureRequestBody,
+    ) -> anyhow::Result<()> {
+        self.inner.write().await.write_state_signature(signature)
+    }
+
+    async fn load_state_signature(
+        &self,
+        height: u64,
+    ) -> anyhow::Result<Option<StateSignatureRequestBody>> {
+        let inner = self.inner.read().await;
+        for delivered in [false, true] {
+            let path = inner.state_signature_path(height, delivered);
+            if path.is_file() {
+                return Ok(Some(Inner::read_state_signature(&path)?));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn load_undelivered_state_signatures(
+        &self,
+    ) -> anyhow::Result<Vec<StateSignatureRequestBody>> {
+        let inner = self.inner.read().await;
+        inner
+            .state_signature_files()?
+            .into_values()
+            .filter(|(delivered, _)| !delivered)
+            .map(|(_, path)| Inner::read_state_signature(&path))
+            .collect()
+    }
+
+    async fn mark_state_signature_delivered(&self, height: u64) -> anyhow::Result<()> {
+        let inner = self.inner.write().await;
+        let path = inner.state_signature_path(height, false);
+        if !path.is_file() {
+            return Ok(());
+        }
+        fs::rename(path, inner.state_signature_path(height, true))
+            .context(format!("marking state signature {height} delivered"))
+    }
+
+    async fn prune_state_signatures(&self, height: u64) -> anyhow::Result<()> {
+        let inner = self.inner.write().await;
+        for (saved_height, (_, path)) in inner.state_signature_files()? {
+            if saved_height >= height {
+                break;
+            }
+            fs::remove_file(path).context(format!("removing state signature {saved_height}"))?;
+        }
+        Ok(())
+    }
+
     async fn load_da_proposal(
         &self,
         view: ViewNumber,
@@ -700,6 +956,95 @@ impl SequencerPersistence for Persistence {
     }
 }
 
+/// Cache finalized L1 blocks and fee contract deposits on disk, one file per block or block range.
+#[async_trait]
+impl L1CacheStorage for Persistence {
+    async fn load_l1_block(&self, number: u64) -> anyhow::Result<Option<L1BlockInfo>> {
+        let inner = self.inner.read().await;
+        let path = inner
+            .l1_block_dir_path()
+            .join(number.to_string())
+            .with_extension("txt");
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path).context("read")?;
+        Ok(Some(bincode::deserialize(&bytes).context("deserialize")?))
+    }
+
+    async fn save_l1_block(&self, block: &L1BlockInfo) -> anyhow::Result<()> {
+        let mut inner = self.inner.write().await;
+        let dir_path = inner.l1_block_dir_path();
+        fs::create_dir_all(&dir_path).context("failed to create L1 block dir")?;
+
+        let path = dir_path
+            .join(block.number.to_string())
+            .with_extension("txt");
+        inner.replace(
+            &path,
+            |_| {
+                // Finalized blocks never change, so there is no need to overwrite.
+                Ok(false)
+            },
+            |mut file| {
+                let bytes = bincode::serialize(block).context("serialize")?;
+                file.write_all(&bytes)?;
+                Ok(())
+            },
+        )?;
+        Inner::prune_l1_cache(
+            &dir_path,
+            block.number.saturating_sub(L1_CACHE_RETENTION),
+            |stem| stem.parse().ok(),
+        )
+    }
+
+    async fn load_l1_deposits(
+        &self,
+        fee_contract: Address,
+        from: u64,
+        to: u64,
+    ) -> anyhow::Result<Option<Vec<FeeInfo>>> {
+        let inner = self.inner.read().await;
+        let path = inner.l1_deposits_path(fee_contract, from, to);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path).context("read")?;
+        Ok(Some(bincode::deserialize(&bytes).context("deserialize")?))
+    }
+
+    async fn save_l1_deposits(
+        &self,
+        fee_contract: Address,
+        from: u64,
+        to: u64,
+        deposits: &[FeeInfo],
+    ) -> anyhow::Result<()> {
+        let mut inner = self.inner.write().await;
+        let dir_path = inner.l1_deposits_dir_path();
+        fs::create_dir_all(&dir_path).context("failed to create L1 deposits dir")?;
+
+        let path = inner.l1_deposits_path(fee_contract, from, to);
+        inner.replace(
+            &path,
+            |_| {
+                // Deposits in a finalized range never change, so there is no need to overwrite.
+                Ok(false)
+            },
+            |mut file| {
+                let bytes = bincode::serialize(deposits).context("serialize")?;
+                file.write_all(&bytes)?;
+                Ok(())
+            },
+        )?;
+        // Deposit files are named `{fee_contract}-{from}-{to}`.
+        Inner::prune_l1_cache(&dir_path, to.saturating_sub(L1_CACHE_RETENTION), |stem| {
+            stem.rsplit('-').next()?.parse().ok()
+        })
+    }
+}
+
 /// Update a `NetworkConfig` that may have originally been persisted with an old version.
 fn migrate_network_config(
     mut network_config: serde_json::Value,
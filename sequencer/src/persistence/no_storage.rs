@@ -11,8 +11,9 @@ use hotshot_types::{
     consensus::CommitmentMap,
     data::{DaProposal, QuorumProposal, VidDisperseShare},
     event::{Event, EventType, HotShotAction, LeafInfo},
+    light_client::StateSignatureRequestBody,
     message::Proposal,
-    simple_certificate::QuorumCertificate,
+    simple_certificate::{QuorumCertificate, UpgradeCertificate},
     utils::View,
 };
 use std::collections::BTreeMap;
@@ -114,6 +115,44 @@ impl SequencerPersistence for NoStorage {
         Ok(Default::default())
     }
 
+    async fn save_upgrade_certificate(
+        &self,
+        _certificate: &UpgradeCertificate<SeqTypes>,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn load_upgrade_certificate(
+        &self,
+    ) -> anyhow::Result<Option<UpgradeCertificate<SeqTypes>>> {
+        Ok(None)
+    }
+
+    async fn save_state_signature(&self, _: &StateSignatureRequestBody) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn load_state_signature(
+        &self,
+        _height: u64,
+    ) -> anyhow::Result<Option<StateSignatureRequestBody>> {
+        Ok(None)
+    }
+
+    async fn load_undelivered_state_signatures(
+        &self,
+    ) -> anyhow::Result<Vec<StateSignatureRequestBody>> {
+        Ok(vec![])
+    }
+
+    async fn mark_state_signature_delivered(&self, _height: u64) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn prune_state_signatures(&self, _height: u64) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     async fn append_vid(
         &self,
         _proposal: &Proposal<SeqTypes, VidDisperseShare<SeqTypes>>,
@@ -5,9 +5,13 @@ use clap::Parser;
 use derivative::Derivative;
 use espresso_types::{
     parse_duration,
-    v0::traits::{EventConsumer, PersistenceOptions, SequencerPersistence, StateCatchup},
-    BackoffParams, Leaf, NetworkConfig, Payload,
+    v0::traits::{
+        EventConsumer, L1CacheStorage, PersistenceOptions, SequencerPersistence, StateCatchup,
+    },
+    BackoffParams, FeeInfo, L1BlockInfo, Leaf, NetworkConfig, Payload, ValidatedState,
+    L1_CACHE_RETENTION,
 };
+use ethers::types::Address;
 use hotshot_query_service::data_source::{
     storage::{
         pruning::PrunerCfg,
@@ -19,8 +23,9 @@ use hotshot_types::{
     consensus::CommitmentMap,
     data::{DaProposal, QuorumProposal, VidDisperseShare},
     event::{Event, EventType, HotShotAction, LeafInfo},
+    light_client::StateSignatureRequestBody,
     message::Proposal,
-    simple_certificate::QuorumCertificate,
+    simple_certificate::{QuorumCertificate, UpgradeCertificate},
     traits::{node_implementation::ConsensusTime, BlockPayload},
     utils::View,
     vote::HasViewNumber,
@@ -259,6 +264,10 @@ impl SequencerPersistence for Persistence {
         Ok(Arc::new(SqlStateCatchup::new(Arc::new(self.db), backoff)))
     }
 
+    fn into_l1_cache_storage(self) -> Option<Arc<dyn L1CacheStorage>> {
+        Some(Arc::new(self))
+    }
+
     async fn load_config(&self) -> anyhow::Result<Option<NetworkConfig>> {
         tracing::info!("loading config from Postgres");
 
@@ -385,6 +394,157 @@ impl SequencerPersistence for Persistence {
         Ok(Some((leaves, state)))
     }
 
+    async fn save_bootstrap_state(
+        &self,
+        height: u64,
+        state: &ValidatedState,
+    ) -> anyhow::Result<()> {
+        let state_bytes = bincode::serialize(state).context("serializing bootstrap state")?;
+
+        let mut tx = self.db.write().await?;
+        tx.upsert(
+            "bootstrap_state",
+            ["id", "height", "state"],
+            ["id"],
+            [[
+                sql_param(&0i32),
+                sql_param(&(height as i64)),
+                sql_param(&state_bytes),
+            ]],
+        )
+        .await?;
+        tx.commit().await
+    }
+
+    async fn load_bootstrap_state(&self, height: u64) -> anyhow::Result<Option<ValidatedState>> {
+        let Some(row) = self
+            .db
+            .read()
+            .await?
+            .query_opt(
+                "SELECT state FROM bootstrap_state WHERE id = 0 AND height = $1",
+                [&(height as i64)],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let state_bytes: Vec<u8> = row.get("state");
+        Ok(Some(bincode::deserialize(&state_bytes)?))
+    }
+
+    async fn save_upgrade_certificate(
+        &self,
+        certificate: &UpgradeCertificate<SeqTypes>,
+    ) -> anyhow::Result<()> {
+        let bytes = bincode::serialize(certificate).context("serializing upgrade certificate")?;
+
+        let mut tx = self.db.write().await?;
+        tx.upsert(
+            "upgrade_certificate",
+            ["id", "data"],
+            ["id"],
+            [[sql_param(&0i32), sql_param(&bytes)]],
+        )
+        .await?;
+        tx.commit().await
+    }
+
+    async fn load_upgrade_certificate(
+        &self,
+    ) -> anyhow::Result<Option<UpgradeCertificate<SeqTypes>>> {
+        let Some(row) = self
+            .db
+            .read()
+            .await?
+            .query_opt_static("SELECT data FROM upgrade_certificate WHERE id = 0")
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let bytes: Vec<u8> = row.get("data");
+        Ok(Some(bincode::deserialize(&bytes)?))
+    }
+
+    async fn save_state_signature(
+        &self,
+        signature: &StateSignatureRequestBody,
+    ) -> anyhow::Result<()> {
+        let height = signature.state.block_height as i64;
+        let bytes = bincode::serialize(signature).context("serializing state signature")?;
+
+        let mut tx = self.db.write().await?;
+        tx.upsert(
+            "state_signature",
+            ["height", "signature", "delivered"],
+            ["height"],
+            [[sql_param(&height), sql_param(&bytes), sql_param(&false)]],
+        )
+        .await?;
+        tx.commit().await
+    }
+
+    async fn load_state_signature(
+        &self,
+        height: u64,
+    ) -> anyhow::Result<Option<StateSignatureRequestBody>> {
+        let Some(row) = self
+            .db
+            .read()
+            .await?
+            .query_opt(
+                "SELECT signature FROM state_signature WHERE height = $1",
+                [&(height as i64)],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let bytes: Vec<u8> = row.get("signature");
+        Ok(Some(bincode::deserialize(&bytes)?))
+    }
+
+    async fn load_undelivered_state_signatures(
+        &self,
+    ) -> anyhow::Result<Vec<StateSignatureRequestBody>> {
+        self.db
+            .read()
+            .await?
+            .query_static(
+                "SELECT signature FROM state_signature WHERE NOT delivered ORDER BY height",
+            )
+            .await?
+            .map(|row| {
+                let bytes: Vec<u8> = row?.get("signature");
+                Ok(bincode::deserialize(&bytes)?)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await
+    }
+
+    async fn mark_state_signature_delivered(&self, height: u64) -> anyhow::Result<()> {
+        let mut tx = self.db.write().await?;
+        tx.execute(
+            "UPDATE state_signature SET delivered = true WHERE height = $1",
+            [&(height as i64)],
+        )
+        .await?;
+        tx.commit().await
+    }
+
+    async fn prune_state_signatures(&self, height: u64) -> anyhow::Result<()> {
+        let mut tx = self.db.write().await?;
+        tx.execute(
+            "DELETE FROM state_signature WHERE height < $1",
+            [&(height as i64)],
+        )
+        .await?;
+        tx.commit().await
+    }
+
     async fn load_da_proposal(
         &self,
         view: ViewNumber,
@@ -548,6 +708,99 @@ impl SequencerPersistence for Persistence {
     }
 }
 
+#[async_trait]
+impl L1CacheStorage for Persistence {
+    async fn load_l1_block(&self, number: u64) -> anyhow::Result<Option<L1BlockInfo>> {
+        let Some(row) = self
+            .db
+            .read()
+            .await?
+            .query_opt(
+                "SELECT data FROM l1_block WHERE number = $1",
+                [&(number as i64)],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(serde_json::from_value(row.get("data"))?))
+    }
+
+    async fn save_l1_block(&self, block: &L1BlockInfo) -> anyhow::Result<()> {
+        let mut tx = self.db.write().await?;
+        tx.upsert(
+            "l1_block",
+            ["number", "data"],
+            ["number"],
+            [[
+                sql_param(&(block.number as i64)),
+                sql_param(&serde_json::to_value(block)?),
+            ]],
+        )
+        .await?;
+        tx.execute(
+            "DELETE FROM l1_block WHERE number < $1",
+            [&(block.number.saturating_sub(L1_CACHE_RETENTION) as i64)],
+        )
+        .await?;
+        tx.commit().await
+    }
+
+    async fn load_l1_deposits(
+        &self,
+        fee_contract: Address,
+        from: u64,
+        to: u64,
+    ) -> anyhow::Result<Option<Vec<FeeInfo>>> {
+        let Some(row) = self
+            .db
+            .read()
+            .await?
+            .query_opt(
+                "SELECT deposits FROM l1_deposits
+                    WHERE fee_contract = $1 AND from_block = $2 AND to_block = $3",
+                [
+                    sql_param(&format!("{fee_contract:#x}")),
+                    sql_param(&(from as i64)),
+                    sql_param(&(to as i64)),
+                ],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(serde_json::from_value(row.get("deposits"))?))
+    }
+
+    async fn save_l1_deposits(
+        &self,
+        fee_contract: Address,
+        from: u64,
+        to: u64,
+        deposits: &[FeeInfo],
+    ) -> anyhow::Result<()> {
+        let mut tx = self.db.write().await?;
+        tx.upsert(
+            "l1_deposits",
+            ["fee_contract", "from_block", "to_block", "deposits"],
+            ["fee_contract", "from_block", "to_block"],
+            [[
+                sql_param(&format!("{fee_contract:#x}")),
+                sql_param(&(from as i64)),
+                sql_param(&(to as i64)),
+                sql_param(&serde_json::to_value(deposits)?),
+            ]],
+        )
+        .await?;
+        tx.execute(
+            "DELETE FROM l1_deposits WHERE to_block < $1",
+            [&(to.saturating_sub(L1_CACHE_RETENTION) as i64)],
+        )
+        .await?;
+        tx.commit().await
+    }
+}
+
 async fn collect_garbage(
     mut tx: Transaction<'_>,
     view: ViewNumber,
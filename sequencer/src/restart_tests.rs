@@ -279,6 +279,7 @@ impl<S: TestableSequencerDataSource> TestNode<S> {
                 l1_finalized: L1Finalized::Number { number: 0 },
                 header: Default::default(),
                 upgrades: Default::default(),
+                chain_config_upgrades: Default::default(),
                 base_version: Version { major: 0, minor: 1 },
                 upgrade_version: Version { major: 0, minor: 2 },
             };
@@ -436,6 +437,7 @@ impl TestNetwork {
             l1_finalized: L1Finalized::Number { number: 0 },
             header: Default::default(),
             upgrades: Default::default(),
+            chain_config_upgrades: Default::default(),
             base_version: Version { major: 0, minor: 1 },
             upgrade_version: Version { major: 0, minor: 2 },
         };
@@ -0,0 +1,569 @@
+//! State snapshots for bootstrapping new nodes.
+//!
+//! A [`StateSnapshot`] bundles everything a node needs to start participating in consensus from a
+//! recent decided leaf, rather than from genesis: the leaf itself and its QC, plus the full
+//! validated state resulting from that leaf. A node bootstrapped from a snapshot does not need to
+//! catch up the fee or blocks state from its peers.
+//!
+//! Snapshots are exported from the SQL storage of an existing node and imported into the storage of
+//! a new one. Importing checks the state against the commitments in the leaf's header, but it does
+//! not check the QC's signatures, so snapshots should only be imported from a trusted source.
+//!
+//! A snapshot can be imported into any consensus storage with [`StateSnapshot::import`]. Nodes
+//! which also run the query service with state storage should use [`StateSnapshot::import_sql`]
+//! instead, which additionally seeds the merklized state tables, so that the node can serve
+//! catchup requests and resume storing state from the snapshot rather than from genesis.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use anyhow::{ensure, Context};
+use async_std::stream::StreamExt;
+use committable::Committable;
+use espresso_types::{
+    traits::{NullEventConsumer, PersistenceOptions, SequencerPersistence},
+    v0_3::{ChainConfig, TransferNonceMerkleTree, TRANSFER_NONCE_MERKLE_TREE_HEIGHT},
+    BlockMerkleTree, FeeAccount, FeeAmount, FeeMerkleTree, Leaf, ValidatedState,
+    BLOCK_MERKLE_TREE_HEIGHT, FEE_MERKLE_TREE_HEIGHT,
+};
+use hotshot_query_service::{
+    availability::LeafQueryData,
+    data_source::{
+        storage::{
+            sql::{Config, SqlStorage},
+            UpdateAvailabilityStorage,
+        },
+        Transaction as _, VersionedDataSource,
+    },
+};
+use hotshot_types::{
+    event::LeafInfo, simple_certificate::QuorumCertificate,
+    traits::node_implementation::ConsensusTime,
+};
+use jf_merkle_tree::{ForgetableMerkleTreeScheme, MerkleTreeScheme, UniversalMerkleTreeScheme};
+use serde::{Deserialize, Serialize};
+
+use crate::{api::data_source::CatchupDataSource, persistence, state::store_full_state, SeqTypes};
+
+/// The version of the snapshot file format produced by this software.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// A snapshot of the validated state at a decided leaf.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// The version of the file format, to detect snapshots we don't know how to read.
+    pub format_version: u32,
+    /// The height of the anchor leaf.
+    pub height: u64,
+    /// The view number of the anchor leaf.
+    pub view: u64,
+    /// The chain config in effect after the anchor leaf.
+    pub chain_config: ChainConfig,
+    /// The anchor leaf.
+    pub leaf: Leaf,
+    /// A QC for the anchor leaf.
+    pub qc: QuorumCertificate<SeqTypes>,
+    /// The full fee state after the anchor leaf.
+    pub fee_merkle_tree: FeeMerkleTree,
+    /// The blocks frontier after the anchor leaf.
+    pub block_merkle_tree: BlockMerkleTree,
+    /// The full transfer nonce state after the anchor leaf.
+    #[serde(default = "empty_transfer_nonce_merkle_tree")]
+    pub transfer_nonce_merkle_tree: TransferNonceMerkleTree,
+}
+
+fn empty_transfer_nonce_merkle_tree() -> TransferNonceMerkleTree {
+    TransferNonceMerkleTree::new(TRANSFER_NONCE_MERKLE_TREE_HEIGHT)
+}
+
+impl StateSnapshot {
+    /// Export a snapshot of the state at `height` from SQL storage.
+    ///
+    /// The storage must be that of a node running the query service with state storage, and its
+    /// merklized state must be up to date with at least `height`.
+    pub async fn export_sql(opt: persistence::sql::Options, height: u64) -> anyhow::Result<Self> {
+        let storage = SqlStorage::connect(Config::try_from(opt)?)
+            .await
+            .context("connecting to SQL storage")?;
+
+        let row = storage
+            .read()
+            .await?
+            .query_one(
+                "SELECT leaf, qc FROM leaf WHERE height = $1",
+                [&(height as i64)],
+            )
+            .await
+            .context(format!("fetching leaf {height}"))?;
+        let leaf: Leaf = serde_json::from_value(row.try_get("leaf")?).context("malformed leaf")?;
+        let qc: QuorumCertificate<SeqTypes> =
+            serde_json::from_value(row.try_get("qc")?).context("malformed QC")?;
+        let header = leaf.block_header();
+
+        // Collect the latest balance of every account as of this height.
+        let balances = storage
+            .read()
+            .await?
+            .query(&latest_entries_query("fee_merkle_tree"), [&(height as i64)])
+            .await?
+            .map(|row| {
+                let row = row?;
+                let account: FeeAccount = serde_json::from_value(row.try_get("index")?)?;
+                let amount: FeeAmount = serde_json::from_value(row.try_get("entry")?)?;
+                Ok((account, amount))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await
+            .context(format!("fetching fee state at height {height}"))?;
+        tracing::info!(height, accounts = balances.len(), "loaded fee state");
+        let fee_merkle_tree = FeeMerkleTree::from_kv_set(FEE_MERKLE_TREE_HEIGHT, balances)
+            .context("building fee state")?;
+
+        // Collect the latest nonce of every account as of this height.
+        let nonces = storage
+            .read()
+            .await?
+            .query(
+                &latest_entries_query("transfer_nonce_merkle_tree"),
+                [&(height as i64)],
+            )
+            .await?
+            .map(|row| {
+                let row = row?;
+                let account: FeeAccount = serde_json::from_value(row.try_get("index")?)?;
+                let nonce: u64 = serde_json::from_value(row.try_get("entry")?)?;
+                Ok((account, nonce))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await
+            .context(format!("fetching transfer nonces at height {height}"))?;
+        tracing::info!(height, accounts = nonces.len(), "loaded transfer nonces");
+        let transfer_nonce_merkle_tree =
+            TransferNonceMerkleTree::from_kv_set(TRANSFER_NONCE_MERKLE_TREE_HEIGHT, nonces)
+                .context("building transfer nonce state")?;
+
+        let block_merkle_tree = if header.block_merkle_tree_root().size() == 0 {
+            BlockMerkleTree::new(BLOCK_MERKLE_TREE_HEIGHT)
+        } else {
+            let mut tree = BlockMerkleTree::from_commitment(header.block_merkle_tree_root());
+            let frontier = storage
+                .get_frontier(height, leaf.view_number())
+                .await
+                .context(format!("fetching blocks frontier at height {height}"))?;
+            let elem = frontier
+                .elem()
+                .context("blocks frontier is missing leaf element")?;
+            tree.remember(tree.num_leaves() - 1, *elem, &frontier)
+                .context("remembering blocks frontier")?;
+            tree
+        };
+
+        let chain_config = match header.chain_config().resolve() {
+            Some(chain_config) => chain_config,
+            None => storage
+                .get_chain_config(header.chain_config().commit())
+                .await
+                .context("fetching chain config")?,
+        };
+
+        let snapshot = Self {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            height,
+            view: leaf.view_number().u64(),
+            chain_config,
+            leaf,
+            qc,
+            fee_merkle_tree,
+            block_merkle_tree,
+            transfer_nonce_merkle_tree,
+        };
+        snapshot
+            .verify()
+            .context("exported snapshot is inconsistent, is the state storage up to date?")?;
+        Ok(snapshot)
+    }
+
+    /// Check that the snapshot is internally consistent.
+    ///
+    /// This checks that the QC is for the anchor leaf, and that the state matches the commitments
+    /// in the leaf's header. It does not check the QC's signatures.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.format_version == SNAPSHOT_FORMAT_VERSION,
+            "unsupported snapshot format version {}",
+            self.format_version
+        );
+
+        let header = self.leaf.block_header();
+        ensure!(
+            header.height() == self.height,
+            "snapshot is for height {}, but leaf has height {}",
+            self.height,
+            header.height()
+        );
+        ensure!(
+            self.leaf.view_number().u64() == self.view,
+            "snapshot is for view {}, but leaf has view {:?}",
+            self.view,
+            self.leaf.view_number()
+        );
+        ensure!(
+            self.qc.view_number == self.leaf.view_number(),
+            "QC is for view {:?}, but leaf has view {:?}",
+            self.qc.view_number,
+            self.leaf.view_number()
+        );
+        ensure!(
+            self.qc.data.leaf_commit == <Leaf as Committable>::commit(&self.leaf),
+            "QC is not for the snapshot leaf"
+        );
+        ensure!(
+            self.fee_merkle_tree.commitment() == header.fee_merkle_tree_root(),
+            "fee state {:?} does not match header {:?}",
+            self.fee_merkle_tree.commitment(),
+            header.fee_merkle_tree_root()
+        );
+        ensure!(
+            self.block_merkle_tree.commitment() == header.block_merkle_tree_root(),
+            "blocks state {:?} does not match header {:?}",
+            self.block_merkle_tree.commitment(),
+            header.block_merkle_tree_root()
+        );
+        match header.transfer_nonce_merkle_tree_root() {
+            Some(root) => ensure!(
+                self.transfer_nonce_merkle_tree.commitment() == root,
+                "transfer nonce state {:?} does not match header {:?}",
+                self.transfer_nonce_merkle_tree.commitment(),
+                root
+            ),
+            None => ensure!(
+                self.transfer_nonce_merkle_tree.num_leaves() == 0,
+                "header does not support transfer nonces, but snapshot has some"
+            ),
+        }
+        ensure!(
+            self.chain_config.commit() == header.chain_config().commit(),
+            "chain config {} does not match header {}",
+            self.chain_config.commit(),
+            header.chain_config().commit()
+        );
+        Ok(())
+    }
+
+    /// The validated state resulting from the anchor leaf.
+    pub fn validated_state(&self) -> ValidatedState {
+        ValidatedState {
+            block_merkle_tree: self.block_merkle_tree.clone(),
+            fee_merkle_tree: self.fee_merkle_tree.clone(),
+            transfer_nonce_merkle_tree: self.transfer_nonce_merkle_tree.clone(),
+            chain_config: self.chain_config.into(),
+        }
+    }
+
+    /// Seed consensus storage with this snapshot.
+    ///
+    /// When a node is started on this storage, it will resume consensus from the snapshot's anchor
+    /// leaf, using the snapshot's state. Fails if the storage already has a decided leaf at least
+    /// as recent as the snapshot, unless `force` is set.
+    pub async fn import(
+        &self,
+        persistence: &impl SequencerPersistence,
+        force: bool,
+    ) -> anyhow::Result<()> {
+        self.verify()?;
+
+        if let Some((leaf, _)) = persistence.load_anchor_leaf().await? {
+            ensure!(
+                force || leaf.height() < self.height,
+                "storage already has decided leaf {}, which is at least as recent as the snapshot",
+                leaf.height()
+            );
+        }
+
+        persistence
+            .save_bootstrap_state(self.height, &self.validated_state())
+            .await
+            .context("saving bootstrap state")?;
+
+        let info = LeafInfo {
+            leaf: self.leaf.clone(),
+            vid_share: None,
+            state: Default::default(),
+            delta: None,
+        };
+        persistence
+            .append_decided_leaves(
+                self.leaf.view_number(),
+                [(&info, self.qc.clone())],
+                &NullEventConsumer,
+            )
+            .await
+            .context("saving anchor leaf")
+    }
+
+    /// Seed the SQL storage of a node running the query service with this snapshot.
+    ///
+    /// In addition to seeding consensus storage as in [`import`](Self::import), this stores the
+    /// anchor leaf and the full merklized state at the snapshot height in the query service tables,
+    /// so that the node resumes updating its state storage from the snapshot. Fails if the storage
+    /// already has a decided leaf at least as recent as the snapshot, unless `force` is set.
+    pub async fn import_sql(
+        &self,
+        opt: persistence::sql::Options,
+        force: bool,
+    ) -> anyhow::Result<()> {
+        let persistence = opt
+            .clone()
+            .create()
+            .await
+            .context("opening consensus storage")?;
+        self.import(&persistence, force).await?;
+
+        let storage = SqlStorage::connect(Config::try_from(opt)?)
+            .await
+            .context("connecting to SQL storage")?;
+        let mut tx = storage
+            .write()
+            .await
+            .context("opening transaction for merklized state")?;
+        tx.insert_leaf(LeafQueryData::new(self.leaf.clone(), self.qc.clone())?)
+            .await
+            .context("storing anchor leaf")?;
+        store_full_state(&mut tx, self.height, &self.validated_state())
+            .await
+            .context("storing merklized state")?;
+        tx.commit()
+            .await
+            .context("committing merklized state, retry the import with --force")
+    }
+
+    pub fn to_file(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).context(format!("snapshot file {}", path.display()))?;
+        serde_json::to_writer(BufWriter::new(file), self).context("writing snapshot")?;
+        Ok(())
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).context(format!("snapshot file {}", path.display()))?;
+        serde_json::from_reader(BufReader::new(file)).context("malformed snapshot file")
+    }
+}
+
+/// Query the latest entry of each index in a merklized state table as of a given height.
+///
+/// The height is bound as the first parameter of the query.
+fn latest_entries_query(table: &str) -> String {
+    format!(
+        "SELECT t.index, t.entry FROM {table} AS t
+            JOIN (
+                SELECT index, max(created) AS created FROM {table}
+                    WHERE index IS NOT NULL AND created <= $1
+                    GROUP BY index
+            ) AS latest ON t.index = latest.index AND t.created = latest.created"
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use espresso_types::NodeState;
+    use ethers::types::Address;
+    use hotshot_example_types::node_types::TestVersions;
+    use hotshot_query_service::data_source::storage::sql::testing::TmpDb;
+    use jf_merkle_tree::AppendableMerkleTreeScheme;
+    use sequencer_utils::test_utils::setup_test;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::ViewNumber;
+
+    async fn genesis_snapshot() -> StateSnapshot {
+        let instance = NodeState::mock();
+        let state = ValidatedState::default();
+        let leaf = Leaf::genesis(&state, &instance).await;
+        let qc = QuorumCertificate::genesis::<TestVersions>(&state, &instance).await;
+        StateSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            height: 0,
+            view: 0,
+            chain_config: state.chain_config.resolve().unwrap(),
+            leaf,
+            qc,
+            fee_merkle_tree: state.fee_merkle_tree,
+            block_merkle_tree: state.block_merkle_tree,
+            transfer_nonce_merkle_tree: state.transfer_nonce_merkle_tree,
+        }
+    }
+
+    /// A snapshot at `height`, with some funded accounts which have made transfers.
+    async fn snapshot_at(height: u64) -> (StateSnapshot, Vec<FeeAccount>) {
+        let accounts = (0..3)
+            .map(|_| FeeAccount::from(Address::random()))
+            .collect::<Vec<_>>();
+        let mut state = ValidatedState::default();
+        for (i, account) in accounts.iter().enumerate() {
+            state.prefund_account(*account, FeeAmount::from(i as u64 + 1));
+            for _ in 0..=i {
+                state.increment_nonce(*account).unwrap();
+            }
+        }
+
+        let instance = NodeState::mock_v3().with_genesis(state.clone());
+        let mut leaf = Leaf::genesis(&state, &instance).await;
+        let mut qc = QuorumCertificate::genesis::<TestVersions>(&state, &instance).await;
+        for _ in 0..height {
+            state
+                .block_merkle_tree
+                .push(leaf.block_header().commit())
+                .unwrap();
+        }
+        *leaf.block_header_mut().height_mut() = height;
+        *leaf.block_header_mut().block_merkle_tree_root_mut() =
+            state.block_merkle_tree.commitment();
+        qc.data.leaf_commit = <Leaf as Committable>::commit(&leaf);
+
+        let snapshot = StateSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            height,
+            view: 0,
+            chain_config: state.chain_config.resolve().unwrap(),
+            leaf,
+            qc,
+            fee_merkle_tree: state.fee_merkle_tree,
+            block_merkle_tree: state.block_merkle_tree,
+            transfer_nonce_merkle_tree: state.transfer_nonce_merkle_tree,
+        };
+        snapshot.verify().unwrap();
+        (snapshot, accounts)
+    }
+
+    fn sql_options(db: &TmpDb) -> persistence::sql::Options {
+        persistence::sql::Options {
+            port: Some(db.port()),
+            host: Some(db.host()),
+            user: Some("postgres".into()),
+            password: Some("password".into()),
+            ..Default::default()
+        }
+    }
+
+    #[async_std::test]
+    async fn test_snapshot_verify() {
+        setup_test();
+
+        let snapshot = genesis_snapshot().await;
+        snapshot.verify().unwrap();
+
+        // Tampering with the fee state is detected.
+        let mut bad = snapshot.clone();
+        bad.fee_merkle_tree
+            .update(FeeAccount::default(), FeeAmount::from(1))
+            .unwrap();
+        bad.verify().unwrap_err();
+
+        // So is a mismatched chain config.
+        let mut bad = snapshot.clone();
+        bad.chain_config.max_block_size = (u64::from(bad.chain_config.max_block_size) + 1).into();
+        bad.verify().unwrap_err();
+
+        // And a mismatched height.
+        let mut bad = snapshot;
+        bad.height = 1;
+        bad.verify().unwrap_err();
+    }
+
+    #[async_std::test]
+    async fn test_snapshot_file_round_trip() {
+        setup_test();
+
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("snapshot.json");
+
+        let snapshot = genesis_snapshot().await;
+        snapshot.to_file(&path).unwrap();
+        let loaded = StateSnapshot::from_file(&path).unwrap();
+        loaded.verify().unwrap();
+        assert_eq!(loaded.validated_state(), snapshot.validated_state());
+        assert_eq!(loaded.leaf, snapshot.leaf);
+    }
+
+    #[async_std::test]
+    async fn test_snapshot_sql_round_trip() {
+        setup_test();
+
+        let height = 3;
+        let (snapshot, accounts) = snapshot_at(height).await;
+
+        // Seed a database with the snapshot and export it again.
+        let source = TmpDb::init().await;
+        snapshot
+            .import_sql(sql_options(&source), false)
+            .await
+            .unwrap();
+        let exported = StateSnapshot::export_sql(sql_options(&source), height)
+            .await
+            .unwrap();
+        assert_eq!(exported.leaf, snapshot.leaf);
+        assert_eq!(exported.chain_config, snapshot.chain_config);
+        assert_eq!(
+            exported.fee_merkle_tree.commitment(),
+            snapshot.fee_merkle_tree.commitment()
+        );
+        assert_eq!(
+            exported.block_merkle_tree.commitment(),
+            snapshot.block_merkle_tree.commitment()
+        );
+        assert_eq!(
+            exported.transfer_nonce_merkle_tree.commitment(),
+            snapshot.transfer_nonce_merkle_tree.commitment()
+        );
+
+        // Import the exported snapshot into a fresh node.
+        let target = TmpDb::init().await;
+        exported
+            .import_sql(sql_options(&target), false)
+            .await
+            .unwrap();
+
+        // The node can serve the state at the snapshot height.
+        let storage = SqlStorage::connect(Config::try_from(sql_options(&target)).unwrap())
+            .await
+            .unwrap();
+        let view = ViewNumber::new(snapshot.view);
+        let fees = storage.get_accounts(height, view, &accounts).await.unwrap();
+        let nonces = storage
+            .get_transfer_nonces(height, view, &accounts)
+            .await
+            .unwrap();
+        for (i, account) in accounts.iter().enumerate() {
+            let (balance, _) = fees.lookup(*account).expect_ok().unwrap();
+            assert_eq!(*balance, FeeAmount::from(i as u64 + 1));
+            let (nonce, _) = nonces.lookup(*account).expect_ok().unwrap();
+            assert_eq!(*nonce, i as u64 + 1);
+        }
+        let frontier = storage.get_frontier(height, view).await.unwrap();
+        let mut blocks = BlockMerkleTree::from_commitment(snapshot.block_merkle_tree.commitment());
+        blocks
+            .remember(height - 1, *frontier.elem().unwrap(), &frontier)
+            .unwrap();
+
+        // Consensus resumes from the snapshot.
+        let persistence = sql_options(&target).create().await.unwrap();
+        let (leaf, _) = persistence.load_anchor_leaf().await.unwrap().unwrap();
+        assert_eq!(leaf, snapshot.leaf);
+
+        // Importing again is refused unless forced.
+        exported
+            .import_sql(sql_options(&target), false)
+            .await
+            .unwrap_err();
+        exported
+            .import_sql(sql_options(&target), true)
+            .await
+            .unwrap();
+    }
+}
@@ -4,7 +4,8 @@ use std::{sync::Arc, time::Duration};
 use anyhow::{bail, ensure, Context};
 use async_std::stream::StreamExt;
 use espresso_types::{
-    v0_3::ChainConfig, BlockMerkleTree, Delta, FeeAccount, FeeMerkleTree, ValidatedState,
+    v0_3::{ChainConfig, TransferNonceMerkleTree},
+    BlockMerkleTree, Delta, FeeAccount, FeeMerkleTree, Header, L1Deposit, ValidatedState,
 };
 use futures::future::Future;
 use hotshot::traits::ValidatedState as HotShotState;
@@ -18,8 +19,10 @@ use hotshot_query_service::{
 use jf_merkle_tree::{LookupResult, MerkleTreeScheme, ToTraversalPath, UniversalMerkleTreeScheme};
 
 use crate::{
-    api::data_source::CatchupDataSource, catchup::SqlStateCatchup,
-    persistence::ChainConfigPersistence, NodeState, SeqTypes,
+    api::data_source::CatchupDataSource,
+    catchup::SqlStateCatchup,
+    persistence::{ChainConfigPersistence, DepositPersistence},
+    NodeState, SeqTypes,
 };
 
 async fn compute_state_update(
@@ -52,6 +55,14 @@ async fn compute_state_update(
         state.fee_merkle_tree.commitment(),
         parent_header.fee_merkle_tree_root()
     );
+    if let Some(root) = parent_header.transfer_nonce_merkle_tree_root() {
+        ensure!(
+            state.transfer_nonce_merkle_tree.commitment() == root,
+            "internal error! in-memory transfer nonce tree {:?} does not match parent header {:?}",
+            state.transfer_nonce_merkle_tree.commitment(),
+            root
+        );
+    }
 
     state
         .apply_header(instance, parent_leaf, header, header.version())
@@ -66,10 +77,14 @@ async fn store_state_update(
 ) -> anyhow::Result<()> {
     let ValidatedState {
         fee_merkle_tree,
+        transfer_nonce_merkle_tree,
         block_merkle_tree,
         ..
     } = state;
-    let Delta { fees_delta } = delta;
+    let Delta {
+        fees_delta,
+        nonces_delta,
+    } = delta;
 
     // Insert fee merkle tree nodes
     for delta in fees_delta {
@@ -94,6 +109,29 @@ async fn store_state_update(
         .context("failed to store fee merkle nodes")?;
     }
 
+    // Insert transfer nonce merkle tree nodes
+    for delta in nonces_delta {
+        let proof = match transfer_nonce_merkle_tree.universal_lookup(delta) {
+            LookupResult::Ok(_, proof) => proof,
+            LookupResult::NotFound(proof) => proof,
+            LookupResult::NotInMemory => bail!("missing merkle path for transfer nonce of {delta}"),
+        };
+        let path: Vec<usize> = <FeeAccount as ToTraversalPath<
+            { TransferNonceMerkleTree::ARITY },
+        >>::to_traversal_path(
+            &delta, transfer_nonce_merkle_tree.height()
+        );
+
+        UpdateStateData::<SeqTypes, _, { TransferNonceMerkleTree::ARITY }>::insert_merkle_nodes(
+            tx,
+            proof,
+            path,
+            block_number,
+        )
+        .await
+        .context("failed to store transfer nonce merkle nodes")?;
+    }
+
     // Insert block merkle tree nodes
     let (_, proof) = block_merkle_tree
         .lookup(block_number - 1)
@@ -124,6 +162,83 @@ async fn store_state_update(
     Ok(())
 }
 
+/// Store the full merklized state resulting from the block at `block_number`.
+///
+/// Unlike [`store_state_update`], this does not require the state of the parent block to already
+/// be stored, so it can be used to seed state storage from a snapshot.
+pub(crate) async fn store_full_state(
+    tx: &mut impl SequencerStateUpdate,
+    block_number: u64,
+    state: &ValidatedState,
+) -> anyhow::Result<()> {
+    ensure!(block_number > 0, "cannot store full state at genesis");
+
+    let delta = Delta {
+        fees_delta: state
+            .fee_merkle_tree
+            .iter()
+            .map(|(account, _)| *account)
+            .collect(),
+        nonces_delta: state
+            .transfer_nonce_merkle_tree
+            .iter()
+            .map(|(account, _)| *account)
+            .collect(),
+    };
+    store_state_update(tx, block_number, state, delta).await?;
+
+    let chain_config = state
+        .chain_config
+        .resolve()
+        .context("failed to resolve to chain config")?;
+    tx.insert_chain_config(chain_config).await?;
+    Ok(())
+}
+
+/// Get the L1 deposits which took effect in the block with `proposed_header`, for indexing.
+async fn fetch_deposits(
+    state: &ValidatedState,
+    instance: &NodeState,
+    parent_header: &Header,
+    proposed_header: &Header,
+) -> anyhow::Result<Vec<L1Deposit>> {
+    let chain_config = state
+        .chain_config
+        .resolve()
+        .context("failed to resolve to chain config")?;
+    let (Some(fee_contract), Some(l1_finalized)) =
+        (chain_config.fee_contract, proposed_header.l1_finalized())
+    else {
+        return Ok(vec![]);
+    };
+    let prev_finalized = parent_header
+        .l1_finalized()
+        .map(|block_info| block_info.number);
+
+    // Computing the state update has already fetched the deposits in this range, so this is cached
+    // and lets us skip querying the L1 for the deposit metadata in the common case where there are
+    // no deposits.
+    let deposits = instance
+        .l1_client
+        .get_finalized_deposits(fee_contract, prev_finalized, l1_finalized.number)
+        .await;
+    if deposits.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let events = instance
+        .l1_client
+        .get_finalized_deposit_events(fee_contract, prev_finalized, l1_finalized.number)
+        .await;
+    ensure!(
+        events.len() == deposits.len(),
+        "L1 returned {} deposit events, but {} deposits were applied",
+        events.len(),
+        deposits.len()
+    );
+    Ok(events)
+}
+
 #[tracing::instrument(
     skip_all,
     fields(
@@ -148,6 +263,14 @@ where
     let (state, delta) = compute_state_update(parent_state, instance, parent_leaf, proposed_leaf)
         .await
         .context("computing state update")?;
+    let deposits = fetch_deposits(
+        &state,
+        instance,
+        parent_leaf.header(),
+        proposed_leaf.header(),
+    )
+    .await
+    .context("fetching deposits")?;
 
     let mut tx = storage
         .write()
@@ -163,6 +286,9 @@ where
 
         tx.insert_chain_config(cf).await?;
     }
+    tx.insert_deposits(proposed_leaf.height(), &deposits)
+        .await
+        .context("storing deposits")?;
 
     tx.commit().await?;
     Ok(state)
@@ -293,26 +419,39 @@ impl<T> SequencerStateDataSource for T where
 pub(crate) trait SequencerStateUpdate:
     Transaction
     + UpdateStateData<SeqTypes, FeeMerkleTree, { FeeMerkleTree::ARITY }>
+    + UpdateStateData<SeqTypes, TransferNonceMerkleTree, { TransferNonceMerkleTree::ARITY }>
     + UpdateStateData<SeqTypes, BlockMerkleTree, { BlockMerkleTree::ARITY }>
     + ChainConfigPersistence
+    + DepositPersistence
 {
 }
 
 impl<T> SequencerStateUpdate for T where
     T: Transaction
         + UpdateStateData<SeqTypes, FeeMerkleTree, { FeeMerkleTree::ARITY }>
+        + UpdateStateData<SeqTypes, TransferNonceMerkleTree, { TransferNonceMerkleTree::ARITY }>
         + UpdateStateData<SeqTypes, BlockMerkleTree, { BlockMerkleTree::ARITY }>
         + ChainConfigPersistence
+        + DepositPersistence
 {
 }
 
 #[cfg(test)]
 mod test {
+    use contract_bindings::{erc1967_proxy::ERC1967Proxy, fee_contract::FeeContract};
     use espresso_types::{
         v0_3::IterableFeeInfo, validate_proposal, BlockSize, FeeAccount, FeeAccountProof,
-        FeeAmount, FeeError, FeeInfo, FeeMerkleProof, Leaf, ProposalValidationError,
+        FeeAmount, FeeError, FeeInfo, FeeMerkleProof, L1BlockInfo, L1Client, Leaf,
+        ProposalValidationError,
+    };
+    use ethers::{
+        abi::Address,
+        middleware::SignerMiddleware,
+        providers::{Http, Provider},
+        signers::{LocalWallet, Signer},
+        types::U256,
+        utils::{parse_ether, Anvil},
     };
-    use ethers::{abi::Address, types::U256};
     use hotshot_types::{
         traits::signature_key::BuilderSignatureKey,
         vid::{vid_scheme, VidSchemeType},
@@ -529,4 +668,118 @@ mod test {
             bincode::serialize(&amt).unwrap(),
         );
     }
+
+    #[async_std::test]
+    async fn test_fetch_deposits() {
+        setup_test();
+
+        let anvil = Anvil::new().spawn();
+        let wallet: LocalWallet = anvil.keys()[0].clone().into();
+        let provider = Provider::<Http>::try_from(anvil.endpoint())
+            .unwrap()
+            .interval(Duration::from_millis(10));
+        let client = Arc::new(SignerMiddleware::new(
+            provider,
+            wallet.with_chain_id(anvil.chain_id()),
+        ));
+
+        // Deploy the fee contract behind a proxy.
+        let implementation = FeeContract::deploy(client.clone(), ())
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+        let initialize_data = implementation
+            .initialize(anvil.addresses()[0])
+            .calldata()
+            .unwrap();
+        let proxy =
+            ERC1967Proxy::deploy(client.clone(), (implementation.address(), initialize_data))
+                .unwrap()
+                .send()
+                .await
+                .unwrap();
+        let fee_contract = FeeContract::new(proxy.address(), client);
+
+        let l1_client = L1Client::new(anvil.endpoint().parse().unwrap(), 1);
+        let instance = NodeState::mock().with_l1(l1_client.clone());
+        let mut state = ValidatedState::default();
+        state.chain_config = ChainConfig {
+            fee_contract: Some(fee_contract.address()),
+            ..Default::default()
+        }
+        .into();
+        let parent = Leaf::genesis(&state, &instance)
+            .await
+            .block_header()
+            .clone();
+        let prev_finalized = l1_client.get_block_number().await;
+        let mut parent_finalized = parent.clone();
+        *parent_finalized.l1_finalized_mut() = Some(L1BlockInfo {
+            number: prev_finalized,
+            ..Default::default()
+        });
+
+        // Make a deposit to each of two accounts.
+        let deposits = [
+            (Address::random(), parse_ether("0.1").unwrap()),
+            (Address::random(), parse_ether("0.2").unwrap()),
+        ];
+        let mut receipts = vec![];
+        for (account, amount) in deposits {
+            let receipt = fee_contract
+                .deposit(account)
+                .value(amount)
+                .send()
+                .await
+                .unwrap()
+                .await
+                .unwrap()
+                .unwrap();
+            receipts.push(receipt);
+        }
+        let mut proposed = parent_finalized.clone();
+        *proposed.height_mut() = 1;
+        *proposed.l1_finalized_mut() = Some(L1BlockInfo {
+            number: l1_client.get_block_number().await,
+            ..Default::default()
+        });
+
+        // Every deposit finalized since the parent is indexed, with its L1 transaction.
+        let events = fetch_deposits(&state, &instance, &parent_finalized, &proposed)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), deposits.len(), "{events:?}");
+        for (event, ((account, amount), receipt)) in
+            events.iter().zip(std::iter::zip(deposits, &receipts))
+        {
+            assert_eq!(event.account, account.into());
+            assert_eq!(event.amount, FeeAmount(amount));
+            assert_eq!(Some(event.l1_block.into()), receipt.block_number);
+            assert_eq!(event.l1_transaction, receipt.transaction_hash);
+        }
+
+        // If the parent had no finalized L1 block, the deposits are counted from the start.
+        let events = fetch_deposits(&state, &instance, &parent, &proposed)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), deposits.len(), "{events:?}");
+
+        // No deposits are indexed if no new L1 block was finalized.
+        let events = fetch_deposits(&state, &instance, &proposed, &proposed)
+            .await
+            .unwrap();
+        assert!(events.is_empty(), "{events:?}");
+
+        // Or if there is no fee contract.
+        let events = fetch_deposits(
+            &ValidatedState::default(),
+            &instance,
+            &parent_finalized,
+            &proposed,
+        )
+        .await
+        .unwrap();
+        assert!(events.is_empty(), "{events:?}");
+    }
 }
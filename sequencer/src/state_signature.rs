@@ -1,24 +1,32 @@
 //! Utilities for generating and storing the most recent light client state signatures.
 
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
 
 use ark_ff::PrimeField;
 use ark_serialize::CanonicalSerialize;
-use async_std::sync::RwLock;
-use espresso_types::Leaf;
+use async_compatibility_layer::channel::{unbounded, UnboundedReceiver, UnboundedSender};
+use async_std::{
+    sync::{Mutex, RwLock},
+    task::sleep,
+};
+use espresso_types::{traits::SequencerPersistence, Leaf};
 use hotshot::types::{Event, EventType};
 use hotshot_stake_table::vec_based::StakeTable;
+use hotshot_state_prover::{circuit::state_signature_message, service::stake_table_state};
 use hotshot_types::{
     event::LeafInfo,
     light_client::{
-        CircuitField, LightClientState, StateSignature, StateSignatureRequestBody,
+        CircuitField, LightClientState, StakeTableState, StateSignature, StateSignatureRequestBody,
         StateSignatureScheme, StateVerKey,
     },
     signature_key::BLSPubKey,
     traits::{
-        node_implementation::ConsensusTime,
-        signature_key::StakeTableEntryType,
-        stake_table::{SnapshotVersion, StakeTableScheme as _},
+        node_implementation::ConsensusTime, signature_key::StakeTableEntryType,
+        stake_table::StakeTableScheme as _,
     },
     PeerConfig,
 };
@@ -26,7 +34,7 @@ use jf_crhf::CRHF;
 use jf_rescue::{crhf::VariableLengthRescueCRHF, RescueError};
 use jf_signature::SignatureScheme;
 use surf_disco::{Client, Url};
-use tide_disco::error::ServerError;
+use tide_disco::{error::ServerError, Error as _};
 use vbs::version::StaticVersionType;
 
 use crate::{SeqTypes, StateKeyPair};
@@ -37,6 +45,20 @@ pub mod relay_server;
 /// Capacity for the in memory signature storage.
 const SIGNATURE_STORAGE_CAPACITY: usize = 100;
 
+/// Number of block heights for which signatures are kept in persistent storage.
+///
+/// Signatures older than this are deleted even if they were never delivered to the relay server.
+const SIGNATURE_RETENTION: u64 = 10_000;
+
+/// How often, in block heights, to delete signatures which have fallen out of the retention window.
+const SIGNATURE_PRUNE_INTERVAL: u64 = 100;
+
+/// Initial delay before retrying delivery of a signature to the relay server.
+const DELIVERY_BASE_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Maximum delay between attempts to deliver a signature to the relay server.
+const DELIVERY_MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
 #[derive(Debug)]
 pub struct StateSigner<ApiVer: StaticVersionType> {
     /// Key pair for signing a new light client state
@@ -45,21 +67,32 @@ pub struct StateSigner<ApiVer: StaticVersionType> {
     /// The most recent light client state signatures
     signatures: RwLock<StateSignatureMemStorage>,
 
-    /// Commitment for current fixed stake table
-    #[allow(dead_code)] // although not used today, might need it for dynamic stake table later
-    stake_table_comm: StakeTableCommitmentType,
+    /// State of the stake table this node is running with.
+    ///
+    /// Every signature commits to it as the stake table for the next light client update, so that
+    /// the light client contract follows validator set changes.
+    stake_table_state: StakeTableState,
 
     /// The state relay server url
     relay_server_client: Option<Client<ServerError, ApiVer>>,
+
+    /// New signatures to be delivered to the relay server.
+    outbox: UnboundedSender<StateSignatureRequestBody>,
+
+    /// The receiving end of `outbox`, until it is taken by the delivery task.
+    outbox_receiver: Mutex<Option<UnboundedReceiver<StateSignatureRequestBody>>>,
 }
 
 impl<ApiVer: StaticVersionType> StateSigner<ApiVer> {
-    pub fn new(key_pair: StateKeyPair, stake_table_comm: StakeTableCommitmentType) -> Self {
+    pub fn new(key_pair: StateKeyPair, stake_table_state: StakeTableState) -> Self {
+        let (outbox, outbox_receiver) = unbounded();
         Self {
             key_pair,
-            stake_table_comm,
+            stake_table_state,
             signatures: Default::default(),
             relay_server_client: Default::default(),
+            outbox,
+            outbox_receiver: Mutex::new(Some(outbox_receiver)),
         }
     }
 
@@ -69,37 +102,144 @@ impl<ApiVer: StaticVersionType> StateSigner<ApiVer> {
         self
     }
 
-    pub(super) async fn handle_event(&self, event: &Event<SeqTypes>) {
+    /// Sign the light client state of a newly decided leaf.
+    ///
+    /// The signature is saved in `persistence` and queued for delivery to the relay server, if
+    /// there is one, by [`deliver_signatures`](Self::deliver_signatures).
+    pub(super) async fn handle_event(
+        &self,
+        event: &Event<SeqTypes>,
+        persistence: &impl SequencerPersistence,
+    ) {
         let EventType::Decide { leaf_chain, .. } = &event.event else {
             return;
         };
         let Some(LeafInfo { leaf, .. }) = leaf_chain.first() else {
             return;
         };
-        match form_light_client_state(leaf) {
-            Ok(state) => {
-                let signature = self.sign_new_state(&state).await;
-                tracing::debug!("New leaves decided. Latest block height: {}", leaf.height(),);
-
-                if let Some(client) = &self.relay_server_client {
-                    let request_body = StateSignatureRequestBody {
-                        key: self.key_pair.ver_key(),
-                        state,
-                        signature,
-                    };
-                    if let Err(error) = client
-                        .post::<()>("api/state")
-                        .body_binary(&request_body)
-                        .unwrap()
-                        .send()
-                        .await
-                    {
-                        tracing::warn!("Error posting signature to the relay server: {:?}", error);
-                    }
-                }
+        let state = match form_light_client_state(leaf) {
+            Ok(state) => state,
+            Err(err) => {
+                tracing::error!("Error generating light client state: {:?}", err);
+                return;
+            }
+        };
+        let signature = self.sign_new_state(&state).await;
+        tracing::debug!("New leaves decided. Latest block height: {}", leaf.height(),);
+
+        let request_body = StateSignatureRequestBody {
+            key: self.key_pair.ver_key(),
+            state,
+            signature,
+        };
+        if let Err(err) = persistence.save_state_signature(&request_body).await {
+            tracing::warn!("Error saving state signature: {err:#}");
+        }
+        let height = leaf.height();
+        if height % SIGNATURE_PRUNE_INTERVAL == 0 && height > SIGNATURE_RETENTION {
+            if let Err(err) = persistence
+                .prune_state_signatures(height - SIGNATURE_RETENTION)
+                .await
+            {
+                tracing::warn!("Error pruning old state signatures: {err:#}");
+            }
+        }
+
+        if self.relay_server_client.is_some() {
+            if let Err(err) = self.outbox.send(request_body).await {
+                tracing::warn!("State signature delivery task is not running: {err}");
             }
+        }
+    }
+
+    /// Deliver signatures to the relay server, retrying with backoff until each is accepted.
+    ///
+    /// Signatures which were saved but not delivered before a restart are delivered first. Each
+    /// signature is marked as delivered in `persistence` once the relay server has accepted it, or
+    /// rejected it with a client error, in which case retrying would not help. This runs until the
+    /// signer is dropped, and returns immediately if there is no relay server.
+    pub(super) async fn deliver_signatures(
+        self: Arc<Self>,
+        persistence: Arc<impl SequencerPersistence>,
+    ) {
+        let Some(client) = &self.relay_server_client else {
+            return;
+        };
+        let Some(mut outbox) = self.outbox_receiver.lock().await.take() else {
+            tracing::error!("State signature delivery task is already running");
+            return;
+        };
+
+        // Replay signatures which were not delivered before the last shutdown.
+        let mut queue = match persistence.load_undelivered_state_signatures().await {
+            Ok(signatures) => signatures
+                .into_iter()
+                .map(|signature| (signature.state.block_height as u64, signature))
+                .collect(),
             Err(err) => {
-                tracing::error!("Error generating light client state: {:?}", err)
+                tracing::warn!("Error loading undelivered state signatures: {err:#}");
+                BTreeMap::new()
+            }
+        };
+        if !queue.is_empty() {
+            tracing::info!(
+                count = queue.len(),
+                "replaying undelivered state signatures"
+            );
+        }
+
+        let mut delay = DELIVERY_BASE_RETRY_DELAY;
+        loop {
+            // Wait for a new signature if there is nothing left to deliver, then pick up any other
+            // signatures that have been generated in the meantime.
+            if queue.is_empty() {
+                let Ok(signature) = outbox.recv().await else {
+                    return;
+                };
+                queue.insert(signature.state.block_height as u64, signature);
+            }
+            while let Ok(signature) = outbox.try_recv() {
+                queue.insert(signature.state.block_height as u64, signature);
+            }
+            // Don't keep retrying signatures which have been pruned from storage.
+            while queue.len() as u64 > SIGNATURE_RETENTION {
+                queue.pop_first();
+            }
+
+            // Deliver signatures in order, so that a relay outage doesn't leave gaps.
+            let Some((height, signature)) = queue.pop_first() else {
+                continue;
+            };
+            match client
+                .post::<()>("api/state")
+                .body_binary(&signature)
+                .unwrap()
+                .send()
+                .await
+            {
+                Ok(()) => {
+                    tracing::debug!(height, "delivered state signature to relay server");
+                    delay = DELIVERY_BASE_RETRY_DELAY;
+                }
+                Err(err) if (400..500).contains(&u16::from(err.status())) => {
+                    // The relay server has seen the signature and will never accept it, for
+                    // example because it already has a signature from us for this height.
+                    tracing::warn!(height, "Relay server rejected state signature: {err}");
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        height,
+                        ?delay,
+                        "Error posting signature to the relay server, will retry: {err}"
+                    );
+                    queue.insert(height, signature);
+                    sleep(delay).await;
+                    delay = (delay * 2).min(DELIVERY_MAX_RETRY_DELAY);
+                    continue;
+                }
+            }
+            if let Err(err) = persistence.mark_state_signature_delivered(height).await {
+                tracing::warn!(height, "Error marking state signature delivered: {err:#}");
             }
         }
     }
@@ -110,9 +250,9 @@ impl<ApiVer: StaticVersionType> StateSigner<ApiVer> {
         pool_guard.get_signature(height)
     }
 
-    /// Sign the light client state at given height and store it.
+    /// Sign the light client state at given height, together with our stake table, and store it.
     async fn sign_new_state(&self, state: &LightClientState) -> StateSignature {
-        let msg: [CircuitField; 3] = state.into();
+        let msg = state_signature_message(state, &self.stake_table_state);
         let signature = StateSignatureScheme::sign(
             &(),
             self.key_pair.sign_key_ref(),
@@ -186,14 +326,11 @@ impl StateSignatureMemStorage {
     }
 }
 
-/// Type for stake table commitment
-pub type StakeTableCommitmentType = (CircuitField, CircuitField, CircuitField);
-
-/// Helper function for stake table commitment
-pub fn static_stake_table_commitment(
+/// Helper function for the state of a fixed stake table
+pub fn static_stake_table_state(
     known_nodes_with_stakes: &[PeerConfig<BLSPubKey>],
     capacity: usize,
-) -> (CircuitField, CircuitField, CircuitField) {
+) -> StakeTableState {
     let mut st = StakeTable::<BLSPubKey, StateVerKey, CircuitField>::new(capacity);
     known_nodes_with_stakes.iter().for_each(|peer| {
         // This `unwrap()` won't fail unless number of entries exceeds `capacity`
@@ -207,5 +344,132 @@ pub fn static_stake_table_commitment(
     st.advance();
     st.advance();
     // This `unwrap()` won't fail
-    st.commitment(SnapshotVersion::LastEpochStart).unwrap()
+    stake_table_state(&st).unwrap().into()
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use async_std::task::spawn;
+    use hotshot_types::{light_client::StateSignaturesBundle, traits::signature_key::SignatureKey};
+    use portpicker::pick_unused_port;
+    use sequencer_utils::test_utils::setup_test;
+
+    use super::{
+        relay_server::{run_relay_server, StakeTableSource},
+        *,
+    };
+    use crate::{
+        persistence::{fs, TestablePersistence},
+        SequencerApiVersion,
+    };
+
+    fn light_client_state(block_height: usize) -> LightClientState {
+        LightClientState {
+            view_number: block_height,
+            block_height,
+            block_comm_root: Default::default(),
+        }
+    }
+
+    async fn undelivered_heights(persistence: &impl SequencerPersistence) -> Vec<usize> {
+        persistence
+            .load_undelivered_state_signatures()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|signature| signature.state.block_height)
+            .collect()
+    }
+
+    #[async_std::test]
+    async fn test_deliver_signatures() {
+        setup_test();
+
+        let tmp = fs::Persistence::tmp_storage().await;
+        let persistence = Arc::new(fs::Persistence::connect(&tmp).await);
+        let key = StateKeyPair::generate_from_seed_indexed([0; 32], 0);
+        let peers = vec![PeerConfig {
+            stake_table_entry: BLSPubKey::generated_from_seed_indexed([0; 32], 0)
+                .0
+                .stake_table_entry(1),
+            state_ver_key: key.ver_key(),
+        }];
+        let stake_table_capacity = 10;
+        let port = pick_unused_port().unwrap();
+        let url: Url = format!("http://localhost:{port}").parse().unwrap();
+        let signer = Arc::new(
+            StateSigner::<SequencerApiVersion>::new(
+                key.clone(),
+                static_stake_table_state(&peers, stake_table_capacity),
+            )
+            .with_relay_server(url.clone()),
+        );
+
+        let mut signatures = vec![];
+        for height in 1..=3 {
+            let state = light_client_state(height);
+            let signature = signer.sign_new_state(&state).await;
+            signatures.push(StateSignatureRequestBody {
+                key: key.ver_key(),
+                state,
+                signature,
+            });
+        }
+
+        // The first two signatures were saved but not delivered before a restart.
+        for signature in &signatures[..2] {
+            persistence.save_state_signature(signature).await.unwrap();
+        }
+        let task = spawn(signer.clone().deliver_signatures(persistence.clone()));
+
+        // A new signature is generated while the relay server is down.
+        persistence
+            .save_state_signature(&signatures[2])
+            .await
+            .unwrap();
+        signer.outbox.send(signatures[2].clone()).await.unwrap();
+
+        // Delivery keeps failing while the relay server is down, and nothing is marked delivered.
+        sleep(Duration::from_secs(2)).await;
+        assert_eq!(undelivered_heights(&*persistence).await, [1, 2, 3]);
+
+        // Once the relay server comes up, all signatures are delivered within the retry delay.
+        let relay = spawn(run_relay_server(
+            None,
+            StakeTableSource::Static(peers),
+            stake_table_capacity,
+            None,
+            vec![],
+            format!("http://0.0.0.0:{port}").parse().unwrap(),
+            SequencerApiVersion::instance(),
+        ));
+        let start = Instant::now();
+        while !undelivered_heights(&*persistence).await.is_empty() {
+            assert!(
+                start.elapsed() < DELIVERY_MAX_RETRY_DELAY,
+                "signatures not delivered after relay server recovered"
+            );
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        // The relay server has a bundle for every height, which it could only form from our
+        // signatures.
+        let client = Client::<ServerError, SequencerApiVersion>::new(url);
+        for height in 1..=3 {
+            let bundle: StateSignaturesBundle = client
+                .get(&format!("api/state/{height}"))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(bundle.state, signatures[height - 1].state);
+            assert!(bundle.signatures.contains_key(&key.ver_key()));
+        }
+
+        // After another restart, there is nothing left to replay.
+        task.cancel().await;
+        assert!(undelivered_heights(&*persistence).await.is_empty());
+        relay.cancel().await;
+    }
 }
@@ -1,18 +1,33 @@
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
     path::PathBuf,
+    sync::Arc,
+    time::Duration,
 };
 
+use anyhow::Context;
 use async_compatibility_layer::channel::OneShotReceiver;
-use async_std::sync::RwLock;
+use async_std::{sync::RwLock, task::sleep};
+use async_trait::async_trait;
 use clap::Args;
-use ethers::types::U256;
-use futures::FutureExt;
-use hotshot_stake_table::vec_based::config::FieldType;
-use hotshot_types::light_client::{
-    StateSignature, StateSignatureScheme, StateSignaturesBundle, StateVerKey,
+use contract_bindings::light_client::LightClient;
+use espresso_types::PubKey;
+use ethers::{
+    providers::{Http, Provider},
+    types::{Address, U256},
+};
+use futures::{future::try_join_all, FutureExt};
+use hotshot_state_prover::{circuit::state_signature_message, service::one_honest_threshold};
+use hotshot_types::{
+    light_client::{
+        StakeTableState, StateSignature, StateSignatureScheme, StateSignaturesBundle, StateVerKey,
+    },
+    traits::signature_key::StakeTableEntryType,
+    PeerConfig,
 };
 use jf_signature::SignatureScheme;
+use surf_disco::Client;
 use tide_disco::{
     api::ApiError,
     error::ServerError,
@@ -22,15 +37,46 @@ use tide_disco::{
 use url::Url;
 use vbs::version::StaticVersionType;
 
-use super::{LightClientState, StateSignatureRequestBody};
+use super::{static_stake_table_state, LightClientState, StateSignatureRequestBody};
+use crate::api::data_source::PublicNetworkConfig;
+
+mod storage;
+
+pub use storage::RelayStorage;
+
+/// Number of block heights for which available signature bundles are kept, counting back from the
+/// latest available bundle, when there are no light client contracts to prune against.
+const BUNDLE_HISTORY_CAPACITY: u64 = 1000;
+
+/// How often to check the light client contracts for a newly finalized state.
+const FINALIZED_STATE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The stake of each state verification key which may sign light client states.
+pub type KnownNodes = HashMap<StateVerKey, U256>;
+
+/// Get the stake of each state verification key from a list of staked nodes.
+pub fn known_nodes_from_peers(peers: &[PeerConfig<PubKey>]) -> KnownNodes {
+    peers
+        .iter()
+        .map(|peer| (peer.state_ver_key.clone(), peer.stake_table_entry.stake()))
+        .collect()
+}
 
 /// State that checks the light client state update and the signature collection
 #[derive(Default)]
 struct StateRelayServerState {
     /// Minimum weight to form an available state signature bundle
     threshold: U256,
-    /// Stake table
-    known_nodes: HashMap<StateVerKey, U256>,
+    /// Capacity of the stake table.
+    ///
+    /// This must match the capacity used by the signers, since the stake table state they commit
+    /// to depends on it.
+    stake_table_capacity: usize,
+    /// Stake table and its state, or `None` if it has not been loaded yet
+    ///
+    /// Signatures commit to the stake table state, as the stake table for the next light client
+    /// update, and are only accepted if it matches ours.
+    stake_table: Option<(KnownNodes, StakeTableState)>,
     /// Signatures bundles for each block height
     bundles: HashMap<u64, HashMap<LightClientState, StateSignaturesBundle>>,
 
@@ -42,18 +88,78 @@ struct StateRelayServerState {
     /// A ordered queue of block heights, used for garbage collection.
     queue: BTreeSet<u64>,
 
+    /// Available signature bundles for recent block heights
+    history: BTreeMap<u64, StateSignaturesBundle>,
+
+    /// On-disk storage for signature bundles, if enabled
+    storage: Option<RelayStorage>,
+
+    /// Whether bundles are pruned once they are behind the state finalized by the light client
+    /// contracts, rather than once they fall out of a fixed-size history window.
+    prune_finalized: bool,
+
     /// shutdown signal
     shutdown: Option<OneShotReceiver<()>>,
 }
 
 impl StateRelayServerState {
-    pub fn new(threshold: U256) -> Self {
+    pub fn new(stake_table_capacity: usize) -> Self {
         Self {
-            threshold,
+            stake_table_capacity,
             ..Default::default()
         }
     }
 
+    pub async fn with_stake_table(mut self, peers: &[PeerConfig<PubKey>]) -> Self {
+        self.update_stake_table(peers).await;
+        self
+    }
+
+    /// Keep available bundles until they are pruned by [`prune`](Self::prune), instead of keeping
+    /// a fixed number of recent bundles.
+    pub fn with_finalized_pruning(mut self) -> Self {
+        self.prune_finalized = true;
+        self
+    }
+
+    /// Persist signature bundles in `storage`, restoring any bundles it already contains.
+    ///
+    /// This must be called before the stake table is set, so that restored pending signatures are
+    /// checked against the current stake table, and their weights recomputed with it.
+    pub async fn with_storage(mut self, storage: RelayStorage) -> anyhow::Result<Self> {
+        self.history = storage.load_available()?;
+        if let Some((height, bundle)) = self.history.last_key_value() {
+            tracing::info!(height, "restored latest available signature bundle");
+            self.latest_block_height = Some(*height);
+            self.latest_available_bundle = Some(bundle.clone());
+        }
+
+        let latest_block_height = self.latest_block_height.unwrap_or(0);
+        for (height, bundles) in storage.load_pending()? {
+            if height <= latest_block_height {
+                // This height was superseded by an available bundle before we shut down.
+                storage.remove_pending(height).await?;
+                continue;
+            }
+            tracing::info!(
+                height,
+                bundles = bundles.len(),
+                "restored pending signatures"
+            );
+            self.queue.insert(height);
+            self.bundles.insert(
+                height,
+                bundles
+                    .into_iter()
+                    .map(|bundle| (bundle.state.clone(), bundle))
+                    .collect(),
+            );
+        }
+
+        self.storage = Some(storage);
+        Ok(self)
+    }
+
     pub fn with_shutdown_signal(mut self, shutdown_listener: Option<OneShotReceiver<()>>) -> Self {
         if self.shutdown.is_some() {
             panic!("A shutdown signal is already registered and can not be registered twice");
@@ -61,6 +167,168 @@ impl StateRelayServerState {
         self.shutdown = shutdown_listener;
         self
     }
+
+    /// Replace the stake table.
+    ///
+    /// If the stake table changed, the threshold is recomputed from the new total stake. Signatures
+    /// already collected are dropped unless they are from keys still in the stake table and commit
+    /// to the new stake table, and the weight of each pending bundle is recomputed with the new
+    /// stakes.
+    async fn update_stake_table(&mut self, peers: &[PeerConfig<PubKey>]) {
+        if peers.len() > self.stake_table_capacity {
+            tracing::warn!(
+                nodes = peers.len(),
+                capacity = self.stake_table_capacity,
+                "ignoring stake table which exceeds the capacity"
+            );
+            return;
+        }
+        let stake_table_state = static_stake_table_state(peers, self.stake_table_capacity);
+        if matches!(&self.stake_table, Some((_, state)) if *state == stake_table_state) {
+            return;
+        }
+
+        let known_nodes = known_nodes_from_peers(peers);
+        let total_stake = known_nodes
+            .values()
+            .fold(U256::zero(), |total, stake| total + *stake);
+        self.threshold = one_honest_threshold(total_stake);
+        tracing::info!(
+            nodes = known_nodes.len(),
+            %total_stake,
+            threshold = %self.threshold,
+            "updated stake table"
+        );
+
+        let mut available = None;
+        let mut changed = vec![];
+        for (height, bundles) in &mut self.bundles {
+            let mut dropped = false;
+            for bundle in bundles.values_mut() {
+                let count = bundle.signatures.len();
+                let msg = state_signature_message(&bundle.state, &stake_table_state);
+                bundle.signatures.retain(|key, signature| {
+                    known_nodes.contains_key(key)
+                        && StateSignatureScheme::verify(&(), key, msg, signature).is_ok()
+                });
+                dropped |= bundle.signatures.len() < count;
+                bundle.accumulated_weight = bundle
+                    .signatures
+                    .keys()
+                    .fold(U256::zero(), |weight, key| weight + known_nodes[key]);
+                if bundle.accumulated_weight >= self.threshold
+                    && available.as_ref().map_or(true, |(h, _)| height > h)
+                {
+                    available = Some((*height, bundle.clone()));
+                }
+            }
+            if dropped {
+                changed.push(*height);
+            }
+        }
+        self.stake_table = Some((known_nodes, stake_table_state));
+
+        // Persist the pending signatures which remain at each height where some were dropped, so
+        // they are not restored if the stake table changes back after a restart.
+        for height in changed {
+            self.save_pending(height).await;
+        }
+        if let Some((height, bundle)) = available {
+            self.make_available(height, bundle).await;
+        }
+    }
+
+    /// Serve `bundle` as the latest available bundle and forget older pending bundles.
+    async fn make_available(&mut self, block_height: u64, bundle: StateSignaturesBundle) {
+        tracing::info!(
+            "State signature bundle at block height {} is ready to serve.",
+            block_height
+        );
+        self.latest_block_height = Some(block_height);
+        self.latest_available_bundle = Some(bundle.clone());
+        self.history.insert(block_height, bundle.clone());
+        if let Some(storage) = &self.storage {
+            if let Err(err) = storage.save_available(block_height, &bundle).await {
+                tracing::warn!(block_height, "failed to save available bundle: {err:#}");
+            }
+        }
+
+        while let Some(height) = self.queue.pop_first() {
+            self.bundles.remove(&height);
+            if let Some(storage) = &self.storage {
+                if let Err(err) = storage.remove_pending(height).await {
+                    tracing::warn!(height, "failed to remove pending bundles: {err:#}");
+                }
+            }
+            if height == block_height {
+                break;
+            }
+        }
+
+        // Forget bundles which have fallen out of the history window.
+        if !self.prune_finalized {
+            self.prune_history(block_height.saturating_sub(BUNDLE_HISTORY_CAPACITY - 1))
+                .await;
+        }
+    }
+
+    /// Forget bundles which are no longer needed because `finalized_height` has been finalized by
+    /// every light client contract.
+    ///
+    /// Available bundles below this height are deleted, except for the latest available bundle,
+    /// which is always served. Pending bundles at or below this height can never be used, and are
+    /// deleted as well.
+    async fn prune(&mut self, finalized_height: u64) {
+        tracing::debug!(finalized_height, "pruning finalized signature bundles");
+        self.prune_history(finalized_height).await;
+
+        while let Some(height) = self.queue.first().copied() {
+            if height > finalized_height {
+                break;
+            }
+            self.queue.pop_first();
+            self.bundles.remove(&height);
+            if let Some(storage) = &self.storage {
+                if let Err(err) = storage.remove_pending(height).await {
+                    tracing::warn!(height, "failed to remove pending bundles: {err:#}");
+                }
+            }
+        }
+    }
+
+    /// Forget available bundles below `height`, except for the latest available bundle.
+    async fn prune_history(&mut self, height: u64) {
+        let latest = self.latest_block_height.unwrap_or(0);
+        while let Some(entry) = self.history.first_entry() {
+            let entry_height = *entry.key();
+            if entry_height >= height || entry_height == latest {
+                break;
+            }
+            entry.remove();
+            if let Some(storage) = &self.storage {
+                if let Err(err) = storage.remove_available(entry_height).await {
+                    tracing::warn!(
+                        height = entry_height,
+                        "failed to remove available bundle: {err:#}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Save the pending bundles at `block_height`, if storage is enabled.
+    ///
+    /// This rewrites all the signatures at this height. A single new signature is saved with
+    /// [`RelayStorage::append_pending`] instead.
+    async fn save_pending(&self, block_height: u64) {
+        let (Some(storage), Some(bundles)) = (&self.storage, self.bundles.get(&block_height))
+        else {
+            return;
+        };
+        if let Err(err) = storage.save_pending(block_height, bundles.values()).await {
+            tracing::warn!(block_height, "failed to save pending bundles: {err:#}");
+        }
+    }
 }
 
 // TODO(Chengyu): move this `RwLock` inside `StateRelayServerState` so that when nodes are submitting
@@ -68,16 +336,23 @@ impl StateRelayServerState {
 type State = RwLock<StateRelayServerState>;
 type Error = ServerError;
 
+#[async_trait]
 pub trait StateRelayServerDataSource {
     /// Get the latest available signatures bundle.
     /// # Errors
     /// Errors if there's no available signatures bundle.
     fn get_latest_signature_bundle(&self) -> Result<StateSignaturesBundle, Error>;
 
+    /// Get the available signatures bundle for the given block height.
+    /// # Errors
+    /// Errors if there's no available signatures bundle for this height, either because not enough
+    /// signatures have been collected or because the bundle is too old to be kept.
+    fn get_signature_bundle(&self, height: u64) -> Result<StateSignaturesBundle, Error>;
+
     /// Post a signature to the relay server
     /// # Errors
     /// Errors if the signature is invalid, already posted, or no longer needed.
-    fn post_signature(
+    async fn post_signature(
         &mut self,
         key: StateVerKey,
         state: LightClientState,
@@ -85,6 +360,7 @@ pub trait StateRelayServerDataSource {
     ) -> Result<(), Error>;
 }
 
+#[async_trait]
 impl StateRelayServerDataSource for StateRelayServerState {
     fn get_latest_signature_bundle(&self) -> Result<StateSignaturesBundle, Error> {
         match &self.latest_available_bundle {
@@ -96,7 +372,17 @@ impl StateRelayServerDataSource for StateRelayServerState {
         }
     }
 
-    fn post_signature(
+    fn get_signature_bundle(&self, height: u64) -> Result<StateSignaturesBundle, Error> {
+        match self.history.get(&height) {
+            Some(bundle) => Ok(bundle.clone()),
+            None => Err(tide_disco::error::ServerError::catch_all(
+                StatusCode::NOT_FOUND,
+                format!("No light client state signatures are available at block height {height}."),
+            )),
+        }
+    }
+
+    async fn post_signature(
         &mut self,
         key: StateVerKey,
         state: LightClientState,
@@ -106,19 +392,24 @@ impl StateRelayServerDataSource for StateRelayServerState {
             // This signature is no longer needed
             return Ok(());
         }
-        let one = U256::one();
-        let weight = self.known_nodes.get(&key).unwrap_or(&one);
-        // TODO(Chengyu): We don't know where to fetch the stake table yet.
-        // Related issue: [https://github.com/EspressoSystems/espresso-sequencer/issues/1022]
-        // .ok_or(tide_disco::error::ServerError::catch_all(
-        //     StatusCode::Unauthorized,
-        //     "The posted key is not found in the stake table.".to_owned(),
-        // ))?;
-        let state_msg: [FieldType; 3] = (&state).into();
+        let (known_nodes, stake_table_state) = self.stake_table.as_ref().ok_or_else(|| {
+            tide_disco::error::ServerError::catch_all(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "The stake table has not been loaded yet.".to_owned(),
+            )
+        })?;
+        let weight = *known_nodes.get(&key).ok_or_else(|| {
+            tide_disco::error::ServerError::catch_all(
+                StatusCode::UNAUTHORIZED,
+                "The posted key is not found in the stake table.".to_owned(),
+            )
+        })?;
+        let state_msg = state_signature_message(&state, stake_table_state);
         if StateSignatureScheme::verify(&(), &key, state_msg, &signature).is_err() {
             return Err(tide_disco::error::ServerError::catch_all(
                 StatusCode::BAD_REQUEST,
-                "The posted signature is not valid.".to_owned(),
+                "The posted signature is not valid for this state and the current stake table."
+                    .to_owned(),
             ));
         }
         let block_height = state.block_height as u64;
@@ -139,7 +430,7 @@ impl StateRelayServerDataSource for StateRelayServerState {
             block_height,
             key
         );
-        match bundle.signatures.entry(key) {
+        match bundle.signatures.entry(key.clone()) {
             std::collections::hash_map::Entry::Occupied(_) => {
                 // A signature is already posted for this key with this state
                 return Err(tide_disco::error::ServerError::catch_all(
@@ -148,29 +439,139 @@ impl StateRelayServerDataSource for StateRelayServerState {
                 ));
             }
             std::collections::hash_map::Entry::Vacant(entry) => {
-                entry.insert(signature);
-                bundle.accumulated_weight += *weight;
+                entry.insert(signature.clone());
+                bundle.accumulated_weight += weight;
             }
         }
 
         if bundle.accumulated_weight >= self.threshold {
-            tracing::info!(
-                "State signature bundle at block height {} is ready to serve.",
-                block_height
-            );
-            self.latest_block_height = Some(block_height);
-            self.latest_available_bundle = Some(bundle.clone());
-            while let Some(height) = self.queue.pop_first() {
-                self.bundles.remove(&height);
-                if height == block_height {
-                    break;
-                }
+            let bundle = bundle.clone();
+            self.make_available(block_height, bundle).await;
+        } else if let Some(storage) = &self.storage {
+            if let Err(err) = storage
+                .append_pending(block_height, &bundle.state, &key, &signature)
+                .await
+            {
+                tracing::warn!(block_height, "failed to save pending signature: {err:#}");
             }
         }
         Ok(())
     }
 }
 
+/// Where the relay server gets its stake table from.
+#[derive(Clone, Debug)]
+pub enum StakeTableSource {
+    /// A fixed stake table.
+    Static(Vec<PeerConfig<PubKey>>),
+    /// A public network config file, in the format served by the `config/hotshot` endpoint,
+    /// reloaded at the given interval.
+    ///
+    /// Updating this file with a new validator set lets the light client contract follow the
+    /// change.
+    File {
+        path: PathBuf,
+        refresh_interval: Duration,
+    },
+    /// The stake table of a sequencer node, fetched from its `config` API and refreshed at the
+    /// given interval.
+    Sequencer {
+        url: Url,
+        refresh_interval: Duration,
+    },
+}
+
+/// A source the stake table is reloaded from.
+enum StakeTableFetcher<ApiVer: StaticVersionType> {
+    File(PathBuf),
+    Sequencer(Client<ServerError, ApiVer>),
+}
+
+impl<ApiVer: StaticVersionType> StakeTableFetcher<ApiVer> {
+    async fn fetch(&self) -> anyhow::Result<Vec<PeerConfig<PubKey>>> {
+        let config = match self {
+            Self::File(path) => {
+                let bytes = fs::read(path).context(format!("reading {}", path.display()))?;
+                serde_json::from_slice::<PublicNetworkConfig>(&bytes)
+                    .context(format!("malformed network config in {}", path.display()))?
+            }
+            Self::Sequencer(client) => client
+                .get::<PublicNetworkConfig>("config/hotshot")
+                .send()
+                .await
+                .context("fetching network config")?,
+        };
+        Ok(config.known_nodes_with_stake().to_vec())
+    }
+}
+
+/// Keep the stake table of the relay server up to date with `fetcher`.
+async fn refresh_stake_table<ApiVer: StaticVersionType>(
+    state: Arc<State>,
+    fetcher: StakeTableFetcher<ApiVer>,
+    refresh_interval: Duration,
+) {
+    // How long to wait before retrying if the stake table has never been loaded.
+    const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+    let mut loaded = false;
+    loop {
+        match fetcher.fetch().await {
+            Ok(peers) => {
+                state.write().await.update_stake_table(&peers).await;
+                loaded = true;
+            }
+            Err(err) => tracing::warn!("failed to fetch stake table: {err:#}"),
+        }
+        sleep(if loaded {
+            refresh_interval
+        } else {
+            RETRY_INTERVAL
+        })
+        .await;
+    }
+}
+
+/// A light client contract which is updated with the signature bundles from the relay server.
+#[derive(Clone, Debug)]
+pub struct LightClientContract {
+    /// URL of a JSON-RPC provider for the chain the contract is deployed on.
+    pub provider: Url,
+    /// Address of the contract.
+    pub address: Address,
+}
+
+/// Read the block height of the latest state finalized by a light client contract.
+async fn fetch_finalized_height(contract: &LightClientContract) -> anyhow::Result<u64> {
+    let provider = Provider::<Http>::try_from(contract.provider.as_str())?;
+    let light_client = LightClient::new(contract.address, Arc::new(provider));
+    let (_, block_height, _) = light_client
+        .finalized_state()
+        .call()
+        .await
+        .context(format!(
+            "reading finalized state from {:#x}",
+            contract.address
+        ))?;
+    Ok(block_height)
+}
+
+/// Prune signature bundles once the states they sign have been finalized by every light client
+/// contract.
+async fn prune_finalized_bundles(state: Arc<State>, contracts: Vec<LightClientContract>) {
+    loop {
+        match try_join_all(contracts.iter().map(fetch_finalized_height)).await {
+            Ok(heights) => {
+                if let Some(height) = heights.into_iter().min() {
+                    state.write().await.prune(height).await;
+                }
+            }
+            Err(err) => tracing::warn!("failed to fetch finalized light client state: {err:#}"),
+        }
+        sleep(FINALIZED_STATE_POLL_INTERVAL).await;
+    }
+}
+
 /// configurability options for the web server
 #[derive(Args, Default)]
 pub struct Options {
@@ -207,6 +608,15 @@ where
     api.get("getlateststate", |_req, state| {
         async move { state.get_latest_signature_bundle() }.boxed()
     })?
+    .get("getstate", |req, state| {
+        async move {
+            let height = req
+                .integer_param("height")
+                .map_err(Error::from_request_error)?;
+            state.get_signature_bundle(height)
+        }
+        .boxed()
+    })?
     .post("poststatesignature", |req, state| {
         async move {
             let StateSignatureRequestBody {
@@ -216,7 +626,7 @@ where
             } = req
                 .body_auto::<StateSignatureRequestBody, ApiVer>(ApiVer::instance())
                 .map_err(Error::from_request_error)?;
-            state.post_signature(key, lcstate, signature)
+            state.post_signature(key, lcstate, signature).await
         }
         .boxed()
     })?;
@@ -224,25 +634,432 @@ where
     Ok(api)
 }
 
+/// Run the state relay server.
+///
+/// Signature bundles are kept until the states they sign have been finalized by every contract in
+/// `light_clients`. If there are no such contracts, a fixed number of recent bundles is kept.
+///
+/// `stake_table_capacity` must match the capacity the signers compute their stake table state
+/// with.
+#[allow(clippy::too_many_arguments)]
 pub async fn run_relay_server<ApiVer: StaticVersionType + 'static>(
     shutdown_listener: Option<OneShotReceiver<()>>,
-    threshold: U256,
+    stake_table: StakeTableSource,
+    stake_table_capacity: usize,
+    storage: Option<PathBuf>,
+    light_clients: Vec<LightClientContract>,
     url: Url,
     bind_version: ApiVer,
-) -> std::io::Result<()> {
+) -> anyhow::Result<()> {
     let options = Options::default();
 
     let api = define_api(&options, bind_version).unwrap();
 
-    // We don't have a stake table yet, putting some temporary value here.
-    // Related issue: [https://github.com/EspressoSystems/espresso-sequencer/issues/1022]
-    let state =
-        State::new(StateRelayServerState::new(threshold).with_shutdown_signal(shutdown_listener));
-    let mut app = App::<State, Error>::with_state(state);
+    let mut state =
+        StateRelayServerState::new(stake_table_capacity).with_shutdown_signal(shutdown_listener);
+    if !light_clients.is_empty() {
+        state = state.with_finalized_pruning();
+    }
+    if let Some(path) = storage {
+        tracing::info!("storing signature bundles in {}", path.display());
+        state = state.with_storage(RelayStorage::open(path)?).await?;
+    }
+    let refresh = match stake_table {
+        StakeTableSource::Static(peers) => {
+            state = state.with_stake_table(&peers).await;
+            None
+        }
+        StakeTableSource::File {
+            path,
+            refresh_interval,
+        } => {
+            tracing::info!("loading stake table from {}", path.display());
+            Some((StakeTableFetcher::File(path), refresh_interval))
+        }
+        StakeTableSource::Sequencer {
+            url,
+            refresh_interval,
+        } => {
+            tracing::info!(%url, "loading stake table from sequencer");
+            Some((
+                StakeTableFetcher::Sequencer(Client::<ServerError, ApiVer>::new(url)),
+                refresh_interval,
+            ))
+        }
+    };
+    let state = Arc::new(State::new(state));
+
+    // Signatures are rejected until the stake table is loaded, but we start serving right away so
+    // that the relay server does not depend on the sequencer being up.
+    let refresh_task = refresh.map(|(fetcher, refresh_interval)| {
+        async_std::task::spawn(refresh_stake_table(
+            state.clone(),
+            fetcher,
+            refresh_interval,
+        ))
+    });
+
+    let prune_task = (!light_clients.is_empty())
+        .then(|| async_std::task::spawn(prune_finalized_bundles(state.clone(), light_clients)));
+
+    let mut app = App::<Arc<State>, Error>::with_state(state);
 
     app.register_module("api", api).unwrap();
 
     let app_future = app.serve(url, bind_version);
 
-    app_future.await
+    let res = app_future.await;
+    if let Some(task) = refresh_task {
+        task.cancel().await;
+    }
+    if let Some(task) = prune_task {
+        task.cancel().await;
+    }
+    Ok(res?)
+}
+
+#[cfg(test)]
+mod test {
+    use hotshot_types::traits::signature_key::SignatureKey;
+    use sequencer_utils::test_utils::setup_test;
+
+    use super::*;
+    use crate::StateKeyPair;
+
+    const STAKE_TABLE_CAPACITY: usize = 10;
+
+    fn sign(
+        key: &StateKeyPair,
+        state: &LightClientState,
+        stake_table_state: &StakeTableState,
+    ) -> StateSignature {
+        let msg = state_signature_message(state, stake_table_state);
+        StateSignatureScheme::sign(&(), key.sign_key_ref(), msg, &mut rand::thread_rng()).unwrap()
+    }
+
+    fn light_client_state(block_height: usize) -> LightClientState {
+        LightClientState {
+            view_number: block_height,
+            block_height,
+            block_comm_root: Default::default(),
+        }
+    }
+
+    /// Generate state keys and a stake table giving the `i`-th key `stakes[i]`.
+    fn stake_table(stakes: &[u64]) -> (Vec<StateKeyPair>, Vec<PeerConfig<PubKey>>) {
+        let keys = (0..stakes.len() as u64)
+            .map(|i| StateKeyPair::generate_from_seed_indexed([0; 32], i))
+            .collect::<Vec<_>>();
+        let peers = keys
+            .iter()
+            .zip(stakes)
+            .enumerate()
+            .map(|(i, (key, stake))| PeerConfig {
+                stake_table_entry: PubKey::generated_from_seed_indexed([0; 32], i as u64)
+                    .0
+                    .stake_table_entry(*stake),
+                state_ver_key: key.ver_key(),
+            })
+            .collect();
+        (keys, peers)
+    }
+
+    #[async_std::test]
+    async fn test_relay_server_stake_table() {
+        setup_test();
+
+        let (keys, peers) = stake_table(&[1, 1, 4]);
+        let st_state = static_stake_table_state(&peers, STAKE_TABLE_CAPACITY);
+        let state = light_client_state(1);
+
+        // Signatures are rejected until the stake table is loaded.
+        let mut server = StateRelayServerState::new(STAKE_TABLE_CAPACITY);
+        server
+            .post_signature(
+                keys[0].ver_key(),
+                state.clone(),
+                sign(&keys[0], &state, &st_state),
+            )
+            .await
+            .unwrap_err();
+
+        // The threshold is computed from the total stake.
+        let mut server = server.with_stake_table(&peers).await;
+        assert_eq!(server.threshold, U256::from(3));
+
+        // Keys outside the stake table are rejected.
+        let outsider = StateKeyPair::generate_from_seed_indexed([1; 32], 0);
+        server
+            .post_signature(
+                outsider.ver_key(),
+                state.clone(),
+                sign(&outsider, &state, &st_state),
+            )
+            .await
+            .unwrap_err();
+
+        // Signatures committing to a different stake table are rejected.
+        server
+            .post_signature(
+                keys[0].ver_key(),
+                state.clone(),
+                sign(&keys[0], &state, &Default::default()),
+            )
+            .await
+            .unwrap_err();
+
+        // Signatures are weighted by stake.
+        for key in &keys[..2] {
+            server
+                .post_signature(key.ver_key(), state.clone(), sign(key, &state, &st_state))
+                .await
+                .unwrap();
+        }
+        server.get_latest_signature_bundle().unwrap_err();
+        server
+            .post_signature(
+                keys[2].ver_key(),
+                state.clone(),
+                sign(&keys[2], &state, &st_state),
+            )
+            .await
+            .unwrap();
+        let bundle = server.get_latest_signature_bundle().unwrap();
+        assert_eq!(bundle.state, state);
+        assert_eq!(bundle.accumulated_weight, U256::from(6));
+
+        // Reloading the same stake table keeps the pending signatures.
+        let state = light_client_state(2);
+        for key in &keys[..2] {
+            server
+                .post_signature(key.ver_key(), state.clone(), sign(key, &state, &st_state))
+                .await
+                .unwrap();
+        }
+        server.update_stake_table(&peers).await;
+        assert_eq!(server.bundles[&2][&state].accumulated_weight, U256::from(2));
+
+        // When the stake table changes, signatures committing to the old one no longer count, and
+        // new signatures must commit to the new one.
+        let new_peers = &peers[..1];
+        let new_st_state = static_stake_table_state(new_peers, STAKE_TABLE_CAPACITY);
+        server.update_stake_table(new_peers).await;
+        assert_eq!(server.threshold, U256::from(1));
+        assert!(server.bundles[&2][&state].signatures.is_empty());
+        server.get_signature_bundle(2).unwrap_err();
+        server
+            .post_signature(
+                keys[0].ver_key(),
+                state.clone(),
+                sign(&keys[0], &state, &st_state),
+            )
+            .await
+            .unwrap_err();
+        server
+            .post_signature(
+                keys[0].ver_key(),
+                state.clone(),
+                sign(&keys[0], &state, &new_st_state),
+            )
+            .await
+            .unwrap();
+        let bundle = server.get_latest_signature_bundle().unwrap();
+        assert_eq!(bundle.state, state);
+        assert_eq!(bundle.accumulated_weight, U256::from(1));
+        assert_eq!(bundle.signatures.len(), 1);
+    }
+
+    #[async_std::test]
+    async fn test_relay_server_storage() {
+        setup_test();
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (keys, peers) = stake_table(&[1, 1]);
+        let st_state = static_stake_table_state(&peers, STAKE_TABLE_CAPACITY);
+
+        let mut server = StateRelayServerState::new(STAKE_TABLE_CAPACITY)
+            .with_storage(RelayStorage::open(tmp.path()).unwrap())
+            .await
+            .unwrap()
+            .with_stake_table(&peers)
+            .await;
+        assert_eq!(server.threshold, U256::from(1));
+
+        // Make a bundle available at height 1 and leave one pending at height 2 from a state which
+        // does not reach the threshold on its own.
+        let state1 = light_client_state(1);
+        server
+            .post_signature(
+                keys[0].ver_key(),
+                state1.clone(),
+                sign(&keys[0], &state1, &st_state),
+            )
+            .await
+            .unwrap();
+        let (_, more_stake) = stake_table(&[1, 6]);
+        let st_state = static_stake_table_state(&more_stake, STAKE_TABLE_CAPACITY);
+        server.update_stake_table(&more_stake).await;
+        let state2 = light_client_state(2);
+        server
+            .post_signature(
+                keys[0].ver_key(),
+                state2.clone(),
+                sign(&keys[0], &state2, &st_state),
+            )
+            .await
+            .unwrap();
+        assert_eq!(server.get_signature_bundle(1).unwrap().state, state1);
+        server.get_signature_bundle(2).unwrap_err();
+
+        // After a restart, the available bundle is still served, and the pending signature still
+        // counts towards the bundle at height 2.
+        drop(server);
+        let mut server = StateRelayServerState::new(STAKE_TABLE_CAPACITY)
+            .with_storage(RelayStorage::open(tmp.path()).unwrap())
+            .await
+            .unwrap()
+            .with_stake_table(&more_stake)
+            .await;
+        assert_eq!(server.get_latest_signature_bundle().unwrap().state, state1);
+        assert_eq!(server.get_signature_bundle(1).unwrap().state, state1);
+        server
+            .post_signature(
+                keys[1].ver_key(),
+                state2.clone(),
+                sign(&keys[1], &state2, &st_state),
+            )
+            .await
+            .unwrap();
+        let bundle = server.get_signature_bundle(2).unwrap();
+        assert_eq!(bundle.state, state2);
+        assert_eq!(bundle.signatures.len(), 2);
+        assert_eq!(server.get_latest_signature_bundle().unwrap().state, state2);
+
+        // Old bundles are still available by height.
+        assert_eq!(server.get_signature_bundle(1).unwrap().state, state1);
+    }
+
+    #[async_std::test]
+    async fn test_relay_server_storage_stake_table_change() {
+        setup_test();
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (keys, peers) = stake_table(&[1, 1, 4]);
+        let st_state = static_stake_table_state(&peers, STAKE_TABLE_CAPACITY);
+        let state = light_client_state(1);
+        let pending = |server: &StateRelayServerState| {
+            server
+                .bundles
+                .get(&1)
+                .and_then(|bundles| bundles.get(&state))
+                .map_or(0, |bundle| bundle.signatures.len())
+        };
+
+        let mut server = StateRelayServerState::new(STAKE_TABLE_CAPACITY)
+            .with_storage(RelayStorage::open(tmp.path()).unwrap())
+            .await
+            .unwrap()
+            .with_stake_table(&peers)
+            .await;
+        for key in &keys[..2] {
+            server
+                .post_signature(key.ver_key(), state.clone(), sign(key, &state, &st_state))
+                .await
+                .unwrap();
+        }
+        server.get_signature_bundle(1).unwrap_err();
+
+        // Restored signatures are checked against the stake table, and still count if it is
+        // unchanged.
+        drop(server);
+        let mut server = StateRelayServerState::new(STAKE_TABLE_CAPACITY)
+            .with_storage(RelayStorage::open(tmp.path()).unwrap())
+            .await
+            .unwrap()
+            .with_stake_table(&peers)
+            .await;
+        assert_eq!(pending(&server), 2);
+
+        // Changing the stake table drops the signatures committing to the old one from storage too.
+        server.update_stake_table(&peers[..2]).await;
+        assert_eq!(pending(&server), 0);
+
+        // After a restart, the dropped signatures do not count even though the stake table is back.
+        drop(server);
+        let mut server = StateRelayServerState::new(STAKE_TABLE_CAPACITY)
+            .with_storage(RelayStorage::open(tmp.path()).unwrap())
+            .await
+            .unwrap()
+            .with_stake_table(&peers)
+            .await;
+        assert_eq!(pending(&server), 0);
+        server
+            .post_signature(
+                keys[2].ver_key(),
+                state.clone(),
+                sign(&keys[2], &state, &st_state),
+            )
+            .await
+            .unwrap();
+        let bundle = server.get_signature_bundle(1).unwrap();
+        assert_eq!(bundle.accumulated_weight, U256::from(4));
+        assert_eq!(bundle.signatures.len(), 1);
+    }
+
+    #[async_std::test]
+    async fn test_relay_server_finalized_pruning() {
+        setup_test();
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (keys, peers) = stake_table(&[1]);
+        let key = &keys[0];
+        let st_state = static_stake_table_state(&peers, STAKE_TABLE_CAPACITY);
+
+        let mut server = StateRelayServerState::new(STAKE_TABLE_CAPACITY)
+            .with_finalized_pruning()
+            .with_storage(RelayStorage::open(tmp.path()).unwrap())
+            .await
+            .unwrap()
+            .with_stake_table(&peers)
+            .await;
+
+        // Bundles are kept beyond the fixed-size history window until they are finalized.
+        let heights = 1..=BUNDLE_HISTORY_CAPACITY + 2;
+        for height in heights.clone() {
+            let state = light_client_state(height as usize);
+            server
+                .post_signature(key.ver_key(), state.clone(), sign(key, &state, &st_state))
+                .await
+                .unwrap();
+        }
+        for height in heights.clone() {
+            server.get_signature_bundle(height).unwrap();
+        }
+
+        // Bundles below the finalized height are pruned, in memory and in storage.
+        server.prune(3).await;
+        for height in 1..3 {
+            server.get_signature_bundle(height).unwrap_err();
+        }
+        server.get_signature_bundle(3).unwrap();
+        drop(server);
+        let mut server = StateRelayServerState::new(STAKE_TABLE_CAPACITY)
+            .with_finalized_pruning()
+            .with_storage(RelayStorage::open(tmp.path()).unwrap())
+            .await
+            .unwrap()
+            .with_stake_table(&peers)
+            .await;
+        server.get_signature_bundle(2).unwrap_err();
+        server.get_signature_bundle(3).unwrap();
+
+        // The latest available bundle is always kept.
+        let latest = *heights.end();
+        server.prune(latest + 1).await;
+        server.get_signature_bundle(latest - 1).unwrap_err();
+        assert_eq!(
+            server.get_signature_bundle(latest).unwrap().state,
+            server.get_latest_signature_bundle().unwrap().state
+        );
+    }
 }
@@ -0,0 +1,216 @@
+//! On-disk storage for the state relay server.
+//!
+//! Signature bundles are stored as one file per block height, so a restarted relay server can keep
+//! serving the bundles it had already collected instead of waiting for sequencers to sign new
+//! states. Bundles which have reached the signing threshold are kept in `available`. Signatures for
+//! bundles still collecting signatures are kept in `pending`, where each file is a log of the
+//! signatures received at that height, so that accepting a signature only appends a record.
+//!
+//! File system operations run on a blocking thread, so they do not stall the async executor.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use async_std::task::spawn_blocking;
+use ethers::types::U256;
+use hotshot_types::light_client::{StateSignature, StateSignaturesBundle, StateVerKey};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::LightClientState;
+
+/// A signature in the log of pending signatures at a block height.
+type PendingSignature = (LightClientState, StateVerKey, StateSignature);
+
+#[derive(Clone, Debug)]
+pub struct RelayStorage {
+    path: PathBuf,
+}
+
+impl RelayStorage {
+    /// Open storage in the directory `path`, creating it if it does not exist.
+    pub fn open(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let storage = Self { path: path.into() };
+        for dir in [storage.available_path(), storage.pending_path()] {
+            fs::create_dir_all(&dir).context(format!("creating {}", dir.display()))?;
+        }
+        Ok(storage)
+    }
+
+    fn available_path(&self) -> PathBuf {
+        self.path.join("available")
+    }
+
+    fn pending_path(&self) -> PathBuf {
+        self.path.join("pending")
+    }
+
+    /// Load the available bundle at each block height.
+    pub fn load_available(&self) -> anyhow::Result<BTreeMap<u64, StateSignaturesBundle>> {
+        load_dir(&self.available_path(), |bytes| {
+            Ok(bincode::deserialize(bytes)?)
+        })
+    }
+
+    /// Load the pending bundles at each block height.
+    ///
+    /// The accumulated weight of each bundle is not stored, so the loaded bundles have zero weight
+    /// until it is recomputed with the current stake table.
+    pub fn load_pending(&self) -> anyhow::Result<BTreeMap<u64, Vec<StateSignaturesBundle>>> {
+        load_dir(&self.pending_path(), |bytes| {
+            let mut bundles = HashMap::<LightClientState, StateSignaturesBundle>::new();
+            for (state, key, signature) in read_records::<PendingSignature>(bytes)? {
+                bundles
+                    .entry(state.clone())
+                    .or_insert_with(|| StateSignaturesBundle {
+                        state,
+                        signatures: Default::default(),
+                        accumulated_weight: U256::zero(),
+                    })
+                    .signatures
+                    .insert(key, signature);
+            }
+            Ok(bundles.into_values().collect())
+        })
+    }
+
+    pub async fn save_available(
+        &self,
+        height: u64,
+        bundle: &StateSignaturesBundle,
+    ) -> anyhow::Result<()> {
+        let path = height_path(&self.available_path(), height);
+        let bytes = bincode::serialize(bundle)?;
+        spawn_blocking(move || replace(&path, &bytes)).await
+    }
+
+    /// Add a signature to the pending signatures at `height`.
+    pub async fn append_pending(
+        &self,
+        height: u64,
+        state: &LightClientState,
+        key: &StateVerKey,
+        signature: &StateSignature,
+    ) -> anyhow::Result<()> {
+        let path = height_path(&self.pending_path(), height);
+        let mut bytes = vec![];
+        write_record(&mut bytes, &(state, key, signature))?;
+        spawn_blocking(move || {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .context(format!("opening {}", path.display()))?;
+            file.write_all(&bytes)
+                .context(format!("appending to {}", path.display()))
+        })
+        .await
+    }
+
+    /// Replace the pending signatures at `height` with the signatures in `bundles`.
+    pub async fn save_pending<'a>(
+        &self,
+        height: u64,
+        bundles: impl IntoIterator<Item = &'a StateSignaturesBundle>,
+    ) -> anyhow::Result<()> {
+        let path = height_path(&self.pending_path(), height);
+        let mut bytes = vec![];
+        for bundle in bundles {
+            for (key, signature) in &bundle.signatures {
+                write_record(&mut bytes, &(&bundle.state, key, signature))?;
+            }
+        }
+        spawn_blocking(move || replace(&path, &bytes)).await
+    }
+
+    pub async fn remove_available(&self, height: u64) -> anyhow::Result<()> {
+        let path = height_path(&self.available_path(), height);
+        spawn_blocking(move || remove(&path)).await
+    }
+
+    pub async fn remove_pending(&self, height: u64) -> anyhow::Result<()> {
+        let path = height_path(&self.pending_path(), height);
+        spawn_blocking(move || remove(&path)).await
+    }
+}
+
+fn height_path(dir: &Path, height: u64) -> PathBuf {
+    dir.join(height.to_string()).with_extension("bin")
+}
+
+fn load_dir<T>(
+    dir: &Path,
+    parse: impl Fn(&[u8]) -> anyhow::Result<T>,
+) -> anyhow::Result<BTreeMap<u64, T>> {
+    let mut items = BTreeMap::new();
+    for entry in fs::read_dir(dir).context(format!("reading {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("bin") {
+            // Skip swap files left behind by an interrupted write.
+            continue;
+        }
+        let Some(height) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse().ok())
+        else {
+            tracing::warn!("ignoring unexpected file {}", path.display());
+            continue;
+        };
+        let bytes = fs::read(&path).context(format!("reading {}", path.display()))?;
+        let item = parse(&bytes).context(format!("parsing {}", path.display()))?;
+        items.insert(height, item);
+    }
+    Ok(items)
+}
+
+/// Append a length-prefixed record to `bytes`.
+fn write_record(bytes: &mut Vec<u8>, item: &impl Serialize) -> anyhow::Result<()> {
+    let record = bincode::serialize(item)?;
+    bytes.extend_from_slice(&(record.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&record);
+    Ok(())
+}
+
+/// Parse a sequence of records written by [`write_record`].
+///
+/// A truncated record at the end, left behind by an interrupted append, is ignored.
+fn read_records<T: DeserializeOwned>(mut bytes: &[u8]) -> anyhow::Result<Vec<T>> {
+    let mut items = vec![];
+    while !bytes.is_empty() {
+        let Some((len, rest)) = bytes.split_first_chunk::<8>() else {
+            tracing::warn!("ignoring truncated record");
+            break;
+        };
+        let len = u64::from_le_bytes(*len) as usize;
+        if rest.len() < len {
+            tracing::warn!("ignoring truncated record");
+            break;
+        }
+        let (record, rest) = rest.split_at(len);
+        items.push(bincode::deserialize(record)?);
+        bytes = rest;
+    }
+    Ok(items)
+}
+
+/// Replace the contents of `path` atomically.
+fn replace(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    // Write to a temporary file first, so that `path` is replaced atomically.
+    let swap_path = path.with_extension("swp");
+    fs::write(&swap_path, bytes).context(format!("writing {}", swap_path.display()))?;
+    fs::rename(&swap_path, path).context(format!("replacing {}", path.display()))?;
+    Ok(())
+}
+
+fn remove(path: &Path) -> anyhow::Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err).context(format!("removing {}", path.display())),
+    }
+}
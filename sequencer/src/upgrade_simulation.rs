@@ -0,0 +1,461 @@
+//! Dry runs of genesis upgrade schedules on a local test network.
+//!
+//! An [`UpgradeSimulation`] takes the [`Genesis`] of a real network, starts a local network of
+//! in-memory nodes with the same chain config, prefunded accounts and upgrade schedule, and runs it
+//! until the upgrade to the genesis `upgrade_version` has been decided. Along the way it checks
+//! that every decided header is of the version expected for its view and survives a serialization
+//! round trip, and it can restart a node in the middle of the upgrade to check that the node
+//! recovers. The result is an [`UpgradeReport`] describing when the upgrade took effect and how the
+//! chain config changed.
+//!
+//! HotShot only performs one protocol upgrade per run, so a simulation covers the upgrade from the
+//! genesis `base_version` to `upgrade_version`, plus any chain config upgrades scheduled for those
+//! versions. A schedule with several protocol upgrades is simulated one step at a time.
+
+use std::{fmt, time::Duration};
+
+use anyhow::{ensure, Context};
+use async_compatibility_layer::art::async_timeout;
+use async_std::task::sleep;
+use committable::{Commitment, Committable};
+use espresso_types::{v0::traits::NullEventConsumer, v0_3::ChainConfig, Header, ValidatedState};
+use ethers::utils::Anvil;
+use futures::stream::StreamExt;
+use hotshot_types::{
+    event::{EventType, LeafInfo},
+    traits::{
+        metrics::NoMetrics,
+        node_implementation::{ConsensusTime, Versions},
+    },
+};
+use portpicker::pick_unused_port;
+use tempfile::TempDir;
+use vbs::version::{StaticVersionType, Version};
+
+use crate::{
+    api::{
+        options,
+        test_helpers::{TestNetwork, TestNetworkConfigBuilder, STAKE_TABLE_CAPACITY_FOR_TEST},
+        Options,
+    },
+    catchup::StatePeers,
+    genesis::Genesis,
+    persistence::fs,
+    testing::TestConfigBuilder,
+    SequencerApiVersion,
+};
+
+const NUM_NODES: usize = 5;
+
+/// A dry run of the upgrade schedule in a genesis file.
+#[derive(Clone, Debug)]
+pub struct UpgradeSimulation {
+    genesis: Genesis,
+    blocks_after_upgrade: u64,
+    restart_mid_upgrade: bool,
+    timeout: Duration,
+}
+
+impl UpgradeSimulation {
+    pub fn new(genesis: Genesis) -> Self {
+        Self {
+            genesis,
+            blocks_after_upgrade: 5,
+            restart_mid_upgrade: false,
+            timeout: Duration::from_secs(300),
+        }
+    }
+
+    /// How many blocks to decide after the upgrade before ending the simulation.
+    pub fn blocks_after_upgrade(mut self, blocks: u64) -> Self {
+        self.blocks_after_upgrade = blocks;
+        self
+    }
+
+    /// Stop a node once the upgrade is proposed, and restart it after the upgrade is decided.
+    ///
+    /// The simulation then checks that the restarted node catches up across the upgrade.
+    pub fn restart_mid_upgrade(mut self, restart: bool) -> Self {
+        self.restart_mid_upgrade = restart;
+        self
+    }
+
+    /// Fail the simulation if it has not completed after `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run the simulation with the protocol versions `V`.
+    ///
+    /// `V::Base` and `V::Upgrade` must match the base and upgrade versions of the genesis, and the
+    /// genesis must schedule an upgrade to `V::Upgrade`.
+    pub async fn run<V: Versions>(self, versions: V) -> anyhow::Result<UpgradeReport> {
+        let genesis = &self.genesis;
+        ensure!(
+            genesis.base_version == V::Base::VERSION,
+            "genesis base version {} does not match simulated base version {}",
+            genesis.base_version,
+            V::Base::VERSION
+        );
+        ensure!(
+            genesis.upgrade_version == V::Upgrade::VERSION,
+            "genesis upgrade version {} does not match simulated upgrade version {}",
+            genesis.upgrade_version,
+            V::Upgrade::VERSION
+        );
+        ensure!(
+            genesis.upgrades.contains_key(&V::Upgrade::VERSION),
+            "genesis does not schedule an upgrade to {}",
+            V::Upgrade::VERSION
+        );
+        genesis.validate()?;
+
+        let mut state = ValidatedState {
+            chain_config: genesis.chain_config.into(),
+            ..Default::default()
+        };
+        for (account, amount) in &genesis.accounts {
+            state.prefund_account(*account, *amount);
+        }
+
+        let anvil = Anvil::new().spawn();
+        let port = pick_unused_port().context("no ports free")?;
+        let peer_url = format!("http://localhost:{port}").parse()?;
+        let catchup = || {
+            StatePeers::<SequencerApiVersion>::from_urls(vec![peer_url.clone()], Default::default())
+        };
+        let storage = TempDir::new()?;
+        let persistence: [_; NUM_NODES] =
+            std::array::from_fn(|i| fs::Options::new(storage.path().join(i.to_string())));
+
+        let config = TestNetworkConfigBuilder::<NUM_NODES, _, _>::with_num_nodes()
+            .api_config(
+                Options::from(options::Http {
+                    port,
+                    max_connections: None,
+                })
+                .catchup(Default::default())
+                .status(Default::default()),
+            )
+            .states(std::array::from_fn(|_| state.clone()))
+            .persistences(persistence.clone())
+            .catchups(std::array::from_fn(|_| catchup()))
+            .network_config(
+                TestConfigBuilder::default()
+                    .l1_url(anvil.endpoint().parse()?)
+                    .upgrades::<V>(genesis.upgrades.clone())
+                    .chain_config_upgrades(genesis.chain_config_upgrades.clone())
+                    .build(),
+            )
+            .build();
+        let mut network = TestNetwork::new(config, versions).await;
+        let mut events = network.server.event_stream().await;
+
+        let res = async_timeout(self.timeout, async {
+            let mut chain_config = genesis.chain_config.commit();
+            let mut report = UpgradeReport {
+                version: V::Upgrade::VERSION,
+                new_version_first_view: None,
+                activation: None,
+                chain_config_changes: vec![],
+                height: 0,
+                restarted_node: None,
+            };
+            let mut stopped = None;
+
+            // Follow the decided chain until enough blocks have been decided after the upgrade.
+            loop {
+                let event = events.next().await.context("event stream ended")?;
+                match event.event {
+                    EventType::UpgradeProposal { proposal, .. } => {
+                        let upgrade = proposal.data.upgrade_proposal;
+                        ensure!(
+                            upgrade.new_version == V::Upgrade::VERSION,
+                            "unexpected upgrade proposal for version {}",
+                            upgrade.new_version
+                        );
+                        if report.new_version_first_view.is_some() {
+                            continue;
+                        }
+                        let first_view = upgrade.new_version_first_view.u64();
+                        tracing::info!(first_view, "upgrade proposed");
+                        report.new_version_first_view = Some(first_view);
+
+                        if self.restart_mid_upgrade {
+                            let mut node = network.peers.pop().context("no peers to restart")?;
+                            let i = node.node_id() as usize;
+                            tracing::info!(i, "stopping node mid-upgrade");
+                            node.shut_down().await;
+                            stopped = Some(i);
+                        }
+                    }
+                    EventType::Decide { leaf_chain, .. } => {
+                        for LeafInfo { leaf, .. } in leaf_chain.iter().rev() {
+                            let header = leaf.block_header();
+                            let view = leaf.view_number().u64();
+                            let expected = match report.new_version_first_view {
+                                Some(first_view) if view >= first_view => V::Upgrade::VERSION,
+                                _ => V::Base::VERSION,
+                            };
+                            ensure!(
+                                header.version() == expected,
+                                "header {} in view {view} has version {}, expected {expected}",
+                                header.height(),
+                                header.version()
+                            );
+                            check_header_serialization(header)
+                                .context(format!("header {}", header.height()))?;
+
+                            let commit = header.chain_config().commit();
+                            if commit != chain_config {
+                                tracing::info!(height = header.height(), view, %commit, "chain config changed");
+                                report.chain_config_changes.push(ChainConfigChange {
+                                    height: header.height(),
+                                    view,
+                                    version: header.version(),
+                                    before: chain_config,
+                                    after: commit,
+                                });
+                            }
+                            if report.activation.is_none() && header.version() == V::Upgrade::VERSION
+                            {
+                                tracing::info!(height = header.height(), view, "upgrade decided");
+                                report.activation = Some(UpgradeActivation {
+                                    height: header.height(),
+                                    view,
+                                    chain_config_before: chain_config,
+                                    chain_config_after: commit,
+                                });
+                            }
+                            chain_config = commit;
+                            report.height = header.height();
+                        }
+                    }
+                    _ => continue,
+                }
+
+                let Some(activation) = &report.activation else {
+                    continue;
+                };
+
+                // Once the rest of the network has upgraded, bring the stopped node back, so that
+                // it has to catch up across the upgrade.
+                if let Some(i) = stopped.take() {
+                    tracing::info!(i, "restarting node");
+                    let node = network
+                        .cfg
+                        .init_node(
+                            i,
+                            state.clone(),
+                            persistence[i].clone(),
+                            catchup(),
+                            &NoMetrics,
+                            STAKE_TABLE_CAPACITY_FOR_TEST,
+                            NullEventConsumer,
+                            versions,
+                            genesis.upgrades.clone(),
+                            network.marketplace_builder_url.clone(),
+                        )
+                        .await;
+                    node.start_consensus().await;
+                    network.peers.push(node);
+                    report.restarted_node = Some(i);
+                }
+
+                if report.height >= activation.height + self.blocks_after_upgrade {
+                    break;
+                }
+            }
+
+            // Wait for the restarted node to catch up to the rest of the network.
+            if let Some(i) = report.restarted_node {
+                let node = network.peers.last().unwrap();
+                while node.decided_leaf().await.height() < report.height {
+                    sleep(Duration::from_millis(200)).await;
+                }
+                let state = node.consensus().read().await.decided_state().await;
+                let header = node.decided_leaf().await.block_header().clone();
+                ensure!(
+                    state.chain_config.commit() == header.chain_config().commit(),
+                    "restarted node {i} has chain config {}, but its decided header has {}",
+                    state.chain_config.commit(),
+                    header.chain_config().commit()
+                );
+                ensure!(
+                    header.version() == V::Upgrade::VERSION,
+                    "restarted node {i} decided a header with version {}",
+                    header.version()
+                );
+            }
+
+            Ok::<_, anyhow::Error>(report)
+        })
+        .await;
+
+        network.server.shut_down().await;
+        for mut node in network.peers {
+            node.shut_down().await;
+        }
+        res.context("upgrade simulation timed out")?
+    }
+}
+
+/// Check that a header serializes in the format of its version, and round trips.
+///
+/// Version 0.1 headers are serialized as a flat struct, while later versions are wrapped in an
+/// object which tags the fields with their version.
+fn check_header_serialization(header: &Header) -> anyhow::Result<()> {
+    let json = serde_json::to_value(header)?;
+    let versioned = header.version() > Version { major: 0, minor: 1 };
+    ensure!(
+        json.get("fields").is_some() == versioned,
+        "version {} header serialized in the wrong format: {json}",
+        header.version()
+    );
+    ensure!(
+        serde_json::from_value::<Header>(json)? == *header,
+        "header changed in JSON round trip"
+    );
+    ensure!(
+        bincode::deserialize::<Header>(&bincode::serialize(header)?)? == *header,
+        "header changed in binary round trip"
+    );
+    Ok(())
+}
+
+/// The outcome of an [`UpgradeSimulation`].
+#[derive(Clone, Debug)]
+pub struct UpgradeReport {
+    /// The version the network upgraded to.
+    pub version: Version,
+    /// The first view of the new version, according to the upgrade certificate.
+    pub new_version_first_view: Option<u64>,
+    /// The first decided block of the new version.
+    pub activation: Option<UpgradeActivation>,
+    /// Every change of chain config on the decided chain.
+    ///
+    /// This includes the change made by the protocol upgrade, if any, and those made by chain config
+    /// upgrades.
+    pub chain_config_changes: Vec<ChainConfigChange>,
+    /// The height of the last decided block.
+    pub height: u64,
+    /// The node which was restarted during the upgrade, if any.
+    pub restarted_node: Option<usize>,
+}
+
+/// The first decided block at the new version.
+#[derive(Clone, Copy, Debug)]
+pub struct UpgradeActivation {
+    pub height: u64,
+    pub view: u64,
+    pub chain_config_before: Commitment<ChainConfig>,
+    pub chain_config_after: Commitment<ChainConfig>,
+}
+
+/// A decided block whose chain config differs from that of its parent.
+#[derive(Clone, Copy, Debug)]
+pub struct ChainConfigChange {
+    pub height: u64,
+    pub view: u64,
+    pub version: Version,
+    pub before: Commitment<ChainConfig>,
+    pub after: Commitment<ChainConfig>,
+}
+
+impl fmt::Display for UpgradeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.activation, self.new_version_first_view) {
+            (Some(activation), first_view) => writeln!(
+                f,
+                "upgrade to {} decided at height {}, view {} (first view {:?}); chain config {} -> {}",
+                self.version,
+                activation.height,
+                activation.view,
+                first_view,
+                activation.chain_config_before,
+                activation.chain_config_after
+            )?,
+            (None, _) => writeln!(f, "upgrade to {} not decided", self.version)?,
+        }
+        for change in &self.chain_config_changes {
+            writeln!(
+                f,
+                "chain config changed at height {}, view {} (version {}): {} -> {}",
+                change.height, change.view, change.version, change.before, change.after
+            )?;
+        }
+        if let Some(i) = self.restarted_node {
+            writeln!(f, "node {i} restarted mid-upgrade and caught up")?;
+        }
+        write!(f, "decided {} blocks", self.height)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use espresso_types::{FeeVersion, MarketplaceVersion, SequencerVersions, V0_1};
+    use sequencer_utils::test_utils::setup_test;
+
+    use super::*;
+
+    #[async_std::test]
+    async fn slow_test_simulate_fee_upgrade() {
+        setup_test();
+
+        let mut genesis = Genesis::from_file("../data/genesis/demo.toml").unwrap();
+        // Simulate only the fee upgrade; the marketplace upgrade is a separate step.
+        genesis
+            .upgrades
+            .retain(|version, _| *version == FeeVersion::version());
+
+        let report = UpgradeSimulation::new(genesis.clone())
+            .restart_mid_upgrade(true)
+            .run(SequencerVersions::<V0_1, FeeVersion>::new())
+            .await
+            .unwrap();
+        tracing::info!("{report}");
+
+        let activation = report.activation.unwrap();
+        assert_eq!(
+            activation.chain_config_before,
+            genesis.chain_config.commit()
+        );
+        assert_eq!(
+            activation.chain_config_after,
+            genesis.upgrades[&FeeVersion::version()]
+                .upgrade_type
+                .data()
+                .commit()
+        );
+        assert_eq!(report.restarted_node, Some(NUM_NODES - 1));
+    }
+
+    #[async_std::test]
+    async fn slow_test_simulate_v0_1_to_marketplace_upgrade() {
+        setup_test();
+
+        let mut genesis = Genesis::from_file("../data/genesis/demo.toml").unwrap();
+        // Skip straight from 0.1 to 0.3, so that headers switch from the legacy serialization to
+        // the versioned one.
+        genesis
+            .upgrades
+            .retain(|version, _| *version == MarketplaceVersion::version());
+        genesis.upgrade_version = MarketplaceVersion::version();
+
+        let report = UpgradeSimulation::new(genesis.clone())
+            .run(SequencerVersions::<V0_1, MarketplaceVersion>::new())
+            .await
+            .unwrap();
+        tracing::info!("{report}");
+
+        let activation = report.activation.unwrap();
+        assert_eq!(
+            activation.chain_config_after,
+            genesis.upgrades[&MarketplaceVersion::version()]
+                .upgrade_type
+                .data()
+                .commit()
+        );
+    }
+}
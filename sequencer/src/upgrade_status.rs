@@ -0,0 +1,321 @@
+//! Tracking the progress of scheduled protocol upgrades.
+//!
+//! An [`UpgradeTracker`] follows the consensus events of a node to learn when each upgrade from the
+//! node's configuration is proposed and decided. Combined with the latest decided leaf, this gives
+//! an [`UpgradeStatus`], which is served by the `config` API so that operators can confirm that a
+//! scheduled upgrade is on track across all nodes.
+//!
+//! Decided upgrade certificates are persisted, so a restarted node still reports an upgrade which
+//! was decided before the restart. Upgrade proposals are only tracked in memory, so an upgrade
+//! which was proposed but not decided before a restart is reported as
+//! [`Pending`](UpgradePhase::Pending) until the node sees a proposal for it again.
+
+use std::collections::BTreeMap;
+
+use async_std::sync::RwLock;
+use committable::Commitment;
+use espresso_types::{
+    v0::traits::SequencerPersistence, v0_3::ChainConfig, Leaf, SeqTypes, Upgrade, UpgradeMode,
+};
+use hotshot::types::{Event, EventType};
+use hotshot_types::{
+    event::LeafInfo, simple_certificate::UpgradeCertificate,
+    traits::node_implementation::ConsensusTime,
+};
+use serde::{Deserialize, Serialize};
+use vbs::version::Version;
+
+use crate::genesis::version_ser;
+
+/// The progress of a scheduled upgrade, as seen by this node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradePhase {
+    /// The upgrade has not been proposed yet.
+    Pending,
+    /// An upgrade proposal has been seen, but no upgrade certificate has been decided.
+    ///
+    /// This is not persisted, so it is lost if the node restarts before the upgrade is decided.
+    Proposed,
+    /// An upgrade certificate has been decided, but the new version is not in effect yet.
+    Decided,
+    /// The new version is in effect.
+    Active,
+}
+
+/// The status of one upgrade from the node's configuration.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ScheduledUpgrade {
+    /// The version being upgraded to.
+    #[serde(with = "version_ser")]
+    pub version: Version,
+    /// The view or time window in which the upgrade may be proposed and voted on.
+    pub mode: UpgradeMode,
+    pub phase: UpgradePhase,
+    /// The first view of the new version, once the upgrade has been proposed.
+    pub new_version_first_view: Option<u64>,
+}
+
+/// Upgrade progress of a node.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct UpgradeStatus {
+    /// The latest view this node has seen.
+    pub current_view: u64,
+    /// The protocol version of the latest decided block.
+    #[serde(with = "version_ser")]
+    pub current_version: Version,
+    /// The commitment of the chain config in effect after the latest decided block.
+    pub chain_config: Commitment<ChainConfig>,
+    /// Each upgrade from the node's configuration, by version.
+    pub upgrades: Vec<ScheduledUpgrade>,
+}
+
+#[derive(Debug, Default)]
+struct UpgradeEvents {
+    /// The latest view seen in any consensus event.
+    view: u64,
+    /// The first view of each proposed upgrade, by version. Not persisted.
+    proposed: BTreeMap<Version, u64>,
+    /// The first view of each upgrade with a decided certificate, by version.
+    decided: BTreeMap<Version, u64>,
+}
+
+/// Follows consensus events to track the progress of upgrades.
+#[derive(Debug, Default)]
+pub struct UpgradeTracker {
+    events: RwLock<UpgradeEvents>,
+}
+
+impl UpgradeTracker {
+    /// Create a tracker which knows about the upgrade certificate saved in `persistence`, if any.
+    pub(crate) async fn restore(persistence: &impl SequencerPersistence) -> anyhow::Result<Self> {
+        let mut events = UpgradeEvents::default();
+        if let Some(cert) = persistence.load_upgrade_certificate().await? {
+            tracing::info!(version = %cert.data.new_version, "restored decided upgrade");
+            events.decide(&cert);
+        }
+        Ok(Self {
+            events: RwLock::new(events),
+        })
+    }
+
+    pub(crate) async fn handle_event(
+        &self,
+        event: &Event<SeqTypes>,
+        persistence: &impl SequencerPersistence,
+    ) {
+        let mut events = self.events.write().await;
+        events.view = events.view.max(event.view_number.u64());
+
+        match &event.event {
+            EventType::UpgradeProposal { proposal, .. } => {
+                let upgrade = &proposal.data.upgrade_proposal;
+                let first_view = upgrade.new_version_first_view.u64();
+                if events
+                    .proposed
+                    .insert(upgrade.new_version, first_view)
+                    .is_none()
+                {
+                    tracing::info!(version = %upgrade.new_version, first_view, "upgrade proposed");
+                }
+            }
+            EventType::Decide { leaf_chain, .. } => {
+                for LeafInfo { leaf, .. } in leaf_chain.iter() {
+                    let Some(cert) = leaf.upgrade_certificate() else {
+                        continue;
+                    };
+                    if !events.decide(&cert) {
+                        continue;
+                    }
+                    tracing::info!(
+                        version = %cert.data.new_version,
+                        first_view = cert.data.new_version_first_view.u64(),
+                        "upgrade decided"
+                    );
+                    if let Err(err) = persistence.save_upgrade_certificate(&cert).await {
+                        tracing::warn!("failed to save upgrade certificate: {err:#}");
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The status of each of `upgrades`, given the latest decided leaf.
+    pub async fn status(
+        &self,
+        upgrades: &BTreeMap<Version, Upgrade>,
+        decided_leaf: &Leaf,
+    ) -> UpgradeStatus {
+        self.events.read().await.status(upgrades, decided_leaf)
+    }
+}
+
+impl UpgradeEvents {
+    /// Record a decided upgrade certificate, returning whether it was not already known.
+    fn decide(&mut self, cert: &UpgradeCertificate<SeqTypes>) -> bool {
+        self.decided
+            .insert(
+                cert.data.new_version,
+                cert.data.new_version_first_view.u64(),
+            )
+            .is_none()
+    }
+
+    fn status(&self, upgrades: &BTreeMap<Version, Upgrade>, decided_leaf: &Leaf) -> UpgradeStatus {
+        let header = decided_leaf.block_header();
+        let current_version = header.version();
+        let current_view = self.view.max(decided_leaf.view_number().u64());
+
+        let upgrades = upgrades
+            .iter()
+            .map(|(version, upgrade)| {
+                let decided = self.decided.get(version).copied();
+                let proposed = self.proposed.get(version).copied();
+                let phase = if current_version >= *version
+                    || decided.is_some_and(|first_view| current_view >= first_view)
+                {
+                    UpgradePhase::Active
+                } else if decided.is_some() {
+                    UpgradePhase::Decided
+                } else if proposed.is_some() {
+                    UpgradePhase::Proposed
+                } else {
+                    UpgradePhase::Pending
+                };
+                ScheduledUpgrade {
+                    version: *version,
+                    mode: upgrade.mode.clone(),
+                    phase,
+                    new_version_first_view: decided.or(proposed),
+                }
+            })
+            .collect();
+
+        UpgradeStatus {
+            current_view,
+            current_version,
+            chain_config: header.chain_config().commit(),
+            upgrades,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use committable::Committable;
+    use espresso_types::{
+        v0::traits::PersistenceOptions, NodeState, UpgradeType, ValidatedState, ViewBasedUpgrade,
+    };
+    use hotshot_types::{data::ViewNumber, simple_vote::UpgradeProposalData};
+    use sequencer_utils::test_utils::setup_test;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::persistence::fs;
+
+    fn scheduled_upgrades(leaf: &Leaf) -> (Version, BTreeMap<Version, Upgrade>) {
+        let version = Version {
+            major: leaf.block_header().version().major,
+            minor: leaf.block_header().version().minor + 1,
+        };
+        let upgrades = BTreeMap::from([(
+            version,
+            Upgrade {
+                mode: UpgradeMode::View(ViewBasedUpgrade {
+                    start_proposing_view: 1,
+                    stop_proposing_view: 10,
+                    start_voting_view: None,
+                    stop_voting_view: None,
+                }),
+                upgrade_type: UpgradeType::Fee {
+                    chain_config: Default::default(),
+                },
+            },
+        )]);
+        (version, upgrades)
+    }
+
+    #[async_std::test]
+    async fn test_upgrade_phases() {
+        setup_test();
+
+        let instance = NodeState::mock();
+        let leaf = Leaf::genesis(&ValidatedState::default(), &instance).await;
+        let (version, upgrades) = scheduled_upgrades(&leaf);
+
+        let mut events = UpgradeEvents::default();
+        let status = events.status(&upgrades, &leaf);
+        assert_eq!(status.current_version, leaf.block_header().version());
+        assert_eq!(
+            status.chain_config,
+            leaf.block_header().chain_config().commit()
+        );
+        assert_eq!(status.upgrades.len(), 1);
+        assert_eq!(status.upgrades[0].version, version);
+        assert_eq!(status.upgrades[0].phase, UpgradePhase::Pending);
+        assert_eq!(status.upgrades[0].new_version_first_view, None);
+
+        events.view = 3;
+        events.proposed.insert(version, 8);
+        let status = events.status(&upgrades, &leaf);
+        assert_eq!(status.current_view, 3);
+        assert_eq!(status.upgrades[0].phase, UpgradePhase::Proposed);
+        assert_eq!(status.upgrades[0].new_version_first_view, Some(8));
+
+        events.decided.insert(version, 8);
+        let status = events.status(&upgrades, &leaf);
+        assert_eq!(status.upgrades[0].phase, UpgradePhase::Decided);
+
+        events.view = 8;
+        let status = events.status(&upgrades, &leaf);
+        assert_eq!(status.upgrades[0].phase, UpgradePhase::Active);
+
+        // The status round trips through the serialization used by the API.
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["upgrades"][0]["phase"], "active");
+        assert_eq!(
+            serde_json::from_value::<UpgradeStatus>(json).unwrap(),
+            status
+        );
+    }
+    #[async_std::test]
+    async fn test_upgrade_restored() {
+        setup_test();
+
+        let instance = NodeState::mock();
+        let leaf = Leaf::genesis(&ValidatedState::default(), &instance).await;
+        let (version, upgrades) = scheduled_upgrades(&leaf);
+
+        let tmp = TempDir::new().unwrap();
+        let persistence = fs::Options::new(tmp.path().into()).create().await.unwrap();
+
+        // Nothing is known about the upgrade before a certificate is decided.
+        let tracker = UpgradeTracker::restore(&persistence).await.unwrap();
+        let status = tracker.status(&upgrades, &leaf).await;
+        assert_eq!(status.upgrades[0].phase, UpgradePhase::Pending);
+
+        // A restarted node reports the upgrade decided before the restart.
+        let data = UpgradeProposalData {
+            old_version: leaf.block_header().version(),
+            new_version: version,
+            decide_by: ViewNumber::new(5),
+            new_version_hash: Default::default(),
+            old_version_last_view: ViewNumber::new(7),
+            new_version_first_view: ViewNumber::new(8),
+        };
+        let cert = UpgradeCertificate::new(
+            data.clone(),
+            data.commit(),
+            ViewNumber::new(5),
+            Default::default(),
+            Default::default(),
+        );
+        persistence.save_upgrade_certificate(&cert).await.unwrap();
+
+        let tracker = UpgradeTracker::restore(&persistence).await.unwrap();
+        let status = tracker.status(&upgrades, &leaf).await;
+        assert_eq!(status.upgrades[0].phase, UpgradePhase::Decided);
+        assert_eq!(status.upgrades[0].new_version_first_view, Some(8));
+    }
+}
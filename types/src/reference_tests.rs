@@ -145,6 +145,7 @@ async fn reference_header(version: Version) -> Header {
         builder_commitment,
         ns_table,
         state.fee_merkle_tree.commitment(),
+        state.transfer_nonce_merkle_tree.commitment(),
         state.block_merkle_tree.commitment(),
         vec![fee_info],
         vec![builder_signature],
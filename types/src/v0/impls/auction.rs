@@ -1,12 +1,13 @@
 use super::{state::ValidatedState, MarketplaceVersion};
 use crate::{
     eth_signature_key::{EthKeyPair, SigningError},
-    v0_3::{BidTx, BidTxBody, FullNetworkTx, SolverAuctionResults},
-    FeeAccount, FeeAmount, FeeError, FeeInfo, NamespaceId,
+    v0_3::{BidTx, BidTxBody, FullNetworkTx, SolverAuctionResults, TransferTx, TransferTxBody},
+    ChainId, FeeAccount, FeeAmount, FeeError, FeeInfo, NamespaceId,
 };
 use anyhow::Context;
 use async_trait::async_trait;
 use committable::{Commitment, Committable};
+use ethers::types::Signature;
 use hotshot_types::{
     data::ViewNumber,
     traits::{
@@ -19,12 +20,51 @@ use std::str::FromStr;
 use thiserror::Error;
 use tide_disco::error::ServerError;
 use url::Url;
+use vbs::version::{StaticVersionType, Version};
 
 impl FullNetworkTx {
     /// Proxy for `execute` method of each transaction variant.
-    pub fn execute(&self, state: &mut ValidatedState) -> Result<(), ExecutionError> {
+    ///
+    /// Fails without modifying `state` if the transaction is not
+    /// supported in protocol `version`.
+    pub fn execute(
+        &self,
+        state: &mut ValidatedState,
+        version: Version,
+    ) -> Result<(), ExecutionError> {
+        if version < self.min_version() {
+            return Err(ExecutionError::UnsupportedVersion(version));
+        }
         match self {
             Self::Bid(bid) => bid.execute(state),
+            Self::Transfer(transfer) => transfer.execute(state),
+        }
+    }
+
+    /// The first protocol version in which this kind of transaction is valid.
+    pub fn min_version(&self) -> Version {
+        match self {
+            Self::Bid(_) | Self::Transfer(_) => MarketplaceVersion::version(),
+        }
+    }
+
+    /// The fee state entries read or written by this transaction, not
+    /// counting the recipients set in `ChainConfig`. These must be in
+    /// memory for the transaction to execute.
+    pub fn accounts(&self) -> Vec<FeeAccount> {
+        match self {
+            Self::Bid(bid) => vec![bid.account()],
+            Self::Transfer(transfer) => transfer.accounts(),
+        }
+    }
+
+    /// The accounts whose transfer nonce is read or written by this
+    /// transaction. These must be in memory for the transaction to
+    /// execute.
+    pub fn nonce_accounts(&self) -> Vec<FeeAccount> {
+        match self {
+            Self::Bid(_) => vec![],
+            Self::Transfer(transfer) => vec![transfer.from()],
         }
     }
 }
@@ -128,6 +168,57 @@ impl Default for BidTx {
     }
 }
 
+impl Committable for TransferTxBody {
+    fn tag() -> String {
+        "TRANSFER_TX".to_string()
+    }
+
+    fn commit(&self) -> Commitment<Self> {
+        committable::RawCommitmentBuilder::new(&Self::tag())
+            .fixed_size_field("from", &self.from.to_fixed_bytes())
+            .fixed_size_field("to", &self.to.to_fixed_bytes())
+            .fixed_size_field("amount", &self.amount.to_fixed_bytes())
+            .fixed_size_field("fee", &self.fee.to_fixed_bytes())
+            .u64_field("nonce", self.nonce)
+            .fixed_size_field("chain_id", &self.chain_id.to_fixed_bytes())
+            .finalize()
+    }
+}
+
+impl TransferTxBody {
+    /// Construct a new `TransferTxBody`.
+    ///
+    /// `nonce` must be the nonce of `from` at the time the transfer
+    /// executes, see [`ValidatedState::nonce`], and `chain_id` the
+    /// chain ID from the `ChainConfig` of the network it executes on.
+    pub fn new(
+        from: FeeAccount,
+        to: FeeAccount,
+        amount: FeeAmount,
+        fee: FeeAmount,
+        nonce: u64,
+        chain_id: ChainId,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            amount,
+            fee,
+            nonce,
+            chain_id,
+        }
+    }
+
+    /// Sign Body and return a `TransferTx`. This is the expected way to obtain a `TransferTx`.
+    pub fn signed(self, key: &EthKeyPair) -> Result<TransferTx, SigningError> {
+        let signature = FeeAccount::sign_builder_message(key, self.commit().as_ref())?;
+        Ok(TransferTx {
+            body: self,
+            signature,
+        })
+    }
+}
+
 #[derive(Error, Debug, Eq, PartialEq)]
 /// Failure cases of transaction execution
 pub enum ExecutionError {
@@ -146,6 +237,21 @@ pub enum ExecutionError {
     #[error("Bid recipient not set on `ChainConfig`")]
     /// Bid Recipient is not set on `ChainConfig`
     BidRecipientNotFound,
+    #[error("Transaction not supported in version {0}")]
+    /// Transaction type is not enabled in the current protocol version.
+    UnsupportedVersion(Version),
+    #[error("Invalid chain ID: expected {expected}, found {actual}")]
+    /// Transfer was signed for a different chain.
+    InvalidChainId { expected: ChainId, actual: ChainId },
+    #[error("Invalid nonce: expected {expected}, found {actual}")]
+    /// Transfer nonce does not match the nonce of the sender.
+    InvalidNonce { expected: u64, actual: u64 },
+    #[error("Fee account {0} not in memory")]
+    /// A fee state entry needed by the transaction has not been fetched.
+    AccountNotInMemory(FeeAccount),
+    #[error("Transfer nonce of {0} not in memory")]
+    /// The transfer nonce of the sender has not been fetched.
+    NonceNotInMemory(FeeAccount),
 }
 
 impl From<FeeError> for ExecutionError {
@@ -227,6 +333,117 @@ impl BidTx {
     }
 }
 
+impl TransferTx {
+    /// Execute `TransferTx`.
+    ///   * verify signature
+    ///   * check chain ID, nonce and sender balance
+    ///   * charge fee
+    ///   * transfer amount
+    ///   * increment nonce
+    ///
+    /// Every check is made before `state` is modified, so a transfer
+    /// either executes in full or has no effect.
+    pub fn execute(&self, state: &mut ValidatedState) -> Result<(), ExecutionError> {
+        self.verify()?;
+
+        let Some(chain_config) = state.chain_config.resolve() else {
+            return Err(ExecutionError::UnresolvableChainConfig);
+        };
+        if chain_config.chain_id != self.chain_id() {
+            return Err(ExecutionError::InvalidChainId {
+                expected: chain_config.chain_id,
+                actual: self.chain_id(),
+            });
+        }
+
+        let from = self.from();
+        let nonce = state
+            .nonce(from)
+            .ok_or(ExecutionError::NonceNotInMemory(from))?;
+        if nonce != self.nonce() {
+            return Err(ExecutionError::InvalidNonce {
+                expected: nonce,
+                actual: self.nonce(),
+            });
+        }
+
+        let balance = state
+            .balance(from)
+            .ok_or(ExecutionError::AccountNotInMemory(from))?;
+        let required = FeeAmount(self.amount().0.saturating_add(self.fee().0));
+        if balance < required {
+            return Err(FeeError::InsufficientFunds {
+                balance: Some(balance),
+                amount: required,
+            }
+            .into());
+        }
+
+        // Make sure the recipients are in memory, so that the charges
+        // below cannot fail half way through.
+        for account in [self.to(), chain_config.fee_recipient] {
+            state
+                .balance(account)
+                .ok_or(ExecutionError::AccountNotInMemory(account))?;
+        }
+
+        // Charge the fee
+        state.charge_fee(FeeInfo::new(from, self.fee()), chain_config.fee_recipient)?;
+
+        // Move the funds
+        state.charge_fee(FeeInfo::new(from, self.amount()), self.to())?;
+
+        state.increment_nonce(from)?;
+
+        Ok(())
+    }
+    /// Cryptographic signature verification
+    pub fn verify(&self) -> Result<(), ExecutionError> {
+        self.body
+            .from
+            .validate_builder_signature(&self.signature, self.body.commit().as_ref())
+            .then_some(())
+            .ok_or(ExecutionError::InvalidSignature)
+    }
+    /// Return the body of the transaction
+    pub fn body(self) -> TransferTxBody {
+        self.body
+    }
+    /// get signature
+    pub fn signature(&self) -> Signature {
+        self.signature
+    }
+    /// get sending account
+    pub fn from(&self) -> FeeAccount {
+        self.body.from
+    }
+    /// get receiving account
+    pub fn to(&self) -> FeeAccount {
+        self.body.to
+    }
+    /// get transfer amount
+    pub fn amount(&self) -> FeeAmount {
+        self.body.amount
+    }
+    /// get fee
+    pub fn fee(&self) -> FeeAmount {
+        self.body.fee
+    }
+    /// get nonce
+    pub fn nonce(&self) -> u64 {
+        self.body.nonce
+    }
+    /// get chain ID
+    pub fn chain_id(&self) -> ChainId {
+        self.body.chain_id
+    }
+    /// The fee state entries read or written by the transfer, apart
+    /// from the fee recipient.
+    pub fn accounts(&self) -> Vec<FeeAccount> {
+        vec![self.from(), self.to()]
+    }
+}
+
 impl SolverAuctionResults {
     /// Construct a `SolverAuctionResults`
     pub fn new(
@@ -238,8 +455,14 @@ impl SolverAuctionResults {
             view_number,
             winning_bids,
             reserve_bids,
+            transfers: vec![],
         }
     }
+    /// Instantiate a `SolverAuctionResults` containing the values of
+    /// `self` with the given `transfers`.
+    pub fn with_transfers(self, transfers: Vec<TransferTx>) -> Self {
+        Self { transfers, ..self }
+    }
     /// Get the view number for these auction results
     pub fn view(&self) -> ViewNumber {
         self.view_number
@@ -252,12 +475,17 @@ impl SolverAuctionResults {
     pub fn reserve_bids(&self) -> &[(NamespaceId, Url)] {
         &self.reserve_bids
     }
+    /// Get the transfers to be executed in the block
+    pub fn transfers(&self) -> &[TransferTx] {
+        &self.transfers
+    }
     /// Empty results for the genesis view.
     pub fn genesis() -> Self {
         Self {
             view_number: ViewNumber::genesis(),
             winning_bids: vec![],
             reserve_bids: vec![],
+            transfers: vec![],
         }
     }
 }
@@ -343,6 +571,115 @@ mod test {
         bidtx.charge(&mut state).unwrap();
     }
 
+    #[test]
+    fn test_transfer_tx() {
+        let key = EthKeyPair::random();
+        let from = key.fee_account();
+        let to = EthKeyPair::random().fee_account();
+        let mut state = ValidatedState::default();
+        state.prefund_account(from, 100.into());
+        let chain_config = state.chain_config.resolve().unwrap();
+        let (fee_recipient, chain_id) = (chain_config.fee_recipient, chain_config.chain_id);
+        let version = MarketplaceVersion::version();
+        assert_eq!(state.nonce(from), Some(0));
+
+        // A transfer signed for another chain is rejected.
+        let other_chain_id = ChainId(chain_id.0 + 1);
+        let tx = FullNetworkTx::Transfer(
+            TransferTxBody::new(from, to, 60.into(), 10.into(), 0, other_chain_id)
+                .signed(&key)
+                .unwrap(),
+        );
+        let before = state.clone();
+        assert_eq!(
+            tx.execute(&mut state, version).unwrap_err(),
+            ExecutionError::InvalidChainId {
+                expected: chain_id,
+                actual: other_chain_id,
+            }
+        );
+        assert_eq!(state, before);
+
+        let tx = FullNetworkTx::Transfer(
+            TransferTxBody::new(from, to, 60.into(), 10.into(), 0, chain_id)
+                .signed(&key)
+                .unwrap(),
+        );
+
+        // Transfers are not enabled before the marketplace upgrade.
+        let err = tx
+            .execute(&mut state.clone(), Version { major: 0, minor: 2 })
+            .unwrap_err();
+        assert!(
+            matches!(err, ExecutionError::UnsupportedVersion(_)),
+            "{err}"
+        );
+
+        tx.execute(&mut state, version).unwrap();
+        assert_eq!(state.balance(from), Some(30.into()));
+        assert_eq!(state.balance(to), Some(60.into()));
+        assert_eq!(state.balance(fee_recipient), Some(10.into()));
+        assert_eq!(state.nonce(from), Some(1));
+
+        // The same transfer cannot be replayed, even if the sender's
+        // balance returns to what it was.
+        state.prefund_account(from, 100.into());
+        let err = tx.execute(&mut state, version).unwrap_err();
+        assert_eq!(
+            err,
+            ExecutionError::InvalidNonce {
+                expected: 1,
+                actual: 0
+            }
+        );
+        state.prefund_account(from, 30.into());
+
+        // A transfer signed by someone other than the sender is rejected.
+        let tx = FullNetworkTx::Transfer(
+            TransferTxBody::new(from, to, 10.into(), 0.into(), 1, chain_id)
+                .signed(&EthKeyPair::random())
+                .unwrap(),
+        );
+        assert_eq!(
+            tx.execute(&mut state, version).unwrap_err(),
+            ExecutionError::InvalidSignature
+        );
+
+        // Balances and nonces are kept apart, so not even a balance too
+        // large to fit in 128 bits changes the nonce.
+        state.prefund_account(from, FeeAmount(ethers::types::U256::one() << 200));
+        assert_eq!(state.nonce(from), Some(1));
+        state.prefund_account(from, 30.into());
+
+        // A transfer which the sender can pay the fee for, but not the
+        // amount as well, fails without charging anything.
+        let before = state.clone();
+        let tx = FullNetworkTx::Transfer(
+            TransferTxBody::new(from, to, 25.into(), 10.into(), 1, chain_id)
+                .signed(&key)
+                .unwrap(),
+        );
+        let err = tx.execute(&mut state, version).unwrap_err();
+        assert_eq!(
+            err,
+            ExecutionError::FeeError(FeeError::InsufficientFunds {
+                balance: Some(30.into()),
+                amount: 35.into(),
+            })
+        );
+        assert_eq!(state, before);
+
+        // A transfer of exactly the sender's balance succeeds.
+        let tx = FullNetworkTx::Transfer(
+            TransferTxBody::new(from, to, 20.into(), 10.into(), 1, chain_id)
+                .signed(&key)
+                .unwrap(),
+        );
+        tx.execute(&mut state, version).unwrap();
+        assert_eq!(state.balance(from), Some(0.into()));
+        assert_eq!(state.nonce(from), Some(2));
+    }
+
     #[test]
     fn test_bid_tx_construct() {
         let key_pair = EthKeyPair::random();
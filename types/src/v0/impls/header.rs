@@ -29,7 +29,10 @@ use crate::{
         MarketplaceVersion,
     },
     v0_1, v0_2,
-    v0_3::{self, ChainConfig, IterableFeeInfo, SolverAuctionResults},
+    v0_3::{
+        self, ChainConfig, FullNetworkTx, IterableFeeInfo, SolverAuctionResults,
+        TransferNonceMerkleCommitment,
+    },
     BlockMerkleCommitment, BuilderSignature, FeeAccount, FeeAmount, FeeInfo, FeeMerkleCommitment,
     Header, L1BlockInfo, L1Snapshot, Leaf, NamespaceId, NsTable, SeqTypes, UpgradeType,
 };
@@ -252,6 +255,7 @@ impl Header {
         builder_commitment: BuilderCommitment,
         ns_table: NsTable,
         fee_merkle_tree_root: FeeMerkleCommitment,
+        transfer_nonce_merkle_tree_root: TransferNonceMerkleCommitment,
         block_merkle_tree_root: BlockMerkleCommitment,
         fee_info: Vec<FeeInfo>,
         builder_signature: Vec<BuilderSignature>,
@@ -308,6 +312,7 @@ impl Header {
                 ns_table,
                 block_merkle_tree_root,
                 fee_merkle_tree_root,
+                transfer_nonce_merkle_tree_root,
                 fee_info,
                 builder_signature,
                 auction_results: SolverAuctionResults::genesis(),
@@ -451,12 +456,28 @@ impl Header {
                 .context(format!("invalid builder fee {fee_info:?}"))?;
         }
 
+        // Execute the transfers included by the solver. Any which cannot be executed, for example
+        // because they were already included in an earlier block, are dropped so that the block
+        // remains valid.
+        let auction_results = auction_results.map(|results| {
+            let mut transfers = vec![];
+            for transfer in results.transfers() {
+                let tx = FullNetworkTx::Transfer(transfer.clone());
+                match tx.execute(&mut state, version) {
+                    Ok(()) => transfers.push(transfer.clone()),
+                    Err(err) => tracing::info!(?transfer, "dropping transfer: {err:#}"),
+                }
+            }
+            results.with_transfers(transfers)
+        });
+
         let fee_info = FeeInfo::from_builder_fees(builder_fee.clone());
 
         let builder_signature: Vec<BuilderSignature> =
             builder_fee.iter().map(|e| e.fee_signature).collect();
 
         let fee_merkle_tree_root = state.fee_merkle_tree.commitment();
+        let transfer_nonce_merkle_tree_root = state.transfer_nonce_merkle_tree.commitment();
 
         let Version { major, minor } = version;
 
@@ -506,6 +527,7 @@ impl Header {
                 ns_table,
                 block_merkle_tree_root,
                 fee_merkle_tree_root,
+                transfer_nonce_merkle_tree_root,
                 fee_info,
                 builder_signature,
                 auction_results: auction_results.unwrap(),
@@ -529,7 +551,10 @@ impl Header {
             return instance_cf;
         }
 
-        match validated_cf.resolve() {
+        match validated_cf
+            .resolve()
+            .or_else(|| instance_state.known_chain_config(validated_cf.commit()))
+        {
             Some(cf) => cf,
             None => {
                 tracing::info!("fetching chain config {} from peers", validated_cf.commit());
@@ -662,6 +687,14 @@ impl Header {
         &mut *field_mut!(self.fee_merkle_tree_root)
     }
 
+    /// Root Commitment of `TransferNonceMerkleTree`, for versions which support transfers.
+    pub fn transfer_nonce_merkle_tree_root(&self) -> Option<TransferNonceMerkleCommitment> {
+        match self {
+            Self::V1(_) | Self::V2(_) => None,
+            Self::V3(fields) => Some(fields.transfer_nonce_merkle_tree_root),
+        }
+    }
+
     /// Fee paid by the block builder
     pub fn fee_info(&self) -> Vec<FeeInfo> {
         match self {
@@ -690,6 +723,20 @@ impl Header {
             Self::V3(fields) => fields.builder_signature.clone(),
         }
     }
+
+    /// Full network transactions executed in this block, after the builder fees are charged.
+    pub fn full_network_txs(&self) -> Vec<FullNetworkTx> {
+        match self {
+            Self::V1(_) | Self::V2(_) => vec![],
+            Self::V3(fields) => fields
+                .auction_results
+                .transfers()
+                .iter()
+                .cloned()
+                .map(FullNetworkTx::Transfer)
+                .collect(),
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -760,6 +807,9 @@ impl BlockHeader<SeqTypes> for Header {
         } else {
             Header::get_chain_config(&validated_state, instance_state).await
         };
+        let chain_config = instance_state
+            .chain_config_upgrade(parent_leaf, version)
+            .unwrap_or(chain_config);
 
         validated_state.chain_config = chain_config.into();
 
@@ -791,7 +841,13 @@ impl BlockHeader<SeqTypes> for Header {
             [chain_config.fee_recipient]
                 .into_iter()
                 .chain(builder_fee.accounts())
-                .chain(l1_deposits.accounts()),
+                .chain(l1_deposits.accounts())
+                .chain(
+                    auction_results
+                        .iter()
+                        .flat_map(|results| results.transfers())
+                        .flat_map(|transfer| transfer.accounts()),
+                ),
         );
 
         if !missing_accounts.is_empty() {
@@ -823,6 +879,33 @@ impl BlockHeader<SeqTypes> for Header {
             }
         }
 
+        // Fetch the nonces of the senders of transfers.
+        let missing_nonces = parent_state.forgotten_nonces(
+            auction_results
+                .iter()
+                .flat_map(|results| results.transfers())
+                .map(|transfer| transfer.from()),
+        );
+        if !missing_nonces.is_empty() {
+            tracing::warn!(
+                height,
+                ?view,
+                ?missing_nonces,
+                "fetching missing transfer nonces from peers"
+            );
+            instance_state
+                .peers
+                .as_ref()
+                .remember_transfer_nonces(
+                    height,
+                    view,
+                    &mut validated_state.transfer_nonce_merkle_tree,
+                    missing_nonces,
+                )
+                .await
+                .context("remembering transfer nonces")?;
+        }
+
         // Ensure merkle tree has frontier
         if validated_state.need_to_fetch_blocks_mt_frontier() {
             tracing::warn!(height, ?view, "fetching block frontier from peers");
@@ -877,6 +960,9 @@ impl BlockHeader<SeqTypes> for Header {
         } else {
             Header::get_chain_config(&validated_state, instance_state).await
         };
+        let chain_config = instance_state
+            .chain_config_upgrade(parent_leaf, version)
+            .unwrap_or(chain_config);
 
         validated_state.chain_config = chain_config.into();
 
@@ -972,11 +1058,13 @@ impl BlockHeader<SeqTypes> for Header {
     ) -> Self {
         let ValidatedState {
             fee_merkle_tree,
+            transfer_nonce_merkle_tree,
             block_merkle_tree,
             ..
         } = ValidatedState::genesis(instance_state).0;
         let block_merkle_tree_root = block_merkle_tree.commitment();
         let fee_merkle_tree_root = fee_merkle_tree.commitment();
+        let transfer_nonce_merkle_tree_root = transfer_nonce_merkle_tree.commitment();
 
         //  The Header is versioned,
         //  so we create the genesis header for the current version of the sequencer.
@@ -993,6 +1081,7 @@ impl BlockHeader<SeqTypes> for Header {
             builder_commitment.clone(),
             ns_table.clone(),
             fee_merkle_tree_root,
+            transfer_nonce_merkle_tree_root,
             block_merkle_tree_root,
             vec![FeeInfo::genesis()],
             vec![],
@@ -1083,7 +1172,8 @@ mod test_headers {
 
     use super::*;
     use crate::{
-        eth_signature_key::EthKeyPair, v0::impls::instance_state::mock::MockStateCatchup,
+        eth_signature_key::EthKeyPair,
+        v0::impls::{auction::ExecutionError, instance_state::mock::MockStateCatchup},
         validate_proposal, ProposalValidationError,
     };
 
@@ -1138,6 +1228,7 @@ mod test_headers {
                 block_merkle_tree: block_merkle_tree.clone(),
                 fee_merkle_tree,
                 chain_config: genesis.instance_state.chain_config.into(),
+                ..Default::default()
             };
 
             let (fee_account, fee_key) = FeeAccount::generated_from_seed_indexed([0; 32], 0);
@@ -1569,6 +1660,99 @@ mod test_headers {
         );
     }
 
+    #[async_std::test]
+    async fn test_header_transfers() {
+        setup_test();
+
+        let genesis = GenesisForTest::default().await;
+        let version = MarketplaceVersion::version();
+
+        let key = EthKeyPair::random();
+        let from = key.fee_account();
+        let to = EthKeyPair::random().fee_account();
+        let mut parent_state = genesis.validated_state.clone();
+        parent_state.prefund_account(from, 100.into());
+
+        // The second copy of the transfer is a replay, so the leader drops it.
+        let chain_id = parent_state.chain_config.resolve().unwrap().chain_id;
+        let transfer = v0_3::TransferTxBody::new(from, to, 50.into(), 1.into(), 0, chain_id)
+            .signed(&key)
+            .unwrap();
+        let auction_results = SolverAuctionResults::genesis()
+            .with_transfers(vec![transfer.clone(), transfer.clone()]);
+
+        let fee_key = EthKeyPair::for_test();
+        let fee_amount = 0;
+        let fee_signature =
+            FeeAccount::sign_sequencing_fee_marketplace(&fee_key, fee_amount).unwrap();
+        let header = Header::from_info(
+            genesis.header.payload_commitment(),
+            genesis.header.builder_commitment().clone(),
+            genesis.ns_table,
+            &genesis.leaf,
+            L1Snapshot {
+                head: 0,
+                finalized: None,
+            },
+            &[],
+            vec![BuilderFee {
+                fee_account: fee_key.fee_account(),
+                fee_amount,
+                fee_signature,
+            }],
+            genesis.header.timestamp(),
+            parent_state.clone(),
+            genesis.instance_state.chain_config,
+            version,
+            Some(auction_results),
+        )
+        .unwrap();
+        assert_eq!(
+            header.full_network_txs(),
+            vec![FullNetworkTx::Transfer(transfer.clone())]
+        );
+
+        // Validators execute the transfer and arrive at the same fee state.
+        let (mut state, delta) = parent_state
+            .apply_header(&genesis.instance_state, &genesis.leaf, &header, version)
+            .await
+            .unwrap();
+        assert_eq!(
+            state.fee_merkle_tree.commitment(),
+            header.fee_merkle_tree_root()
+        );
+        assert_eq!(
+            Some(state.transfer_nonce_merkle_tree.commitment()),
+            header.transfer_nonce_merkle_tree_root()
+        );
+        assert_eq!(state.balance(from), Some(49.into()));
+        assert_eq!(state.balance(to), Some(50.into()));
+        assert_eq!(state.nonce(from), Some(1));
+        assert!(delta.fees_delta.contains(&to));
+        assert!(delta.nonces_delta.contains(&from));
+
+        // A header including a transfer which cannot be executed is rejected.
+        let mut invalid = header.clone();
+        let Header::V3(fields) = &mut invalid else {
+            panic!("expected a V3 header");
+        };
+        fields.auction_results = fields
+            .auction_results
+            .clone()
+            .with_transfers(vec![transfer.clone(), transfer]);
+        let err = parent_state
+            .apply_header(&genesis.instance_state, &genesis.leaf, &invalid, version)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(&ExecutionError::InvalidNonce {
+                expected: 1,
+                actual: 0
+            })
+        );
+    }
+
     #[test]
     fn verify_header_signature() {
         // simulate a fixed size hash by padding our message
@@ -1592,6 +1776,10 @@ mod test_headers {
         let ns_table = genesis.ns_table;
 
         let (fee_account, _) = FeeAccount::generated_from_seed_indexed([0; 32], 0);
+        let transfer_nonce_merkle_tree_root = genesis
+            .validated_state
+            .transfer_nonce_merkle_tree
+            .commitment();
 
         let v1_header = Header::create(
             genesis.instance_state.chain_config,
@@ -1603,6 +1791,7 @@ mod test_headers {
             header.builder_commitment().clone(),
             ns_table.clone(),
             header.fee_merkle_tree_root(),
+            transfer_nonce_merkle_tree_root,
             header.block_merkle_tree_root(),
             vec![FeeInfo {
                 amount: 0.into(),
@@ -1626,6 +1815,7 @@ mod test_headers {
             header.builder_commitment().clone(),
             ns_table.clone(),
             header.fee_merkle_tree_root(),
+            transfer_nonce_merkle_tree_root,
             header.block_merkle_tree_root(),
             vec![FeeInfo {
                 amount: 0.into(),
@@ -1649,6 +1839,7 @@ mod test_headers {
             header.builder_commitment().clone(),
             ns_table.clone(),
             header.fee_merkle_tree_root(),
+            transfer_nonce_merkle_tree_root,
             header.block_merkle_tree_root(),
             vec![FeeInfo {
                 amount: 0.into(),
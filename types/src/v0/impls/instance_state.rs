@@ -1,8 +1,9 @@
 use crate::{
-    v0::traits::StateCatchup, v0_3::ChainConfig, GenesisHeader, L1BlockInfo, L1Client, PubKey,
-    Timestamp, Upgrade, UpgradeMode,
+    v0::traits::StateCatchup, v0_3::ChainConfig, ChainConfigUpgrade, ChainConfigUpgradeActivation,
+    GenesisHeader, L1BlockInfo, L1Client, Leaf, PubKey, Timestamp, Upgrade, UpgradeMode,
 };
-use hotshot_types::traits::states::InstanceState;
+use committable::{Commitment, Committable};
+use hotshot_types::traits::{node_implementation::ConsensusTime, states::InstanceState};
 use hotshot_types::HotShotConfig;
 use std::{collections::BTreeMap, sync::Arc};
 use vbs::version::{StaticVersion, StaticVersionType, Version};
@@ -31,6 +32,12 @@ pub struct NodeState {
     /// listed in the genesis TOML file. It will be very useful if multiple upgrades
     /// are supported in the future.
     pub upgrades: BTreeMap<Version, Upgrade>,
+    /// Planned chain config changes which do not change the protocol version, by the version they
+    /// apply to.
+    ///
+    /// Within each version, upgrades are listed in the order they are meant to take effect. If the
+    /// activation points of several upgrades have passed, the last one listed is in effect.
+    pub chain_config_upgrades: BTreeMap<Version, Vec<ChainConfigUpgrade>>,
     /// Current version of the sequencer.
     ///
     /// This version is checked to determine if an upgrade is planned,
@@ -60,6 +67,7 @@ impl NodeState {
             },
             l1_genesis: None,
             upgrades: Default::default(),
+            chain_config_upgrades: Default::default(),
             current_version,
         }
     }
@@ -110,10 +118,52 @@ impl NodeState {
         self
     }
 
+    pub fn with_chain_config_upgrades(
+        mut self,
+        upgrades: BTreeMap<Version, Vec<ChainConfigUpgrade>>,
+    ) -> Self {
+        self.chain_config_upgrades = upgrades;
+        self
+    }
+
     pub fn with_current_version(mut self, ver: Version) -> Self {
         self.current_version = ver;
         self
     }
+
+    /// The chain config set by a [`ChainConfigUpgrade`] for a block built on `parent` at `version`.
+    ///
+    /// Returns `None` if no chain config upgrade is active for this block, in which case the chain
+    /// config is inherited from the parent state (or set by a protocol upgrade).
+    pub fn chain_config_upgrade(&self, parent: &Leaf, version: Version) -> Option<ChainConfig> {
+        self.chain_config_upgrades
+            .get(&version)?
+            .iter()
+            .filter(|upgrade| upgrade.is_active(parent))
+            .last()
+            .map(|upgrade| upgrade.chain_config)
+    }
+
+    /// Look up a chain config by commitment among the configs this node was configured with.
+    ///
+    /// This covers the genesis chain config and the chain configs of all scheduled upgrades, so
+    /// that a node only needs to fetch a chain config from peers if it was not told about it in
+    /// advance.
+    pub fn known_chain_config(&self, commit: Commitment<ChainConfig>) -> Option<ChainConfig> {
+        std::iter::once(self.chain_config)
+            .chain(
+                self.upgrades
+                    .values()
+                    .map(|upgrade| upgrade.upgrade_type.data()),
+            )
+            .chain(
+                self.chain_config_upgrades
+                    .values()
+                    .flatten()
+                    .map(|upgrade| upgrade.chain_config),
+            )
+            .find(|cf| cf.commit() == commit)
+    }
 }
 
 // This allows us to turn on `Default` on InstanceState trait
@@ -163,18 +213,31 @@ impl Upgrade {
     }
 }
 
+impl ChainConfigUpgrade {
+    /// Whether this upgrade is in effect for a block built on `parent`.
+    pub fn is_active(&self, parent: &Leaf) -> bool {
+        match self.activation {
+            ChainConfigUpgradeActivation::View(view) => parent.view_number().u64() >= view,
+            ChainConfigUpgradeActivation::Time(time) => {
+                parent.block_header().timestamp() >= time.unix_timestamp()
+            }
+        }
+    }
+}
+
 #[cfg(any(test, feature = "testing"))]
 pub mod mock {
     use std::collections::HashMap;
 
     use async_trait::async_trait;
-    use committable::Commitment;
     use hotshot_types::data::ViewNumber;
     use jf_merkle_tree::{ForgetableMerkleTreeScheme, MerkleTreeScheme};
 
     use super::*;
     use crate::{
+        remember_transfer_nonces,
         v0_1::{AccountQueryData, FeeAccountProof},
+        v0_3::TransferNonceMerkleTree,
         BackoffParams, BlockMerkleTree, FeeAccount, FeeMerkleCommitment,
     };
 
@@ -234,6 +297,18 @@ pub mod mock {
             Ok(())
         }
 
+        async fn try_remember_transfer_nonces(
+            &self,
+            _height: u64,
+            view: ViewNumber,
+            tree: &mut TransferNonceMerkleTree,
+            accounts: &[FeeAccount],
+        ) -> anyhow::Result<()> {
+            tracing::info!("catchup: fetching transfer nonces {accounts:?} for view {view:?}");
+            let src = &self.state[&view].transfer_nonce_merkle_tree;
+            remember_transfer_nonces(tree, src, accounts)
+        }
+
         async fn try_fetch_chain_config(
             &self,
             _commitment: Commitment<ChainConfig>,
@@ -1,21 +1,44 @@
 use std::{
-    cmp::{min, Ordering},
-    sync::Arc,
+    cmp::{min, Ordering, Reverse},
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Debug, Display, Formatter},
+    future::Future,
+    sync::{
+        atomic::{self, AtomicUsize},
+        Arc,
+    },
     time::Duration,
 };
 
-use async_std::task::sleep;
+use anyhow::ensure;
+use async_broadcast::broadcast;
+use async_std::{future::timeout, sync::RwLock, task::sleep};
 use committable::{Commitment, Committable, RawCommitmentBuilder};
 use contract_bindings::fee_contract::FeeContract;
 use ethers::prelude::{H256, U256, *};
 use futures::{
+    future::join_all,
     join,
     stream::{self, StreamExt},
 };
 use url::Url;
 
 use super::L1BlockInfo;
-use crate::{FeeInfo, L1Client, L1Snapshot};
+use crate::{
+    traits::L1CacheStorage,
+    v0_1::{L1Cache, L1Subscription, L1SubscriptionTask},
+    FeeInfo, L1Client, L1Deposit, L1Provider, L1Snapshot,
+};
+
+/// The default number of finalized blocks and deposit ranges an [`L1Client`] keeps in memory.
+pub const DEFAULT_L1_CACHE_CAPACITY: usize = 100;
+
+/// The number of L1 blocks of finalized data kept by [`L1CacheStorage`].
+///
+/// Whenever a block or deposit range is saved, cached data more than this many blocks older than it
+/// is pruned. This is about a day of L1 blocks, which comfortably covers the data needed to validate
+/// recent proposals.
+pub const L1_CACHE_RETENTION: u64 = 7200;
 
 impl PartialOrd for L1BlockInfo {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -63,44 +86,421 @@ impl L1BlockInfo {
     }
 }
 
+impl L1Provider {
+    fn new(url: Url) -> Self {
+        Self {
+            provider: Arc::new(Provider::new(Http::new(url.clone()))),
+            url,
+            consecutive_failures: AtomicUsize::new(0),
+            failures: AtomicUsize::new(0),
+        }
+    }
+
+    /// Whether the most recent request to this provider succeeded.
+    pub fn is_healthy(&self) -> bool {
+        self.consecutive_failures.load(atomic::Ordering::Relaxed) == 0
+    }
+
+    /// Total number of requests to this provider which have failed.
+    pub fn failures(&self) -> usize {
+        self.failures.load(atomic::Ordering::Relaxed)
+    }
+}
+
+impl Debug for L1Cache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("L1Cache")
+            .field("capacity", &self.capacity)
+            .field("persistent", &self.storage.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl L1Cache {
+    fn new(capacity: usize, storage: Option<Arc<dyn L1CacheStorage>>) -> Self {
+        Self {
+            capacity,
+            blocks: Default::default(),
+            deposits: Default::default(),
+            storage,
+        }
+    }
+
+    /// Look up a finalized block, first in memory and then in storage.
+    async fn block(&self, number: u64) -> Option<L1BlockInfo> {
+        if let Some(block) = self.blocks.read().await.get(&number) {
+            return Some(*block);
+        }
+        let storage = self.storage.as_ref()?;
+        match storage.load_l1_block(number).await {
+            Ok(block) => {
+                let block = block?;
+                self.remember_block(block).await;
+                Some(block)
+            }
+            Err(err) => {
+                tracing::warn!(number, "failed to load L1 block from storage: {err:#}");
+                None
+            }
+        }
+    }
+
+    /// Cache a finalized block, in memory and in storage.
+    async fn insert_block(&self, block: L1BlockInfo) {
+        self.remember_block(block).await;
+        if let Some(storage) = &self.storage {
+            if let Err(err) = storage.save_l1_block(&block).await {
+                tracing::warn!(number = block.number, "failed to save L1 block: {err:#}");
+            }
+        }
+    }
+
+    async fn remember_block(&self, block: L1BlockInfo) {
+        let mut blocks = self.blocks.write().await;
+        blocks.insert(block.number, block);
+        evict(&mut blocks, self.capacity);
+    }
+
+    /// Look up the deposits in a range of finalized blocks, first in memory and then in storage.
+    async fn deposits(&self, fee_contract: Address, from: u64, to: u64) -> Option<Vec<FeeInfo>> {
+        if let Some(deposits) = self.deposits.read().await.get(&(to, from, fee_contract)) {
+            return Some(deposits.clone());
+        }
+        let storage = self.storage.as_ref()?;
+        match storage.load_l1_deposits(fee_contract, from, to).await {
+            Ok(deposits) => {
+                let deposits = deposits?;
+                self.remember_deposits(fee_contract, from, to, deposits.clone())
+                    .await;
+                Some(deposits)
+            }
+            Err(err) => {
+                tracing::warn!(from, to, "failed to load L1 deposits from storage: {err:#}");
+                None
+            }
+        }
+    }
+
+    /// Cache the deposits in a range of finalized blocks, in memory and in storage.
+    async fn insert_deposits(
+        &self,
+        fee_contract: Address,
+        from: u64,
+        to: u64,
+        deposits: Vec<FeeInfo>,
+    ) {
+        if let Some(storage) = &self.storage {
+            if let Err(err) = storage
+                .save_l1_deposits(fee_contract, from, to, &deposits)
+                .await
+            {
+                tracing::warn!(from, to, "failed to save L1 deposits: {err:#}");
+            }
+        }
+        self.remember_deposits(fee_contract, from, to, deposits)
+            .await;
+    }
+
+    async fn remember_deposits(
+        &self,
+        fee_contract: Address,
+        from: u64,
+        to: u64,
+        deposits: Vec<FeeInfo>,
+    ) {
+        let mut cache = self.deposits.write().await;
+        cache.insert((to, from, fee_contract), deposits);
+        evict(&mut cache, self.capacity);
+    }
+}
+
+impl Drop for L1SubscriptionTask {
+    fn drop(&mut self) {
+        if let Some(task) = self.0.take() {
+            // The last client may be dropped inside an async task, where blocking on the
+            // cancellation could stall the executor, so cancel the subscription in the background.
+            async_std::task::spawn(task.cancel());
+        }
+    }
+}
+
+/// Evict the lowest entries of `map` until it fits within `capacity`.
+///
+/// Since the cache is keyed by L1 block number, this evicts the oldest data first.
+fn evict<K: Ord, V>(map: &mut BTreeMap<K, V>, capacity: usize) {
+    while map.len() > capacity {
+        map.pop_first();
+    }
+}
+
 impl L1Client {
     /// Instantiate an `L1Client` for a given `Url`.
     pub fn new(url: Url, events_max_block_range: u64) -> Self {
-        let provider = Arc::new(Provider::new(Http::new(url)));
+        Self::with_providers(vec![L1Provider::new(url)], events_max_block_range)
+    }
+
+    /// Instantiate an `L1Client` which fails over between several providers.
+    ///
+    /// Requests go to the first provider until it fails, at which point the client switches to the
+    /// healthiest of the remaining providers, and so on. Fails if `urls` is empty.
+    pub fn from_urls(
+        urls: impl IntoIterator<Item = Url>,
+        events_max_block_range: u64,
+    ) -> anyhow::Result<Self> {
+        let providers = urls.into_iter().map(L1Provider::new).collect::<Vec<_>>();
+        ensure!(
+            !providers.is_empty(),
+            "L1 client requires at least one provider"
+        );
+        Ok(Self::with_providers(providers, events_max_block_range))
+    }
+
+    fn with_providers(providers: Vec<L1Provider>, events_max_block_range: u64) -> Self {
         Self {
             retry_delay: Duration::from_secs(1),
-            provider,
+            providers: Arc::new(providers),
+            current: Default::default(),
             events_max_block_range,
+            finalized_quorum: 1,
+            subscription: None,
+            subscription_task: None,
+            cache: Arc::new(L1Cache::new(DEFAULT_L1_CACHE_CAPACITY, None)),
+        }
+    }
+
+    /// Configure the cache of finalized L1 data.
+    ///
+    /// Up to `capacity` finalized blocks and deposit ranges are kept in memory. If `storage` is
+    /// provided, all cached data is also saved there, so that it survives restarts.
+    pub fn with_cache(mut self, capacity: usize, storage: Option<Arc<dyn L1CacheStorage>>) -> Self {
+        self.cache = Arc::new(L1Cache::new(capacity, storage));
+        self
+    }
+
+    /// Maintain the latest L1 state using a WebSocket subscription to new heads.
+    ///
+    /// This spawns a background task which subscribes to new heads from the WebSocket provider at
+    /// `url` and, on each new head, updates a cached [`L1Snapshot`]. While the subscription is
+    /// connected, [`snapshot`](Self::snapshot) is served from the cache without any RPC calls.
+    /// Whenever the socket drops, the client falls back to polling over HTTP until the
+    /// subscription is reestablished. The task is cancelled when the last clone of this client is
+    /// dropped.
+    pub fn with_ws_subscription(mut self, url: Url) -> Self {
+        let (mut sender, receiver) = broadcast(1);
+        sender.set_overflow(true);
+        let subscription = Arc::new(L1Subscription {
+            url,
+            snapshot: RwLock::new(None),
+            sender,
+            receiver: receiver.deactivate(),
+        });
+        // The task gets a client without the subscription task handle, so that it does not keep
+        // itself alive.
+        let task = async_std::task::spawn(Self::subscribe(self.clone(), subscription.clone()));
+        self.subscription = Some(subscription);
+        self.subscription_task = Some(Arc::new(L1SubscriptionTask(Some(task))));
+        self
+    }
+
+    /// Require `quorum` providers to agree on the finalized block.
+    pub fn with_finalized_quorum(mut self, quorum: usize) -> Self {
+        self.finalized_quorum = quorum;
+        self
+    }
+
+    /// The provider currently in use.
+    pub fn provider(&self) -> Arc<Provider<Http>> {
+        self.providers[self.current()].provider.clone()
+    }
+
+    /// All configured providers, in order of preference.
+    pub fn providers(&self) -> &[L1Provider] {
+        &self.providers
+    }
+
+    fn current(&self) -> usize {
+        self.current.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Update the health of provider `index` with the result of a request.
+    ///
+    /// If the request failed and this is the provider currently in use, fail over to the healthiest
+    /// other provider, preferring the one that comes next in order.
+    fn record<T, E: Display>(&self, index: usize, res: &Result<T, E>) {
+        let provider = &self.providers[index];
+        let Err(err) = res else {
+            provider
+                .consecutive_failures
+                .store(0, atomic::Ordering::Relaxed);
+            return;
+        };
+        provider.failures.fetch_add(1, atomic::Ordering::Relaxed);
+        provider
+            .consecutive_failures
+            .fetch_add(1, atomic::Ordering::Relaxed);
+
+        let n = self.providers.len();
+        if n == 1 {
+            return;
+        }
+        let Some(next) = (1..n).map(|offset| (index + offset) % n).min_by_key(|&i| {
+            self.providers[i]
+                .consecutive_failures
+                .load(atomic::Ordering::Relaxed)
+        }) else {
+            return;
+        };
+        // Only fail over if nobody else has already done so in response to a different error.
+        if self
+            .current
+            .compare_exchange(
+                index,
+                next,
+                atomic::Ordering::Relaxed,
+                atomic::Ordering::Relaxed,
+            )
+            .is_ok()
+        {
+            tracing::warn!(
+                %err,
+                from = %provider.url,
+                to = %self.providers[next].url,
+                "L1 provider failed, failing over"
+            );
+        }
+    }
+
+    /// Make a request, failing over between providers until one succeeds.
+    ///
+    /// Each provider is tried at most once. If all of them fail, the last error is returned.
+    async fn with_failover<T, E, F, Fut>(&self, f: F) -> Result<T, E>
+    where
+        E: Display,
+        F: Fn(Arc<Provider<Http>>) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut res = None;
+        for _ in 0..self.providers.len() {
+            let index = self.current();
+            let attempt = f(self.providers[index].provider.clone()).await;
+            self.record(index, &attempt);
+            if attempt.is_ok() {
+                return attempt;
+            }
+            res = Some(attempt);
         }
+        res.expect("L1Client has at least one provider")
     }
+
     /// Get a snapshot from the l1.
     pub async fn snapshot(&self) -> L1Snapshot {
+        if let Some(snapshot) = self.cached_snapshot().await {
+            return snapshot;
+        }
         let (head, finalized) = join!(self.get_block_number(), self.get_finalized_block());
         L1Snapshot { head, finalized }
     }
 
+    /// The latest snapshot from the WebSocket subscription, if it is connected.
+    pub async fn cached_snapshot(&self) -> Option<L1Snapshot> {
+        *self.subscription.as_ref()?.snapshot.read().await
+    }
+
+    /// Wait until the L1 state may have changed.
+    ///
+    /// If the WebSocket subscription is connected, this returns as soon as there is a new head, or
+    /// after `interval` at most, in case the subscription drops in the meantime. Otherwise this
+    /// just sleeps for `interval`.
+    async fn wait_for_update(&self, interval: Duration) {
+        match &self.subscription {
+            Some(subscription) if subscription.snapshot.read().await.is_some() => {
+                let mut updates = subscription.receiver.activate_cloned();
+                timeout(interval, updates.recv()).await.ok();
+            }
+            _ => sleep(interval).await,
+        }
+    }
+
+    /// Background task maintaining the cached snapshot for a WebSocket subscription.
+    async fn subscribe(self, subscription: Arc<L1Subscription>) {
+        loop {
+            if let Err(err) = self.follow_heads(&subscription).await {
+                tracing::warn!(
+                    %err,
+                    url = %subscription.url,
+                    "L1 subscription failed, falling back to HTTP"
+                );
+            } else {
+                tracing::warn!(
+                    url = %subscription.url,
+                    "L1 subscription closed, falling back to HTTP"
+                );
+            }
+            *subscription.snapshot.write().await = None;
+            sleep(self.retry_delay).await;
+        }
+    }
+
+    async fn follow_heads(&self, subscription: &L1Subscription) -> anyhow::Result<()> {
+        let provider = Provider::<Ws>::connect(subscription.url.as_str()).await?;
+        let mut heads = provider.subscribe_blocks().await?;
+        tracing::info!(url = %subscription.url, "subscribed to L1 heads");
+
+        let mut finalized = None;
+        while let Some(head) = heads.next().await {
+            let Some(head) = head.number else {
+                tracing::warn!(hash = ?head.hash, "ignoring L1 head with no number");
+                continue;
+            };
+            // The finalized block is fetched over HTTP, so that the provider failover and quorum
+            // settings still apply to it. This happens off the critical path of any caller, though.
+            match self.try_get_finalized_block().await {
+                Ok(block) => finalized = block,
+                Err(err) => {
+                    tracing::warn!(%err, "failed to update finalized block, using previous")
+                }
+            }
+            let snapshot = L1Snapshot {
+                head: head.as_u64(),
+                finalized,
+            };
+            tracing::debug!(?snapshot, "new L1 snapshot");
+            *subscription.snapshot.write().await = Some(snapshot);
+            subscription.sender.try_broadcast(snapshot).ok();
+        }
+        Ok(())
+    }
+
     /// Get information about the given block.
     ///
     /// If the desired block number is not finalized yet, this function will block until it becomes
     /// finalized.
     pub async fn wait_for_finalized_block(&self, number: u64) -> L1BlockInfo {
-        let interval = self.provider.get_interval();
+        if let Some(block) = self.cache.block(number).await {
+            return block;
+        }
+        let interval = self.provider().get_interval();
 
         // Wait for the block to finalize.
         let finalized = loop {
-            let Some(block) = self.get_finalized_block().await else {
+            let finalized = match self.cached_snapshot().await {
+                Some(snapshot) => snapshot.finalized,
+                None => self.get_finalized_block().await,
+            };
+            let Some(block) = finalized else {
                 tracing::info!("waiting for finalized block");
-                sleep(interval).await;
+                self.wait_for_update(interval).await;
                 continue;
             };
             if block.number >= number {
                 break block;
             }
             tracing::info!(current_finalized = %block.number, "waiting for finalized block");
-            sleep(interval).await;
+            self.wait_for_update(interval).await;
             continue;
         };
 
+        self.cache.insert_block(finalized).await;
         if finalized.number == number {
             return finalized;
         }
@@ -108,7 +508,10 @@ impl L1Client {
         // The finalized block may have skipped over the block of interest. In this case, our block
         // is still finalized, since it is before the finalized block. We just need to fetch it.
         loop {
-            let block = match self.provider.get_block(number).await {
+            let block = match self
+                .with_failover(|provider| async move { provider.get_block(number).await })
+                .await
+            {
                 Ok(Some(block)) => block,
                 Ok(None) => {
                     tracing::error!(number, "no such block");
@@ -126,18 +529,23 @@ impl L1Client {
                 sleep(interval).await;
                 continue;
             };
-            break L1BlockInfo {
+            let block = L1BlockInfo {
                 number,
                 hash,
                 timestamp: block.timestamp,
             };
+            self.cache.insert_block(block).await;
+            break block;
         }
     }
 
     /// Proxy to `Provider.get_block_number`.
     async fn get_block_number(&self) -> u64 {
         loop {
-            match self.provider.get_block_number().await {
+            match self
+                .with_failover(|provider| async move { provider.get_block_number().await })
+                .await
+            {
                 Ok(n) => return n.as_u64(),
                 Err(e) => {
                     tracing::warn!("Blocknumber error: {}", e);
@@ -149,7 +557,7 @@ impl L1Client {
     /// Proxy to `get_finalized_block`.
     async fn get_finalized_block(&self) -> Option<L1BlockInfo> {
         loop {
-            match get_finalized_block(&self.provider).await {
+            match self.try_get_finalized_block().await {
                 Ok(block) => return block,
                 Err(e) => {
                     tracing::warn!("Finalized block error: {}", e);
@@ -158,6 +566,128 @@ impl L1Client {
             }
         }
     }
+
+    async fn try_get_finalized_block(&self) -> Result<Option<L1BlockInfo>, ProviderError> {
+        if self.finalized_quorum > 1 {
+            self.get_finalized_block_quorum().await
+        } else {
+            self.with_failover(|provider| async move { get_finalized_block(&provider).await })
+                .await
+        }
+    }
+
+    /// Get the finalized block, cross-checked between providers.
+    ///
+    /// Every provider is asked for its latest finalized block. Since providers may lag behind one
+    /// another, the candidate is the latest block which at least `finalized_quorum` providers have
+    /// finalized, and at least that many providers must agree on that block's hash. Providers
+    /// which disagree with the majority are logged, since this indicates either a faulty provider
+    /// or a catastrophic reorg of the L1.
+    async fn get_finalized_block_quorum(&self) -> Result<Option<L1BlockInfo>, ProviderError> {
+        let quorum = self.finalized_quorum;
+        let mut finalized = join_all(self.providers.iter().enumerate().map(
+            |(i, provider)| async move {
+                let res = get_finalized_block(&provider.provider).await;
+                self.record(i, &res);
+                Some((i, res.ok()?))
+            },
+        ))
+        .await
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        if finalized.len() < quorum {
+            return Err(ProviderError::CustomError(format!(
+                "only {} of {} L1 providers responded, need {quorum}",
+                finalized.len(),
+                self.providers.len()
+            )));
+        }
+
+        // Sort from latest to earliest (`None` is earliest) and find the latest block finalized by
+        // a quorum.
+        finalized.sort_by_key(|(_, block)| Reverse(*block));
+        let Some(target) = finalized[quorum - 1].1 else {
+            return Ok(None);
+        };
+
+        // Get each provider's version of the target block.
+        let votes = join_all(finalized.into_iter().filter_map(|(i, block)| {
+            let block = block.filter(|block| block.number >= target.number)?;
+            Some(async move {
+                if block.number == target.number {
+                    return Some((i, block));
+                }
+                let number = target.number;
+                let provider = &self.providers[i];
+                match provider.provider.get_block(number).await {
+                    Ok(Some(Block {
+                        hash: Some(hash),
+                        timestamp,
+                        ..
+                    })) => Some((
+                        i,
+                        L1BlockInfo {
+                            number,
+                            timestamp,
+                            hash,
+                        },
+                    )),
+                    Ok(_) => {
+                        tracing::warn!(number, url = %provider.url, "missing finalized L1 block");
+                        None
+                    }
+                    Err(err) => {
+                        tracing::warn!(
+                            number,
+                            url = %provider.url,
+                            %err,
+                            "failed to get finalized L1 block"
+                        );
+                        None
+                    }
+                }
+            })
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        let mut tally = HashMap::<L1BlockInfo, usize>::new();
+        for (_, block) in &votes {
+            *tally.entry(*block).or_default() += 1;
+        }
+        let (block, count) = tally
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(block, count)| (*block, *count))
+            .ok_or_else(|| {
+                ProviderError::CustomError(format!(
+                    "no L1 provider returned finalized block {}",
+                    target.number
+                ))
+            })?;
+        if tally.len() > 1 {
+            let responses = votes
+                .iter()
+                .map(|(i, block)| format!("{}: {:?}", self.providers[*i].url, block.hash))
+                .collect::<Vec<_>>();
+            tracing::error!(
+                number = target.number,
+                ?responses,
+                "L1 providers disagree on finalized block"
+            );
+        }
+        if count < quorum {
+            return Err(ProviderError::CustomError(format!(
+                "only {count} L1 providers agree on finalized block {}, need {quorum}",
+                target.number
+            )));
+        }
+        Ok(Some(block))
+    }
+
     /// Get fee info for each `Deposit` occurring between `prev`
     /// and `new`. Returns `Vec<FeeInfo>`
     pub async fn get_finalized_deposits(
@@ -166,50 +696,42 @@ impl L1Client {
         prev_finalized: Option<u64>,
         new_finalized: u64,
     ) -> Vec<FeeInfo> {
-        // No new blocks have been finalized, therefore there are no
-        // new deposits.
-        if prev_finalized >= Some(new_finalized) {
-            return vec![];
-        }
-
-        // `prev` should have already been processed unless we
-        // haven't processed *any* blocks yet.
-        let prev = prev_finalized.map(|prev| prev + 1).unwrap_or(0);
-
-        // Divide the range `prev_finalized..=new_finalized` into chunks of size
-        // `events_max_block_range`.
-        let mut start = prev;
-        let end = new_finalized;
-        let chunk_size = self.events_max_block_range;
-        let chunks = std::iter::from_fn(move || {
-            let chunk_end = min(start + chunk_size - 1, end);
-            if chunk_end < start {
-                return None;
-            }
-
-            let chunk = (start, chunk_end);
-            start = chunk_end + 1;
-            Some(chunk)
-        });
-
         // Fetch events for each chunk.
-        let events = stream::iter(chunks).then(|(from, to)| {
+        let chunks = self.event_chunks(prev_finalized, new_finalized);
+        let deposits = stream::iter(chunks).then(|(from, to)| {
             let retry_delay = self.retry_delay;
-            let fee_contract = FeeContract::new(fee_contract_address, self.provider.clone());
             async move {
+                // The range is finalized, so if we have fetched it before, the result can't have
+                // changed.
+                if let Some(deposits) = self.cache.deposits(fee_contract_address, from, to).await {
+                    tracing::debug!(from, to, "using cached events in range");
+                    return stream::iter(deposits);
+                }
                 tracing::debug!(from, to, "fetch events in range");
 
                 // query for deposit events, loop until successful.
                 loop {
-                    match fee_contract
-                        .deposit_filter()
-                        .address(fee_contract.address().into())
-                        .from_block(from)
-                        .to_block(to)
-                        .query()
+                    match self
+                        .with_failover(|provider| async move {
+                            let fee_contract = FeeContract::new(fee_contract_address, provider);
+                            fee_contract
+                                .deposit_filter()
+                                .address(fee_contract.address().into())
+                                .from_block(from)
+                                .to_block(to)
+                                .query()
+                                .await
+                        })
                         .await
                     {
-                        Ok(events) => break stream::iter(events),
+                        Ok(events) => {
+                            let deposits =
+                                events.into_iter().map(FeeInfo::from).collect::<Vec<_>>();
+                            self.cache
+                                .insert_deposits(fee_contract_address, from, to, deposits.clone())
+                                .await;
+                            break stream::iter(deposits);
+                        }
                         Err(err) => {
                             tracing::warn!(from, to, %err, "Fee Event Error");
                             sleep(retry_delay).await;
@@ -218,7 +740,92 @@ impl L1Client {
                 }
             }
         });
-        events.flatten().map(FeeInfo::from).collect().await
+        deposits.flatten().collect().await
+    }
+
+    /// Get each `Deposit` occurring between `prev` and `new`, along with the L1 transaction that
+    /// made it.
+    ///
+    /// This returns the same deposits as [`get_finalized_deposits`](Self::get_finalized_deposits),
+    /// in the same order, but since it needs the event metadata, it always queries the L1 rather
+    /// than the cache.
+    pub async fn get_finalized_deposit_events(
+        &self,
+        fee_contract_address: Address,
+        prev_finalized: Option<u64>,
+        new_finalized: u64,
+    ) -> Vec<L1Deposit> {
+        let chunks = self.event_chunks(prev_finalized, new_finalized);
+        let deposits = stream::iter(chunks).then(|(from, to)| {
+            let retry_delay = self.retry_delay;
+            async move {
+                tracing::debug!(from, to, "fetch events with metadata in range");
+                loop {
+                    match self
+                        .with_failover(|provider| async move {
+                            let fee_contract = FeeContract::new(fee_contract_address, provider);
+                            fee_contract
+                                .deposit_filter()
+                                .address(fee_contract.address().into())
+                                .from_block(from)
+                                .to_block(to)
+                                .query_with_meta()
+                                .await
+                        })
+                        .await
+                    {
+                        Ok(events) => {
+                            break stream::iter(events.into_iter().map(|(event, meta)| {
+                                let FeeInfo { account, amount } = event.into();
+                                L1Deposit {
+                                    account,
+                                    amount,
+                                    l1_block: meta.block_number.as_u64(),
+                                    l1_transaction: meta.transaction_hash,
+                                    log_index: meta.log_index.as_u64(),
+                                }
+                            }))
+                        }
+                        Err(err) => {
+                            tracing::warn!(from, to, %err, "Fee Event Error");
+                            sleep(retry_delay).await;
+                        }
+                    }
+                }
+            }
+        });
+        deposits.flatten().collect().await
+    }
+
+    /// Divide the range of newly finalized blocks `prev_finalized + 1..=new_finalized` into chunks
+    /// of size `events_max_block_range`.
+    fn event_chunks(
+        &self,
+        prev_finalized: Option<u64>,
+        new_finalized: u64,
+    ) -> impl Iterator<Item = (u64, u64)> {
+        // No new blocks have been finalized, therefore there are no
+        // new deposits.
+        let finished = prev_finalized >= Some(new_finalized);
+
+        // `prev` should have already been processed unless we
+        // haven't processed *any* blocks yet.
+        let mut start = prev_finalized.map(|prev| prev + 1).unwrap_or(0);
+        let end = new_finalized;
+        let chunk_size = self.events_max_block_range;
+        std::iter::from_fn(move || {
+            if finished {
+                return None;
+            }
+            let chunk_end = min(start + chunk_size - 1, end);
+            if chunk_end < start {
+                return None;
+            }
+
+            let chunk = (start, chunk_end);
+            start = chunk_end + 1;
+            Some(chunk)
+        })
     }
 }
 
@@ -256,7 +863,7 @@ mod test {
     use std::ops::Add;
 
     use contract_bindings::fee_contract::FeeContract;
-    use ethers::utils::{hex, parse_ether, Anvil};
+    use ethers::utils::{hex, parse_ether, Anvil, AnvilInstance};
     use sequencer_utils::test_utils::setup_test;
 
     use super::*;
@@ -270,7 +877,7 @@ mod test {
         // also some sanity testing demonstrating `Anvil` availability.
         let anvil = Anvil::new().block_time(1u32).spawn();
         let l1_client = L1Client::new(anvil.endpoint().parse().unwrap(), 1);
-        let provider = &l1_client.provider;
+        let provider = l1_client.provider();
 
         let version = provider.client_version().await.unwrap();
         assert_eq!("anvil/v0.2.0", version);
@@ -278,7 +885,7 @@ mod test {
         // Test that nothing funky is happening to the provider when
         // passed along in state.
         let state = NodeState::mock().with_l1(L1Client::new(anvil.endpoint().parse().unwrap(), 1));
-        let version = state.l1_client.provider.client_version().await.unwrap();
+        let version = state.l1_client.provider().client_version().await.unwrap();
         assert_eq!("anvil/v0.2.0", version);
 
         // compare response of underlying provider w/ `get_block_number`
@@ -299,6 +906,180 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_l1_client_requires_provider() {
+        L1Client::from_urls([], 1).unwrap_err();
+    }
+
+    #[async_std::test]
+    async fn test_l1_provider_failover() {
+        setup_test();
+
+        let anvil = Anvil::new().block_time(1u32).spawn();
+        let l1_client = L1Client::from_urls(
+            [
+                "http://localhost:1".parse().unwrap(),
+                anvil.endpoint().parse().unwrap(),
+            ],
+            1,
+        )
+        .unwrap();
+
+        // The first provider is unreachable, so the client fails over to the second.
+        let expected_head = anvil_provider(&anvil).get_block_number().await.unwrap();
+        let head = l1_client.get_block_number().await;
+        assert!(head >= expected_head.as_u64());
+        assert_eq!(l1_client.current(), 1);
+        assert!(!l1_client.providers()[0].is_healthy());
+        assert_eq!(l1_client.providers()[0].failures(), 1);
+        assert!(l1_client.providers()[1].is_healthy());
+
+        // Subsequent requests go straight to the healthy provider.
+        l1_client.get_finalized_block().await.unwrap();
+        assert_eq!(l1_client.current(), 1);
+        assert_eq!(l1_client.providers()[0].failures(), 1);
+    }
+
+    #[async_std::test]
+    async fn test_l1_finalized_quorum() {
+        setup_test();
+
+        let anvil = Anvil::new().block_time(1u32).spawn();
+        let url: Url = anvil.endpoint().parse().unwrap();
+        let l1_client = L1Client::from_urls(
+            [
+                url.clone(),
+                "http://localhost:1".parse().unwrap(),
+                url.clone(),
+            ],
+            1,
+        )
+        .unwrap()
+        .with_finalized_quorum(2);
+
+        // Two out of three providers agree, which is enough for a quorum.
+        let finalized = l1_client
+            .get_finalized_block_quorum()
+            .await
+            .unwrap()
+            .unwrap();
+        let expected = anvil_provider(&anvil)
+            .get_block(finalized.number)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(finalized.hash, expected.hash.unwrap());
+        assert_eq!(finalized.timestamp, expected.timestamp);
+
+        // With only one working provider, the quorum cannot be reached.
+        let l1_client = L1Client::from_urls([url, "http://localhost:1".parse().unwrap()], 1)
+            .unwrap()
+            .with_finalized_quorum(2);
+        l1_client.get_finalized_block_quorum().await.unwrap_err();
+    }
+
+    #[async_std::test]
+    async fn test_l1_ws_subscription() {
+        setup_test();
+
+        let anvil = Anvil::new().block_time(1u32).spawn();
+        let l1_client = L1Client::new(anvil.endpoint().parse().unwrap(), 1)
+            .with_ws_subscription(anvil.ws_endpoint().parse().unwrap());
+
+        // Wait for the subscription to see a new head.
+        let snapshot = loop {
+            if let Some(snapshot) = l1_client.cached_snapshot().await {
+                break snapshot;
+            }
+            sleep(Duration::from_millis(100)).await;
+        };
+        assert_eq!(l1_client.snapshot().await.head, snapshot.head);
+
+        // The cache follows the L1 as new blocks are produced.
+        let target = snapshot.head + 2;
+        let block = l1_client.wait_for_finalized_block(target).await;
+        assert_eq!(block.number, target);
+        assert!(l1_client.snapshot().await.head >= target);
+
+        // Once the L1 goes away, the subscription is dropped and the cache invalidated.
+        drop(anvil);
+        while l1_client.cached_snapshot().await.is_some() {
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    #[async_std::test]
+    async fn test_l1_ws_subscription_cancelled_on_drop() {
+        setup_test();
+
+        let anvil = Anvil::new().block_time(1u32).spawn();
+        let l1_client = L1Client::new(anvil.endpoint().parse().unwrap(), 1)
+            .with_ws_subscription(anvil.ws_endpoint().parse().unwrap());
+        let subscription = Arc::downgrade(l1_client.subscription.as_ref().unwrap());
+        while l1_client.cached_snapshot().await.is_none() {
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        // The subscription outlives clones of the client.
+        drop(l1_client.clone());
+        assert!(l1_client.cached_snapshot().await.is_some());
+
+        // Once the last clone is dropped, the task is cancelled in the background, releasing the
+        // subscription.
+        drop(l1_client);
+        async_std::future::timeout(Duration::from_secs(5), async {
+            while subscription.upgrade().is_some() {
+                sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("subscription was not released");
+    }
+
+    #[async_std::test]
+    async fn test_l1_cache() {
+        setup_test();
+
+        let anvil = Anvil::new().block_time(1u32).spawn();
+        let l1_client = L1Client::new(anvil.endpoint().parse().unwrap(), 10000).with_cache(2, None);
+        let head = anvil_provider(&anvil)
+            .get_block_number()
+            .await
+            .unwrap()
+            .as_u64();
+        let block = l1_client.wait_for_finalized_block(head + 1).await;
+        let deposits = l1_client
+            .get_finalized_deposits(Address::zero(), None, head + 1)
+            .await;
+
+        // Once fetched, finalized data is served from the cache, even without the L1.
+        drop(anvil);
+        assert_eq!(l1_client.wait_for_finalized_block(head + 1).await, block);
+        assert_eq!(
+            l1_client
+                .get_finalized_deposits(Address::zero(), None, head + 1)
+                .await,
+            deposits
+        );
+
+        // The oldest blocks are evicted once the cache is full.
+        for number in head + 2..head + 4 {
+            l1_client
+                .cache
+                .insert_block(L1BlockInfo { number, ..block })
+                .await;
+        }
+        assert_eq!(l1_client.cache.block(head + 1).await, None);
+        assert_eq!(
+            l1_client.cache.block(head + 3).await.unwrap().number,
+            head + 3
+        );
+    }
+
+    fn anvil_provider(anvil: &AnvilInstance) -> Provider<Http> {
+        Provider::try_from(anvil.endpoint()).unwrap()
+    }
+
     #[async_std::test]
     async fn test_get_finalized_deposits() -> anyhow::Result<()> {
         setup_test();
@@ -465,7 +1246,7 @@ mod test {
 
         let anvil = Anvil::new().block_time(1u32).spawn();
         let l1_client = L1Client::new(anvil.endpoint().parse().unwrap(), 1);
-        let provider = &l1_client.provider;
+        let provider = l1_client.provider();
 
         // Wait for a block 10 blocks in the future.
         let block_height = provider.get_block_number().await.unwrap().as_u64();
@@ -14,5 +14,9 @@ mod transaction;
 pub use auction::SolverAuctionResultsProvider;
 pub use fee_info::FeeError;
 pub use instance_state::{mock, NodeState};
+pub use l1::{DEFAULT_L1_CACHE_CAPACITY, L1_CACHE_RETENTION};
 pub use state::ProposalValidationError;
-pub use state::{validate_proposal, BuilderValidationError, StateValidationError, ValidatedState};
+pub use state::{
+    remember_transfer_nonces, validate_proposal, BuilderValidationError, StateValidationError,
+    ValidatedState,
+};
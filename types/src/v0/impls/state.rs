@@ -1,4 +1,4 @@
-use anyhow::bail;
+use anyhow::{bail, ensure};
 use committable::{Commitment, Committable};
 use ethers::types::Address;
 use hotshot_query_service::merklized_state::MerklizedState;
@@ -30,7 +30,10 @@ use super::{
     BlockSize, FeeMerkleCommitment,
 };
 use crate::{
-    v0_3::{ChainConfig, FullNetworkTx, IterableFeeInfo, ResolvableChainConfig},
+    v0_3::{
+        ChainConfig, FullNetworkTx, IterableFeeInfo, ResolvableChainConfig,
+        TransferNonceMerkleCommitment, TransferNonceMerkleTree, TRANSFER_NONCE_MERKLE_TREE_HEIGHT,
+    },
     BlockMerkleTree, Delta, FeeAccount, FeeAmount, FeeInfo, FeeMerkleTree, Header, Leaf,
     NsTableValidationError, PayloadByteLen, SeqTypes, UpgradeType, BLOCK_MERKLE_TREE_HEIGHT,
     FEE_MERKLE_TREE_HEIGHT,
@@ -90,6 +93,13 @@ pub enum ProposalValidationError {
         expected_root: FeeMerkleCommitment,
         proposal_root: FeeMerkleCommitment,
     },
+    #[error(
+        "Invalid Transfer Nonce Root Error: expected={expected_root}, proposal={proposal_root}"
+    )]
+    InvalidTransferNonceRoot {
+        expected_root: TransferNonceMerkleCommitment,
+        proposal_root: TransferNonceMerkleCommitment,
+    },
     #[error("Invalid namespace table: {err}")]
     InvalidNsTable { err: NsTableValidationError },
     #[error("Some fee amount or their sum total out of range")]
@@ -104,6 +114,8 @@ pub struct ValidatedState {
     pub block_merkle_tree: BlockMerkleTree,
     /// Fee Merkle Tree
     pub fee_merkle_tree: FeeMerkleTree,
+    /// Transfer Nonce Merkle Tree
+    pub transfer_nonce_merkle_tree: TransferNonceMerkleTree,
     pub chain_config: ResolvableChainConfig,
 }
 
@@ -124,11 +136,15 @@ impl Default for ValidatedState {
         )
         .unwrap();
 
+        let transfer_nonce_merkle_tree =
+            TransferNonceMerkleTree::new(TRANSFER_NONCE_MERKLE_TREE_HEIGHT);
+
         let chain_config = ResolvableChainConfig::from(ChainConfig::default());
 
         Self {
             block_merkle_tree,
             fee_merkle_tree,
+            transfer_nonce_merkle_tree,
             chain_config,
         }
     }
@@ -148,6 +164,25 @@ impl ValidatedState {
         }
     }
 
+    /// The nonce expected in the next transfer from `account`, which is
+    /// the number of transfers executed from it so far.
+    pub fn nonce(&self, account: FeeAccount) -> Option<u64> {
+        match self.transfer_nonce_merkle_tree.lookup(account) {
+            LookupResult::Ok(nonce, _) => Some(*nonce),
+            LookupResult::NotFound(_) => Some(0),
+            LookupResult::NotInMemory => None,
+        }
+    }
+
+    /// Increment the transfer nonce of `account`.
+    pub fn increment_nonce(&mut self, account: FeeAccount) -> Result<(), FeeError> {
+        self.transfer_nonce_merkle_tree
+            .update_with(account, |nonce| {
+                Some(nonce.copied().unwrap_or_default() + 1)
+            })?;
+        Ok(())
+    }
+
     /// Find accounts that are not in memory.
     ///
     /// As an optimization we could try to apply updates and return the
@@ -168,6 +203,23 @@ impl ValidatedState {
             .collect()
     }
 
+    /// Find accounts whose transfer nonce is not in memory.
+    pub fn forgotten_nonces(
+        &self,
+        accounts: impl IntoIterator<Item = FeeAccount>,
+    ) -> Vec<FeeAccount> {
+        accounts
+            .into_iter()
+            .unique()
+            .filter(|account| {
+                self.transfer_nonce_merkle_tree
+                    .lookup(*account)
+                    .expect_not_in_memory()
+                    .is_ok()
+            })
+            .collect()
+    }
+
     /// Check if the merkle tree is available
     pub fn need_to_fetch_blocks_mt_frontier(&self) -> bool {
         let num_leaves = self.block_merkle_tree.num_leaves();
@@ -243,6 +295,9 @@ impl ValidatedState {
     pub fn forget(&self) -> Self {
         Self {
             fee_merkle_tree: FeeMerkleTree::from_commitment(self.fee_merkle_tree.commitment()),
+            transfer_nonce_merkle_tree: TransferNonceMerkleTree::from_commitment(
+                self.transfer_nonce_merkle_tree.commitment(),
+            ),
             block_merkle_tree: BlockMerkleTree::from_commitment(
                 self.block_merkle_tree.commitment(),
             ),
@@ -251,6 +306,34 @@ impl ValidatedState {
     }
 }
 
+/// Remember the transfer nonces of `accounts` in `tree`, using `snapshot`, a sparse copy of `tree`
+/// in which the paths to these accounts are known, such as one served by a peer for catchup.
+///
+/// Each path is checked against the root of `tree`, so a peer cannot get away with forging part of
+/// the snapshot. Fails without modifying `tree` if any of the accounts cannot be remembered.
+pub fn remember_transfer_nonces(
+    tree: &mut TransferNonceMerkleTree,
+    snapshot: &TransferNonceMerkleTree,
+    accounts: &[FeeAccount],
+) -> anyhow::Result<()> {
+    ensure!(
+        snapshot.commitment() == tree.commitment(),
+        "snapshot has wrong root: expected {:?}, got {:?}",
+        tree.commitment(),
+        snapshot.commitment()
+    );
+    let mut updated = tree.clone();
+    for account in accounts {
+        match snapshot.universal_lookup(*account) {
+            LookupResult::Ok(nonce, proof) => updated.remember(*account, nonce, proof)?,
+            LookupResult::NotFound(proof) => updated.non_membership_remember(*account, proof)?,
+            LookupResult::NotInMemory => bail!("snapshot is missing transfer nonce of {account}"),
+        }
+    }
+    *tree = updated;
+    Ok(())
+}
+
 impl From<NsTableValidationError> for ProposalValidationError {
     fn from(err: NsTableValidationError) -> Self {
         Self::InvalidNsTable { err }
@@ -328,6 +411,16 @@ pub fn validate_proposal(
         });
     }
 
+    if let Some(proposal_root) = proposal.transfer_nonce_merkle_tree_root() {
+        let transfer_nonce_merkle_tree_root = state.transfer_nonce_merkle_tree.commitment();
+        if proposal_root != transfer_nonce_merkle_tree_root {
+            return Err(ProposalValidationError::InvalidTransferNonceRoot {
+                expected_root: transfer_nonce_merkle_tree_root,
+                proposal_root,
+            });
+        }
+    }
+
     proposal
         .ns_table()
         .validate(&PayloadByteLen::from_vid_common(vid_common))?;
@@ -414,7 +507,7 @@ impl ValidatedState {
         // through returned value.
 
         let mut validated_state = self.clone();
-        validated_state.apply_upgrade(instance, version);
+        validated_state.apply_upgrade(instance, parent_leaf, version);
 
         let chain_config = validated_state
             .get_chain_config(instance, &proposed_header.chain_config())
@@ -432,14 +525,24 @@ impl ValidatedState {
         )
         .await;
 
+        let full_network_txs = proposed_header.full_network_txs();
+
         // Find missing fee state entries. We will need to use the builder account which is paying a
         // fee and the recipient account which is receiving it, plus any counts receiving deposits
-        // in this block.
+        // in this block, and the accounts used by full network transactions.
         let missing_accounts = self.forgotten_accounts(
             [chain_config.fee_recipient]
                 .into_iter()
                 .chain(proposed_header.fee_info().accounts())
-                .chain(l1_deposits.accounts()),
+                .chain(l1_deposits.accounts())
+                .chain(full_network_txs.iter().flat_map(FullNetworkTx::accounts)),
+        );
+
+        // Likewise, the senders of transfers need their transfer nonces.
+        let missing_nonces = self.forgotten_nonces(
+            full_network_txs
+                .iter()
+                .flat_map(FullNetworkTx::nonce_accounts),
         );
 
         let parent_height = parent_leaf.height();
@@ -492,6 +595,26 @@ impl ValidatedState {
             }
         }
 
+        // Fetch missing transfer nonces
+        if !missing_nonces.is_empty() {
+            tracing::info!(
+                parent_height,
+                ?parent_view,
+                ?missing_nonces,
+                "fetching missing transfer nonces from peers"
+            );
+            instance
+                .peers
+                .as_ref()
+                .remember_transfer_nonces(
+                    parent_height,
+                    parent_view,
+                    &mut validated_state.transfer_nonce_merkle_tree,
+                    missing_nonces,
+                )
+                .await?;
+        }
+
         let mut delta = Delta::default();
 
         let mut validated_state =
@@ -504,11 +627,26 @@ impl ValidatedState {
             chain_config.fee_recipient,
         )?;
 
+        apply_full_transactions(&mut validated_state, &mut delta, full_network_txs, version)?;
+
         Ok((validated_state, delta))
     }
 
-    /// Updates the `ValidatedState` if a protocol upgrade has occurred.
-    pub(crate) fn apply_upgrade(&mut self, instance: &NodeState, version: Version) {
+    /// Updates the `ValidatedState` if a protocol upgrade or chain config upgrade has occurred.
+    ///
+    /// A chain config upgrade scheduled for `version` takes precedence over the chain config set by
+    /// the protocol upgrade to `version`, once the chain config upgrade is active.
+    pub(crate) fn apply_upgrade(
+        &mut self,
+        instance: &NodeState,
+        parent_leaf: &Leaf,
+        version: Version,
+    ) {
+        if let Some(chain_config) = instance.chain_config_upgrade(parent_leaf, version) {
+            self.chain_config = chain_config.into();
+            return;
+        }
+
         // Check for protocol upgrade based on sequencer version
         if version <= instance.current_version {
             return;
@@ -530,7 +668,8 @@ impl ValidatedState {
     ///
     ///  Returns the `NodeState` `ChainConfig` if the `ValidatedState` `ChainConfig` commitment matches the `NodeState` `ChainConfig`` commitment.
     ///  If the commitments do not match, it returns the `ChainConfig` available in either `ValidatedState` or proposed header.
-    ///  If neither has the `ChainConfig`, it fetches the config from the peers.
+    ///  If neither has the `ChainConfig`, it looks for it among the upgrades scheduled in `NodeState`, and
+    ///  failing that, fetches the config from the peers.
     ///
     /// Returns an error if it fails to fetch the `ChainConfig` from the peers.
     pub(crate) async fn get_chain_config(
@@ -547,26 +686,48 @@ impl ValidatedState {
         let cf = match (state_cf.resolve(), header_cf.resolve()) {
             (Some(cf), _) => cf,
             (_, Some(cf)) if cf.commit() == state_cf.commit() => cf,
-            (_, Some(_)) | (None, None) => {
-                instance
-                    .peers
-                    .as_ref()
-                    .fetch_chain_config(state_cf.commit())
-                    .await
-            }
+            (_, Some(_)) | (None, None) => match instance.known_chain_config(state_cf.commit()) {
+                Some(cf) => cf,
+                None => {
+                    instance
+                        .peers
+                        .as_ref()
+                        .fetch_chain_config(state_cf.commit())
+                        .await
+                }
+            },
         };
 
         Ok(cf)
     }
 }
 
-fn _apply_full_transactions(
+/// Execute full network transactions in order, recording the fee state entries and transfer nonces
+/// they touch in `delta`. Fails on the first transaction that cannot be executed.
+fn apply_full_transactions(
     validated_state: &mut ValidatedState,
+    delta: &mut Delta,
     full_network_txs: Vec<FullNetworkTx>,
+    version: Version,
 ) -> Result<(), ExecutionError> {
-    full_network_txs
-        .iter()
-        .try_for_each(|tx| tx.execute(validated_state))
+    // Transactions also pay the recipients set in the chain config.
+    let recipients = validated_state
+        .chain_config
+        .resolve()
+        .map(|chain_config| {
+            std::iter::once(chain_config.fee_recipient)
+                .chain(chain_config.bid_recipient)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    for tx in full_network_txs {
+        tx.execute(validated_state, version)?;
+        delta.fees_delta.extend(tx.accounts());
+        delta.fees_delta.extend(recipients.iter().copied());
+        delta.nonces_delta.extend(tx.nonce_accounts());
+    }
+    Ok(())
 }
 
 pub async fn get_l1_deposits(
@@ -651,12 +812,24 @@ impl HotShotState<SeqTypes> for ValidatedState {
             return Err(BlockError::InvalidBlockHeader);
         }
 
+        let res = self
+            .apply_header(instance, parent_leaf, proposed_header, version)
+            .await;
+
+        // A full network transaction that cannot be executed makes the proposal invalid, no matter
+        // how many times we retry.
+        if let Some(err) = res
+            .as_ref()
+            .err()
+            .and_then(|err| err.downcast_ref::<ExecutionError>())
+        {
+            tracing::error!("invalid full network transaction: {err:#}");
+            return Err(BlockError::InvalidBlockHeader);
+        }
+
         // Unwrapping here is okay as we retry in a loop
         //so we should either get a validated state or until hotshot cancels the task
-        let (validated_state, delta) = self
-            .apply_header(instance, parent_leaf, proposed_header, version)
-            .await
-            .unwrap();
+        let (validated_state, delta) = res.unwrap();
 
         let chain_config = validated_state
             .chain_config
@@ -700,8 +873,14 @@ impl HotShotState<SeqTypes> for ValidatedState {
         } else {
             BlockMerkleTree::from_commitment(block_header.block_merkle_tree_root())
         };
+        // Headers from before transfers were introduced have no transfer nonces.
+        let transfer_nonce_merkle_tree = match block_header.transfer_nonce_merkle_tree_root() {
+            Some(root) if root.size() != 0 => TransferNonceMerkleTree::from_commitment(root),
+            _ => TransferNonceMerkleTree::new(TRANSFER_NONCE_MERKLE_TREE_HEIGHT),
+        };
         Self {
             fee_merkle_tree,
+            transfer_nonce_merkle_tree,
             block_merkle_tree,
             chain_config: block_header.chain_config(),
         }
@@ -796,6 +975,38 @@ impl MerklizedState<SeqTypes, { Self::ARITY }> for FeeMerkleTree {
     }
 }
 
+impl MerklizedState<SeqTypes, { Self::ARITY }> for TransferNonceMerkleTree {
+    type Key = Self::Index;
+    type Entry = Self::Element;
+    type T = Sha3Node;
+    type Commit = Self::Commitment;
+    type Digest = Sha3Digest;
+
+    fn state_type() -> &'static str {
+        "transfer_nonce_merkle_tree"
+    }
+
+    fn header_state_commitment_field() -> &'static str {
+        "transfer_nonce_merkle_tree_root"
+    }
+
+    fn tree_height() -> usize {
+        TRANSFER_NONCE_MERKLE_TREE_HEIGHT
+    }
+
+    fn insert_path(
+        &mut self,
+        key: Self::Key,
+        proof: &MerkleProof<Self::Entry, Self::Key, Self::T, { Self::ARITY }>,
+    ) -> anyhow::Result<()> {
+        match proof.elem() {
+            Some(elem) => self.remember(key, elem, proof)?,
+            None => self.non_membership_remember(key, proof)?,
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use async_compatibility_layer::logging::{setup_backtrace, setup_logging};
@@ -804,13 +1015,15 @@ mod test {
     use jf_vid::VidScheme;
     use sequencer_utils::ser::FromStringOrInteger;
     use tracing::debug;
+    use vbs::version::{StaticVersion, StaticVersionType};
 
     use super::*;
     use crate::{
         eth_signature_key::{BuilderSignature, EthKeyPair},
         v0_1, v0_2,
         v0_3::{self, BidTx},
-        BlockSize, FeeAccountProof, FeeMerkleProof,
+        BlockSize, ChainConfigUpgrade, ChainConfigUpgradeActivation, FeeAccountProof,
+        FeeMerkleProof, MarketplaceVersion, Timestamp,
     };
 
     pub fn mock_full_network_txs(key: Option<EthKeyPair>) -> Vec<FullNetworkTx> {
@@ -831,13 +1044,25 @@ mod test {
         let mut state = ValidatedState::default();
         let txs = mock_full_network_txs(None);
         // Default key can be verified b/c it is the same that signs the mock tx
-        _apply_full_transactions(&mut state, txs).unwrap();
+        apply_full_transactions(
+            &mut state,
+            &mut Delta::default(),
+            txs,
+            MarketplaceVersion::version(),
+        )
+        .unwrap();
 
         // Tx will be invalid if it is signed by a different key than
         // set in `account` field.
         let key = FeeAccount::generated_from_seed_indexed([1; 32], 0).1;
         let invalid = mock_full_network_txs(Some(key));
-        let err = _apply_full_transactions(&mut state, invalid).unwrap_err();
+        let err = apply_full_transactions(
+            &mut state,
+            &mut Delta::default(),
+            invalid,
+            MarketplaceVersion::version(),
+        )
+        .unwrap_err();
         assert_eq!(ExecutionError::InvalidSignature, err);
     }
 
@@ -1174,4 +1399,91 @@ mod test {
 
         validate_builder_fee(&header).unwrap();
     }
+
+    #[async_std::test]
+    async fn test_chain_config_upgrade() {
+        setup_logging();
+        setup_backtrace();
+
+        let version = StaticVersion::<0, 1>::version();
+        let base = NodeState::mock();
+        let parent = Leaf::genesis(&base.genesis_state, &base).await;
+        let upgraded = ChainConfig {
+            max_block_size: (u64::from(base.chain_config.max_block_size) + 1).into(),
+            ..base.chain_config
+        };
+        let later = ChainConfig {
+            base_fee: 1.into(),
+            ..upgraded
+        };
+
+        let apply = |upgrades: Vec<ChainConfigUpgrade>, version| {
+            let instance = base
+                .clone()
+                .with_chain_config_upgrades([(version, upgrades)].into_iter().collect());
+            let mut state = base.genesis_state.clone();
+            state.apply_upgrade(&instance, &parent, version);
+            state.chain_config.resolve().unwrap()
+        };
+
+        // Upgrades whose activation point has not passed have no effect.
+        let pending = vec![
+            ChainConfigUpgrade {
+                activation: ChainConfigUpgradeActivation::View(1),
+                chain_config: upgraded,
+            },
+            ChainConfigUpgrade {
+                activation: ChainConfigUpgradeActivation::Time(Timestamp::from_integer(1).unwrap()),
+                chain_config: upgraded,
+            },
+        ];
+        assert_eq!(apply(pending, version), base.chain_config);
+
+        // An active upgrade swaps the chain config, and the last active one wins.
+        let active = vec![
+            ChainConfigUpgrade {
+                activation: ChainConfigUpgradeActivation::View(0),
+                chain_config: upgraded,
+            },
+            ChainConfigUpgrade {
+                activation: ChainConfigUpgradeActivation::Time(Timestamp::default()),
+                chain_config: later,
+            },
+            ChainConfigUpgrade {
+                activation: ChainConfigUpgradeActivation::View(1),
+                chain_config: upgraded,
+            },
+        ];
+        assert_eq!(apply(active.clone(), version), later);
+
+        // Upgrades scheduled for a different version have no effect.
+        assert_eq!(
+            apply(active, StaticVersion::<0, 2>::version()),
+            base.chain_config
+        );
+
+        // A node which knows about an upgrade can resolve its chain config without asking peers.
+        let instance = base.clone().with_chain_config_upgrades(
+            [(
+                version,
+                vec![ChainConfigUpgrade {
+                    activation: ChainConfigUpgradeActivation::View(0),
+                    chain_config: upgraded,
+                }],
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let state = ValidatedState {
+            chain_config: upgraded.commit().into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            state
+                .get_chain_config(&instance, &upgraded.commit().into())
+                .await
+                .unwrap(),
+            upgraded
+        );
+    }
 }
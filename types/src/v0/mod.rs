@@ -17,8 +17,8 @@ pub mod traits;
 mod utils;
 pub use header::Header;
 pub use impls::{
-    mock, validate_proposal, BuilderValidationError, FeeError, ProposalValidationError,
-    StateValidationError,
+    mock, remember_transfer_nonces, validate_proposal, BuilderValidationError, FeeError,
+    ProposalValidationError, StateValidationError, DEFAULT_L1_CACHE_CAPACITY, L1_CACHE_RETENTION,
 };
 pub use utils::*;
 use vbs::version::{StaticVersion, StaticVersionType};
@@ -69,11 +69,14 @@ macro_rules! reexport_unchanged_types {
 }
 reexport_unchanged_types!(
     AccountQueryData,
+    BalanceChange,
+    BalanceHistoryEntry,
     BlockMerkleCommitment,
     BlockMerkleTree,
     BuilderSignature,
     ChainId,
     Delta,
+    DepositQueryData,
     FeeAccount,
     FeeAccountProof,
     FeeAmount,
@@ -85,6 +88,8 @@ reexport_unchanged_types!(
     Iter,
     L1BlockInfo,
     L1Client,
+    L1Deposit,
+    L1Provider,
     L1Snapshot,
     NamespaceId,
     NsIndex,
@@ -117,6 +122,8 @@ reexport_unchanged_types!(
     UpgradeMode,
     TimeBasedUpgrade,
     ViewBasedUpgrade,
+    ChainConfigUpgrade,
+    ChainConfigUpgradeActivation,
     BlockSize,
 );
 
@@ -6,14 +6,16 @@ use anyhow::{bail, ensure, Context};
 use async_trait::async_trait;
 use committable::Commitment;
 use dyn_clone::DynClone;
+use ethers::types::Address;
 use futures::{FutureExt, TryFutureExt};
 use hotshot::{types::EventType, HotShotInitializer};
 use hotshot_types::{
     consensus::CommitmentMap,
     data::{DaProposal, QuorumProposal, VidDisperseShare, ViewNumber},
     event::{HotShotAction, LeafInfo},
+    light_client::StateSignatureRequestBody,
     message::Proposal,
-    simple_certificate::QuorumCertificate,
+    simple_certificate::{QuorumCertificate, UpgradeCertificate},
     traits::{
         node_implementation::{ConsensusTime, Versions},
         storage::Storage,
@@ -21,11 +23,14 @@ use hotshot_types::{
     },
     utils::View,
 };
+use jf_merkle_tree::MerkleTreeScheme;
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
-    v0::impls::ValidatedState, v0_3::ChainConfig, AccountQueryData, BackoffParams, BlockMerkleTree,
-    Event, FeeAccount, FeeMerkleCommitment, Leaf, NetworkConfig, SeqTypes,
+    v0::impls::ValidatedState,
+    v0_3::{ChainConfig, TransferNonceMerkleTree},
+    AccountQueryData, BackoffParams, BlockMerkleTree, Event, FeeAccount, FeeInfo,
+    FeeMerkleCommitment, L1BlockInfo, Leaf, NetworkConfig, SeqTypes,
 };
 
 use super::impls::NodeState;
@@ -41,30 +46,49 @@ pub trait StateCatchup: Send + Sync + std::fmt::Debug {
         account: FeeAccount,
     ) -> anyhow::Result<AccountQueryData>;
 
-    /// Fetch the given list of accounts, retrying on transient errors.
-    async fn fetch_accounts(
+    /// Try to fetch the given list of accounts, failing without retrying if unable.
+    ///
+    /// The default implementation fetches each account individually. Providers which can serve
+    /// several accounts in a single round trip should override this.
+    async fn try_fetch_accounts(
         &self,
         height: u64,
         view: ViewNumber,
         fee_merkle_tree_root: FeeMerkleCommitment,
-        accounts: Vec<FeeAccount>,
+        accounts: &[FeeAccount],
     ) -> anyhow::Result<Vec<AccountQueryData>> {
         let mut ret = vec![];
         for account in accounts {
-            let account = self
-                .backoff()
-                .retry(self, |provider| {
-                    provider
-                        .try_fetch_account(height, view, fee_merkle_tree_root, account)
-                        .map_err(|err| err.context("fetching account {account}"))
-                        .boxed()
-                })
-                .await;
-            ret.push(account);
+            ret.push(
+                self.try_fetch_account(height, view, fee_merkle_tree_root, *account)
+                    .await?,
+            );
         }
         Ok(ret)
     }
 
+    /// Fetch the given list of accounts, retrying on transient errors.
+    async fn fetch_accounts(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        fee_merkle_tree_root: FeeMerkleCommitment,
+        accounts: Vec<FeeAccount>,
+    ) -> anyhow::Result<Vec<AccountQueryData>> {
+        if accounts.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(self
+            .backoff()
+            .retry(self, |provider| {
+                provider
+                    .try_fetch_accounts(height, view, fee_merkle_tree_root, &accounts)
+                    .map_err(|err| err.context(format!("fetching {} accounts", accounts.len())))
+                    .boxed()
+            })
+            .await)
+    }
+
     /// Try to fetch and remember the blocks frontier, failing without retrying if unable.
     async fn try_remember_blocks_merkle_tree(
         &self,
@@ -90,6 +114,39 @@ pub trait StateCatchup: Send + Sync + std::fmt::Debug {
         Ok(())
     }
 
+    /// Try to fetch and remember the transfer nonces of the given accounts, failing without
+    /// retrying if unable.
+    async fn try_remember_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        tree: &mut TransferNonceMerkleTree,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<()>;
+
+    /// Fetch and remember the transfer nonces of the given accounts, retrying on transient errors.
+    async fn remember_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        tree: &mut TransferNonceMerkleTree,
+        accounts: Vec<FeeAccount>,
+    ) -> anyhow::Result<()> {
+        if accounts.is_empty() {
+            return Ok(());
+        }
+        self.backoff()
+            .retry(tree, |tree| {
+                self.try_remember_transfer_nonces(height, view, tree, &accounts)
+                    .map_err(|err| {
+                        err.context(format!("fetching {} transfer nonces", accounts.len()))
+                    })
+                    .boxed()
+            })
+            .await;
+        Ok(())
+    }
+
     async fn try_fetch_chain_config(
         &self,
         commitment: Commitment<ChainConfig>,
@@ -123,6 +180,18 @@ impl<T: StateCatchup + ?Sized> StateCatchup for Box<T> {
             .await
     }
 
+    async fn try_fetch_accounts(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        fee_merkle_tree_root: FeeMerkleCommitment,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<Vec<AccountQueryData>> {
+        (**self)
+            .try_fetch_accounts(height, view, fee_merkle_tree_root, accounts)
+            .await
+    }
+
     async fn fetch_accounts(
         &self,
         height: u64,
@@ -155,6 +224,30 @@ impl<T: StateCatchup + ?Sized> StateCatchup for Box<T> {
         (**self).remember_blocks_merkle_tree(height, view, mt).await
     }
 
+    async fn try_remember_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        tree: &mut TransferNonceMerkleTree,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<()> {
+        (**self)
+            .try_remember_transfer_nonces(height, view, tree, accounts)
+            .await
+    }
+
+    async fn remember_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        tree: &mut TransferNonceMerkleTree,
+        accounts: Vec<FeeAccount>,
+    ) -> anyhow::Result<()> {
+        (**self)
+            .remember_transfer_nonces(height, view, tree, accounts)
+            .await
+    }
+
     async fn try_fetch_chain_config(
         &self,
         commitment: Commitment<ChainConfig>,
@@ -185,6 +278,18 @@ impl<T: StateCatchup + ?Sized> StateCatchup for Arc<T> {
             .await
     }
 
+    async fn try_fetch_accounts(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        fee_merkle_tree_root: FeeMerkleCommitment,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<Vec<AccountQueryData>> {
+        (**self)
+            .try_fetch_accounts(height, view, fee_merkle_tree_root, accounts)
+            .await
+    }
+
     async fn fetch_accounts(
         &self,
         height: u64,
@@ -217,6 +322,30 @@ impl<T: StateCatchup + ?Sized> StateCatchup for Arc<T> {
         (**self).remember_blocks_merkle_tree(height, view, mt).await
     }
 
+    async fn try_remember_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        tree: &mut TransferNonceMerkleTree,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<()> {
+        (**self)
+            .try_remember_transfer_nonces(height, view, tree, accounts)
+            .await
+    }
+
+    async fn remember_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        tree: &mut TransferNonceMerkleTree,
+        accounts: Vec<FeeAccount>,
+    ) -> anyhow::Result<()> {
+        (**self)
+            .remember_transfer_nonces(height, view, tree, accounts)
+            .await
+    }
+
     async fn try_fetch_chain_config(
         &self,
         commitment: Commitment<ChainConfig>,
@@ -259,6 +388,29 @@ impl<T: StateCatchup> StateCatchup for Vec<T> {
         bail!("could not fetch account from any provider");
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn try_fetch_accounts(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        fee_merkle_tree_root: FeeMerkleCommitment,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<Vec<AccountQueryData>> {
+        for provider in self {
+            match provider
+                .try_fetch_accounts(height, view, fee_merkle_tree_root, accounts)
+                .await
+            {
+                Ok(accounts) => return Ok(accounts),
+                Err(err) => {
+                    tracing::warn!(?provider, "failed to fetch accounts: {err:#}");
+                }
+            }
+        }
+
+        bail!("could not fetch accounts from any provider");
+    }
+
     #[tracing::instrument(skip(self, mt))]
     async fn try_remember_blocks_merkle_tree(
         &self,
@@ -281,6 +433,29 @@ impl<T: StateCatchup> StateCatchup for Vec<T> {
         bail!("could not fetch account from any provider");
     }
 
+    #[tracing::instrument(skip(self, tree))]
+    async fn try_remember_transfer_nonces(
+        &self,
+        height: u64,
+        view: ViewNumber,
+        tree: &mut TransferNonceMerkleTree,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<()> {
+        for provider in self {
+            match provider
+                .try_remember_transfer_nonces(height, view, tree, accounts)
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    tracing::warn!(?provider, "failed to fetch transfer nonces: {err:#}");
+                }
+            }
+        }
+
+        bail!("could not fetch transfer nonces from any provider");
+    }
+
     async fn try_fetch_chain_config(
         &self,
         commitment: Commitment<ChainConfig>,
@@ -319,6 +494,43 @@ pub trait PersistenceOptions: Clone + Send + Sync + 'static {
     ) -> anyhow::Result<Arc<dyn StateCatchup>> {
         self.create().await?.into_catchup_provider(backoff)
     }
+
+    async fn create_l1_cache_storage(self) -> anyhow::Result<Option<Arc<dyn L1CacheStorage>>> {
+        Ok(self.create().await?.into_l1_cache_storage())
+    }
+}
+
+/// Durable storage for finalized L1 data cached by an [`L1Client`](crate::L1Client).
+///
+/// Since finalized L1 data can never change, it never needs to be invalidated. To bound the size of
+/// the cache, saving a block or deposit range prunes data for blocks more than
+/// [`L1_CACHE_RETENTION`](crate::L1_CACHE_RETENTION) blocks older than it.
+#[async_trait]
+pub trait L1CacheStorage: Send + Sync + 'static {
+    /// Load a finalized L1 block by number.
+    async fn load_l1_block(&self, number: u64) -> anyhow::Result<Option<L1BlockInfo>>;
+
+    /// Save a finalized L1 block.
+    async fn save_l1_block(&self, block: &L1BlockInfo) -> anyhow::Result<()>;
+
+    /// Load the deposits to `fee_contract` in the L1 block range `from..=to`.
+    ///
+    /// Returns `None` if this exact range has not been saved.
+    async fn load_l1_deposits(
+        &self,
+        fee_contract: Address,
+        from: u64,
+        to: u64,
+    ) -> anyhow::Result<Option<Vec<FeeInfo>>>;
+
+    /// Save the deposits to `fee_contract` in the finalized L1 block range `from..=to`.
+    async fn save_l1_deposits(
+        &self,
+        fee_contract: Address,
+        from: u64,
+        to: u64,
+        deposits: &[FeeInfo],
+    ) -> anyhow::Result<()>;
 }
 
 #[async_trait]
@@ -331,6 +543,11 @@ pub trait SequencerPersistence: Sized + Send + Sync + 'static {
         bail!("state catchup is not implemented for this persistence type");
     }
 
+    /// Use this storage as a durable L1 cache, if supported.
+    fn into_l1_cache_storage(self) -> Option<Arc<dyn L1CacheStorage>> {
+        None
+    }
+
     /// Load the orchestrator config from storage.
     ///
     /// Returns `None` if no config exists (we are joining a network for the first time). Fails with
@@ -362,6 +579,65 @@ pub trait SequencerPersistence: Sized + Send + Sync + 'static {
         view: ViewNumber,
     ) -> anyhow::Result<Option<Proposal<SeqTypes, DaProposal<SeqTypes>>>>;
 
+    /// Save a full validated state to bootstrap consensus from.
+    ///
+    /// `state` must be the state resulting from the decided leaf at `height`. When consensus is
+    /// later restored from an anchor leaf at this height, the saved state is used in place of a
+    /// sparse state, so the node does not have to catch up the state from peers.
+    async fn save_bootstrap_state(
+        &self,
+        _height: u64,
+        _state: &ValidatedState,
+    ) -> anyhow::Result<()> {
+        bail!("bootstrap state is not supported for this persistence type");
+    }
+
+    /// Load the state saved with [`save_bootstrap_state`](Self::save_bootstrap_state).
+    ///
+    /// Returns `None` if there is no saved state for `height`.
+    async fn load_bootstrap_state(&self, _height: u64) -> anyhow::Result<Option<ValidatedState>> {
+        Ok(None)
+    }
+
+    /// Save the most recently decided upgrade certificate, replacing any previously saved one.
+    async fn save_upgrade_certificate(
+        &self,
+        certificate: &UpgradeCertificate<SeqTypes>,
+    ) -> anyhow::Result<()>;
+
+    /// Load the certificate saved with [`save_upgrade_certificate`](Self::save_upgrade_certificate).
+    async fn load_upgrade_certificate(
+        &self,
+    ) -> anyhow::Result<Option<UpgradeCertificate<SeqTypes>>>;
+
+    /// Save a light client state signature generated by this node.
+    ///
+    /// The signature is saved as undelivered, replacing any existing signature for the same block
+    /// height, until it is marked with
+    /// [`mark_state_signature_delivered`](Self::mark_state_signature_delivered).
+    async fn save_state_signature(
+        &self,
+        signature: &StateSignatureRequestBody,
+    ) -> anyhow::Result<()>;
+
+    /// Load the state signature saved for the light client state at `height`.
+    async fn load_state_signature(
+        &self,
+        height: u64,
+    ) -> anyhow::Result<Option<StateSignatureRequestBody>>;
+
+    /// Load all saved state signatures which have not been delivered to the relay server, in
+    /// increasing order of block height.
+    async fn load_undelivered_state_signatures(
+        &self,
+    ) -> anyhow::Result<Vec<StateSignatureRequestBody>>;
+
+    /// Record that the state signature at `height` has been delivered to the relay server.
+    async fn mark_state_signature_delivered(&self, height: u64) -> anyhow::Result<()>;
+
+    /// Delete all saved state signatures, delivered or not, for block heights below `height`.
+    async fn prune_state_signatures(&self, height: u64) -> anyhow::Result<()>;
+
     /// Load the latest known consensus state.
     ///
     /// Returns an initializer to resume HotShot from the latest saved state (or start from genesis,
@@ -418,6 +694,27 @@ pub trait SequencerPersistence: Sized + Send + Sync + 'static {
         let validated_state = if leaf.block_header().height() == 0 {
             // If we are starting from genesis, we can provide the full state.
             Some(Arc::new(genesis_validated_state))
+        } else if let Some(state) = self
+            .load_bootstrap_state(leaf.height())
+            .await
+            .context("loading bootstrap state")?
+        {
+            // If we were bootstrapped from a snapshot of exactly this leaf, we can also provide the
+            // full state, as long as it is consistent with the leaf.
+            let header = leaf.block_header();
+            if state.fee_merkle_tree.commitment() == header.fee_merkle_tree_root()
+                && state.block_merkle_tree.commitment() == header.block_merkle_tree_root()
+                && state.chain_config.commit() == header.chain_config().commit()
+            {
+                tracing::info!(height = leaf.height(), "starting from bootstrap state");
+                Some(Arc::new(state))
+            } else {
+                tracing::warn!(
+                    height = leaf.height(),
+                    "bootstrap state does not match anchor leaf, ignoring it"
+                );
+                None
+            }
         } else {
             // Otherwise, we will have to construct a sparse state and fetch missing data during
             // catchup.
@@ -1,6 +1,9 @@
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use derive_more::{Add, Display, From, Into, Mul, Sub};
-use ethers::{abi::Address, types::U256};
+use ethers::{
+    abi::Address,
+    types::{H256, U256},
+};
 use jf_merkle_tree::{MerkleTreeScheme, UniversalMerkleTreeScheme};
 use serde::{Deserialize, Serialize};
 
@@ -88,3 +91,56 @@ pub struct AccountQueryData {
     pub balance: U256,
     pub proof: FeeAccountProof,
 }
+
+/// A deposit to the fee contract, along with the L1 transaction which made it.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct L1Deposit {
+    pub account: FeeAccount,
+    pub amount: FeeAmount,
+    /// The number of the L1 block containing the deposit.
+    pub l1_block: u64,
+    /// The hash of the L1 transaction which made the deposit.
+    pub l1_transaction: H256,
+    /// The index of the deposit event among the logs of its L1 block.
+    pub log_index: u64,
+}
+
+/// A deposit which has been applied to the fee state.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct DepositQueryData {
+    /// The height of the Espresso block in which the deposit took effect.
+    pub height: u64,
+    pub deposit: L1Deposit,
+}
+
+/// An event which changed the balance of a fee account.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum BalanceChange {
+    /// The balance allocated to the account in the genesis state.
+    Genesis(FeeAmount),
+    /// A deposit to the account from the L1.
+    Deposit(L1Deposit),
+    /// A fee paid by the account to the fee recipient, e.g. by a builder.
+    FeePaid(FeeInfo),
+    /// A fee paid by another account to this one, as the fee recipient.
+    FeeReceived(FeeInfo),
+    /// A winning bid, including gas, paid by the account to the bid recipient.
+    BidPaid(FeeInfo),
+    /// A winning bid, including gas, paid by another account to this one, as the bid recipient.
+    BidReceived(FeeInfo),
+    /// Funds transferred from the account to another one.
+    TransferSent { to: FeeAccount, amount: FeeAmount },
+    /// Funds transferred to the account from another one.
+    TransferReceived { from: FeeAccount, amount: FeeAmount },
+}
+
+/// The state of a fee account after a block which changed its balance.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BalanceHistoryEntry {
+    /// The height of the block which changed the balance.
+    pub height: u64,
+    /// The balance after the block, with a proof relative to the fee state root at `height`.
+    pub account: AccountQueryData,
+    /// The events in the block which changed the balance.
+    pub changes: Vec<BalanceChange>,
+}
@@ -66,3 +66,29 @@ pub struct Upgrade {
     /// The type of the upgrade.
     pub upgrade_type: UpgradeType,
 }
+
+/// The point at which a [`ChainConfigUpgrade`] takes effect.
+///
+/// Activation is evaluated against the parent of each new block, so that the chain config for a
+/// block is fixed before the block itself is built.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainConfigUpgradeActivation {
+    /// Take effect in blocks whose parent has at least this view number.
+    View(u64),
+    /// Take effect in blocks whose parent has at least this timestamp.
+    Time(Timestamp),
+}
+
+/// A change of chain config parameters which does not change the protocol version.
+///
+/// Unlike an [`Upgrade`], a chain config upgrade does not require an upgrade certificate from
+/// HotShot. Every node applies it deterministically once its activation point has passed, as long
+/// as the chain is still at the protocol version the upgrade was scheduled for.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ChainConfigUpgrade {
+    /// When the new chain config takes effect.
+    pub activation: ChainConfigUpgradeActivation,
+    /// The new chain config.
+    pub chain_config: ChainConfig,
+}
@@ -1,9 +1,18 @@
+use async_broadcast::{InactiveReceiver, Sender};
+use async_std::{sync::RwLock, task::JoinHandle};
 use ethers::{
-    prelude::{H256, U256},
+    prelude::{Address, H256, U256},
     providers::{Http, Provider},
 };
 use serde::{Deserialize, Serialize};
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::BTreeMap,
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
+use url::Url;
+
+use crate::{traits::L1CacheStorage, FeeInfo};
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct L1BlockInfo {
@@ -34,11 +43,63 @@ pub struct L1Snapshot {
 }
 
 #[derive(Clone, Debug)]
-/// An Http Provider and configuration to interact with the L1.
+/// Http Providers and configuration to interact with the L1.
 pub struct L1Client {
     pub retry_delay: Duration,
-    /// `Provider` from `ethers-provider`.
-    pub provider: Arc<Provider<Http>>,
+    /// Providers for each configured L1 RPC endpoint, in order of preference.
+    pub(crate) providers: Arc<Vec<L1Provider>>,
+    /// Index of the provider currently in use.
+    pub(crate) current: Arc<AtomicUsize>,
     /// Maximum number of L1 blocks that can be scanned for events in a single query.
     pub events_max_block_range: u64,
+    /// Number of providers which must agree on the latest finalized block.
+    ///
+    /// If this is 1, the finalized block is read from the current provider alone. Otherwise, all
+    /// providers are queried and their responses cross-checked.
+    pub finalized_quorum: usize,
+    /// WebSocket subscription to new L1 heads, if enabled.
+    pub(crate) subscription: Option<Arc<L1Subscription>>,
+    /// Background task maintaining `subscription`, shared by all clones of this client.
+    pub(crate) subscription_task: Option<Arc<L1SubscriptionTask>>,
+    /// Cache of finalized L1 data.
+    pub(crate) cache: Arc<L1Cache>,
+}
+
+#[derive(Debug)]
+/// A single L1 RPC endpoint, with the health information used to fail over between endpoints.
+pub struct L1Provider {
+    pub url: Url,
+    /// `Provider` from `ethers-provider`.
+    pub provider: Arc<Provider<Http>>,
+    /// Number of requests to this provider which have failed since the last success.
+    pub(crate) consecutive_failures: AtomicUsize,
+    /// Total number of requests to this provider which have failed.
+    pub(crate) failures: AtomicUsize,
+}
+
+#[derive(Debug)]
+/// The latest L1 state, as maintained by a WebSocket subscription to new heads.
+pub(crate) struct L1Subscription {
+    pub(crate) url: Url,
+    /// The latest snapshot, or `None` if the subscription is currently disconnected.
+    pub(crate) snapshot: RwLock<Option<L1Snapshot>>,
+    /// Notifies listeners of each new snapshot.
+    pub(crate) sender: Sender<L1Snapshot>,
+    pub(crate) receiver: InactiveReceiver<L1Snapshot>,
+}
+
+#[derive(Debug)]
+/// Handle to the task maintaining an [`L1Subscription`], which is cancelled when the last
+/// [`L1Client`] using the subscription is dropped.
+pub(crate) struct L1SubscriptionTask(pub(crate) Option<JoinHandle<()>>);
+
+/// A bounded cache of finalized L1 data, optionally backed by durable storage.
+pub(crate) struct L1Cache {
+    /// The maximum number of blocks, and of deposit ranges, to keep in memory.
+    pub(crate) capacity: usize,
+    pub(crate) blocks: RwLock<BTreeMap<u64, L1BlockInfo>>,
+    /// Deposits indexed by the end of the L1 block range, the start of the range and the fee
+    /// contract, so that the oldest ranges are evicted first.
+    pub(crate) deposits: RwLock<BTreeMap<(u64, u64, Address), Vec<FeeInfo>>>,
+    pub(crate) storage: Option<Arc<dyn L1CacheStorage>>,
 }
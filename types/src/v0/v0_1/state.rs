@@ -24,4 +24,6 @@ pub const FEE_MERKLE_TREE_HEIGHT: usize = 20;
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct Delta {
     pub fees_delta: HashSet<FeeAccount>,
+    /// Accounts whose transfer nonce changed.
+    pub nonces_delta: HashSet<FeeAccount>,
 }
@@ -2,8 +2,9 @@ use vbs::version::Version;
 
 // Re-export types which haven't changed since the last minor version.
 pub use super::v0_1::{
-    AccountQueryData, BlockMerkleCommitment, BlockMerkleTree, BlockSize, BuilderSignature,
-    ChainConfig, ChainId, Delta, FeeAccount, FeeAccountProof, FeeAmount, FeeInfo,
+    AccountQueryData, BalanceChange, BalanceHistoryEntry, BlockMerkleCommitment, BlockMerkleTree,
+    BlockSize, BuilderSignature, ChainConfig, ChainConfigUpgrade, ChainConfigUpgradeActivation,
+    ChainId, Delta, DepositQueryData, FeeAccount, FeeAccountProof, FeeAmount, FeeInfo,
     FeeMerkleCommitment, FeeMerkleProof, FeeMerkleTree, Header, Index, Iter, L1BlockInfo, L1Client,
     L1Snapshot, NamespaceId, NsIndex, NsIter, NsPayload, NsPayloadBuilder, NsPayloadByteLen,
     NsPayloadOwned, NsPayloadRange, NsProof, NsTable, NsTableBuilder, NsTableValidationError,
@@ -1,4 +1,4 @@
-use crate::{FeeAccount, FeeAmount, NamespaceId};
+use crate::{ChainId, FeeAccount, FeeAmount, NamespaceId};
 use ethers::types::Signature;
 use hotshot_types::data::ViewNumber;
 use serde::{Deserialize, Serialize};
@@ -9,6 +9,7 @@ use url::Url;
 /// will be a variant of this enum.
 pub enum FullNetworkTx {
     Bid(BidTx),
+    Transfer(TransferTx),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, Hash)]
@@ -40,6 +41,35 @@ pub struct BidTxBody {
     pub(crate) namespaces: Vec<NamespaceId>,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, Hash)]
+/// A transaction moving funds from one fee account to another. It is
+/// the `signed` form of `TransferTxBody`.
+pub struct TransferTx {
+    pub(crate) body: TransferTxBody,
+    pub(crate) signature: Signature,
+}
+
+/// A transaction body holding data required for a transfer.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, Hash)]
+pub struct TransferTxBody {
+    /// Account sending the funds, responsible for the signature
+    pub(crate) from: FeeAccount,
+    /// Account receiving the funds
+    pub(crate) to: FeeAccount,
+    /// The amount to transfer, in Wei
+    pub(crate) amount: FeeAmount,
+    /// Fee paid to the fee recipient for sequencing the transfer
+    pub(crate) fee: FeeAmount,
+    /// The number of transfers previously executed from `from`. The
+    /// transfer only executes if this matches the nonce recorded in
+    /// the state, which prevents it from being replayed.
+    pub(crate) nonce: u64,
+    /// The chain the transfer is for. The transfer only executes on
+    /// the chain whose `ChainConfig` has this ID, which prevents it
+    /// from being replayed on another network.
+    pub(crate) chain_id: ChainId,
+}
+
 /// The results of an Auction
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, Hash)]
 pub struct SolverAuctionResults {
@@ -49,4 +79,7 @@ pub struct SolverAuctionResults {
     pub(crate) winning_bids: Vec<BidTx>,
     /// A list of reserve sequencers being used
     pub(crate) reserve_bids: Vec<(NamespaceId, Url)>,
+    /// Transfers between fee accounts to be executed in this block
+    #[serde(default)]
+    pub(crate) transfers: Vec<TransferTx>,
 }
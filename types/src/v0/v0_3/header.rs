@@ -1,11 +1,12 @@
 use super::{
     BlockMerkleCommitment, BuilderSignature, FeeInfo, FeeMerkleCommitment, L1BlockInfo,
-    ResolvableChainConfig, SolverAuctionResults,
+    ResolvableChainConfig, SolverAuctionResults, TransferNonceMerkleCommitment,
 };
 use crate::NsTable;
 use ark_serialize::CanonicalSerialize;
 use committable::{Commitment, Committable, RawCommitmentBuilder};
 use hotshot_types::{utils::BuilderCommitment, vid::VidCommitment};
+use jf_merkle_tree::MerkleCommitment;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
@@ -22,6 +23,7 @@ pub struct Header {
     pub(crate) ns_table: NsTable,
     pub(crate) block_merkle_tree_root: BlockMerkleCommitment,
     pub(crate) fee_merkle_tree_root: FeeMerkleCommitment,
+    pub(crate) transfer_nonce_merkle_tree_root: TransferNonceMerkleCommitment,
     pub(crate) fee_info: Vec<FeeInfo>,
     pub(crate) builder_signature: Vec<BuilderSignature>,
     pub(crate) auction_results: SolverAuctionResults,
@@ -38,7 +40,8 @@ impl Committable for Header {
             .serialize_with_mode(&mut fmt_bytes, ark_serialize::Compress::Yes)
             .unwrap();
 
-        RawCommitmentBuilder::new(&Self::tag())
+        let auction_results = &self.auction_results;
+        let mut comm = RawCommitmentBuilder::new(&Self::tag())
             .field("chain_config", self.chain_config.commit())
             .u64_field("height", self.height)
             .u64_field("timestamp", self.timestamp)
@@ -54,9 +57,34 @@ impl Committable for Header {
             .var_size_field("fee_info", &bincode::serialize(&self.fee_info).unwrap())
             .var_size_field(
                 "auction_results",
-                &bincode::serialize(&self.auction_results).unwrap(),
-            )
-            .finalize()
+                // Transfers are committed to separately, and only if there are any, so that the
+                // commitment of a header without transfers is unchanged by their introduction.
+                &bincode::serialize(&(
+                    auction_results.view_number,
+                    &auction_results.winning_bids,
+                    &auction_results.reserve_bids,
+                ))
+                .unwrap(),
+            );
+
+        if !auction_results.transfers.is_empty() {
+            comm = comm.var_size_field(
+                "transfers",
+                &bincode::serialize(&auction_results.transfers).unwrap(),
+            );
+        }
+
+        // Likewise, the transfer nonces are only committed to once some account has made a
+        // transfer.
+        if self.transfer_nonce_merkle_tree_root.size() != 0 {
+            let mut tnmt_bytes = vec![];
+            self.transfer_nonce_merkle_tree_root
+                .serialize_with_mode(&mut tnmt_bytes, ark_serialize::Compress::Yes)
+                .unwrap();
+            comm = comm.var_size_field("transfer_nonce_merkle_tree_root", &tnmt_bytes);
+        }
+
+        comm.finalize()
     }
 
     fn tag() -> String {
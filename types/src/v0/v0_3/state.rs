@@ -0,0 +1,14 @@
+use super::FeeAccount;
+use jf_merkle_tree::{
+    prelude::{Sha3Digest, Sha3Node},
+    universal_merkle_tree::UniversalMerkleTree,
+    MerkleTreeScheme,
+};
+
+/// The nonce of each account which has sent a transfer, which is the number of transfers executed
+/// from that account so far.
+pub type TransferNonceMerkleTree = UniversalMerkleTree<u64, Sha3Digest, FeeAccount, 256, Sha3Node>;
+pub type TransferNonceMerkleCommitment = <TransferNonceMerkleTree as MerkleTreeScheme>::Commitment;
+
+// Indexed by account, like the fee merkle tree.
+pub const TRANSFER_NONCE_MERKLE_TREE_HEIGHT: usize = 20;